@@ -76,7 +76,7 @@ async fn run_command_under_sandbox(
         },
     )?;
     let stdio_policy = StdioPolicy::Inherit;
-    let env = create_env(&config.shell_environment_policy);
+    let env = create_env(&config.shell_environment_policy, &config.sandbox_policy);
 
     let mut child = match sandbox_type {
         SandboxType::Seatbelt => {
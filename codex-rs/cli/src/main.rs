@@ -12,6 +12,10 @@ use codex_cli::login::run_login_with_api_key;
 use codex_cli::login::run_login_with_chatgpt;
 use codex_cli::login::run_logout;
 use codex_cli::proto;
+use codex_cli::sessions::SessionsCommand;
+use codex_cli::sessions::run_sessions_command;
+use codex_cli::worktree::WorktreeCommand;
+use codex_cli::worktree::run_worktree_command;
 use codex_common::CliConfigOverrides;
 use codex_exec::Cli as ExecCli;
 use codex_tui::Cli as TuiCli;
@@ -63,6 +67,12 @@ enum Subcommand {
     #[clap(visible_alias = "p")]
     Proto(ProtoCli),
 
+    /// Search past sessions.
+    Sessions(SessionsCommand),
+
+    /// Diff, merge or discard the git worktrees of `--worktree` sessions.
+    Worktree(WorktreeCommand),
+
     /// Generate shell completion scripts.
     Completion(CompletionCommand),
 
@@ -184,6 +194,14 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut proto_cli.config_overrides, cli.config_overrides);
             proto::run_main(proto_cli).await?;
         }
+        Some(Subcommand::Sessions(mut sessions_cli)) => {
+            prepend_config_flags(&mut sessions_cli.config_overrides, cli.config_overrides);
+            run_sessions_command(sessions_cli).await?;
+        }
+        Some(Subcommand::Worktree(mut worktree_cli)) => {
+            prepend_config_flags(&mut worktree_cli.config_overrides, cli.config_overrides);
+            run_worktree_command(worktree_cli).await?;
+        }
         Some(Subcommand::Completion(completion_cli)) => {
             print_completion(completion_cli);
         }
@@ -0,0 +1,105 @@
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::RolloutRecorder;
+use codex_core::SearchEntryKind;
+use codex_core::SearchHit;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+
+#[derive(Debug, Parser)]
+pub struct SessionsCommand {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub action: SessionsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionsSubcommand {
+    /// Search past sessions for messages, commands run and files touched.
+    Search(SearchCommand),
+}
+
+#[derive(Debug, Parser)]
+pub struct SearchCommand {
+    /// Terms that must all appear in a session (case-insensitive).
+    #[arg(required = true, num_args = 1..)]
+    pub query: Vec<String>,
+
+    /// Maximum number of sessions to show.
+    #[arg(long, short = 'n', default_value_t = 20)]
+    pub limit: usize,
+
+    /// Print results as JSON Lines, one session per line.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+pub async fn run_sessions_command(cmd: SessionsCommand) -> anyhow::Result<()> {
+    let cli_overrides = cmd
+        .config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(cli_overrides, ConfigOverrides::default())?;
+
+    match cmd.action {
+        SessionsSubcommand::Search(search) => {
+            let query = search.query.join(" ");
+            let page = RolloutRecorder::search_conversations(
+                &config.codex_home,
+                &query,
+                search.limit,
+                None,
+            )
+            .await?;
+            if page.items.is_empty() && !search.json {
+                eprintln!("No sessions match `{query}`.");
+            }
+            for hit in &page.items {
+                if search.json {
+                    println!("{}", hit_to_json(hit));
+                } else {
+                    print_hit(hit);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn session_meta_field<'a>(hit: &'a SearchHit, field: &str) -> Option<&'a str> {
+    hit.head.first()?.get(field)?.as_str()
+}
+
+fn kind_label(kind: SearchEntryKind) -> &'static str {
+    match kind {
+        SearchEntryKind::UserMessage => "user",
+        SearchEntryKind::AgentMessage => "codex",
+        SearchEntryKind::Command => "exec",
+        SearchEntryKind::File => "file",
+    }
+}
+
+fn hit_to_json(hit: &SearchHit) -> serde_json::Value {
+    serde_json::json!({
+        "id": session_meta_field(hit, "id"),
+        "timestamp": session_meta_field(hit, "timestamp"),
+        "cwd": session_meta_field(hit, "cwd"),
+        "path": hit.path,
+        "matches": hit.matches,
+    })
+}
+
+fn print_hit(hit: &SearchHit) {
+    let timestamp = session_meta_field(hit, "timestamp").unwrap_or("unknown time");
+    let cwd = session_meta_field(hit, "cwd").unwrap_or_default();
+    println!("{timestamp}  {cwd}");
+    println!("  {}", hit.path.display());
+    for entry in &hit.matches {
+        let first_line = entry.text.lines().next().unwrap_or_default();
+        let snippet: String = first_line.chars().take(120).collect();
+        println!("  {:>5}: {snippet}", kind_label(entry.kind));
+    }
+    println!();
+}
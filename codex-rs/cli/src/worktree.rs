@@ -0,0 +1,77 @@
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::git_worktree;
+
+#[derive(Debug, Parser)]
+pub struct WorktreeCommand {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub action: WorktreeSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum WorktreeSubcommand {
+    /// List the worktrees of sessions started with `--worktree`.
+    List,
+
+    /// Show the changes made in a worktree since it was created.
+    Diff(WorktreeNameArg),
+
+    /// Commit the worktree's changes, merge its branch into the current
+    /// branch of the main checkout and remove the worktree.
+    Merge(WorktreeNameArg),
+
+    /// Remove a worktree and its branch, dropping all its changes.
+    Discard(WorktreeNameArg),
+}
+
+#[derive(Debug, Parser)]
+pub struct WorktreeNameArg {
+    /// Name of the worktree, as shown by `codex worktree list`.
+    pub name: String,
+}
+
+pub async fn run_worktree_command(cmd: WorktreeCommand) -> anyhow::Result<()> {
+    let cli_overrides = cmd
+        .config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(cli_overrides, ConfigOverrides::default())?;
+    let codex_home = config.codex_home.as_path();
+
+    match cmd.action {
+        WorktreeSubcommand::List => {
+            let worktrees = git_worktree::list_worktrees(codex_home).await?;
+            if worktrees.is_empty() {
+                eprintln!("No Codex worktrees.");
+            }
+            for worktree in worktrees {
+                println!(
+                    "{}  {}  {}",
+                    worktree.name,
+                    worktree.branch,
+                    worktree.path.display()
+                );
+            }
+        }
+        WorktreeSubcommand::Diff(WorktreeNameArg { name }) => {
+            let worktree = git_worktree::find_worktree(codex_home, &name).await?;
+            println!("{}", git_worktree::worktree_diff(&worktree).await?);
+        }
+        WorktreeSubcommand::Merge(WorktreeNameArg { name }) => {
+            let worktree = git_worktree::find_worktree(codex_home, &name).await?;
+            git_worktree::merge_worktree(&worktree).await?;
+            println!("Merged {} and removed worktree {name}.", worktree.branch);
+        }
+        WorktreeSubcommand::Discard(WorktreeNameArg { name }) => {
+            let worktree = git_worktree::find_worktree(codex_home, &name).await?;
+            git_worktree::discard_worktree(&worktree).await?;
+            println!("Discarded worktree {name}.");
+        }
+    }
+    Ok(())
+}
@@ -1,14 +1,33 @@
 use codex_core::protocol::SandboxPolicy;
 
 pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
+    let mut summary = summarize_sandbox_mode(sandbox_policy);
+    if let Some(readable_roots) = sandbox_policy.readable_roots() {
+        let mut readable_entries = vec!["workdir".to_string()];
+        readable_entries.extend(
+            readable_roots
+                .iter()
+                .map(|p| p.to_string_lossy().to_string()),
+        );
+        summary.push_str(&format!(
+            " (reads limited to {})",
+            readable_entries.join(", ")
+        ));
+    }
+    summary
+}
+
+fn summarize_sandbox_mode(sandbox_policy: &SandboxPolicy) -> String {
     match sandbox_policy {
         SandboxPolicy::DangerFullAccess => "danger-full-access".to_string(),
-        SandboxPolicy::ReadOnly => "read-only".to_string(),
+        SandboxPolicy::ReadOnly { .. } => "read-only".to_string(),
         SandboxPolicy::WorkspaceWrite {
             writable_roots,
             network_access,
+            network_allowlist,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
+            readable_roots: _,
         } => {
             let mut summary = "workspace-write".to_string();
 
@@ -29,6 +48,11 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             summary.push_str(&format!(" [{}]", writable_entries.join(", ")));
             if *network_access {
                 summary.push_str(" (network access enabled)");
+            } else if !network_allowlist.is_empty() {
+                summary.push_str(&format!(
+                    " (network limited to {})",
+                    network_allowlist.join(", ")
+                ));
             }
             summary
         }
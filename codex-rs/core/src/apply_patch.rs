@@ -88,14 +88,16 @@ pub(crate) async fn apply_patch(
                 }
             }
         }
-        SafetyCheck::Reject { reason } => ResponseInputItem::FunctionCallOutput {
-            call_id: call_id.to_owned(),
-            output: FunctionCallOutputPayload {
-                content: format!("patch rejected: {reason}"),
-                success: Some(false),
-            },
+        SafetyCheck::Reject { reason } | SafetyCheck::Forbidden { reason } => {
+            ResponseInputItem::FunctionCallOutput {
+                call_id: call_id.to_owned(),
+                output: FunctionCallOutputPayload {
+                    content: format!("patch rejected: {reason}"),
+                    success: Some(false),
+                },
+            }
+            .into()
         }
-        .into(),
     }
 }
 
@@ -0,0 +1,282 @@
+//! Record-and-replay of model traffic ("cassettes") for deterministic,
+//! offline end-to-end tests.
+//!
+//! In record mode every request body and the raw SSE response are written to
+//! the cassette directory as `NNNN.request.json` and `NNNN.sse`, numbered by
+//! the order of model requests in the session. In replay mode no network
+//! requests are made: request `N` is answered with the recorded `NNNN.sse`,
+//! provided its body hashes to the same value as the recorded request.
+//!
+//! Bodies are normalized before hashing so recordings stay valid across runs:
+//! `prompt_cache_key` (derived from the random conversation id) is dropped and
+//! the session's cwd and Codex home are replaced with placeholders.
+
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use bytes::Bytes;
+use futures::Stream;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use sha1::Digest;
+use sha1::Sha1;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::config_types::CassetteMode;
+use crate::error::CodexErr;
+use crate::error::Result;
+
+/// Raw SSE bytes, either from the provider or from a cassette.
+pub(crate) type SseByteStream = Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedRequest {
+    wire_api: String,
+    hash: String,
+    body: Value,
+}
+
+#[derive(Debug)]
+pub(crate) struct Cassette {
+    mode: CassetteMode,
+    /// Number of model requests made so far in this session.
+    requests: AtomicUsize,
+    /// Session-specific strings replaced before hashing, longest first.
+    replacements: Vec<(String, &'static str)>,
+}
+
+/// What to do with a single model request.
+pub(crate) enum CassetteRequest {
+    /// Serve the recorded response instead of calling the provider.
+    Replay(SseByteStream),
+    /// Call the provider and record its response.
+    Record(Recording),
+}
+
+pub(crate) struct Recording {
+    sse_path: PathBuf,
+}
+
+impl Cassette {
+    /// Returns the cassette configured for this session, if any. In record
+    /// mode the cassette directory is created.
+    pub(crate) fn from_config(config: &Config) -> std::io::Result<Option<Self>> {
+        let Some(mode) = config.cassette.clone() else {
+            return Ok(None);
+        };
+        if let CassetteMode::Record(dir) = &mode {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut replacements = vec![
+            (config.cwd.to_string_lossy().into_owned(), "<cwd>"),
+            (
+                config.codex_home.to_string_lossy().into_owned(),
+                "<codex_home>",
+            ),
+        ];
+        replacements.retain(|(path, _)| !path.is_empty());
+        replacements.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+
+        Ok(Some(Self {
+            mode,
+            requests: AtomicUsize::new(0),
+            replacements,
+        }))
+    }
+
+    /// Registers the next model request. `body` is the JSON payload about to
+    /// be sent to the `wire_api` endpoint.
+    pub(crate) fn begin_request(&self, wire_api: &str, body: &Value) -> Result<CassetteRequest> {
+        let index = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+        let (normalized, hash) = self.normalize(body)?;
+
+        match &self.mode {
+            CassetteMode::Record(dir) => {
+                let recorded = RecordedRequest {
+                    wire_api: wire_api.to_string(),
+                    hash,
+                    body: normalized,
+                };
+                std::fs::write(
+                    request_path(dir, index),
+                    serde_json::to_string_pretty(&recorded)?,
+                )?;
+                Ok(CassetteRequest::Record(Recording {
+                    sse_path: sse_path(dir, index),
+                }))
+            }
+            CassetteMode::Replay(dir) => {
+                let recorded: RecordedRequest =
+                    match std::fs::read_to_string(request_path(dir, index)) {
+                        Ok(contents) => serde_json::from_str(&contents)?,
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                            return Err(CodexErr::Cassette(format!(
+                                "no recorded request {index} in {}",
+                                dir.display()
+                            )));
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                if recorded.wire_api != wire_api || recorded.hash != hash {
+                    return Err(CodexErr::Cassette(format!(
+                        "request {index} does not match {}; re-record the cassette if the change is intended",
+                        request_path(dir, index).display()
+                    )));
+                }
+                let sse = std::fs::read(sse_path(dir, index))?;
+                Ok(CassetteRequest::Replay(Box::pin(futures::stream::iter([
+                    Ok(Bytes::from(sse)),
+                ]))))
+            }
+        }
+    }
+
+    fn normalize(&self, body: &Value) -> Result<(Value, String)> {
+        let mut body = body.clone();
+        if let Some(object) = body.as_object_mut() {
+            object.remove("prompt_cache_key");
+        }
+        let mut serialized = serde_json::to_string(&body)?;
+        for (needle, placeholder) in &self.replacements {
+            serialized = serialized.replace(needle.as_str(), placeholder);
+        }
+
+        let mut hasher = Sha1::new();
+        hasher.update(serialized.as_bytes());
+        let hash = format!("{:x}", hasher.finalize());
+        Ok((serde_json::from_str(&serialized)?, hash))
+    }
+}
+
+impl CassetteRequest {
+    /// Convenience for callers without a cassette: `None` means "send the
+    /// request and do not record it".
+    pub(crate) fn begin(
+        cassette: Option<&Cassette>,
+        wire_api: &str,
+        body: &Value,
+    ) -> Result<Option<Self>> {
+        cassette
+            .map(|cassette| cassette.begin_request(wire_api, body))
+            .transpose()
+    }
+}
+
+impl Recording {
+    /// Passes `stream` through while appending every chunk to the cassette.
+    pub(crate) fn tee<S>(&self, stream: S) -> Result<SseByteStream>
+    where
+        S: Stream<Item = Result<Bytes>> + Send + 'static,
+    {
+        let mut file = std::fs::File::create(&self.sse_path)?;
+        let path = self.sse_path.clone();
+        Ok(Box::pin(stream.inspect(move |chunk| {
+            if let Ok(bytes) = chunk
+                && let Err(e) = file.write_all(bytes)
+            {
+                warn!("failed to record SSE to {}: {e}", path.display());
+            }
+        })))
+    }
+}
+
+fn request_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("{index:04}.request.json"))
+}
+
+fn sse_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("{index:04}.sse"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn cassette(mode: CassetteMode) -> Cassette {
+        Cassette {
+            mode,
+            requests: AtomicUsize::new(0),
+            replacements: vec![("/work/repo".to_string(), "<cwd>")],
+        }
+    }
+
+    async fn collect(stream: SseByteStream) -> Vec<u8> {
+        stream
+            .map(|chunk| chunk.expect("chunk").to_vec())
+            .concat()
+            .await
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_turns_in_order() {
+        let dir = TempDir::new().unwrap();
+        let recorder = cassette(CassetteMode::Record(dir.path().to_path_buf()));
+        for (prompt, sse) in [("first", "data: one\n\n"), ("second", "data: two\n\n")] {
+            let body = json!({ "input": prompt, "prompt_cache_key": prompt });
+            let Some(CassetteRequest::Record(recording)) =
+                CassetteRequest::begin(Some(&recorder), "responses", &body).unwrap()
+            else {
+                panic!("expected to record");
+            };
+            let live = futures::stream::iter([Ok(Bytes::from(sse))]);
+            assert_eq!(collect(recording.tee(live).unwrap()).await, sse.as_bytes());
+        }
+
+        let player = cassette(CassetteMode::Replay(dir.path().to_path_buf()));
+        for (prompt, sse) in [("first", "data: one\n\n"), ("second", "data: two\n\n")] {
+            // A different cache key must not affect matching.
+            let body = json!({ "input": prompt, "prompt_cache_key": "other" });
+            let Ok(CassetteRequest::Replay(stream)) = player.begin_request("responses", &body)
+            else {
+                panic!("expected to replay");
+            };
+            assert_eq!(collect(stream).await, sse.as_bytes());
+        }
+
+        let Err(err) = player.begin_request("responses", &json!({ "input": "third" })) else {
+            panic!("expected missing recording");
+        };
+        assert!(err.to_string().contains("no recorded request 3"));
+    }
+
+    #[tokio::test]
+    async fn replay_rejects_changed_request() {
+        let dir = TempDir::new().unwrap();
+        let recorder = cassette(CassetteMode::Record(dir.path().to_path_buf()));
+        let Ok(CassetteRequest::Record(recording)) =
+            recorder.begin_request("chat", &json!({ "messages": ["hi"] }))
+        else {
+            panic!("expected to record");
+        };
+        collect(recording.tee(futures::stream::empty()).unwrap()).await;
+
+        let player = cassette(CassetteMode::Replay(dir.path().to_path_buf()));
+        let Err(err) = player.begin_request("chat", &json!({ "messages": ["bye"] })) else {
+            panic!("expected a mismatch");
+        };
+        assert!(err.to_string().contains("request 1 does not match"));
+    }
+
+    #[test]
+    fn normalizes_session_paths() {
+        let (normalized, _) = cassette(CassetteMode::Replay(PathBuf::new()))
+            .normalize(&json!({
+                "input": "cwd is /work/repo/src",
+                "prompt_cache_key": "123",
+            }))
+            .unwrap();
+
+        assert_eq!(normalized, json!({ "input": "cwd is <cwd>/src" }));
+    }
+}
@@ -16,9 +16,15 @@ use tracing::debug;
 use tracing::trace;
 
 use crate::ModelProviderInfo;
+use crate::cassette::Cassette;
+use crate::cassette::CassetteRequest;
+use crate::cassette::SseByteStream;
+use crate::client_common::MAX_RETRY_AFTER_BEFORE_FAILOVER;
+use crate::client_common::OUTPUT_SCHEMA_NAME;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
+use crate::client_common::supports_strict_mode;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::model_family::ModelFamily;
@@ -34,6 +40,8 @@ pub(crate) async fn stream_chat_completions(
     model_family: &ModelFamily,
     client: &reqwest::Client,
     provider: &ModelProviderInfo,
+    failover_available: bool,
+    cassette: Option<&Cassette>,
 ) -> Result<ResponseStream> {
     // Build messages array
     let mut messages = Vec::<serde_json::Value>::new();
@@ -268,12 +276,22 @@ pub(crate) async fn stream_chat_completions(
     }
 
     let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
-    let payload = json!({
+    let mut payload = json!({
         "model": model_family.slug,
         "messages": messages,
         "stream": true,
         "tools": tools_json,
     });
+    if let Some(schema) = &prompt.output_schema {
+        payload["response_format"] = json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": OUTPUT_SCHEMA_NAME,
+                "schema": schema,
+                "strict": supports_strict_mode(schema),
+            },
+        });
+    }
 
     debug!(
         "POST to {}: {}",
@@ -281,6 +299,20 @@ pub(crate) async fn stream_chat_completions(
         serde_json::to_string_pretty(&payload).unwrap_or_default()
     );
 
+    let recording = match CassetteRequest::begin(cassette, "chat", &payload)? {
+        Some(CassetteRequest::Replay(stream)) => {
+            let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+            tokio::spawn(process_chat_sse(
+                stream,
+                tx_event,
+                provider.stream_idle_timeout(),
+            ));
+            return Ok(ResponseStream { rx_event });
+        }
+        Some(CassetteRequest::Record(recording)) => Some(recording),
+        None => None,
+    };
+
     let mut attempt = 0;
     let max_retries = provider.request_max_retries();
     loop {
@@ -298,6 +330,10 @@ pub(crate) async fn stream_chat_completions(
             Ok(resp) if resp.status().is_success() => {
                 let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
                 let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                let stream: SseByteStream = match &recording {
+                    Some(recording) => recording.tee(stream)?,
+                    None => Box::pin(stream),
+                };
                 tokio::spawn(process_chat_sse(
                     stream,
                     tx_event,
@@ -322,6 +358,16 @@ pub(crate) async fn stream_chat_completions(
                     .and_then(|v| v.to_str().ok())
                     .and_then(|s| s.parse::<u64>().ok());
 
+                // Rather than waiting out a long rate limit, let the turn
+                // fail over to the next provider.
+                if failover_available
+                    && retry_after_secs.is_some_and(|secs| {
+                        Duration::from_secs(secs) > MAX_RETRY_AFTER_BEFORE_FAILOVER
+                    })
+                {
+                    return Err(CodexErr::RetryLimit(status));
+                }
+
                 let delay = retry_after_secs
                     .map(|s| Duration::from_millis(s * 1_000))
                     .unwrap_or_else(|| backoff(attempt));
@@ -21,8 +21,12 @@ use tracing::debug;
 use tracing::trace;
 use tracing::warn;
 
+use crate::cassette::Cassette;
+use crate::cassette::CassetteRequest;
+use crate::cassette::SseByteStream;
 use crate::chat_completions::AggregateStreamExt;
 use crate::chat_completions::stream_chat_completions;
+use crate::client_common::MAX_RETRY_AFTER_BEFORE_FAILOVER;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
@@ -30,15 +34,18 @@ use crate::client_common::ResponsesApiRequest;
 use crate::client_common::create_reasoning_param_for_request;
 use crate::client_common::create_text_param_for_request;
 use crate::config::Config;
+use crate::config_types::ModelPricing;
 use crate::default_client::create_client;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::UsageLimitReachedError;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
+use crate::messages_api::stream_messages;
 use crate::model_family::ModelFamily;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
 use crate::openai_model_info::get_model_info;
+use crate::openai_model_info::get_model_pricing;
 use crate::openai_tools::create_tools_json_for_responses_api;
 use crate::protocol::TokenUsage;
 use crate::token_data::PlanType;
@@ -74,6 +81,13 @@ pub struct ModelClient {
     conversation_id: ConversationId,
     effort: Option<ReasoningEffortConfig>,
     summary: ReasoningSummaryConfig,
+    /// Index into `config.fallback_providers` of the provider this client
+    /// talks to, or `None` for the configured provider.
+    fallback_index: Option<usize>,
+    /// Records or replays model traffic when `--record`/`--replay` is set.
+    /// Shared by every client of the session so requests are numbered in
+    /// order.
+    cassette: Option<Arc<Cassette>>,
 }
 
 impl ModelClient {
@@ -95,9 +109,51 @@ impl ModelClient {
             conversation_id,
             effort,
             summary,
+            fallback_index: None,
+            cassette: None,
         }
     }
 
+    pub(crate) fn with_cassette(mut self, cassette: Option<Arc<Cassette>>) -> Self {
+        self.cassette = cassette;
+        self
+    }
+
+    /// Returns a client for the next entry in `fallback_providers`, or `None`
+    /// when the chain is exhausted. Reasoning settings and the conversation
+    /// id carry over; the model changes only when the entry names one.
+    pub fn next_fallback(&self) -> Option<ModelClient> {
+        let index = self.fallback_index.map_or(0, |index| index + 1);
+        let fallback = self.config.fallback_providers.get(index)?;
+
+        let mut config = (*self.config).clone();
+        config.model_provider_id = fallback.provider_id.clone();
+        config.model_provider = fallback.provider.clone();
+        if let Some(model) = &fallback.model {
+            config.set_model(model);
+        }
+
+        Some(Self {
+            config: Arc::new(config),
+            provider: fallback.provider.clone(),
+            fallback_index: Some(index),
+            ..self.clone()
+        })
+    }
+
+    fn has_fallback(&self) -> bool {
+        let next = self.fallback_index.map_or(0, |index| index + 1);
+        next < self.config.fallback_providers.len()
+    }
+
+    /// Whether a `Retry-After` of `retry_after_secs` should fail the request
+    /// so that the turn fails over rather than waiting.
+    fn should_fail_over(&self, retry_after_secs: Option<u64>) -> bool {
+        self.has_fallback()
+            && retry_after_secs
+                .is_some_and(|secs| Duration::from_secs(secs) > MAX_RETRY_AFTER_BEFORE_FAILOVER)
+    }
+
     pub fn get_model_context_window(&self) -> Option<u64> {
         self.config
             .model_context_window
@@ -110,8 +166,18 @@ impl ModelClient {
         })
     }
 
-    /// Dispatches to either the Responses or Chat implementation depending on
-    /// the provider config.  Public callers always invoke `stream()` – the
+    /// Pricing for the current model: `model_pricing` from config if set for
+    /// this model, otherwise the built-in table.
+    pub fn get_model_pricing(&self) -> Option<ModelPricing> {
+        self.config
+            .model_pricing
+            .get(&self.config.model)
+            .copied()
+            .or_else(|| get_model_pricing(&self.config.model_family))
+    }
+
+    /// Dispatches to the Responses, Chat or Messages implementation depending
+    /// on the provider config.  Public callers always invoke `stream()` – the
     /// specialised helpers are private to avoid accidental misuse.
     pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
         match self.provider.wire_api {
@@ -123,6 +189,8 @@ impl ModelClient {
                     &self.config.model_family,
                     &self.client,
                     &self.provider,
+                    self.has_fallback(),
+                    self.cassette.as_deref(),
                 )
                 .await?;
 
@@ -151,6 +219,21 @@ impl ModelClient {
 
                 Ok(ResponseStream { rx_event: rx })
             }
+            WireApi::Messages => {
+                // Content blocks already arrive complete, so no aggregation
+                // adapter is needed.
+                stream_messages(
+                    prompt,
+                    &self.config.model_family,
+                    self.config.model_max_output_tokens,
+                    self.effort,
+                    &self.client,
+                    &self.provider,
+                    self.has_fallback(),
+                    self.cassette.as_deref(),
+                )
+                .await
+            }
         }
     }
 
@@ -181,8 +264,8 @@ impl ModelClient {
         let input_with_instructions = prompt.get_formatted_input();
 
         // Only include `text.verbosity` for GPT-5 family models
-        let text = if self.config.model_family.family == "gpt-5" {
-            create_text_param_for_request(self.config.model_verbosity)
+        let verbosity = if self.config.model_family.family == "gpt-5" {
+            self.config.model_verbosity
         } else {
             if self.config.model_verbosity.is_some() {
                 warn!(
@@ -192,6 +275,7 @@ impl ModelClient {
             }
             None
         };
+        let text = create_text_param_for_request(verbosity, prompt.output_schema.as_ref());
 
         // In general, we want to explicitly send `store: false` when using the Responses API,
         // but in practice, the Azure Responses API rejects `store: false`:
@@ -223,6 +307,21 @@ impl ModelClient {
         }
         let payload_body = serde_json::to_string(&payload_json)?;
 
+        let recording =
+            match CassetteRequest::begin(self.cassette.as_deref(), "responses", &payload_json)? {
+                Some(CassetteRequest::Replay(stream)) => {
+                    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                    tokio::spawn(process_sse(
+                        stream,
+                        tx_event,
+                        self.provider.stream_idle_timeout(),
+                    ));
+                    return Ok(ResponseStream { rx_event });
+                }
+                Some(CassetteRequest::Record(recording)) => Some(recording),
+                None => None,
+            };
+
         let mut attempt = 0;
         let max_retries = self.provider.request_max_retries();
 
@@ -276,6 +375,10 @@ impl ModelClient {
 
                     // spawn task to process SSE
                     let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                    let stream: SseByteStream = match &recording {
+                        Some(recording) => recording.tee(stream)?,
+                        None => Box::pin(stream),
+                    };
                     tokio::spawn(process_sse(
                         stream,
                         tx_event,
@@ -346,6 +449,10 @@ impl ModelClient {
                         return Err(CodexErr::RetryLimit(status));
                     }
 
+                    if self.should_fail_over(retry_after_secs) {
+                        return Err(CodexErr::RetryLimit(status));
+                    }
+
                     let delay = retry_after_secs
                         .map(|s| Duration::from_millis(s * 1_000))
                         .unwrap_or_else(|| backoff(attempt));
@@ -366,6 +473,11 @@ impl ModelClient {
         self.provider.clone()
     }
 
+    /// Returns the key of the provider in the `model_providers` map.
+    pub fn get_provider_id(&self) -> String {
+        self.config.model_provider_id.clone()
+    }
+
     /// Returns the currently configured model slug.
     pub fn get_model(&self) -> String {
         self.config.model.clone()
@@ -389,6 +501,11 @@ impl ModelClient {
     pub fn get_auth_manager(&self) -> Option<Arc<AuthManager>> {
         self.auth_manager.clone()
     }
+
+    /// Returns the configuration this client was built from.
+    pub(crate) fn get_config(&self) -> Arc<Config> {
+        self.config.clone()
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -9,16 +9,23 @@ use codex_protocol::config_types::Verbosity as VerbosityConfig;
 use codex_protocol::models::ResponseItem;
 use futures::Stream;
 use serde::Serialize;
+use serde_json::Value;
 use std::borrow::Cow;
 use std::pin::Pin;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 /// The `instructions` field in the payload sent to a model should always start
 /// with this content.
 const BASE_INSTRUCTIONS: &str = include_str!("../prompt.md");
 
+/// When a fallback provider is available, a rate limit whose `Retry-After`
+/// exceeds this is not waited out: the request fails so the turn can fail
+/// over instead.
+pub(crate) const MAX_RETRY_AFTER_BEFORE_FAILOVER: Duration = Duration::from_secs(30);
+
 /// Review thread system prompt. Edit `core/src/review_prompt.md` to customize.
 pub const REVIEW_PROMPT: &str = include_str!("../review_prompt.md");
 
@@ -34,6 +41,9 @@ pub struct Prompt {
 
     /// Optional override for the built-in BASE_INSTRUCTIONS.
     pub base_instructions_override: Option<String>,
+
+    /// Optional JSON Schema the final assistant message must conform to.
+    pub output_schema: Option<Value>,
 }
 
 impl Prompt {
@@ -90,11 +100,32 @@ pub(crate) struct Reasoning {
     pub(crate) summary: Option<ReasoningSummaryConfig>,
 }
 
-/// Controls under the `text` field in the Responses API for GPT-5.
-#[derive(Debug, Serialize, Default, Clone, Copy)]
+/// Name given to the structured output format derived from `output_schema`.
+pub(crate) const OUTPUT_SCHEMA_NAME: &str = "codex_output_schema";
+
+/// Controls under the `text` field in the Responses API.
+#[derive(Debug, Serialize, Default, Clone)]
 pub(crate) struct TextControls {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) verbosity: Option<OpenAiVerbosity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) format: Option<TextFormat>,
+}
+
+/// Structured output format under `text.format`.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct TextFormat {
+    pub(crate) r#type: TextFormatType,
+    pub(crate) name: &'static str,
+    pub(crate) strict: bool,
+    pub(crate) schema: Value,
+}
+
+#[derive(Debug, Serialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TextFormatType {
+    #[default]
+    JsonSchema,
 }
 
 #[derive(Debug, Serialize, Default, Clone, Copy)]
@@ -156,9 +187,68 @@ pub(crate) fn create_reasoning_param_for_request(
 
 pub(crate) fn create_text_param_for_request(
     verbosity: Option<VerbosityConfig>,
+    output_schema: Option<&Value>,
 ) -> Option<TextControls> {
-    verbosity.map(|v| TextControls {
-        verbosity: Some(v.into()),
+    if verbosity.is_none() && output_schema.is_none() {
+        return None;
+    }
+
+    Some(TextControls {
+        verbosity: verbosity.map(Into::into),
+        format: output_schema.map(|schema| TextFormat {
+            r#type: TextFormatType::JsonSchema,
+            name: OUTPUT_SCHEMA_NAME,
+            strict: supports_strict_mode(schema),
+            schema: schema.clone(),
+        }),
+    })
+}
+
+/// Whether `schema` can be sent with `strict: true`. Strict structured outputs
+/// require every object schema to set `additionalProperties: false` and list
+/// all of its properties in `required`; providers reject the request
+/// otherwise, so other schemas are sent non-strict.
+pub(crate) fn supports_strict_mode(schema: &Value) -> bool {
+    let Some(map) = schema.as_object() else {
+        // Boolean schemas and malformed entries have nothing to check here.
+        return true;
+    };
+
+    let is_object = map.get("type").is_some_and(|ty| match ty {
+        Value::String(ty) => ty == "object",
+        Value::Array(types) => types.iter().any(|ty| ty == "object"),
+        _ => false,
+    }) || map.contains_key("properties");
+    if is_object {
+        if map.get("additionalProperties") != Some(&Value::Bool(false)) {
+            return false;
+        }
+        let required: Vec<&str> = map
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        if let Some(properties) = map.get("properties").and_then(Value::as_object)
+            && properties
+                .keys()
+                .any(|name| !required.contains(&name.as_str()))
+        {
+            return false;
+        }
+    }
+
+    map.iter().all(|(keyword, value)| match keyword.as_str() {
+        "properties" | "$defs" | "definitions" => value
+            .as_object()
+            .is_none_or(|schemas| schemas.values().all(supports_strict_mode)),
+        "anyOf" | "allOf" | "oneOf" | "prefixItems" => value
+            .as_array()
+            .is_none_or(|schemas| schemas.iter().all(supports_strict_mode)),
+        "items" | "not" => match value {
+            Value::Array(schemas) => schemas.iter().all(supports_strict_mode),
+            schema => supports_strict_mode(schema),
+        },
+        _ => true,
     })
 }
 
@@ -209,6 +299,7 @@ mod tests {
             prompt_cache_key: None,
             text: Some(TextControls {
                 verbosity: Some(OpenAiVerbosity::Low),
+                format: None,
             }),
         };
 
@@ -221,6 +312,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serializes_text_format_for_output_schema() {
+        let input: Vec<ResponseItem> = vec![];
+        let tools: Vec<serde_json::Value> = vec![];
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": { "answer": { "type": "string" } },
+            "required": ["answer"],
+            "additionalProperties": false,
+        });
+        let req = ResponsesApiRequest {
+            model: "gpt-5",
+            instructions: "i",
+            input: &input,
+            tools: &tools,
+            tool_choice: "auto",
+            parallel_tool_calls: false,
+            reasoning: None,
+            store: false,
+            stream: true,
+            include: vec![],
+            prompt_cache_key: None,
+            text: create_text_param_for_request(None, Some(&schema)),
+        };
+
+        let v = serde_json::to_value(&req).expect("json");
+        assert_eq!(
+            v.get("text"),
+            Some(&serde_json::json!({
+                "format": {
+                    "type": "json_schema",
+                    "name": OUTPUT_SCHEMA_NAME,
+                    "strict": true,
+                    "schema": schema,
+                }
+            }))
+        );
+    }
+
+    #[test]
+    fn sends_non_strict_format_for_schemas_strict_mode_rejects() {
+        let strict = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" } },
+                        "required": ["name"],
+                        "additionalProperties": false,
+                    },
+                },
+            },
+            "required": ["items"],
+            "additionalProperties": false,
+        });
+        assert!(supports_strict_mode(&strict));
+
+        // A nested object with an optional property.
+        let mut optional = strict.clone();
+        optional["properties"]["items"]["items"]["properties"]["note"] =
+            serde_json::json!({ "type": "string" });
+        assert!(!supports_strict_mode(&optional));
+
+        // A top-level object that allows additional properties.
+        let mut open = strict.clone();
+        open.as_object_mut()
+            .expect("object schema")
+            .remove("additionalProperties");
+        assert!(!supports_strict_mode(&open));
+
+        let text = create_text_param_for_request(None, Some(&optional)).expect("text controls");
+        assert_eq!(text.format.map(|format| format.strict), Some(false));
+    }
+
     #[test]
     fn omits_text_when_not_set() {
         let input: Vec<ResponseItem> = vec![];
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -43,12 +44,16 @@ use crate::apply_patch::ApplyPatchExec;
 use crate::apply_patch::CODEX_APPLY_PATCH_ARG1;
 use crate::apply_patch::InternalApplyPatchInvocation;
 use crate::apply_patch::convert_apply_patch_to_protocol;
+use crate::cassette::Cassette;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::config::Config;
+use crate::config_types::ContainerSandboxConfig;
+use crate::config_types::Redaction;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::conversation_history::ConversationHistory;
+use crate::delegate::handle_delegate;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
@@ -66,11 +71,16 @@ use crate::exec_command::ExecSessionManager;
 use crate::exec_command::WRITE_STDIN_TOOL_NAME;
 use crate::exec_command::WriteStdinParams;
 use crate::exec_env::create_env;
+use crate::exec_policy::ExecPolicy;
+use crate::git_checkpoint;
+use crate::git_checkpoint::GitCheckpoint;
+use crate::git_worktree;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::mcp_tool_call::handle_mcp_tool_call;
 use crate::model_family::find_family_for_model;
 use crate::openai_model_info::get_model_info;
 use crate::openai_tools::ApplyPatchToolArgs;
+use crate::openai_tools::DELEGATE_TOOL_NAME;
 use crate::openai_tools::ToolsConfig;
 use crate::openai_tools::ToolsConfigParams;
 use crate::openai_tools::get_openai_tools;
@@ -84,6 +94,8 @@ use crate::protocol::AgentReasoningSectionBreakEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
+use crate::protocol::Checkpoint;
+use crate::protocol::CheckpointCreatedEvent;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
@@ -93,12 +105,16 @@ use crate::protocol::ExecCommandEndEvent;
 use crate::protocol::FileChange;
 use crate::protocol::InputItem;
 use crate::protocol::ListCustomPromptsResponseEvent;
+use crate::protocol::McpInvocation;
+use crate::protocol::McpToolCallApprovalRequestEvent;
 use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
 use crate::protocol::ReviewDecision;
 use crate::protocol::ReviewOutputEvent;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::SecretsRedactedEvent;
+use crate::protocol::SessionBudgetExceededEvent;
 use crate::protocol::SessionConfiguredEvent;
 use crate::protocol::StreamErrorEvent;
 use crate::protocol::Submission;
@@ -106,7 +122,10 @@ use crate::protocol::TaskCompleteEvent;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
 use crate::protocol::TurnDiffEvent;
+use crate::protocol::TurnUndoneEvent;
 use crate::protocol::WebSearchBeginEvent;
+use crate::protocol::WorktreeInfo;
+use crate::redact::Redactor;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
 use crate::safety::SafetyCheck;
@@ -175,13 +194,14 @@ pub(crate) const MODEL_FORMAT_HEAD_BYTES: usize = MODEL_FORMAT_MAX_BYTES / 2;
 impl Codex {
     /// Spawn a new [`Codex`] and initialize the session.
     pub async fn spawn(
-        config: Config,
+        mut config: Config,
         auth_manager: Arc<AuthManager>,
         conversation_history: InitialHistory,
     ) -> CodexResult<CodexSpawnOk> {
         let (tx_sub, rx_sub) = async_channel::bounded(SUBMISSION_CHANNEL_CAPACITY);
         let (tx_event, rx_event) = async_channel::unbounded();
 
+        let worktree = git_worktree::enter_worktree(&mut config, &conversation_history).await?;
         let user_instructions = get_user_instructions(&config).await;
 
         let config = Arc::new(config);
@@ -197,6 +217,7 @@ impl Codex {
             sandbox_policy: config.sandbox_policy.clone(),
             notify: config.notify.clone(),
             cwd: config.cwd.clone(),
+            worktree,
         };
 
         // Generate a unique ID for the lifetime of this Codex session.
@@ -263,12 +284,23 @@ impl Codex {
 #[derive(Default)]
 struct State {
     approved_commands: HashSet<Vec<String>>,
+    /// (server, tool) pairs the user approved for the rest of the session.
+    approved_mcp_tools: HashSet<(String, String)>,
     current_task: Option<AgentTask>,
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
-    pending_input: Vec<ResponseInputItem>,
+    /// Input submitted while a task is running. MCP items are resolved by the
+    /// task, so fetching them does not hold up the submission loop.
+    pending_input: Vec<Vec<InputItem>>,
     history: ConversationHistory,
     token_info: Option<TokenUsageInfo>,
     next_internal_sub_id: u64,
+    /// Checkpoints of turns that can still be undone, oldest first.
+    checkpoints: Vec<GitCheckpoint>,
+    /// Number of the last checkpoint created. Never reused, so an undone
+    /// checkpoint's ref cannot be mistaken for a newer one.
+    last_checkpoint_number: usize,
+    /// Set while `Op::UndoTurn` restores a checkpoint in the background.
+    undo_in_progress: bool,
 }
 
 /// Context for an initialized model agent
@@ -292,8 +324,28 @@ pub(crate) struct Session {
     rollout: Mutex<Option<RolloutRecorder>>,
     state: Mutex<State>,
     codex_linux_sandbox_exe: Option<PathBuf>,
+    /// When set, sandboxed commands run in a container instead of the
+    /// platform sandbox.
+    container_sandbox: Option<ContainerSandboxConfig>,
     user_shell: shell::Shell,
     show_raw_agent_reasoning: bool,
+
+    /// Policy consulted before asking the user to approve a command.
+    exec_policy: ExecPolicy,
+
+    /// Limits after which tasks stop instead of calling the model again.
+    max_session_tokens: Option<u64>,
+    max_session_cost: Option<f64>,
+
+    /// Scrubs secrets from tool output before it is recorded.
+    redactor: Redactor,
+
+    /// Set by `--record`/`--replay`; shared by every model client built for
+    /// this session.
+    cassette: Option<Arc<Cassette>>,
+
+    /// Whether to snapshot the git working tree before each turn.
+    checkpoints_enabled: bool,
 }
 
 /// The context needed for a single turn of the conversation.
@@ -311,10 +363,12 @@ pub(crate) struct TurnContext {
     pub(crate) shell_environment_policy: ShellEnvironmentPolicy,
     pub(crate) tools_config: ToolsConfig,
     pub(crate) is_review_mode: bool,
+    /// JSON Schema the final agent message of the turn must conform to.
+    pub(crate) final_output_json_schema: Option<serde_json::Value>,
 }
 
 impl TurnContext {
-    fn resolve_path(&self, path: Option<String>) -> PathBuf {
+    pub(crate) fn resolve_path(&self, path: Option<String>) -> PathBuf {
         path.as_ref()
             .map(PathBuf::from)
             .map_or_else(|| self.cwd.clone(), |p| self.cwd.join(p))
@@ -356,6 +410,9 @@ struct ConfigureSession {
     /// `ConfigureSession` operation so that the business-logic layer can
     /// operate deterministically.
     cwd: PathBuf,
+
+    /// The worktree `cwd` was moved into when worktree mode is on.
+    worktree: Option<WorktreeInfo>,
 }
 
 impl Session {
@@ -377,6 +434,7 @@ impl Session {
             sandbox_policy,
             notify,
             cwd,
+            worktree,
         } = configure_session;
         debug!("Configuring session: model={model}; provider={provider:?}");
         if !cwd.is_absolute() {
@@ -453,6 +511,51 @@ impl Session {
             }
         }
 
+        // Invalid user or project policy files are skipped, and the built-in
+        // policy is the last resort, so a typo does not prevent the session
+        // from starting.
+        let exec_policy = match ExecPolicy::load(&config.codex_home, &config.cwd) {
+            Ok((exec_policy, errors)) => {
+                for e in errors {
+                    let message = format!("Skipped invalid exec policy file {e}");
+                    error!("{message}");
+                    post_session_configured_error_events.push(Event {
+                        id: INITIAL_SUBMIT_ID.to_owned(),
+                        msg: EventMsg::Error(ErrorEvent { message }),
+                    });
+                }
+                exec_policy
+            }
+            Err(e) => {
+                let message = format!("Failed to load exec policy: {e:#}");
+                error!("{message}");
+                post_session_configured_error_events.push(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::Error(ErrorEvent { message }),
+                });
+                ExecPolicy::default_policy()?
+            }
+        };
+
+        // Patterns are validated when the config is loaded, so this only fails
+        // for configs constructed in code; keep the built-in detectors then.
+        let redactor = match Redactor::new(&config.redaction) {
+            Ok(redactor) => redactor,
+            Err(e) => {
+                let message = format!("Failed to compile redaction patterns: {e:#}");
+                error!("{message}");
+                post_session_configured_error_events.push(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::Error(ErrorEvent { message }),
+                });
+                Redactor::new(&Redaction::default())?
+            }
+        };
+
+        let cassette = Cassette::from_config(&config)
+            .map_err(|e| anyhow::anyhow!("failed to set up the model traffic cassette: {e:#}"))?
+            .map(Arc::new);
+
         // Now that the conversation id is final (may have been updated by resume),
         // construct the model client.
         let client = ModelClient::new(
@@ -462,7 +565,25 @@ impl Session {
             model_reasoning_effort,
             model_reasoning_summary,
             conversation_id,
-        );
+        )
+        .with_cassette(cassette.clone());
+
+        // The cost limit is computed from the model's pricing, so without it
+        // only `max_session_tokens` can stop the session.
+        if let Some(max_cost) = config.max_session_cost
+            && client.get_model_pricing().is_none()
+        {
+            let message = format!(
+                "max_session_cost (${max_cost:.2}) is not enforced: no pricing is known for model `{}`. Add it to `model_pricing` to enable the limit.",
+                config.model
+            );
+            warn!("{message}");
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Error(ErrorEvent { message }),
+            });
+        }
+
         let turn_context = TurnContext {
             client,
             tools_config: ToolsConfig::new(&ToolsConfigParams {
@@ -475,6 +596,7 @@ impl Session {
                 use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                 include_view_image_tool: config.include_view_image_tool,
                 experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
+                include_delegate_tool: config.include_delegate_tool,
             }),
             user_instructions,
             base_instructions,
@@ -483,6 +605,7 @@ impl Session {
             shell_environment_policy: config.shell_environment_policy.clone(),
             cwd,
             is_review_mode: false,
+            final_output_json_schema: config.output_schema.clone(),
         };
         let sess = Arc::new(Session {
             conversation_id,
@@ -494,8 +617,15 @@ impl Session {
             state: Mutex::new(state),
             rollout: Mutex::new(Some(rollout_recorder)),
             codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
+            container_sandbox: config.container_sandbox.clone(),
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            exec_policy,
+            max_session_tokens: config.max_session_tokens,
+            max_session_cost: config.max_session_cost,
+            redactor,
+            cassette,
+            checkpoints_enabled: config.checkpoints,
         });
 
         // Dispatch the SessionConfiguredEvent first and then report any errors.
@@ -514,6 +644,7 @@ impl Session {
                 history_entry_count,
                 initial_messages,
                 rollout_path,
+                worktree,
             }),
         })
         .chain(post_session_configured_error_events.into_iter());
@@ -651,6 +782,57 @@ impl Session {
         rx_approve
     }
 
+    pub async fn request_mcp_tool_call_approval(
+        &self,
+        sub_id: String,
+        call_id: String,
+        invocation: McpInvocation,
+        reason: Option<String>,
+    ) -> oneshot::Receiver<ReviewDecision> {
+        // Add the tx_approve callback to the map before sending the request.
+        let (tx_approve, rx_approve) = oneshot::channel();
+        let event_id = sub_id.clone();
+        let prev_entry = {
+            let mut state = self.state.lock_unchecked();
+            state.pending_approvals.insert(sub_id, tx_approve)
+        };
+        if prev_entry.is_some() {
+            warn!("Overwriting existing pending approval for sub_id: {event_id}");
+        }
+
+        let event = Event {
+            id: event_id,
+            msg: EventMsg::McpToolCallApprovalRequest(McpToolCallApprovalRequestEvent {
+                call_id,
+                invocation,
+                reason,
+            }),
+        };
+        self.send_event(event).await;
+        rx_approve
+    }
+
+    /// Re-issues an approval request raised by a sub-agent under this
+    /// session's `sub_id`, so the user answers it like any other request.
+    pub(crate) async fn request_forwarded_approval(
+        &self,
+        sub_id: String,
+        msg: EventMsg,
+    ) -> oneshot::Receiver<ReviewDecision> {
+        let (tx_approve, rx_approve) = oneshot::channel();
+        let event_id = sub_id.clone();
+        let prev_entry = {
+            let mut state = self.state.lock_unchecked();
+            state.pending_approvals.insert(sub_id, tx_approve)
+        };
+        if prev_entry.is_some() {
+            warn!("Overwriting existing pending approval for sub_id: {event_id}");
+        }
+
+        self.send_event(Event { id: event_id, msg }).await;
+        rx_approve
+    }
+
     pub fn notify_approval(&self, sub_id: &str, decision: ReviewDecision) {
         let entry = {
             let mut state = self.state.lock_unchecked();
@@ -671,6 +853,25 @@ impl Session {
         state.approved_commands.insert(cmd);
     }
 
+    pub fn add_approved_mcp_tool(&self, server: String, tool: String) {
+        let mut state = self.state.lock_unchecked();
+        state.approved_mcp_tools.insert((server, tool));
+    }
+
+    /// Whether calling `tool` on `server` must first be approved by the user.
+    pub fn mcp_tool_requires_approval(&self, server: &str, tool: &str) -> bool {
+        if !self
+            .mcp_connection_manager
+            .tool_requires_approval(server, tool)
+        {
+            return false;
+        }
+        let state = self.state.lock_unchecked();
+        !state
+            .approved_mcp_tools
+            .contains(&(server.to_string(), tool.to_string()))
+    }
+
     /// Records input items: always append to conversation history and
     /// persist these response items to rollout.
     async fn record_conversation_items(&self, items: &[ResponseItem]) {
@@ -723,19 +924,71 @@ impl Session {
 
     fn update_token_usage_info(
         &self,
-        turn_context: &TurnContext,
+        client: &ModelClient,
         token_usage: &Option<TokenUsage>,
     ) -> Option<TokenUsageInfo> {
         let mut state = self.state.lock_unchecked();
-        let info = TokenUsageInfo::new_or_append(
+        let mut info = TokenUsageInfo::new_or_append(
             &state.token_info,
             token_usage,
-            turn_context.client.get_model_context_window(),
+            client.get_model_context_window(),
         );
+        // Price each response with the model that produced it, since a
+        // fallback provider may be serving a different model.
+        if let (Some(info), Some(usage), Some(pricing)) =
+            (info.as_mut(), token_usage, client.get_model_pricing())
+        {
+            info.add_cost(pricing.cost_usd(usage));
+        }
         state.token_info = info.clone();
         info
     }
 
+    /// Adds a sub-agent's response usage to the session totals so delegated
+    /// work counts against the session budget. The last usage is left alone
+    /// because it describes the parent's own context window.
+    pub(crate) fn add_sub_agent_token_usage(
+        &self,
+        usage: &TokenUsage,
+        cost_usd: Option<f64>,
+    ) -> TokenUsageInfo {
+        let mut state = self.state.lock_unchecked();
+        let info = state.token_info.get_or_insert_with(|| TokenUsageInfo {
+            total_token_usage: TokenUsage::default(),
+            last_token_usage: TokenUsage::default(),
+            model_context_window: None,
+            total_cost_usd: None,
+        });
+        info.total_token_usage.add_assign(usage);
+        if let Some(cost_usd) = cost_usd {
+            info.add_cost(cost_usd);
+        }
+        info.clone()
+    }
+
+    /// Returns a description of the session limit that has been reached, if
+    /// any.
+    pub(crate) fn exceeded_session_budget(&self) -> Option<String> {
+        let state = self.state.lock_unchecked();
+        let info = state.token_info.as_ref()?;
+        let total_tokens = info.total_token_usage.total_tokens;
+        if let Some(max_tokens) = self.max_session_tokens
+            && total_tokens >= max_tokens
+        {
+            return Some(format!(
+                "Session token limit reached: used {total_tokens} of {max_tokens} tokens (max_session_tokens)."
+            ));
+        }
+        if let (Some(max_cost), Some(cost)) = (self.max_session_cost, info.total_cost_usd)
+            && cost >= max_cost
+        {
+            return Some(format!(
+                "Session cost limit reached: spent ${cost:.2} of ${max_cost:.2} (max_session_cost)."
+            ));
+        }
+        None
+    }
+
     /// Record a user input item to conversation history and also persist a
     /// corresponding UserMessage EventMsg to rollout.
     async fn record_input_and_rollout_usermsg(&self, response_input: &ResponseInputItem) {
@@ -883,6 +1136,7 @@ impl Session {
             exec_args.sandbox_type,
             exec_args.sandbox_policy,
             exec_args.codex_linux_sandbox_exe,
+            exec_args.container_sandbox,
             exec_args.stdout_stream,
         )
         .await;
@@ -913,6 +1167,22 @@ impl Session {
         result
     }
 
+    /// Replace MCP resources and prompts in `items` with their contents and
+    /// convert the result to model input, reporting any that could not be
+    /// fetched as background events.
+    async fn resolve_mcp_input(
+        &self,
+        sub_id: &str,
+        items: Vec<InputItem>,
+    ) -> Vec<ResponseInputItem> {
+        let (items, errors) =
+            crate::mcp_input::resolve_mcp_input_items(&self.mcp_connection_manager, items).await;
+        for error in errors {
+            self.notify_background_event(sub_id, error).await;
+        }
+        items
+    }
+
     /// Helper that emits a BackgroundEvent with the given message. This keeps
     /// the call‑sites terse so adding more diagnostics does not clutter the
     /// core agent logic.
@@ -946,14 +1216,14 @@ impl Session {
     pub fn inject_input(&self, input: Vec<InputItem>) -> Result<(), Vec<InputItem>> {
         let mut state = self.state.lock_unchecked();
         if state.current_task.is_some() {
-            state.pending_input.push(input.into());
+            state.pending_input.push(input);
             Ok(())
         } else {
             Err(input)
         }
     }
 
-    pub fn get_pending_input(&self) -> Vec<ResponseInputItem> {
+    pub fn get_pending_input(&self) -> Vec<Vec<InputItem>> {
         let mut state = self.state.lock_unchecked();
         if state.pending_input.is_empty() {
             Vec::with_capacity(0)
@@ -986,6 +1256,86 @@ impl Session {
         }
     }
 
+    /// Snapshots the workspace before a turn so the turn can be undone.
+    /// Failures are logged and do not stop the turn.
+    async fn create_checkpoint(&self, sub_id: &str, cwd: &Path, summary: String) {
+        if !self.checkpoints_enabled {
+            return;
+        }
+        let number = {
+            let mut state = self.state.lock_unchecked();
+            state.last_checkpoint_number += 1;
+            state.last_checkpoint_number
+        };
+        match git_checkpoint::create_checkpoint(cwd, self.conversation_id, number, summary).await {
+            Ok(Some(checkpoint)) => {
+                let event = Event {
+                    id: sub_id.to_string(),
+                    msg: EventMsg::CheckpointCreated(CheckpointCreatedEvent {
+                        checkpoint: checkpoint.checkpoint.clone(),
+                    }),
+                };
+                self.state.lock_unchecked().checkpoints.push(checkpoint);
+                self.send_event(event).await;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("failed to create checkpoint: {e}"),
+        }
+    }
+
+    /// Takes the checkpoint of the most recent turn that has not been undone
+    /// yet and marks an undo as running. Called from the submission loop so
+    /// that no turn can start between this check and the restore.
+    fn begin_undo(&self) -> Result<GitCheckpoint, String> {
+        let mut state = self.state.lock_unchecked();
+        if state.current_task.is_some() {
+            return Err("Cannot undo while a turn is running.".to_string());
+        }
+        if state.undo_in_progress {
+            return Err("Another undo is still running.".to_string());
+        }
+        let checkpoint = state
+            .checkpoints
+            .pop()
+            .ok_or_else(|| "No turn to undo.".to_string())?;
+        state.undo_in_progress = true;
+        Ok(checkpoint)
+    }
+
+    /// Restores the workspace to `checkpoint`, taken by [`Self::begin_undo`].
+    async fn undo_turn(&self, checkpoint: GitCheckpoint) -> Result<Checkpoint, String> {
+        let restored = git_checkpoint::restore_checkpoint(&checkpoint, self.conversation_id).await;
+        {
+            let mut state = self.state.lock_unchecked();
+            state.undo_in_progress = false;
+            if let Err(e) = restored {
+                state.checkpoints.push(checkpoint);
+                return Err(format!("Failed to undo turn: {e}"));
+            }
+        }
+        // The conversation is not rewound, so tell the model its view of the
+        // files is out of date.
+        self.record_conversation_items(&[git_checkpoint::undo_notice(&checkpoint.checkpoint)])
+            .await;
+        Ok(checkpoint.checkpoint)
+    }
+
+    /// Reports an error and returns `true` if an undo is restoring the
+    /// workspace, as a new turn would run against a half-restored tree.
+    async fn reject_turn_during_undo(&self, sub_id: &str) -> bool {
+        if !self.state.lock_unchecked().undo_in_progress {
+            return false;
+        }
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::Error(ErrorEvent {
+                message: "Cannot start a turn while an undo is running.".to_string(),
+            }),
+        })
+        .await;
+        true
+    }
+
     /// Spawn the configured notifier (if any) with the given JSON payload as
     /// the last argument. Failures are logged but otherwise ignored so that
     /// notification issues do not interfere with the main workflow.
@@ -1197,7 +1547,8 @@ async fn submission_loop(
                     effective_effort,
                     effective_summary,
                     sess.conversation_id,
-                );
+                )
+                .with_cassette(sess.cassette.clone());
 
                 let new_approval_policy = approval_policy.unwrap_or(prev.approval_policy);
                 let new_sandbox_policy = sandbox_policy
@@ -1215,6 +1566,7 @@ async fn submission_loop(
                     use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                     include_view_image_tool: config.include_view_image_tool,
                     experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
+                    include_delegate_tool: config.include_delegate_tool,
                 });
 
                 let new_turn_context = TurnContext {
@@ -1227,6 +1579,7 @@ async fn submission_loop(
                     shell_environment_policy: prev.shell_environment_policy.clone(),
                     cwd: new_cwd.clone(),
                     is_review_mode: false,
+                    final_output_json_schema: prev.final_output_json_schema.clone(),
                 };
 
                 // Install the new persistent context for subsequent tasks/turns.
@@ -1247,6 +1600,9 @@ async fn submission_loop(
             Op::UserInput { items } => {
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items) {
+                    if sess.reject_turn_during_undo(&sub.id).await {
+                        continue;
+                    }
                     // no current task, spawn a new one
                     let task =
                         AgentTask::spawn(sess.clone(), Arc::clone(&turn_context), sub.id, items);
@@ -1264,6 +1620,9 @@ async fn submission_loop(
             } => {
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items) {
+                    if sess.reject_turn_during_undo(&sub.id).await {
+                        continue;
+                    }
                     // Derive a fresh TurnContext for this turn using the provided overrides.
                     let provider = turn_context.client.get_provider();
                     let auth_manager = turn_context.client.get_auth_manager();
@@ -1289,7 +1648,8 @@ async fn submission_loop(
                         effort,
                         summary,
                         sess.conversation_id,
-                    );
+                    )
+                    .with_cassette(sess.cassette.clone());
 
                     let fresh_turn_context = TurnContext {
                         client,
@@ -1305,6 +1665,7 @@ async fn submission_loop(
                             include_view_image_tool: config.include_view_image_tool,
                             experimental_unified_exec_tool: config
                                 .use_experimental_unified_exec_tool,
+                            include_delegate_tool: config.include_delegate_tool,
                         }),
                         user_instructions: turn_context.user_instructions.clone(),
                         base_instructions: turn_context.base_instructions.clone(),
@@ -1313,6 +1674,7 @@ async fn submission_loop(
                         shell_environment_policy: turn_context.shell_environment_policy.clone(),
                         cwd,
                         is_review_mode: false,
+                        final_output_json_schema: turn_context.final_output_json_schema.clone(),
                     };
                     // TODO: record the new environment context in the conversation history
                     // no current task, spawn a new one with the per‑turn context
@@ -1327,6 +1689,12 @@ async fn submission_loop(
                 }
                 other => sess.notify_approval(&id, other),
             },
+            Op::McpToolCallApproval { id, decision } => match decision {
+                ReviewDecision::Abort => {
+                    sess.interrupt_task();
+                }
+                other => sess.notify_approval(&id, other),
+            },
             Op::PatchApproval { id, decision } => match decision {
                 ReviewDecision::Abort => {
                     sess.interrupt_task();
@@ -1368,6 +1736,7 @@ async fn submission_loop(
                                         conversation_id: e.session_id,
                                         ts: e.ts,
                                         text: e.text,
+                                        cwd: e.cwd,
                                     }
                                 }),
                             },
@@ -1377,18 +1746,147 @@ async fn submission_loop(
                     sess_clone.send_event(event).await;
                 });
             }
+            Op::ListHistoryEntries => {
+                let config = config.clone();
+                let sess_clone = sess.clone();
+                let sub_id = sub.id.clone();
+
+                tokio::spawn(async move {
+                    // Reading the whole file takes a shared lock, so keep it off the runtime.
+                    let entries = tokio::task::spawn_blocking(move || {
+                        crate::message_history::load_entries(&config)
+                    })
+                    .await
+                    .unwrap_or_default();
+
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::ListHistoryEntriesResponse(
+                            crate::protocol::ListHistoryEntriesResponseEvent {
+                                entries: entries
+                                    .into_iter()
+                                    .map(|e| codex_protocol::message_history::HistoryEntry {
+                                        conversation_id: e.session_id,
+                                        ts: e.ts,
+                                        text: e.text,
+                                        cwd: e.cwd,
+                                    })
+                                    .collect(),
+                            },
+                        ),
+                    };
+
+                    sess_clone.send_event(event).await;
+                });
+            }
             Op::ListMcpTools => {
                 let sub_id = sub.id.clone();
 
-                // This is a cheap lookup from the connection manager's cache.
-                let tools = sess.mcp_connection_manager.list_all_tools();
-                let event = Event {
-                    id: sub_id,
-                    msg: EventMsg::McpListToolsResponse(
-                        crate::protocol::McpListToolsResponseEvent { tools },
-                    ),
-                };
-                sess.send_event(event).await;
+                // Tools are cached by the connection manager, but a reload
+                // submitted earlier may still be starting servers.
+                let reloaded = sess.mcp_connection_manager.wait_for_reloads();
+                let sess_clone = sess.clone();
+                tokio::spawn(async move {
+                    reloaded.await;
+                    let tools = sess_clone.mcp_connection_manager.list_all_tools();
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::McpListToolsResponse(
+                            crate::protocol::McpListToolsResponseEvent { tools },
+                        ),
+                    };
+                    sess_clone.send_event(event).await;
+                });
+            }
+            Op::ReloadMcpServers => {
+                let sub_id = sub.id.clone();
+
+                // Starting servers can take up to their startup timeout, so
+                // reload off the submission loop to keep handling Interrupt
+                // and approvals. Listing ops submitted afterwards wait for it.
+                let pending = sess.mcp_connection_manager.begin_reload();
+                let sess_clone = sess.clone();
+                let config = Arc::clone(&config);
+                tokio::spawn(async move {
+                    let sess = sess_clone;
+                    match crate::config::load_config_as_toml_with_cli_overrides(
+                        &config.codex_home,
+                        config.cli_overrides.clone(),
+                    ) {
+                        Ok(config_toml) => {
+                            let errors = sess
+                                .mcp_connection_manager
+                                .reload(pending, config_toml.mcp_servers)
+                                .await;
+                            for (server_name, err) in errors {
+                                let message = format!(
+                                    "MCP client for `{server_name}` failed to start: {err:#}"
+                                );
+                                error!("{message}");
+                                sess.send_event(Event {
+                                    id: sub_id.clone(),
+                                    msg: EventMsg::Error(ErrorEvent { message }),
+                                })
+                                .await;
+                            }
+                        }
+                        Err(e) => {
+                            drop(pending);
+                            let message = format!("Failed to reload MCP servers: {e:#}");
+                            error!("{message}");
+                            sess.send_event(Event {
+                                id: sub_id.clone(),
+                                msg: EventMsg::Error(ErrorEvent { message }),
+                            })
+                            .await;
+                        }
+                    }
+
+                    let tools = sess.mcp_connection_manager.list_all_tools();
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::McpListToolsResponse(
+                            crate::protocol::McpListToolsResponseEvent { tools },
+                        ),
+                    };
+                    sess.send_event(event).await;
+                });
+            }
+            Op::ListMcpResources => {
+                let sub_id = sub.id.clone();
+
+                // Unlike tools, resources are not cached: ask the servers
+                // off the submission loop, after any pending reload.
+                let reloaded = sess.mcp_connection_manager.wait_for_reloads();
+                let sess_clone = sess.clone();
+                tokio::spawn(async move {
+                    reloaded.await;
+                    let resources = sess_clone.mcp_connection_manager.list_all_resources().await;
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::McpListResourcesResponse(
+                            crate::protocol::McpListResourcesResponseEvent { resources },
+                        ),
+                    };
+                    sess_clone.send_event(event).await;
+                });
+            }
+            Op::ListMcpPrompts => {
+                let sub_id = sub.id.clone();
+
+                let reloaded = sess.mcp_connection_manager.wait_for_reloads();
+                let sess_clone = sess.clone();
+                tokio::spawn(async move {
+                    reloaded.await;
+                    let prompts = sess_clone.mcp_connection_manager.list_all_prompts().await;
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::McpListPromptsResponse(
+                            crate::protocol::McpListPromptsResponseEvent { prompts },
+                        ),
+                    };
+                    sess_clone.send_event(event).await;
+                });
             }
             Op::ListCustomPrompts => {
                 let sub_id = sub.id.clone();
@@ -1413,6 +1911,9 @@ async fn submission_loop(
                 if let Err(items) = sess.inject_input(vec![InputItem::Text {
                     text: compact::COMPACT_TRIGGER_TEXT.to_string(),
                 }]) {
+                    if sess.reject_turn_during_undo(&sub.id).await {
+                        continue;
+                    }
                     compact::spawn_compact_task(
                         sess.clone(),
                         Arc::clone(&turn_context),
@@ -1421,9 +1922,35 @@ async fn submission_loop(
                     );
                 }
             }
+            Op::UndoTurn => {
+                let checkpoint = match sess.begin_undo() {
+                    Ok(checkpoint) => checkpoint,
+                    Err(message) => {
+                        sess.send_event(Event {
+                            id: sub.id,
+                            msg: EventMsg::Error(ErrorEvent { message }),
+                        })
+                        .await;
+                        continue;
+                    }
+                };
+                // Restoring a large tree takes a while; keep handling ops
+                // such as Interrupt meanwhile.
+                let sess = sess.clone();
+                tokio::spawn(async move {
+                    let msg = match sess.undo_turn(checkpoint).await {
+                        Ok(checkpoint) => EventMsg::TurnUndone(TurnUndoneEvent { checkpoint }),
+                        Err(message) => EventMsg::Error(ErrorEvent { message }),
+                    };
+                    sess.send_event(Event { id: sub.id, msg }).await;
+                });
+            }
             Op::Shutdown => {
                 info!("Shutting down Codex instance");
 
+                let checkpoints = std::mem::take(&mut sess.state.lock_unchecked().checkpoints);
+                git_checkpoint::delete_checkpoints(&checkpoints).await;
+
                 // Gracefully flush and shutdown rollout recorder on session end so tests
                 // that inspect the rollout file do not race with the background writer.
                 let recorder_opt = sess.rollout.lock_unchecked().take();
@@ -1475,6 +2002,9 @@ async fn submission_loop(
                 sess.send_event(event).await;
             }
             Op::Review { review_request } => {
+                if sess.reject_turn_during_undo(&sub.id).await {
+                    continue;
+                }
                 spawn_review_thread(
                     sess.clone(),
                     config.clone(),
@@ -1513,6 +2043,7 @@ async fn spawn_review_thread(
         use_streamable_shell_tool: false,
         include_view_image_tool: false,
         experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
+        include_delegate_tool: false,
     });
 
     let base_instructions = Some(REVIEW_PROMPT.to_string());
@@ -1535,7 +2066,8 @@ async fn spawn_review_thread(
         parent_turn_context.client.get_reasoning_effort(),
         parent_turn_context.client.get_reasoning_summary(),
         sess.conversation_id,
-    );
+    )
+    .with_cassette(sess.cassette.clone());
 
     let review_turn_context = TurnContext {
         client,
@@ -1547,6 +2079,7 @@ async fn spawn_review_thread(
         shell_environment_policy: parent_turn_context.shell_environment_policy.clone(),
         cwd: parent_turn_context.cwd.clone(),
         is_review_mode: true,
+        final_output_json_schema: None,
     };
 
     // Seed the child task with the review prompt as the initial user message.
@@ -1594,6 +2127,30 @@ async fn run_task(
     if input.is_empty() {
         return;
     }
+    let checkpoint_summary = git_checkpoint::summarize_input(&input);
+    let input = sess.resolve_mcp_input(&sub_id, input).await;
+    if input.is_empty() {
+        // Every item was an MCP prompt or resource that failed to resolve
+        // (each failure has been reported). End the task so that later input
+        // starts a new turn instead of queueing behind this one.
+        sess.send_event(Event {
+            id: sub_id.clone(),
+            msg: EventMsg::Error(ErrorEvent {
+                message: "None of the input could be resolved; nothing was sent to the model."
+                    .to_string(),
+            }),
+        })
+        .await;
+        sess.remove_task(&sub_id);
+        sess.send_event(Event {
+            id: sub_id,
+            msg: EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message: None,
+            }),
+        })
+        .await;
+        return;
+    }
     let event = Event {
         id: sub_id.clone(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
@@ -1602,34 +2159,51 @@ async fn run_task(
     };
     sess.send_event(event).await;
 
-    let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
+    if !turn_context.is_review_mode {
+        sess.create_checkpoint(&sub_id, &turn_context.cwd, checkpoint_summary)
+            .await;
+    }
+
     // For review threads, keep an isolated in-memory history so the
     // model sees a fresh conversation without the parent session's history.
     // For normal turns, continue recording to the session history as before.
     let is_review_mode = turn_context.is_review_mode;
     let mut review_thread_history: Vec<ResponseItem> = Vec::new();
-    if is_review_mode {
-        review_thread_history.push(initial_input_for_turn.into());
-    } else {
-        sess.record_input_and_rollout_usermsg(&initial_input_for_turn)
-            .await;
+    for initial_input_for_turn in input {
+        if is_review_mode {
+            review_thread_history.push(initial_input_for_turn.into());
+        } else {
+            sess.record_input_and_rollout_usermsg(&initial_input_for_turn)
+                .await;
+        }
     }
 
     let mut last_agent_message: Option<String> = None;
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
     let mut turn_diff_tracker = TurnDiffTracker::new();
+    // Replaced by `run_turn` when the task fails over to a fallback provider.
+    let mut client = turn_context.client.clone();
     let mut auto_compact_recently_attempted = false;
 
     loop {
+        if let Some(message) = sess.exceeded_session_budget() {
+            sess.send_event(Event {
+                id: sub_id.clone(),
+                msg: EventMsg::SessionBudgetExceeded(SessionBudgetExceededEvent { message }),
+            })
+            .await;
+            break;
+        }
+
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
-        let pending_input = sess
-            .get_pending_input()
-            .into_iter()
-            .map(ResponseItem::from)
-            .collect::<Vec<ResponseItem>>();
+        let mut pending_input = Vec::new();
+        for items in sess.get_pending_input() {
+            let resolved = sess.resolve_mcp_input(&sub_id, items).await;
+            pending_input.extend(resolved.into_iter().map(ResponseItem::from));
+        }
 
         // Construct the input that we will send to the model.
         //
@@ -1667,6 +2241,7 @@ async fn run_task(
         match run_turn(
             &sess,
             turn_context.as_ref(),
+            &mut client,
             &mut turn_diff_tracker,
             sub_id.clone(),
             turn_input,
@@ -1781,6 +2356,17 @@ async fn run_task(
                     }
                 }
 
+                let redacted = sess
+                    .redactor
+                    .redact_tool_outputs(&mut items_to_record_in_conversation_history);
+                if redacted > 0 {
+                    sess.send_event(Event {
+                        id: sub_id.clone(),
+                        msg: EventMsg::SecretsRedacted(SecretsRedactedEvent { count: redacted }),
+                    })
+                    .await;
+                }
+
                 // Only attempt to take the lock if there is something to record.
                 if !items_to_record_in_conversation_history.is_empty() {
                     if is_review_mode {
@@ -1893,36 +2479,84 @@ fn parse_review_output_event(text: &str) -> ReviewOutputEvent {
     }
 }
 
+/// `client` starts out as the turn context's client and is replaced when the
+/// turn fails over to a fallback provider, so later turns of the same task
+/// stay on the provider that worked.
 async fn run_turn(
     sess: &Session,
     turn_context: &TurnContext,
+    client: &mut ModelClient,
     turn_diff_tracker: &mut TurnDiffTracker,
     sub_id: String,
     input: Vec<ResponseItem>,
 ) -> CodexResult<TurnRunResult> {
-    let tools = get_openai_tools(
-        &turn_context.tools_config,
-        Some(sess.mcp_connection_manager.list_all_tools()),
-    );
+    // Start restarting crashed MCP servers in the background and pick up tool
+    // list changes so the model only sees tools that can actually be called
+    // this turn.
+    for message in sess.mcp_connection_manager.refresh().await {
+        sess.notify_background_event(&sub_id, message).await;
+    }
 
-    let prompt = Prompt {
-        input,
-        tools,
-        base_instructions_override: turn_context.base_instructions.clone(),
-    };
+    let mut prompt = turn_prompt(sess, turn_context, client, input);
 
     let mut retries = 0;
     loop {
-        match try_run_turn(sess, turn_context, turn_diff_tracker, &sub_id, &prompt).await {
+        match try_run_turn(
+            sess,
+            turn_context,
+            client,
+            turn_diff_tracker,
+            &sub_id,
+            &prompt,
+        )
+        .await
+        {
             Ok(output) => return Ok(output),
             Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
             Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
-            Err(e @ (CodexErr::UsageLimitReached(_) | CodexErr::UsageNotIncluded)) => {
+            Err(
+                e @ (CodexErr::UsageLimitReached(_)
+                | CodexErr::UsageNotIncluded
+                | CodexErr::UnsupportedByWireApi(_)
+                | CodexErr::Cassette(_)),
+            ) => {
                 return Err(e);
             }
             Err(e) => {
                 // Use the configured provider-specific stream retry budget.
-                let max_retries = turn_context.client.get_provider().stream_max_retries();
+                let max_retries = client.get_provider().stream_max_retries();
+
+                // Failed requests were already retried by the client, so
+                // move on to the next provider right away if there is one.
+                // Dropped streams fail over once the retry budget is spent.
+                let provider_failed = match &e {
+                    CodexErr::RetryLimit(_)
+                    | CodexErr::InternalServerError
+                    | CodexErr::Reqwest(_) => true,
+                    CodexErr::Stream(..) => retries >= max_retries,
+                    _ => false,
+                };
+                if provider_failed && let Some(next_client) = client.next_fallback() {
+                    let message = format!(
+                        "model provider `{}` ({}) failed: {e}; switching to `{}` ({})",
+                        client.get_provider_id(),
+                        client.get_model(),
+                        next_client.get_provider_id(),
+                        next_client.get_model(),
+                    );
+                    warn!("{message}");
+                    sess.notify_background_event(&sub_id, message).await;
+                    *client = next_client;
+                    prompt = turn_prompt(
+                        sess,
+                        turn_context,
+                        client,
+                        std::mem::take(&mut prompt.input),
+                    );
+                    retries = 0;
+                    continue;
+                }
+
                 if retries < max_retries {
                     retries += 1;
                     let delay = match e {
@@ -1953,6 +2587,32 @@ async fn run_turn(
     }
 }
 
+/// Builds the prompt for a turn sent through `client`. The tools are chosen
+/// for the client's model, which differs from the session's once the turn
+/// has failed over to a fallback provider.
+fn turn_prompt(
+    sess: &Session,
+    turn_context: &TurnContext,
+    client: &ModelClient,
+    input: Vec<ResponseItem>,
+) -> Prompt {
+    let tools_config = turn_context.tools_config.for_model_family(
+        &client.get_model_family(),
+        client.get_config().include_apply_patch_tool,
+    );
+    let tools = get_openai_tools(
+        &tools_config,
+        Some(sess.mcp_connection_manager.list_all_tools()),
+    );
+
+    Prompt {
+        input,
+        tools,
+        base_instructions_override: turn_context.base_instructions.clone(),
+        output_schema: turn_context.final_output_json_schema.clone(),
+    }
+}
+
 /// When the model is prompted, it returns a stream of events. Some of these
 /// events map to a `ResponseItem`. A `ResponseItem` may need to be
 /// "handled" such that it produces a `ResponseInputItem` that needs to be
@@ -1972,6 +2632,7 @@ struct TurnRunResult {
 async fn try_run_turn(
     sess: &Session,
     turn_context: &TurnContext,
+    client: &ModelClient,
     turn_diff_tracker: &mut TurnDiffTracker,
     sub_id: &str,
     prompt: &Prompt,
@@ -2035,12 +2696,13 @@ async fn try_run_turn(
         cwd: turn_context.cwd.clone(),
         approval_policy: turn_context.approval_policy,
         sandbox_policy: turn_context.sandbox_policy.clone(),
-        model: turn_context.client.get_model(),
-        effort: turn_context.client.get_reasoning_effort(),
-        summary: turn_context.client.get_reasoning_summary(),
+        model: client.get_model(),
+        model_provider: Some(client.get_provider_id()),
+        effort: client.get_reasoning_effort(),
+        summary: client.get_reasoning_summary(),
     });
     sess.persist_rollout_items(&[rollout_item]).await;
-    let mut stream = turn_context.client.clone().stream(&prompt).await?;
+    let mut stream = client.clone().stream(&prompt).await?;
 
     let mut output = Vec::new();
 
@@ -2093,7 +2755,7 @@ async fn try_run_turn(
                 response_id: _,
                 token_usage,
             } => {
-                let info = sess.update_token_usage_info(turn_context, &token_usage);
+                let info = sess.update_token_usage_info(client, &token_usage);
                 let _ = sess
                     .send_event(Event {
                         id: sub_id.to_string(),
@@ -2471,6 +3133,17 @@ async fn handle_function_call(
             .await
         }
         "update_plan" => handle_update_plan(sess, arguments, sub_id, call_id).await,
+        DELEGATE_TOOL_NAME => {
+            handle_delegate(
+                sess,
+                turn_context,
+                turn_diff_tracker,
+                sub_id,
+                call_id,
+                arguments,
+            )
+            .await
+        }
         EXEC_COMMAND_TOOL_NAME => {
             // TODO(mbolin): Sandbox check.
             let exec_params = match serde_json::from_str::<ExecCommandParams>(&arguments) {
@@ -2525,7 +3198,14 @@ async fn handle_function_call(
                     // TODO(mbolin): Determine appropriate timeout for tool call.
                     let timeout = None;
                     handle_mcp_tool_call(
-                        sess, &sub_id, call_id, server, tool_name, arguments, timeout,
+                        sess,
+                        &sub_id,
+                        call_id,
+                        server,
+                        tool_name,
+                        arguments,
+                        timeout,
+                        turn_context.approval_policy,
                     )
                     .await
                 }
@@ -2601,7 +3281,10 @@ fn to_exec_params(params: ShellToolCallParams, turn_context: &TurnContext) -> Ex
         command: params.command,
         cwd: turn_context.resolve_path(params.workdir.clone()),
         timeout_ms: params.timeout_ms,
-        env: create_env(&turn_context.shell_environment_policy),
+        env: create_env(
+            &turn_context.shell_environment_policy,
+            &turn_context.sandbox_policy,
+        ),
         with_escalated_permissions: params.with_escalated_permissions,
         justification: params.justification,
     }
@@ -2634,6 +3317,7 @@ pub struct ExecInvokeArgs<'a> {
     pub sandbox_type: SandboxType,
     pub sandbox_policy: &'a SandboxPolicy,
     pub codex_linux_sandbox_exe: &'a Option<PathBuf>,
+    pub container_sandbox: Option<&'a ContainerSandboxConfig>,
     pub stdout_stream: Option<StdoutStream>,
 }
 
@@ -2744,6 +3428,7 @@ async fn handle_container_exec_with_params(
                 let state = sess.state.lock_unchecked();
                 assess_command_safety(
                     &params.command,
+                    &sess.exec_policy,
                     turn_context.approval_policy,
                     &turn_context.sandbox_policy,
                     &state.approved_commands,
@@ -2797,6 +3482,36 @@ async fn handle_container_exec_with_params(
                 },
             };
         }
+        SafetyCheck::Forbidden { reason } => {
+            sess.notify_background_event(
+                &sub_id,
+                format!("Command forbidden by exec policy: {reason}"),
+            )
+            .await;
+            // Hand the policy's reason to the model so it can pick an
+            // alternative the policy allows instead of retrying.
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content: format!(
+                        "exec command forbidden by policy: {reason}. Do not retry this command; use an alternative that the policy allows."
+                    ),
+                    success: Some(false),
+                },
+            };
+        }
+    };
+
+    // Shell commands use the container backend in place of the platform
+    // sandbox. apply_patch keeps the platform sandbox because it re-executes
+    // the host's codex binary, which a container image may not provide.
+    let sandbox_type = match sandbox_type {
+        SandboxType::MacosSeatbelt | SandboxType::LinuxSeccomp
+            if sess.container_sandbox.is_some() && apply_patch_exec.is_none() =>
+        {
+            SandboxType::Container
+        }
+        other => other,
     };
 
     let exec_command_context = ExecCommandContext {
@@ -2825,6 +3540,7 @@ async fn handle_container_exec_with_params(
                 sandbox_type,
                 sandbox_policy: &turn_context.sandbox_policy,
                 codex_linux_sandbox_exe: &sess.codex_linux_sandbox_exe,
+                container_sandbox: sess.container_sandbox.as_ref(),
                 stdout_stream: if exec_command_context.apply_patch.is_some() {
                     None
                 } else {
@@ -2962,6 +3678,7 @@ async fn handle_sandbox_error(
                         sandbox_type: SandboxType::None,
                         sandbox_policy: &turn_context.sandbox_policy,
                         codex_linux_sandbox_exe: &sess.codex_linux_sandbox_exe,
+                        container_sandbox: sess.container_sandbox.as_ref(),
                         stdout_stream: if exec_command_context.apply_patch.is_some() {
                             None
                         } else {
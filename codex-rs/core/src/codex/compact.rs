@@ -115,6 +115,7 @@ async fn run_compact_task_inner(
         input: turn_input,
         tools: Vec::new(),
         base_instructions_override: Some(instructions_override),
+        output_schema: None,
     };
 
     let max_retries = turn_context.client.get_provider().stream_max_retries();
@@ -125,6 +126,7 @@ async fn run_compact_task_inner(
         approval_policy: turn_context.approval_policy,
         sandbox_policy: turn_context.sandbox_policy.clone(),
         model: turn_context.client.get_model(),
+        model_provider: Some(turn_context.client.get_provider_id()),
         effort: turn_context.client.get_reasoning_effort(),
         summary: turn_context.client.get_reasoning_summary(),
     });
@@ -1,7 +1,12 @@
 use crate::config_profile::ConfigProfile;
+use crate::config_types::CassetteMode;
+use crate::config_types::ContainerSandboxConfig;
 use crate::config_types::History;
 use crate::config_types::McpServerConfig;
+use crate::config_types::ModelPricing;
 use crate::config_types::ReasoningSummaryFormat;
+use crate::config_types::Redaction;
+use crate::config_types::SandboxBackend;
 use crate::config_types::SandboxWorkspaceWrite;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
@@ -9,7 +14,7 @@ use crate::config_types::Tui;
 use crate::config_types::UriBasedFileOpener;
 use crate::git_info::resolve_root_git_project_for_trust;
 use crate::model_family::ModelFamily;
-use crate::model_family::find_family_for_model;
+use crate::model_family::derive_model_family;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
 use crate::openai_model_info::get_model_info;
@@ -68,11 +73,31 @@ pub struct Config {
     /// Info needed to make an API request to the model.
     pub model_provider: ModelProviderInfo,
 
+    /// Providers a turn fails over to, in order, when the current provider
+    /// keeps failing with server errors, rate limits or connection errors.
+    pub fallback_providers: Vec<FallbackProvider>,
+
+    /// Per-model pricing overrides, keyed by model slug, used to estimate
+    /// session cost. Models not listed here use the built-in pricing.
+    pub model_pricing: HashMap<String, ModelPricing>,
+
+    /// Once the session has used this many tokens, the task stops before the
+    /// next model request.
+    pub max_session_tokens: Option<u64>,
+
+    /// Once the estimated session cost in USD reaches this amount, the task
+    /// stops before the next model request.
+    pub max_session_cost: Option<f64>,
+
     /// Approval policy for executing commands.
     pub approval_policy: AskForApproval,
 
     pub sandbox_policy: SandboxPolicy,
 
+    /// When set, sandboxed commands run in a rootless container instead of
+    /// the platform sandbox.
+    pub container_sandbox: Option<ContainerSandboxConfig>,
+
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
     /// When `true`, `AgentReasoning` events emitted by the backend will be
@@ -120,6 +145,10 @@ pub struct Config {
     /// Definition for MCP servers that Codex can reach out to for tool calls.
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// The `-c key=value` overrides the config was loaded with, kept so that
+    /// reloading parts of the config (e.g. MCP servers) applies them again.
+    pub cli_overrides: Vec<(String, TomlValue)>,
+
     /// Combined provider map (defaults merged with user-defined overrides).
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
@@ -133,6 +162,17 @@ pub struct Config {
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     pub history: History,
 
+    /// Secret redaction applied to tool output before it reaches the model.
+    pub redaction: Redaction,
+
+    /// Snapshot the git working tree before each turn so the turn can be
+    /// undone with `Op::UndoTurn`.
+    pub checkpoints: bool,
+
+    /// Run each new conversation in its own git worktree under
+    /// `$CODEX_HOME/worktrees` instead of the user's checkout.
+    pub worktree: bool,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
@@ -172,6 +212,13 @@ pub struct Config {
 
     pub tools_web_search_request: bool,
 
+    /// JSON Schema the final agent message of each turn must conform to.
+    /// Passed to the model as a structured output format when set.
+    pub output_schema: Option<serde_json::Value>,
+
+    /// Record or replay model traffic. Cannot be set in the config file.
+    pub cassette: Option<CassetteMode>,
+
     pub use_experimental_streamable_shell_tool: bool,
 
     /// If set to `true`, used only the experimental unified exec tool.
@@ -180,6 +227,10 @@ pub struct Config {
     /// Include the `view_image` tool that lets the agent attach a local image path to context.
     pub include_view_image_tool: bool,
 
+    /// Include the `delegate` tool that runs sub-tasks in sub-agent
+    /// conversations.
+    pub include_delegate_tool: bool,
+
     /// The active profile name used to derive this `Config` (if any).
     pub active_profile: Option<String>,
 
@@ -187,6 +238,46 @@ pub struct Config {
     /// All characters are inserted as they are received, and no buffering
     /// or placeholder replacement will occur for fast keypress bursts.
     pub disable_paste_burst: bool,
+
+    /// TUI-specific settings from the `[tui]` table.
+    pub tui: Tui,
+}
+
+/// A provider from `fallback_providers`, resolved against `model_providers`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FallbackProvider {
+    /// Key into the model_providers map.
+    pub provider_id: String,
+
+    pub provider: ModelProviderInfo,
+
+    /// Model to use with this provider. When `None`, the session's model is
+    /// kept.
+    pub model: Option<String>,
+}
+
+impl FallbackProvider {
+    /// Resolve a `"<provider>"` or `"<provider>:<model>"` entry.
+    fn resolve(
+        entry: &str,
+        model_providers: &HashMap<String, ModelProviderInfo>,
+    ) -> std::io::Result<Self> {
+        let (provider_id, model) = match entry.split_once(':') {
+            Some((provider_id, model)) => (provider_id, Some(model.to_string())),
+            None => (entry, None),
+        };
+        let provider = model_providers.get(provider_id).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Fallback model provider `{provider_id}` not found"),
+            )
+        })?;
+        Ok(Self {
+            provider_id: provider_id.to_string(),
+            provider: provider.clone(),
+            model,
+        })
+    }
 }
 
 impl Config {
@@ -209,7 +300,7 @@ impl Config {
         let mut root_value = load_config_as_toml(&codex_home)?;
 
         // Step 2: apply the `-c` overrides.
-        for (path, value) in cli_overrides.into_iter() {
+        for (path, value) in cli_overrides.iter().cloned() {
             apply_toml_override(&mut root_value, &path, value);
         }
 
@@ -221,7 +312,9 @@ impl Config {
         })?;
 
         // Step 4: merge with the strongly-typed overrides.
-        Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)
+        let mut config = Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)?;
+        config.cli_overrides = cli_overrides;
+        Ok(config)
     }
 }
 
@@ -522,6 +615,10 @@ pub struct ConfigToml {
     /// Provider to use from the model_providers map.
     pub model_provider: Option<String>,
 
+    /// Keys into the model_providers map to fail over to, in order. An entry
+    /// may name a model to use with that provider as `"<provider>:<model>"`.
+    pub fallback_providers: Option<Vec<String>>,
+
     /// Size of the context window for the model, in tokens.
     pub model_context_window: Option<u64>,
 
@@ -531,6 +628,16 @@ pub struct ConfigToml {
     /// Token usage threshold triggering auto-compaction of conversation history.
     pub model_auto_compact_token_limit: Option<i64>,
 
+    /// Per-model pricing in USD per million tokens, keyed by model slug.
+    #[serde(default)]
+    pub model_pricing: HashMap<String, ModelPricing>,
+
+    /// Maximum number of tokens a session may use before tasks are stopped.
+    pub max_session_tokens: Option<u64>,
+
+    /// Maximum estimated session cost in USD before tasks are stopped.
+    pub max_session_cost: Option<f64>,
+
     /// Default approval policy for executing commands.
     pub approval_policy: Option<AskForApproval>,
 
@@ -543,6 +650,16 @@ pub struct ConfigToml {
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
+    /// When set, sandboxed commands may only read from these folders, the cwd,
+    /// the writable roots and a set of system folders. Enforced on Linux only.
+    pub sandbox_readable_roots: Option<Vec<PathBuf>>,
+
+    /// Mechanism used to sandbox commands. Defaults to the platform sandbox.
+    pub sandbox_backend: Option<SandboxBackend>,
+
+    /// Container settings used when `sandbox_backend` is `container`.
+    pub sandbox_container: Option<ContainerSandboxConfig>,
+
     /// Optional external command to spawn for end-user notifications.
     #[serde(default)]
     pub notify: Option<Vec<String>>,
@@ -572,6 +689,18 @@ pub struct ConfigToml {
     #[serde(default)]
     pub history: Option<History>,
 
+    /// Secret redaction applied to tool output before it reaches the model.
+    #[serde(default)]
+    pub redaction: Option<Redaction>,
+
+    /// Snapshot the git working tree before each turn so it can be undone.
+    /// Defaults to `false`.
+    pub checkpoints: Option<bool>,
+
+    /// Run each new conversation in a fresh git worktree on its own branch.
+    /// Defaults to `false`.
+    pub worktree: Option<bool>,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: Option<UriBasedFileOpener>,
@@ -657,6 +786,11 @@ pub struct ToolsToml {
     /// Enable the `view_image` tool that lets the agent attach local images.
     #[serde(default)]
     pub view_image: Option<bool>,
+
+    /// Enable the `delegate` tool that lets the agent hand sub-tasks to
+    /// sub-agents.
+    #[serde(default)]
+    pub delegate: Option<bool>,
 }
 
 impl From<ToolsToml> for Tools {
@@ -664,6 +798,7 @@ impl From<ToolsToml> for Tools {
         Self {
             web_search: tools_toml.web_search,
             view_image: tools_toml.view_image,
+            delegate: tools_toml.delegate,
         }
     }
 }
@@ -674,24 +809,28 @@ impl ConfigToml {
         let resolved_sandbox_mode = sandbox_mode_override
             .or(self.sandbox_mode)
             .unwrap_or_default();
-        match resolved_sandbox_mode {
+        let policy = match resolved_sandbox_mode {
             SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
             SandboxMode::WorkspaceWrite => match self.sandbox_workspace_write.as_ref() {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
+                    network_allowlist,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
                     network_access: *network_access,
+                    network_allowlist: network_allowlist.clone(),
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
+                    readable_roots: None,
                 },
                 None => SandboxPolicy::new_workspace_write_policy(),
             },
             SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
-        }
+        };
+        policy.with_readable_roots(self.sandbox_readable_roots.clone())
     }
 
     pub fn is_cwd_trusted(&self, resolved_cwd: &Path) -> bool {
@@ -759,6 +898,9 @@ pub struct ConfigOverrides {
     pub include_view_image_tool: Option<bool>,
     pub show_raw_agent_reasoning: Option<bool>,
     pub tools_web_search_request: Option<bool>,
+    pub output_schema: Option<serde_json::Value>,
+    pub cassette: Option<CassetteMode>,
+    pub worktree: Option<bool>,
 }
 
 impl Config {
@@ -787,6 +929,9 @@ impl Config {
             include_view_image_tool,
             show_raw_agent_reasoning,
             tools_web_search_request: override_tools_web_search_request,
+            output_schema,
+            cassette,
+            worktree,
         } = overrides;
 
         let active_profile_name = config_profile_key
@@ -808,6 +953,10 @@ impl Config {
         };
 
         let sandbox_policy = cfg.derive_sandbox_policy(sandbox_mode);
+        let container_sandbox = match cfg.sandbox_backend.unwrap_or_default() {
+            SandboxBackend::Platform => None,
+            SandboxBackend::Container => Some(cfg.sandbox_container.clone().unwrap_or_default()),
+        };
 
         let mut model_providers = built_in_model_providers();
         // Merge user-defined providers into the built-in list.
@@ -829,6 +978,13 @@ impl Config {
             })?
             .clone();
 
+        let fallback_providers = cfg
+            .fallback_providers
+            .unwrap_or_default()
+            .iter()
+            .map(|entry| FallbackProvider::resolve(entry, &model_providers))
+            .collect::<std::io::Result<Vec<_>>>()?;
+
         let shell_environment_policy = cfg.shell_environment_policy.into();
 
         let resolved_cwd = {
@@ -851,6 +1007,24 @@ impl Config {
         };
 
         let history = cfg.history.unwrap_or_default();
+        for pattern in &history.sensitive_patterns {
+            regex_lite::Regex::new(pattern).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid regex in history.sensitive_patterns `{pattern}`: {e}"),
+                )
+            })?;
+        }
+
+        let redaction = cfg.redaction.unwrap_or_default();
+        for pattern in &redaction.patterns {
+            regex_lite::Regex::new(pattern).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid regex in redaction.patterns `{pattern}`: {e}"),
+                )
+            })?;
+        }
 
         let tools_web_search_request = override_tools_web_search_request
             .or(cfg.tools.as_ref().and_then(|t| t.web_search))
@@ -860,20 +1034,14 @@ impl Config {
             .or(cfg.tools.as_ref().and_then(|t| t.view_image))
             .unwrap_or(true);
 
+        let include_delegate_tool = cfg.tools.as_ref().and_then(|t| t.delegate).unwrap_or(false);
+
         let model = model
             .or(config_profile.model)
             .or(cfg.model)
             .unwrap_or_else(default_model);
 
-        let mut model_family = find_family_for_model(&model).unwrap_or_else(|| ModelFamily {
-            slug: model.clone(),
-            family: model.clone(),
-            needs_special_apply_patch_instructions: false,
-            supports_reasoning_summaries: false,
-            reasoning_summary_format: ReasoningSummaryFormat::None,
-            uses_local_shell_tool: false,
-            apply_patch_tool_type: None,
-        });
+        let mut model_family = derive_model_family(&model);
 
         if let Some(supports_reasoning_summaries) = cfg.model_supports_reasoning_summaries {
             model_family.supports_reasoning_summaries = supports_reasoning_summaries;
@@ -924,21 +1092,30 @@ impl Config {
             model_auto_compact_token_limit,
             model_provider_id,
             model_provider,
+            fallback_providers,
+            model_pricing: cfg.model_pricing,
+            max_session_tokens: cfg.max_session_tokens,
+            max_session_cost: cfg.max_session_cost,
             cwd: resolved_cwd,
             approval_policy: approval_policy
                 .or(config_profile.approval_policy)
                 .or(cfg.approval_policy)
                 .unwrap_or_else(AskForApproval::default),
             sandbox_policy,
+            container_sandbox,
             shell_environment_policy,
             notify: cfg.notify,
             user_instructions,
             base_instructions,
             mcp_servers: cfg.mcp_servers,
+            cli_overrides: Vec::new(),
             model_providers,
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
             codex_home,
             history,
+            redaction,
+            checkpoints: cfg.checkpoints.unwrap_or(false),
+            worktree: worktree.or(cfg.worktree).unwrap_or(false),
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             codex_linux_sandbox_exe,
 
@@ -964,6 +1141,8 @@ impl Config {
             include_plan_tool: include_plan_tool.unwrap_or(false),
             include_apply_patch_tool: include_apply_patch_tool.unwrap_or(false),
             tools_web_search_request,
+            output_schema,
+            cassette,
             use_experimental_streamable_shell_tool: cfg
                 .experimental_use_exec_command_tool
                 .unwrap_or(false),
@@ -971,12 +1150,26 @@ impl Config {
                 .experimental_use_unified_exec_tool
                 .unwrap_or(false),
             include_view_image_tool,
+            include_delegate_tool,
             active_profile: active_profile_name,
             disable_paste_burst: cfg.disable_paste_burst.unwrap_or(false),
+            tui: cfg.tui.unwrap_or_default(),
         };
         Ok(config)
     }
 
+    /// Switches to `model`, resetting the model family and the limits derived
+    /// from it.
+    pub(crate) fn set_model(&mut self, model: &str) {
+        self.model = model.to_string();
+        self.model_family = derive_model_family(model);
+        let model_info = get_model_info(&self.model_family);
+        self.model_context_window = model_info.as_ref().map(|info| info.context_window);
+        self.model_max_output_tokens = model_info.as_ref().map(|info| info.max_output_tokens);
+        self.model_auto_compact_token_limit =
+            model_info.and_then(|info| info.auto_compact_token_limit);
+    }
+
     fn load_instructions(codex_dir: Option<&Path>) -> Option<String> {
         let mut p = match codex_dir {
             Some(p) => p.to_path_buf(),
@@ -1082,7 +1275,18 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 
 #[cfg(test)]
 mod tests {
+    use crate::config_types::ContainerRuntime;
+    use crate::config_types::EditingMode;
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::KeyBindings;
+    use crate::config_types::McpHttpTransport;
+    use crate::config_types::McpServerTransportConfig;
+    use crate::config_types::McpToolApproval;
+    use crate::config_types::McpToolConfig;
+    use crate::config_types::ThemeColor;
+    use crate::config_types::ThemePreset;
+    use crate::model_family::find_family_for_model;
+    use crate::protocol::TokenUsage;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -1101,6 +1305,7 @@ persistence = "save-all"
             Some(History {
                 persistence: HistoryPersistence::SaveAll,
                 max_bytes: None,
+                sensitive_patterns: Vec::new(),
             }),
             history_with_persistence_cfg.history
         );
@@ -1116,6 +1321,7 @@ persistence = "none"
             Some(History {
                 persistence: HistoryPersistence::None,
                 max_bytes: None,
+                sensitive_patterns: Vec::new(),
             }),
             history_no_persistence_cfg.history
         );
@@ -1148,7 +1354,7 @@ network_access = true  # This should be ignored.
             .expect("TOML deserialization should succeed");
         let sandbox_mode_override = None;
         assert_eq!(
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             sandbox_read_only_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
 
@@ -1159,6 +1365,7 @@ sandbox_mode = "workspace-write"
 writable_roots = [
     "/my/workspace",
 ]
+network_allowlist = ["crates.io", "index.crates.io"]
 exclude_tmpdir_env_var = true
 exclude_slash_tmp = true
 "#;
@@ -1170,11 +1377,320 @@ exclude_slash_tmp = true
             SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![PathBuf::from("/my/workspace")],
                 network_access: false,
+                network_allowlist: vec!["crates.io".to_string(), "index.crates.io".to_string()],
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                readable_roots: None,
             },
             sandbox_workspace_write_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
+
+        let sandbox_readable_roots = r#"
+sandbox_mode = "read-only"
+sandbox_readable_roots = ["/my/workspace", "/shared/docs"]
+"#;
+
+        let sandbox_readable_roots_cfg = toml::from_str::<ConfigToml>(sandbox_readable_roots)
+            .expect("TOML deserialization should succeed");
+        let readable_roots = Some(vec![
+            PathBuf::from("/my/workspace"),
+            PathBuf::from("/shared/docs"),
+        ]);
+        assert_eq!(
+            SandboxPolicy::ReadOnly {
+                readable_roots: readable_roots.clone(),
+            },
+            sandbox_readable_roots_cfg.derive_sandbox_policy(None)
+        );
+        assert_eq!(
+            SandboxPolicy::new_workspace_write_policy().with_readable_roots(readable_roots),
+            sandbox_readable_roots_cfg.derive_sandbox_policy(Some(SandboxMode::WorkspaceWrite))
+        );
+    }
+
+    #[test]
+    fn test_mcp_servers_parsing() {
+        let mcp_servers = r#"
+[mcp_servers.local]
+command = "npx"
+args = ["-y", "mcp-server"]
+
+[mcp_servers.shared]
+url = "https://mcp.example.com/mcp"
+bearer_token_env_var = "SHARED_MCP_TOKEN"
+http_headers = { "X-Team" = "codex" }
+startup_timeout_ms = 20_000
+
+[mcp_servers.legacy]
+url = "https://mcp.example.com/sse"
+transport = "sse"
+"#;
+        let cfg =
+            toml::from_str::<ConfigToml>(mcp_servers).expect("TOML deserialization should succeed");
+
+        assert_eq!(
+            McpServerConfig {
+                transport: McpServerTransportConfig::Stdio {
+                    command: "npx".to_string(),
+                    args: vec!["-y".to_string(), "mcp-server".to_string()],
+                    env: None,
+                },
+                startup_timeout_ms: None,
+                enabled_tools: None,
+                disabled_tools: None,
+                tools: HashMap::new(),
+            },
+            cfg.mcp_servers["local"]
+        );
+        assert_eq!(
+            McpServerConfig {
+                transport: McpServerTransportConfig::Http {
+                    url: "https://mcp.example.com/mcp".to_string(),
+                    transport: McpHttpTransport::StreamableHttp,
+                    bearer_token_env_var: Some("SHARED_MCP_TOKEN".to_string()),
+                    http_headers: Some(HashMap::from([(
+                        "X-Team".to_string(),
+                        "codex".to_string()
+                    )])),
+                },
+                startup_timeout_ms: Some(20_000),
+                enabled_tools: None,
+                disabled_tools: None,
+                tools: HashMap::new(),
+            },
+            cfg.mcp_servers["shared"]
+        );
+        assert_eq!(
+            McpServerConfig {
+                transport: McpServerTransportConfig::Http {
+                    url: "https://mcp.example.com/sse".to_string(),
+                    transport: McpHttpTransport::Sse,
+                    bearer_token_env_var: None,
+                    http_headers: None,
+                },
+                startup_timeout_ms: None,
+                enabled_tools: None,
+                disabled_tools: None,
+                tools: HashMap::new(),
+            },
+            cfg.mcp_servers["legacy"]
+        );
+    }
+
+    #[test]
+    fn test_mcp_server_tool_filters_and_approval() {
+        let mcp_servers = r#"
+[mcp_servers.github]
+command = "github-mcp"
+enabled_tools = ["get_*", "list_*", "create_issue"]
+disabled_tools = ["get_secret*"]
+
+[mcp_servers.github.tools.create_issue]
+approval = "always"
+
+[mcp_servers.github.tools.list_repos]
+approval = "on-request"
+"#;
+        let cfg =
+            toml::from_str::<ConfigToml>(mcp_servers).expect("TOML deserialization should succeed");
+        let github = &cfg.mcp_servers["github"];
+
+        assert_eq!(
+            HashMap::from([
+                (
+                    "create_issue".to_string(),
+                    McpToolConfig {
+                        approval: McpToolApproval::Always,
+                    }
+                ),
+                (
+                    "list_repos".to_string(),
+                    McpToolConfig {
+                        approval: McpToolApproval::OnRequest,
+                    }
+                ),
+            ]),
+            github.tools
+        );
+        assert!(github.is_tool_enabled("get_file"));
+        assert!(github.is_tool_enabled("create_issue"));
+        assert!(!github.is_tool_enabled("get_secret_value"));
+        assert!(!github.is_tool_enabled("delete_repo"));
+        assert_eq!(
+            McpToolApproval::Always,
+            github.tool_approval("create_issue")
+        );
+        assert_eq!(McpToolApproval::Never, github.tool_approval("get_file"));
+    }
+
+    #[test]
+    fn test_fallback_providers_are_resolved() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+fallback_providers = ["oss", "openai:gpt-4.1"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        let providers = built_in_model_providers();
+        assert_eq!(
+            vec![
+                FallbackProvider {
+                    provider_id: "oss".to_string(),
+                    provider: providers["oss"].clone(),
+                    model: None,
+                },
+                FallbackProvider {
+                    provider_id: "openai".to_string(),
+                    provider: providers["openai"].clone(),
+                    model: Some("gpt-4.1".to_string()),
+                },
+            ],
+            config.fallback_providers
+        );
+
+        let cfg = toml::from_str::<ConfigToml>(r#"fallback_providers = ["missing"]"#)
+            .expect("TOML deserialization should succeed");
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .expect_err("unknown fallback provider should be rejected");
+        assert_eq!(std::io::ErrorKind::NotFound, err.kind());
+        Ok(())
+    }
+
+    #[test]
+    fn test_container_sandbox_backend() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+sandbox_backend = "container"
+
+[sandbox_container]
+image = "docker.io/library/rust:1.89"
+extra_args = ["--memory", "4g"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        let container_sandbox = config.container_sandbox.expect("container backend");
+        assert_eq!(ContainerRuntime::Podman, container_sandbox.runtime());
+        assert_eq!(
+            Some("docker.io/library/rust:1.89".to_string()),
+            container_sandbox.image
+        );
+        assert_eq!(vec!["--memory", "4g"], container_sandbox.extra_args);
+
+        // The `[sandbox_container]` table alone does not switch backends.
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[sandbox_container]
+runtime = "bwrap"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(None, config.container_sandbox);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tui_theme_and_keymap() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[tui]
+editing_mode = "vim"
+
+[tui.theme]
+preset = "light"
+accent = "light-blue"
+
+[tui.keymap]
+submit = "ctrl+enter"
+newline = ["enter", "ctrl+j"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(EditingMode::Vim, config.tui.editing_mode);
+        assert_eq!(ThemePreset::Light, config.tui.theme.preset);
+        assert_eq!(Some(ThemeColor::LightBlue), config.tui.theme.accent);
+        assert_eq!(None, config.tui.theme.error);
+        let keymap = &config.tui.keymap;
+        assert_eq!(
+            Some(&["ctrl+enter".to_string()][..]),
+            keymap.submit.as_ref().map(KeyBindings::keys)
+        );
+        assert_eq!(
+            Some(&["enter".to_string(), "ctrl+j".to_string()][..]),
+            keymap.newline.as_ref().map(KeyBindings::keys)
+        );
+        assert_eq!(None, keymap.open_transcript);
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_pricing_and_session_limits() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+max_session_tokens = 2000000
+max_session_cost = 5.5
+
+[model_pricing.my-model]
+input = 3.0
+output = 15.0
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(Some(2_000_000), config.max_session_tokens);
+        assert_eq!(Some(5.5), config.max_session_cost);
+
+        let pricing = config.model_pricing["my-model"];
+        assert_eq!(
+            ModelPricing {
+                input: 3.0,
+                cached_input: None,
+                output: 15.0,
+            },
+            pricing
+        );
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            cached_input_tokens: 500_000,
+            output_tokens: 100_000,
+            reasoning_output_tokens: 0,
+            total_tokens: 1_100_000,
+        };
+        assert_eq!(4.5, pricing.cost_usd(&usage));
+        Ok(())
     }
 
     #[tokio::test]
@@ -1467,17 +1983,26 @@ model_verbosity = "high"
                 model_auto_compact_token_limit: None,
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
+                fallback_providers: Vec::new(),
+                model_pricing: HashMap::new(),
+                max_session_tokens: None,
+                max_session_cost: None,
                 approval_policy: AskForApproval::Never,
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
+                container_sandbox: None,
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 user_instructions: None,
                 notify: None,
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
+                cli_overrides: Vec::new(),
                 model_providers: fixture.model_provider_map.clone(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 codex_home: fixture.codex_home(),
                 history: History::default(),
+                redaction: Redaction::default(),
+                checkpoints: false,
+                worktree: false,
                 file_opener: UriBasedFileOpener::VsCode,
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
@@ -1491,11 +2016,15 @@ model_verbosity = "high"
                 include_plan_tool: false,
                 include_apply_patch_tool: false,
                 tools_web_search_request: false,
+                output_schema: None,
+                cassette: None,
                 use_experimental_streamable_shell_tool: false,
                 use_experimental_unified_exec_tool: false,
                 include_view_image_tool: true,
+                include_delegate_tool: false,
                 active_profile: Some("o3".to_string()),
                 disable_paste_burst: false,
+                tui: Tui::default(),
             },
             o3_profile_config
         );
@@ -1525,17 +2054,26 @@ model_verbosity = "high"
             model_auto_compact_token_limit: None,
             model_provider_id: "openai-chat-completions".to_string(),
             model_provider: fixture.openai_chat_completions_provider.clone(),
+            fallback_providers: Vec::new(),
+            model_pricing: HashMap::new(),
+            max_session_tokens: None,
+            max_session_cost: None,
             approval_policy: AskForApproval::UnlessTrusted,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            container_sandbox: None,
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            cli_overrides: Vec::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            redaction: Redaction::default(),
+            checkpoints: false,
+            worktree: false,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -1549,11 +2087,15 @@ model_verbosity = "high"
             include_plan_tool: false,
             include_apply_patch_tool: false,
             tools_web_search_request: false,
+            output_schema: None,
+            cassette: None,
             use_experimental_streamable_shell_tool: false,
             use_experimental_unified_exec_tool: false,
             include_view_image_tool: true,
+            include_delegate_tool: false,
             active_profile: Some("gpt3".to_string()),
             disable_paste_burst: false,
+            tui: Tui::default(),
         };
 
         assert_eq!(expected_gpt3_profile_config, gpt3_profile_config);
@@ -1598,17 +2140,26 @@ model_verbosity = "high"
             model_auto_compact_token_limit: None,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
+            fallback_providers: Vec::new(),
+            model_pricing: HashMap::new(),
+            max_session_tokens: None,
+            max_session_cost: None,
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            container_sandbox: None,
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            cli_overrides: Vec::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            redaction: Redaction::default(),
+            checkpoints: false,
+            worktree: false,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -1622,11 +2173,15 @@ model_verbosity = "high"
             include_plan_tool: false,
             include_apply_patch_tool: false,
             tools_web_search_request: false,
+            output_schema: None,
+            cassette: None,
             use_experimental_streamable_shell_tool: false,
             use_experimental_unified_exec_tool: false,
             include_view_image_tool: true,
+            include_delegate_tool: false,
             active_profile: Some("zdr".to_string()),
             disable_paste_burst: false,
+            tui: Tui::default(),
         };
 
         assert_eq!(expected_zdr_profile_config, zdr_profile_config);
@@ -1657,17 +2212,26 @@ model_verbosity = "high"
             model_auto_compact_token_limit: None,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
+            fallback_providers: Vec::new(),
+            model_pricing: HashMap::new(),
+            max_session_tokens: None,
+            max_session_cost: None,
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            container_sandbox: None,
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            cli_overrides: Vec::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            redaction: Redaction::default(),
+            checkpoints: false,
+            worktree: false,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -1681,11 +2245,15 @@ model_verbosity = "high"
             include_plan_tool: false,
             include_apply_patch_tool: false,
             tools_web_search_request: false,
+            output_schema: None,
+            cassette: None,
             use_experimental_streamable_shell_tool: false,
             use_experimental_unified_exec_tool: false,
             include_view_image_tool: true,
+            include_delegate_tool: false,
             active_profile: Some("gpt5".to_string()),
             disable_paste_burst: false,
+            tui: Tui::default(),
         };
 
         assert_eq!(expected_gpt5_profile_config, gpt5_profile_config);
@@ -11,17 +11,120 @@ use serde::Deserialize;
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct McpServerConfig {
-    pub command: String,
+    /// How to reach the server: either a `command` to launch or a `url` to
+    /// connect to.
+    #[serde(flatten)]
+    pub transport: McpServerTransportConfig,
 
+    /// Startup timeout in milliseconds for initializing MCP server & initially listing tools.
     #[serde(default)]
-    pub args: Vec<String>,
+    pub startup_timeout_ms: Option<u64>,
 
+    /// Glob patterns of the tool names to expose to the model. When unset,
+    /// every tool is exposed.
     #[serde(default)]
-    pub env: Option<HashMap<String, String>>,
+    pub enabled_tools: Option<Vec<String>>,
 
-    /// Startup timeout in milliseconds for initializing MCP server & initially listing tools.
+    /// Glob patterns of the tool names to hide from the model. Takes
+    /// precedence over `enabled_tools`.
     #[serde(default)]
-    pub startup_timeout_ms: Option<u64>,
+    pub disabled_tools: Option<Vec<String>>,
+
+    /// Per-tool settings, keyed by the tool name as given by the server.
+    #[serde(default)]
+    pub tools: HashMap<String, McpToolConfig>,
+}
+
+impl McpServerConfig {
+    /// Whether `tool` passes the `enabled_tools` / `disabled_tools` filters.
+    pub fn is_tool_enabled(&self, tool: &str) -> bool {
+        let matches_any = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| McpToolPattern::new(pattern).matches(tool))
+        };
+        if self.disabled_tools.as_deref().is_some_and(matches_any) {
+            return false;
+        }
+        self.enabled_tools.as_deref().is_none_or(matches_any)
+    }
+
+    /// Approval setting for `tool`.
+    pub fn tool_approval(&self, tool: &str) -> McpToolApproval {
+        self.tools
+            .get(tool)
+            .map(|tool_config| tool_config.approval)
+            .unwrap_or_default()
+    }
+}
+
+pub type McpToolPattern = WildMatchPattern<'*', '?'>;
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct McpToolConfig {
+    /// When to ask the user before calling the tool.
+    #[serde(default)]
+    pub approval: McpToolApproval,
+}
+
+/// Determines when the user is consulted before an MCP tool is called.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpToolApproval {
+    /// Ask the user before every call.
+    Always,
+
+    /// Call the tool without asking the user.
+    #[default]
+    Never,
+
+    /// Ask the user unless the server annotates the tool as read-only
+    /// (`readOnlyHint`).
+    OnRequest,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum McpServerTransportConfig {
+    /// Launch the server as a subprocess that communicates over stdio.
+    Stdio {
+        command: String,
+
+        #[serde(default)]
+        args: Vec<String>,
+
+        #[serde(default)]
+        env: Option<HashMap<String, String>>,
+    },
+
+    /// Connect to a server that is already running behind HTTP.
+    Http {
+        url: String,
+
+        #[serde(default)]
+        transport: McpHttpTransport,
+
+        /// Name of the environment variable that holds a bearer token to send
+        /// in the `Authorization` header.
+        #[serde(default)]
+        bearer_token_env_var: Option<String>,
+
+        /// Additional headers to send with every request.
+        #[serde(default)]
+        http_headers: Option<HashMap<String, String>>,
+    },
+}
+
+/// Wire protocol spoken by an HTTP MCP server.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum McpHttpTransport {
+    /// The Streamable HTTP transport (MCP 2025-03-26 and later).
+    #[default]
+    StreamableHttp,
+
+    /// The legacy HTTP+SSE transport (MCP 2024-11-05).
+    Sse,
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
@@ -55,15 +158,43 @@ impl UriBasedFileOpener {
     }
 }
 
+/// Prices for a model in USD per million tokens, used to estimate session cost.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct ModelPricing {
+    /// Price per million uncached input tokens.
+    pub input: f64,
+
+    /// Price per million cached input tokens. Defaults to `input`.
+    pub cached_input: Option<f64>,
+
+    /// Price per million output tokens (including reasoning tokens).
+    pub output: f64,
+}
+
+impl ModelPricing {
+    pub fn cost_usd(&self, usage: &codex_protocol::protocol::TokenUsage) -> f64 {
+        let cached_input = self.cached_input.unwrap_or(self.input);
+        (usage.non_cached_input() as f64 * self.input
+            + usage.cached_input() as f64 * cached_input
+            + usage.output_tokens as f64 * self.output)
+            / 1_000_000.0
+    }
+}
+
 /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct History {
     /// If true, history entries will not be written to disk.
     pub persistence: HistoryPersistence,
 
-    /// If set, the maximum size of the history file in bytes.
-    /// TODO(mbolin): Not currently honored.
+    /// If set, the maximum size of the history file in bytes. When an append
+    /// pushes the file past this size, older entries are dropped.
     pub max_bytes: Option<usize>,
+
+    /// Regular expressions for secrets; messages matching any of them are not
+    /// written to the history file.
+    #[serde(default)]
+    pub sensitive_patterns: Vec<String>,
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
@@ -76,9 +207,168 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// Settings for scrubbing secrets from tool output before it is recorded in
+/// the conversation history and sent to the model.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Redaction {
+    /// When false, tool output is passed through unchanged.
+    pub enabled: bool,
+
+    /// Regular expressions for additional secrets, applied on top of the
+    /// built-in detectors (AWS keys, GitHub tokens, private keys, JWTs).
+    pub patterns: Vec<String>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// Record-and-replay of model traffic, set from `codex exec --record` or
+/// `--replay`. See [`crate::cassette`] for the on-disk format.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CassetteMode {
+    /// Send requests to the provider and save each request and response in
+    /// the directory.
+    Record(PathBuf),
+    /// Serve responses recorded in the directory instead of calling the
+    /// provider.
+    Replay(PathBuf),
+}
+
 /// Collection of settings that are specific to the TUI.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
-pub struct Tui {}
+pub struct Tui {
+    /// Color theme: a named preset plus optional per-element overrides.
+    #[serde(default)]
+    pub theme: TuiTheme,
+
+    /// Key bindings for composer and global actions. Unset actions keep their
+    /// default keys.
+    #[serde(default)]
+    pub keymap: TuiKeymap,
+
+    /// How keys are interpreted while editing in the composer.
+    #[serde(default)]
+    pub editing_mode: EditingMode,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TuiTheme {
+    #[serde(default)]
+    pub preset: ThemePreset,
+
+    /// Color for tips, key hints, selection and status indicators.
+    pub accent: Option<ThemeColor>,
+
+    /// Color for success messages and added lines.
+    pub success: Option<ThemeColor>,
+
+    /// Color for errors, failures and removed lines.
+    pub error: Option<ThemeColor>,
+
+    /// Color used to mark output from Codex.
+    pub codex: Option<ThemeColor>,
+
+    /// Color for warnings such as a nearly full context window.
+    pub warning: Option<ThemeColor>,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    /// Detect whether the terminal background is light or dark.
+    #[default]
+    Auto,
+    Dark,
+    Light,
+    /// No colors; only bold, dim and similar modifiers.
+    Monochrome,
+}
+
+/// One of the 16 ANSI colors, or the terminal's default foreground.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeColor {
+    Default,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+    DarkGray,
+    LightRed,
+    LightGreen,
+    LightYellow,
+    LightBlue,
+    LightMagenta,
+    LightCyan,
+    White,
+}
+
+/// Keys bound to TUI actions, written like `"ctrl+t"` or `"shift+enter"`.
+/// Each action accepts a single key or a list of keys.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TuiKeymap {
+    /// Submit the composer contents. Default: `enter`.
+    pub submit: Option<KeyBindings>,
+
+    /// Insert a newline in the composer. Default: `shift+enter`, `ctrl+j`.
+    pub newline: Option<KeyBindings>,
+
+    /// Interrupt the running task, or quit when pressed twice. Default:
+    /// `ctrl+c`.
+    pub interrupt: Option<KeyBindings>,
+
+    /// Quit when the composer is empty. Default: `ctrl+d`.
+    pub quit: Option<KeyBindings>,
+
+    /// Step back to edit a previous message. Default: `esc`.
+    pub backtrack: Option<KeyBindings>,
+
+    /// Open the transcript overlay. Default: `ctrl+t`.
+    pub open_transcript: Option<KeyBindings>,
+
+    /// Paste an image from the clipboard. Default: `ctrl+v`.
+    pub paste_image: Option<KeyBindings>,
+
+    /// Search the persistent message history. Default: `ctrl+r`.
+    pub history_search: Option<KeyBindings>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum KeyBindings {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBindings {
+    pub fn keys(&self) -> &[String] {
+        match self {
+            KeyBindings::One(key) => std::slice::from_ref(key),
+            KeyBindings::Many(keys) => keys,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum EditingMode {
+    /// Emacs-style editing (Ctrl-A, Ctrl-E, ...), always inserting text.
+    #[default]
+    Emacs,
+    /// Modal Vim-style editing with insert and normal modes.
+    Vim,
+}
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct SandboxWorkspaceWrite {
@@ -87,6 +377,8 @@ pub struct SandboxWorkspaceWrite {
     #[serde(default)]
     pub network_access: bool,
     #[serde(default)]
+    pub network_allowlist: Vec<String>,
+    #[serde(default)]
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
     pub exclude_slash_tmp: bool,
@@ -97,12 +389,66 @@ impl From<SandboxWorkspaceWrite> for codex_protocol::mcp_protocol::SandboxSettin
         Self {
             writable_roots: sandbox_workspace_write.writable_roots,
             network_access: Some(sandbox_workspace_write.network_access),
+            network_allowlist: sandbox_workspace_write.network_allowlist,
             exclude_tmpdir_env_var: Some(sandbox_workspace_write.exclude_tmpdir_env_var),
             exclude_slash_tmp: Some(sandbox_workspace_write.exclude_slash_tmp),
         }
     }
 }
 
+/// Mechanism used to sandbox commands.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SandboxBackend {
+    /// Seatbelt on macOS, Landlock + seccomp on Linux.
+    #[default]
+    Platform,
+    /// Run each command in a rootless container, see [`ContainerSandboxConfig`].
+    Container,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContainerRuntime {
+    /// `bwrap`, which builds the container from the host root file-system.
+    #[serde(rename = "bwrap")]
+    Bubblewrap,
+    /// `podman run` with the configured image.
+    Podman,
+}
+
+/// Settings for `sandbox_backend = "container"`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ContainerSandboxConfig {
+    /// Defaults to `podman` when `image` is set and `bwrap` otherwise.
+    #[serde(default)]
+    pub runtime: Option<ContainerRuntime>,
+
+    /// Image to run commands in. Required for `podman`.
+    #[serde(default)]
+    pub image: Option<String>,
+
+    /// Extra arguments passed to the runtime before the command (for `bwrap`)
+    /// or before the image (for `podman`).
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+
+    /// Case-insensitive glob patterns of the environment variables passed
+    /// into a `podman` container. Other host variables are not forwarded.
+    #[serde(default)]
+    pub forward_env: Vec<String>,
+}
+
+impl ContainerSandboxConfig {
+    pub fn runtime(&self) -> ContainerRuntime {
+        match (self.runtime, &self.image) {
+            (Some(runtime), _) => runtime,
+            (None, Some(_)) => ContainerRuntime::Podman,
+            (None, None) => ContainerRuntime::Bubblewrap,
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "kebab-case")]
 pub enum ShellEnvironmentPolicyInherit {
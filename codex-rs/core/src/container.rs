@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::process::Child;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config_types::ContainerRuntime;
+use crate::config_types::ContainerSandboxConfig;
+use crate::config_types::EnvironmentVariablePattern;
+use crate::network_proxy::reject_network_allowlist;
+use crate::protocol::SandboxPolicy;
+use crate::spawn::CODEX_SANDBOX_ENV_VAR;
+use crate::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use crate::spawn::StdioPolicy;
+use crate::spawn::spawn_child_async;
+
+/// Host folders that `bwrap` replaces with fresh instances inside the
+/// container, so they are never bind-mounted from the host.
+const BWRAP_VIRTUAL_ROOTS: &[&str] = &["/dev", "/proc"];
+
+/// Spawn a shell tool command inside a rootless container.
+///
+/// The container gets its own PID, mount, IPC and UTS namespaces (plus a
+/// network namespace unless the policy grants full network access), and only
+/// the paths the [`SandboxPolicy`] allows are mounted into it: writable roots
+/// read-write, and the cwd and readable roots read-only. Policies that limit
+/// the network to an allowlist are rejected.
+///
+/// For `podman`, the returned [`PodmanContainer`] must be kept until the
+/// command has finished: it removes the container if the command does not
+/// exit on its own.
+pub async fn spawn_command_under_container(
+    container_sandbox: &ContainerSandboxConfig,
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    cwd: PathBuf,
+    stdio_policy: StdioPolicy,
+    mut env: HashMap<String, String>,
+) -> std::io::Result<(Child, Option<PodmanContainer>)> {
+    reject_network_allowlist(sandbox_policy, "container")?;
+    env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "container".to_string());
+    let (program, args, container) = match container_sandbox.runtime() {
+        ContainerRuntime::Bubblewrap => (
+            "bwrap",
+            create_bwrap_command_args(container_sandbox, command, sandbox_policy, &cwd),
+            None,
+        ),
+        ContainerRuntime::Podman => {
+            let container = PodmanContainer::new();
+            let args = create_podman_command_args(
+                container_sandbox,
+                command,
+                sandbox_policy,
+                &cwd,
+                &env,
+                &container.name,
+            )?;
+            ("podman", args, Some(container))
+        }
+    };
+    let arg0 = None;
+    let child = spawn_child_async(
+        PathBuf::from(program),
+        args,
+        arg0,
+        cwd,
+        sandbox_policy,
+        stdio_policy,
+        env,
+    )
+    .await?;
+    Ok((child, container))
+}
+
+/// A container started with `podman run`. Killing the `podman` client (e.g.
+/// when the command times out) leaves the container running, so it is
+/// force-removed on drop unless [`PodmanContainer::exited`] was called.
+pub struct PodmanContainer {
+    name: String,
+    exited: bool,
+}
+
+impl PodmanContainer {
+    fn new() -> Self {
+        Self {
+            name: format!("codex-{}", Uuid::new_v4()),
+            exited: false,
+        }
+    }
+
+    /// Records that the `podman` client exited on its own, which means the
+    /// container has stopped (and been removed by `--rm`).
+    pub fn exited(mut self) {
+        self.exited = true;
+    }
+}
+
+impl Drop for PodmanContainer {
+    fn drop(&mut self) {
+        if self.exited {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            warn!(
+                "cannot remove podman container {} without a runtime",
+                self.name
+            );
+            return;
+        };
+        let name = std::mem::take(&mut self.name);
+        handle.spawn(async move {
+            let result = tokio::process::Command::new("podman")
+                .args(["rm", "--force", "--ignore", "--time=0", &name])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .await;
+            if let Err(e) = result {
+                warn!("failed to remove podman container {name}: {e}");
+            }
+        });
+    }
+}
+
+/// Builds a `bwrap` invocation that reuses the host root file-system.
+fn create_bwrap_command_args(
+    container_sandbox: &ContainerSandboxConfig,
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "--unshare-all".to_string(),
+        "--die-with-parent".to_string(),
+        "--new-session".to_string(),
+    ];
+    if sandbox_policy.has_full_network_access() {
+        args.push("--share-net".to_string());
+    }
+
+    match sandbox_policy.get_readable_roots_with_cwd(cwd) {
+        None => push_mount(&mut args, "--ro-bind", Path::new("/")),
+        Some(readable_roots) => {
+            for root in readable_roots
+                .iter()
+                .filter(|root| !BWRAP_VIRTUAL_ROOTS.iter().any(|v| root == Path::new(v)))
+            {
+                push_mount(&mut args, "--ro-bind-try", root);
+            }
+        }
+    }
+    args.extend(["--dev", "/dev", "--proc", "/proc"].map(String::from));
+
+    // Later mounts take precedence, so writable roots go after the read-only
+    // ones and their read-only subpaths (e.g. `.git`) go last.
+    let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+    for writable_root in &writable_roots {
+        push_mount(&mut args, "--bind", &writable_root.root);
+    }
+    for subpath in writable_roots
+        .iter()
+        .flat_map(|writable_root| &writable_root.read_only_subpaths)
+    {
+        push_mount(&mut args, "--ro-bind", subpath);
+    }
+
+    args.push("--chdir".to_string());
+    args.push(cwd.to_string_lossy().to_string());
+    args.extend(container_sandbox.extra_args.iter().cloned());
+    args.push("--".to_string());
+    args.extend(command);
+    args
+}
+
+fn push_mount(args: &mut Vec<String>, flag: &str, path: &Path) {
+    let path = path.to_string_lossy().to_string();
+    args.push(flag.to_string());
+    args.push(path.clone());
+    args.push(path);
+}
+
+/// Builds a `podman run` invocation for the configured image, naming the
+/// container `name`. Only the cwd, the writable roots and any explicitly
+/// configured `readable_roots` are mounted, and only the variables set by
+/// Codex and those matching `forward_env` are passed in; everything else
+/// comes from the image.
+fn create_podman_command_args(
+    container_sandbox: &ContainerSandboxConfig,
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    env: &HashMap<String, String>,
+    name: &str,
+) -> std::io::Result<Vec<String>> {
+    let Some(image) = &container_sandbox.image else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "sandbox_container.image must be set to use podman",
+        ));
+    };
+
+    let network = if sandbox_policy.has_full_network_access() {
+        "host"
+    } else {
+        "none"
+    };
+    let mut args: Vec<String> = vec![
+        "run".to_string(),
+        "--rm".to_string(),
+        format!("--name={name}"),
+        "--init".to_string(),
+        "--userns=keep-id".to_string(),
+        "--security-opt=no-new-privileges".to_string(),
+        format!("--network={network}"),
+        format!("--workdir={}", cwd.to_string_lossy()),
+    ];
+
+    // Host variables such as PATH or HOME would break the image, so only
+    // Codex's own variables and the configured ones are forwarded. They are
+    // passed by name so their values do not show up in `ps`; podman reads
+    // them from its own environment.
+    let forward_env: Vec<EnvironmentVariablePattern> = container_sandbox
+        .forward_env
+        .iter()
+        .map(|pattern| EnvironmentVariablePattern::new_case_insensitive(pattern))
+        .collect();
+    let mut env_keys: Vec<&str> = env
+        .keys()
+        .map(String::as_str)
+        .filter(|key| {
+            key.starts_with(CODEX_SANDBOX_ENV_VAR)
+                || forward_env.iter().any(|pattern| pattern.matches(key))
+        })
+        .collect();
+    if !sandbox_policy.has_full_network_access() {
+        env_keys.push(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR);
+    }
+    env_keys.sort_unstable();
+    env_keys.dedup();
+    for key in env_keys {
+        args.push(format!("--env={key}"));
+    }
+
+    let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+    let mut volumes: Vec<(PathBuf, &str)> = Vec::new();
+    if !writable_roots
+        .iter()
+        .any(|writable_root| writable_root.root == cwd)
+    {
+        volumes.push((cwd.to_path_buf(), "ro"));
+    }
+    for root in sandbox_policy.readable_roots().unwrap_or_default() {
+        if root.exists() {
+            volumes.push((root.clone(), "ro"));
+        }
+    }
+    for writable_root in &writable_roots {
+        volumes.push((writable_root.root.clone(), "rw"));
+        for subpath in &writable_root.read_only_subpaths {
+            volumes.push((subpath.clone(), "ro"));
+        }
+    }
+    for (path, mode) in volumes {
+        let path = path.to_string_lossy();
+        args.push(format!("--volume={path}:{path}:{mode}"));
+    }
+
+    args.extend(container_sandbox.extra_args.iter().cloned());
+    args.push(image.clone());
+    args.extend(command);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn workspace_write_policy(writable_root: &Path) -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![writable_root.to_path_buf()],
+            network_access: false,
+            network_allowlist: Vec::new(),
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            readable_roots: None,
+        }
+    }
+
+    #[test]
+    fn bwrap_args_mount_writable_roots_over_read_only_host() {
+        let tmp = TempDir::new().expect("tempdir");
+        let cwd = tmp.path().join("cwd");
+        let cache = tmp.path().join("cache");
+        std::fs::create_dir_all(cwd.join(".git")).expect("create .git");
+        let cwd_str = cwd.to_string_lossy().to_string();
+        let cache_str = cache.to_string_lossy().to_string();
+        let git_str = cwd.join(".git").to_string_lossy().to_string();
+
+        let args = create_bwrap_command_args(
+            &ContainerSandboxConfig::default(),
+            vec!["cargo".to_string(), "build".to_string()],
+            &workspace_write_policy(&cache),
+            &cwd,
+        );
+
+        let expected: Vec<String> = [
+            "--unshare-all",
+            "--die-with-parent",
+            "--new-session",
+            "--ro-bind",
+            "/",
+            "/",
+            "--dev",
+            "/dev",
+            "--proc",
+            "/proc",
+            "--bind",
+            cache_str.as_str(),
+            cache_str.as_str(),
+            "--bind",
+            cwd_str.as_str(),
+            cwd_str.as_str(),
+            "--ro-bind",
+            git_str.as_str(),
+            git_str.as_str(),
+            "--chdir",
+            cwd_str.as_str(),
+            "--",
+            "cargo",
+            "build",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert_eq!(expected, args);
+    }
+
+    #[test]
+    fn bwrap_args_only_mount_readable_roots_when_reads_are_restricted() {
+        let cwd = PathBuf::from("/work");
+        let policy = SandboxPolicy::new_read_only_policy()
+            .with_readable_roots(Some(vec![PathBuf::from("/shared")]));
+
+        let args = create_bwrap_command_args(
+            &ContainerSandboxConfig::default(),
+            vec!["ls".to_string()],
+            &policy,
+            &cwd,
+        );
+
+        assert!(!args.windows(3).any(|w| w == ["--ro-bind", "/", "/"]));
+        assert!(
+            args.windows(3)
+                .any(|w| w == ["--ro-bind-try", "/shared", "/shared"])
+        );
+        assert!(
+            args.windows(3)
+                .any(|w| w == ["--ro-bind-try", "/work", "/work"])
+        );
+        assert!(
+            !args
+                .windows(2)
+                .any(|w| w == ["--ro-bind-try", "/proc"] || w == ["--ro-bind-try", "/dev"])
+        );
+        assert!(!args.contains(&"--share-net".to_string()));
+    }
+
+    #[test]
+    fn podman_args_mount_cwd_and_forward_configured_env_by_name() {
+        let tmp = TempDir::new().expect("tempdir");
+        let cwd = tmp.path().join("cwd");
+        let cwd_str = cwd.to_string_lossy().to_string();
+        let container_sandbox = ContainerSandboxConfig {
+            runtime: None,
+            image: Some("rust:1.89".to_string()),
+            extra_args: vec!["--memory=4g".to_string()],
+            forward_env: vec!["cargo_*".to_string()],
+        };
+        let env = HashMap::from([
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("HOME".to_string(), "/home/me".to_string()),
+            ("CARGO_TERM_COLOR".to_string(), "never".to_string()),
+            (CODEX_SANDBOX_ENV_VAR.to_string(), "container".to_string()),
+        ]);
+
+        let args = create_podman_command_args(
+            &container_sandbox,
+            vec!["cargo".to_string(), "test".to_string()],
+            &SandboxPolicy::new_read_only_policy(),
+            &cwd,
+            &env,
+            "codex-test",
+        )
+        .expect("podman args");
+
+        let expected: Vec<String> = vec![
+            "run".to_string(),
+            "--rm".to_string(),
+            "--name=codex-test".to_string(),
+            "--init".to_string(),
+            "--userns=keep-id".to_string(),
+            "--security-opt=no-new-privileges".to_string(),
+            "--network=none".to_string(),
+            format!("--workdir={cwd_str}"),
+            "--env=CARGO_TERM_COLOR".to_string(),
+            format!("--env={CODEX_SANDBOX_ENV_VAR}"),
+            format!("--env={CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR}"),
+            format!("--volume={cwd_str}:{cwd_str}:ro"),
+            "--memory=4g".to_string(),
+            "rust:1.89".to_string(),
+            "cargo".to_string(),
+            "test".to_string(),
+        ];
+        assert_eq!(expected, args);
+    }
+
+    #[test]
+    fn podman_requires_an_image() {
+        let container_sandbox = ContainerSandboxConfig {
+            runtime: Some(ContainerRuntime::Podman),
+            ..Default::default()
+        };
+        let err = create_podman_command_args(
+            &container_sandbox,
+            vec!["true".to_string()],
+            &SandboxPolicy::new_read_only_policy(),
+            Path::new("/work"),
+            &HashMap::new(),
+            "codex-test",
+        )
+        .expect_err("image is required");
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[tokio::test]
+    async fn network_allowlist_is_rejected() {
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: Vec::new(),
+            network_access: false,
+            network_allowlist: vec!["crates.io".to_string()],
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            readable_roots: None,
+        };
+
+        let Err(err) = spawn_command_under_container(
+            &ContainerSandboxConfig::default(),
+            vec!["true".to_string()],
+            &policy,
+            PathBuf::from("/work"),
+            StdioPolicy::RedirectForShellTool,
+            HashMap::new(),
+        )
+        .await
+        else {
+            panic!("expected the allowlist to be rejected");
+        };
+        assert_eq!(std::io::ErrorKind::Unsupported, err.kind());
+        assert!(err.to_string().contains("network_allowlist"), "{err}");
+    }
+}
@@ -0,0 +1,504 @@
+//! The `delegate` tool: runs sub-tasks in sub-agent conversations.
+//!
+//! Each task gets its own [`Codex`] session, configured from the current turn
+//! with the requested cwd, sandbox and model. Sub-agents of one call run
+//! concurrently. Their events are forwarded to the parent wrapped in
+//! [`SubAgentEvent`]s, and their approval requests are re-issued by the parent
+//! session one at a time so the user answers them as usual. Their token usage
+//! counts against the parent's session budget and their patches are added to
+//! the parent's turn diff.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ResponseInputItem;
+use futures::future::BoxFuture;
+use futures::future::join_all;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::AuthManager;
+use crate::codex::Codex;
+use crate::codex::CodexSpawnOk;
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::config::Config;
+use crate::config_types::CassetteMode;
+use crate::error::Result as CodexResult;
+use crate::protocol::ErrorEvent;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::InitialHistory;
+use crate::protocol::InputItem;
+use crate::protocol::Op;
+use crate::protocol::PatchApplyBeginEvent;
+use crate::protocol::SandboxPolicy;
+use crate::protocol::SubAgentBeginEvent;
+use crate::protocol::SubAgentEndEvent;
+use crate::protocol::SubAgentEvent;
+use crate::protocol::TaskCompleteEvent;
+use crate::protocol::TokenCountEvent;
+use crate::protocol::TokenUsageInfo;
+use crate::protocol::TurnDiffEvent;
+use crate::turn_diff_tracker::TurnDiffTracker;
+
+#[derive(Debug, Deserialize)]
+struct DelegateArgs {
+    tasks: Vec<DelegateTask>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DelegateTask {
+    prompt: String,
+    #[serde(default)]
+    cwd: Option<String>,
+    #[serde(default)]
+    sandbox: Option<SandboxMode>,
+    #[serde(default)]
+    model: Option<String>,
+}
+
+pub(crate) async fn handle_delegate(
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: String,
+    call_id: String,
+    arguments: String,
+) -> ResponseInputItem {
+    let failure = |call_id: String, content: String| ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            content,
+            success: Some(false),
+        },
+    };
+
+    let args = match serde_json::from_str::<DelegateArgs>(&arguments) {
+        Ok(args) => args,
+        Err(e) => return failure(call_id, format!("failed to parse function arguments: {e}")),
+    };
+    if args.tasks.is_empty() {
+        return failure(call_id, "no tasks to delegate".to_string());
+    }
+    let Some(auth_manager) = turn_context.client.get_auth_manager() else {
+        return failure(
+            call_id,
+            "sub-agents are not available in this session".to_string(),
+        );
+    };
+
+    // Validate every task before starting any of them.
+    let mut tasks = Vec::with_capacity(args.tasks.len());
+    for (index, task) in args.tasks.into_iter().enumerate() {
+        let number = index + 1;
+        match sub_agent_config(turn_context, &call_id, number, &task) {
+            Ok(config) => tasks.push((number, task.prompt, config)),
+            Err(e) => return failure(call_id, format!("task {number}: {e}")),
+        }
+    }
+
+    let delegate_call = DelegateCall {
+        sess,
+        sub_id: &sub_id,
+        call_id: &call_id,
+        auth_manager,
+        approvals: Mutex::new(()),
+        turn_diff_tracker: Mutex::new(turn_diff_tracker),
+    };
+    let results = join_all(
+        tasks
+            .into_iter()
+            .map(|(number, prompt, config)| delegate_call.run(number, prompt, config)),
+    )
+    .await;
+
+    let success = results.iter().all(Result::is_ok);
+    let content = match results.as_slice() {
+        [Ok(message)] => message.clone(),
+        [Err(e)] => format!("sub-agent failed: {e}"),
+        _ => results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| match result {
+                Ok(message) => format!("Task {} finished:\n{message}", index + 1),
+                Err(e) => format!("Task {} failed: {e}", index + 1),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    };
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            content,
+            success: Some(success),
+        },
+    }
+}
+
+/// Derives the sub-agent's config from the current turn.
+fn sub_agent_config(
+    turn_context: &TurnContext,
+    call_id: &str,
+    task: usize,
+    spec: &DelegateTask,
+) -> Result<Config, String> {
+    let cwd = turn_context.resolve_path(spec.cwd.clone());
+    if !cwd.is_dir() {
+        return Err(format!("{} is not a directory", cwd.display()));
+    }
+    let sandbox_policy = sub_agent_sandbox_policy(
+        &turn_context.sandbox_policy,
+        &turn_context.cwd,
+        spec.sandbox,
+        &cwd,
+    )?;
+
+    let mut config = (*turn_context.client.get_config()).clone();
+    if let Some(model) = &spec.model {
+        config.set_model(model);
+    }
+    config.cwd = cwd;
+    config.sandbox_policy = sandbox_policy;
+    config.approval_policy = turn_context.approval_policy;
+    // Sub-agents do not delegate further, resume rollouts or notify. The
+    // parent's checkpoint already covers the whole turn, and its cwd is
+    // already inside the worktree when worktree mode is on.
+    config.include_delegate_tool = false;
+    config.checkpoints = false;
+    config.worktree = false;
+    config.experimental_resume = None;
+    config.notify = None;
+    config.output_schema = None;
+    // Recorded call ids are replayed verbatim, so they name stable cassettes.
+    let cassette_dir = format!("{call_id}-{task}");
+    config.cassette = config.cassette.map(|mode| match mode {
+        CassetteMode::Record(dir) => CassetteMode::Record(dir.join(cassette_dir)),
+        CassetteMode::Replay(dir) => CassetteMode::Replay(dir.join(cassette_dir)),
+    });
+    Ok(config)
+}
+
+/// Returns the sandbox for a sub-agent in `cwd`. It defaults to the parent's
+/// and may be narrowed, but never grants access the parent does not have.
+fn sub_agent_sandbox_policy(
+    parent: &SandboxPolicy,
+    parent_cwd: &Path,
+    requested: Option<SandboxMode>,
+    cwd: &Path,
+) -> Result<SandboxPolicy, String> {
+    let less_restrictive = |mode: SandboxMode| {
+        Err(format!(
+            "sandbox `{mode}` is less restrictive than the current sandbox"
+        ))
+    };
+    let policy = match (requested, parent) {
+        (None, _) => parent.clone(),
+        (Some(SandboxMode::ReadOnly), SandboxPolicy::ReadOnly { .. }) => parent.clone(),
+        (Some(SandboxMode::ReadOnly), _) => SandboxPolicy::new_read_only_policy()
+            .with_readable_roots(parent.readable_roots().map(<[PathBuf]>::to_vec)),
+        (Some(SandboxMode::WorkspaceWrite), SandboxPolicy::WorkspaceWrite { .. }) => parent.clone(),
+        (Some(SandboxMode::WorkspaceWrite), SandboxPolicy::DangerFullAccess) => {
+            SandboxPolicy::new_workspace_write_policy()
+        }
+        (Some(SandboxMode::DangerFullAccess), SandboxPolicy::DangerFullAccess) => parent.clone(),
+        (Some(mode), _) => return less_restrictive(mode),
+    };
+
+    // The sub-agent's cwd becomes part of its workspace, so it must already
+    // be accessible to the parent.
+    if let Some(readable_roots) = parent.get_readable_roots_with_cwd(parent_cwd)
+        && !readable_roots.iter().any(|root| cwd.starts_with(root))
+    {
+        return Err(format!(
+            "{} is not readable in the current sandbox",
+            cwd.display()
+        ));
+    }
+    if matches!(policy, SandboxPolicy::WorkspaceWrite { .. })
+        && matches!(parent, SandboxPolicy::WorkspaceWrite { .. })
+        && !parent
+            .get_writable_roots_with_cwd(parent_cwd)
+            .iter()
+            .any(|root| root.is_path_writable(cwd))
+    {
+        return Err(format!(
+            "{} is not writable in the current sandbox",
+            cwd.display()
+        ));
+    }
+    Ok(policy)
+}
+
+/// Interrupts and shuts down the sub-agent once the `delegate` call is over,
+/// including when the parent's task is aborted mid-call.
+struct SubAgent(Arc<Codex>);
+
+impl Drop for SubAgent {
+    fn drop(&mut self) {
+        let codex = self.0.clone();
+        tokio::spawn(async move {
+            let _ = codex.submit(Op::Interrupt).await;
+            let _ = codex.submit(Op::Shutdown).await;
+        });
+    }
+}
+
+/// Sub-agents run the same submission loop that handles this tool, so the
+/// spawn future is boxed to keep its type from referring to itself.
+fn spawn_sub_agent(
+    config: Config,
+    auth_manager: Arc<AuthManager>,
+) -> BoxFuture<'static, CodexResult<CodexSpawnOk>> {
+    Box::pin(Codex::spawn(config, auth_manager, InitialHistory::New))
+}
+
+/// State shared by the sub-agents of one `delegate` call.
+struct DelegateCall<'a> {
+    sess: &'a Session,
+    sub_id: &'a str,
+    call_id: &'a str,
+    auth_manager: Arc<AuthManager>,
+    /// The parent keys pending approvals by `sub_id`, so parallel sub-agents
+    /// take turns asking.
+    approvals: Mutex<()>,
+    /// The parent's tracker, so the turn diff includes sub-agent patches.
+    turn_diff_tracker: Mutex<&'a mut TurnDiffTracker>,
+}
+
+impl DelegateCall<'_> {
+    async fn run(&self, task: usize, prompt: String, config: Config) -> Result<String, String> {
+        let cwd = config.cwd.clone();
+        let model = config.model.clone();
+        let CodexSpawnOk {
+            codex,
+            conversation_id,
+        } = spawn_sub_agent(config, self.auth_manager.clone())
+            .await
+            .map_err(|e| format!("failed to start sub-agent: {e}"))?;
+        let sub_agent = SubAgent(Arc::new(codex));
+
+        self.send(EventMsg::SubAgentBegin(SubAgentBeginEvent {
+            call_id: self.call_id.to_string(),
+            task,
+            conversation_id,
+            prompt: prompt.clone(),
+            cwd,
+            model,
+        }))
+        .await;
+
+        let result = self.drive(task, &sub_agent.0, prompt).await;
+
+        self.send(EventMsg::SubAgentEnd(SubAgentEndEvent {
+            call_id: self.call_id.to_string(),
+            task,
+            result: result.clone(),
+        }))
+        .await;
+        result
+    }
+
+    /// Submits `prompt` and forwards the sub-agent's events until its task
+    /// ends.
+    async fn drive(&self, task: usize, codex: &Codex, prompt: String) -> Result<String, String> {
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text { text: prompt }],
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut last_error = None;
+        let mut reported_cost_usd = 0.0;
+        loop {
+            let event = codex.next_event().await.map_err(|e| e.to_string())?;
+            let outcome = match &event.msg {
+                EventMsg::SessionConfigured(_) => continue,
+                EventMsg::ExecApprovalRequest(_)
+                | EventMsg::ApplyPatchApprovalRequest(_)
+                | EventMsg::McpToolCallApprovalRequest(_) => {
+                    let decision = {
+                        let _guard = self.approvals.lock().await;
+                        let rx_approve = self
+                            .sess
+                            .request_forwarded_approval(self.sub_id.to_string(), event.msg.clone())
+                            .await;
+                        rx_approve.await.unwrap_or_default()
+                    };
+                    let id = event.id;
+                    let op = match event.msg {
+                        EventMsg::ExecApprovalRequest(_) => Op::ExecApproval { id, decision },
+                        EventMsg::ApplyPatchApprovalRequest(_) => {
+                            Op::PatchApproval { id, decision }
+                        }
+                        _ => Op::McpToolCallApproval { id, decision },
+                    };
+                    codex.submit(op).await.map_err(|e| e.to_string())?;
+                    continue;
+                }
+                EventMsg::Error(ErrorEvent { message }) => {
+                    last_error = Some(message.clone());
+                    None
+                }
+                EventMsg::TokenCount(TokenCountEvent { info: Some(info) }) => {
+                    self.record_token_usage(info, &mut reported_cost_usd).await;
+                    self.sess.exceeded_session_budget().map(Err)
+                }
+                EventMsg::PatchApplyBegin(PatchApplyBeginEvent { changes, .. }) => {
+                    // Sent before the sub-agent launches the apply_patch
+                    // process, so the baseline is the unpatched file.
+                    self.turn_diff_tracker.lock().await.on_patch_begin(changes);
+                    None
+                }
+                EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                    Some(match (last_agent_message, last_error.take()) {
+                        (Some(message), _) => Ok(message.clone()),
+                        (None, Some(error)) => Err(error),
+                        (None, None) => {
+                            Ok("The sub-agent finished without a final message.".to_string())
+                        }
+                    })
+                }
+                EventMsg::TurnAborted(_) => {
+                    Some(Err("the sub-agent's task was aborted".to_string()))
+                }
+                EventMsg::ShutdownComplete => {
+                    Some(Err("the sub-agent shut down before finishing".to_string()))
+                }
+                _ => None,
+            };
+
+            let patch_applied = matches!(event.msg, EventMsg::PatchApplyEnd(_));
+            self.send(EventMsg::SubAgent(SubAgentEvent {
+                call_id: self.call_id.to_string(),
+                task,
+                msg: Box::new(event.msg),
+            }))
+            .await;
+            if patch_applied {
+                let unified_diff = self.turn_diff_tracker.lock().await.get_unified_diff();
+                if let Ok(Some(unified_diff)) = unified_diff {
+                    self.send(EventMsg::TurnDiff(TurnDiffEvent { unified_diff }))
+                        .await;
+                }
+            }
+
+            if let Some(result) = outcome {
+                return result;
+            }
+        }
+    }
+
+    /// Adds the usage of one sub-agent response to the parent's totals.
+    /// `reported_cost_usd` is the sub-agent's total cost already added.
+    async fn record_token_usage(&self, info: &TokenUsageInfo, reported_cost_usd: &mut f64) {
+        let cost_usd = info.total_cost_usd.map(|total_cost_usd| {
+            let cost_usd = total_cost_usd - *reported_cost_usd;
+            *reported_cost_usd = total_cost_usd;
+            cost_usd
+        });
+        let info = self
+            .sess
+            .add_sub_agent_token_usage(&info.last_token_usage, cost_usd);
+        self.send(EventMsg::TokenCount(TokenCountEvent { info: Some(info) }))
+            .await;
+    }
+
+    async fn send(&self, msg: EventMsg) {
+        self.sess
+            .send_event(Event {
+                id: self.sub_id.to_string(),
+                msg,
+            })
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn workspace_write(writable_roots: Vec<PathBuf>) -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots,
+            network_access: false,
+            network_allowlist: Vec::new(),
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            readable_roots: None,
+        }
+    }
+
+    #[test]
+    fn sandbox_defaults_to_parent_and_can_be_narrowed() {
+        let parent = workspace_write(vec![]);
+        let repo = Path::new("/repo");
+
+        assert_eq!(
+            sub_agent_sandbox_policy(&parent, repo, None, Path::new("/repo/crate")),
+            Ok(parent.clone())
+        );
+        assert_eq!(
+            sub_agent_sandbox_policy(
+                &parent,
+                repo,
+                Some(SandboxMode::ReadOnly),
+                Path::new("/repo/crate")
+            ),
+            Ok(SandboxPolicy::new_read_only_policy())
+        );
+    }
+
+    #[test]
+    fn sandbox_cannot_be_widened() {
+        let parent = SandboxPolicy::new_read_only_policy();
+        let repo = Path::new("/repo");
+
+        assert_eq!(
+            sub_agent_sandbox_policy(&parent, repo, Some(SandboxMode::WorkspaceWrite), repo),
+            Err(
+                "sandbox `workspace-write` is less restrictive than the current sandbox"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            sub_agent_sandbox_policy(
+                &workspace_write(vec![]),
+                repo,
+                Some(SandboxMode::DangerFullAccess),
+                repo
+            ),
+            Err(
+                "sandbox `danger-full-access` is less restrictive than the current sandbox"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn workspace_write_cwd_must_be_writable_by_parent() {
+        let parent = workspace_write(vec![PathBuf::from("/shared")]);
+        let repo = Path::new("/repo");
+
+        assert!(sub_agent_sandbox_policy(&parent, repo, None, Path::new("/shared/out")).is_ok());
+        assert_eq!(
+            sub_agent_sandbox_policy(&parent, repo, None, Path::new("/etc")),
+            Err("/etc is not writable in the current sandbox".to_string())
+        );
+        // Read-only sub-agents may look elsewhere.
+        assert!(
+            sub_agent_sandbox_policy(
+                &parent,
+                repo,
+                Some(SandboxMode::ReadOnly),
+                Path::new("/etc")
+            )
+            .is_ok()
+        );
+    }
+}
@@ -100,6 +100,19 @@ pub enum CodexErr {
     #[error("codex-linux-sandbox was required but not provided")]
     LandlockSandboxExecutableNotProvided,
 
+    #[error("a container sandbox was required but `sandbox_container` is not configured")]
+    ContainerSandboxNotConfigured,
+
+    /// The prompt uses a feature that the provider's wire API cannot
+    /// express.
+    #[error("{0}")]
+    UnsupportedByWireApi(String),
+
+    /// A `--replay` cassette has no recording for the request, or the request
+    /// differs from the recorded one.
+    #[error("cassette replay failed: {0}")]
+    Cassette(String),
+
     // -----------------------------------------------------------------
     // Automatic conversions for common external error types
     // -----------------------------------------------------------------
@@ -14,6 +14,8 @@ use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
 
+use crate::config_types::ContainerSandboxConfig;
+use crate::container::spawn_command_under_container;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
@@ -68,6 +70,10 @@ pub enum SandboxType {
 
     /// Only available on Linux.
     LinuxSeccomp,
+
+    /// Rootless container (bubblewrap or podman), opted into via
+    /// `sandbox_backend = "container"`.
+    Container,
 }
 
 #[derive(Clone)]
@@ -82,6 +88,7 @@ pub async fn process_exec_tool_call(
     sandbox_type: SandboxType,
     sandbox_policy: &SandboxPolicy,
     codex_linux_sandbox_exe: &Option<PathBuf>,
+    container_sandbox: Option<&ContainerSandboxConfig>,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
@@ -125,6 +132,36 @@ pub async fn process_exec_tool_call(
 
             consume_truncated_output(child, timeout, stdout_stream).await
         }
+        SandboxType::Container => {
+            let timeout = params.timeout_duration();
+            let ExecParams {
+                command, cwd, env, ..
+            } = params;
+
+            let container_sandbox =
+                container_sandbox.ok_or(CodexErr::ContainerSandboxNotConfigured)?;
+            let (child, container) = spawn_command_under_container(
+                container_sandbox,
+                command,
+                sandbox_policy,
+                cwd,
+                StdioPolicy::RedirectForShellTool,
+                env,
+            )
+            .await?;
+
+            let output = consume_truncated_output(child, timeout, stdout_stream).await;
+            // A timed out or interrupted `podman` client was killed, which
+            // leaves its container running; dropping `container` removes it.
+            if let Some(container) = container
+                && output
+                    .as_ref()
+                    .is_ok_and(|output| output.exit_status.code().is_some())
+            {
+                container.exited();
+            }
+            output
+        }
     };
     let duration = start.elapsed();
     match raw_output_result {
@@ -1,6 +1,9 @@
 use crate::config_types::EnvironmentVariablePattern;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyInherit;
+use crate::network_proxy::network_proxy_for_policy;
+use crate::protocol::SandboxPolicy;
+use crate::spawn::CODEX_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
@@ -10,9 +13,23 @@ use std::collections::HashSet;
 /// process.
 ///
 /// The derivation follows the algorithm documented in the struct-level comment
-/// for [`ShellEnvironmentPolicy`].
-pub fn create_env(policy: &ShellEnvironmentPolicy) -> HashMap<String, String> {
-    populate_env(std::env::vars(), policy)
+/// for [`ShellEnvironmentPolicy`]. When `sandbox_policy` limits the network to
+/// an allowlist, the proxy variables for the matching
+/// [`crate::network_proxy::NetworkProxy`] are added last so they cannot be
+/// overridden.
+pub fn create_env(
+    policy: &ShellEnvironmentPolicy,
+    sandbox_policy: &SandboxPolicy,
+) -> HashMap<String, String> {
+    let mut env_map = populate_env(std::env::vars(), policy);
+    if let Some(proxy) = network_proxy_for_policy(sandbox_policy) {
+        env_map.extend(proxy.env_vars());
+        env_map.insert(
+            CODEX_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR.to_string(),
+            proxy.port().to_string(),
+        );
+    }
+    env_map
 }
 
 fn populate_env<I>(vars: I, policy: &ShellEnvironmentPolicy) -> HashMap<String, String>
@@ -0,0 +1,403 @@
+//! Evaluation of model-proposed commands against `codex-execpolicy` policies.
+//!
+//! The effective policy is the default policy that ships with
+//! `codex-execpolicy`, extended by every `*.policy` file found in
+//! `$CODEX_HOME/policy` (evaluated in lexical order) and finally by the
+//! project's `.codex/policy.star`, if any. Commands that the policy verifies as
+//! safe are auto-approved, commands it forbids are rejected with the policy's
+//! reason, and everything else falls back to the regular approval flow in
+//! [`crate::safety`].
+//!
+//! The project policy lives in the repository and is therefore not trusted to
+//! the same degree as the user's own policy files: it may forbid anything, but
+//! commands that only it verifies as safe still run under the sandbox.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::Policy;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::default_policy_parser;
+
+use crate::bash::try_parse_bash;
+use crate::bash::try_parse_word_only_commands_sequence;
+use crate::git_info::get_git_repo_root;
+
+/// Directory inside `$CODEX_HOME` that holds user policy files.
+const POLICY_DIR: &str = "policy";
+
+/// File extension for user policy files.
+const POLICY_FILE_EXTENSION: &str = "policy";
+
+/// Location of the project policy, relative to the project root.
+const PROJECT_POLICY_FILE: &str = ".codex/policy.star";
+
+/// Outcome of checking a command against the exec policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ExecPolicyDecision {
+    /// Every program in the command matched a rule and none of them may
+    /// write files.
+    Safe,
+    /// Like [`ExecPolicyDecision::Safe`], but only because of rules from the
+    /// project policy, so the command should still be sandboxed.
+    SafeInSandbox,
+    /// At least one program in the command is forbidden by the policy.
+    Forbidden { reason: String },
+    /// The policy has no opinion; defer to the regular approval flow.
+    Unverified,
+}
+
+pub(crate) struct ExecPolicy {
+    /// Default policy plus the user's policy files.
+    policy: Policy,
+    /// `policy` extended by the project policy, when the project has one.
+    project_policy: Option<Policy>,
+}
+
+impl std::fmt::Debug for ExecPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecPolicy")
+            .field("has_project_policy", &self.project_policy.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ExecPolicy {
+    /// Load the default policy extended by the user's policy files in
+    /// `$CODEX_HOME/policy` and by the `.codex/policy.star` of the project
+    /// containing `cwd`. A policy file that cannot be read or parsed is
+    /// skipped, so one typo does not drop the rules in the other files; the
+    /// returned messages describe the skipped files.
+    pub(crate) fn load(codex_home: &Path, cwd: &Path) -> anyhow::Result<(Self, Vec<String>)> {
+        let mut errors = Vec::new();
+        let mut parsers = vec![default_policy_parser()];
+        let mut policy = parse_policies(&parsers)?;
+
+        let policy_dir = codex_home.join(POLICY_DIR);
+        let user_files = user_policy_files(&policy_dir).unwrap_or_else(|e| {
+            errors.push(format!("{}: {e}", policy_dir.display()));
+            Vec::new()
+        });
+        for path in user_files {
+            if let Some(extended) = extend_policy(&mut parsers, &path, &mut errors) {
+                policy = extended;
+            }
+        }
+
+        let project_policy = project_policy_file(cwd)
+            .and_then(|path| extend_policy(&mut parsers, &path, &mut errors));
+
+        Ok((
+            Self {
+                policy,
+                project_policy,
+            },
+            errors,
+        ))
+    }
+
+    /// The policy that ships with `codex-execpolicy`, without any user or
+    /// project extensions.
+    pub(crate) fn default_policy() -> anyhow::Result<Self> {
+        Ok(Self {
+            policy: parse_policies(&[default_policy_parser()])?,
+            project_policy: None,
+        })
+    }
+
+    /// Check `command` against the policy. `bash -lc "<script>"` invocations
+    /// are checked command by command when the script is a plain sequence of
+    /// word-only commands; otherwise the policy cannot reason about them.
+    pub(crate) fn evaluate(&self, command: &[String]) -> ExecPolicyDecision {
+        let Some(project_policy) = &self.project_policy else {
+            return evaluate_command(&self.policy, command);
+        };
+
+        match evaluate_command(project_policy, command) {
+            ExecPolicyDecision::Safe => match evaluate_command(&self.policy, command) {
+                ExecPolicyDecision::Safe => ExecPolicyDecision::Safe,
+                _ => ExecPolicyDecision::SafeInSandbox,
+            },
+            decision => decision,
+        }
+    }
+}
+
+fn evaluate_command(policy: &Policy, command: &[String]) -> ExecPolicyDecision {
+    if let [bash, flag, script] = command
+        && bash == "bash"
+        && flag == "-lc"
+    {
+        let Some(commands) = try_parse_bash(script)
+            .and_then(|tree| try_parse_word_only_commands_sequence(&tree, script))
+        else {
+            return ExecPolicyDecision::Unverified;
+        };
+        if commands.is_empty() {
+            return ExecPolicyDecision::Unverified;
+        }
+
+        let mut all_safe = true;
+        for cmd in &commands {
+            match evaluate_exec(policy, cmd) {
+                forbidden @ ExecPolicyDecision::Forbidden { .. } => return forbidden,
+                ExecPolicyDecision::Safe => {}
+                _ => all_safe = false,
+            }
+        }
+        return if all_safe {
+            ExecPolicyDecision::Safe
+        } else {
+            ExecPolicyDecision::Unverified
+        };
+    }
+
+    evaluate_exec(policy, command)
+}
+
+fn evaluate_exec(policy: &Policy, command: &[String]) -> ExecPolicyDecision {
+    let Some((program, args)) = command.split_first() else {
+        return ExecPolicyDecision::Unverified;
+    };
+    let exec_call = ExecCall {
+        program: program.clone(),
+        args: args.to_vec(),
+    };
+    match policy.check(&exec_call) {
+        Ok(MatchedExec::Match { exec }) if !exec.might_write_files() => ExecPolicyDecision::Safe,
+        Ok(MatchedExec::Match { .. }) | Err(_) => ExecPolicyDecision::Unverified,
+        Ok(MatchedExec::Forbidden { reason, .. }) => ExecPolicyDecision::Forbidden { reason },
+    }
+}
+
+fn parse_policies(parsers: &[PolicyParser]) -> anyhow::Result<Policy> {
+    PolicyParser::parse_all(parsers).map_err(|err| err.into_anyhow())
+}
+
+/// Appends the policy file at `path` to `parsers` and returns the combined
+/// policy. If the file cannot be read or the combination does not parse,
+/// `parsers` is left unchanged and the error is added to `errors`.
+fn extend_policy(
+    parsers: &mut Vec<PolicyParser>,
+    path: &Path,
+    errors: &mut Vec<String>,
+) -> Option<Policy> {
+    let result = read_policy_file(path).and_then(|parser| {
+        parsers.push(parser);
+        parse_policies(parsers).inspect_err(|_| {
+            parsers.pop();
+        })
+    });
+    match result {
+        Ok(policy) => Some(policy),
+        Err(e) => {
+            errors.push(format!("{}: {e:#}", path.display()));
+            None
+        }
+    }
+}
+
+fn read_policy_file(path: &Path) -> anyhow::Result<PolicyParser> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(PolicyParser::new(&path.to_string_lossy(), &contents))
+}
+
+/// Returns the project policy for `cwd`: `.codex/policy.star` at the root of
+/// the enclosing git repository, or in `cwd` itself outside of a repository.
+fn project_policy_file(cwd: &Path) -> Option<PathBuf> {
+    let project_root = get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    let path = project_root.join(PROJECT_POLICY_FILE);
+    path.is_file().then_some(path)
+}
+
+/// Returns the `*.policy` files in `dir`, sorted by file name. A missing
+/// directory yields an empty list.
+fn user_policy_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_policy = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext == POLICY_FILE_EXTENSION);
+        if is_policy && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    fn write_user_policy(codex_home: &Path, name: &str, contents: &str) {
+        let dir = codex_home.join(POLICY_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(name), contents).unwrap();
+    }
+
+    #[test]
+    fn default_policy_verifies_read_only_commands() {
+        let policy = ExecPolicy::default_policy().unwrap();
+        assert_eq!(
+            ExecPolicyDecision::Safe,
+            policy.evaluate(&vec_str(&["pwd"]))
+        );
+        assert_eq!(
+            ExecPolicyDecision::Unverified,
+            policy.evaluate(&vec_str(&["git", "commit"]))
+        );
+        // `cp` matches a rule but writes a file, so it is not auto-approved.
+        assert_eq!(
+            ExecPolicyDecision::Unverified,
+            policy.evaluate(&vec_str(&["cp", "foo", "bar"]))
+        );
+    }
+
+    #[test]
+    fn bash_lc_scripts_are_checked_per_command() {
+        let tmp = TempDir::new().unwrap();
+        write_user_policy(
+            tmp.path(),
+            "deny.policy",
+            r#"
+forbid_program_regex(
+    regex="^rm$",
+    reason="use `git clean` instead of `rm`",
+)
+"#,
+        );
+        let (policy, _) = ExecPolicy::load(tmp.path(), tmp.path()).unwrap();
+
+        assert_eq!(
+            ExecPolicyDecision::Safe,
+            policy.evaluate(&vec_str(&["bash", "-lc", "pwd && ls"]))
+        );
+        assert_eq!(
+            ExecPolicyDecision::Forbidden {
+                reason: "use `git clean` instead of `rm`".to_string()
+            },
+            policy.evaluate(&vec_str(&["bash", "-lc", "pwd && rm foo"]))
+        );
+        assert_eq!(
+            ExecPolicyDecision::Unverified,
+            policy.evaluate(&vec_str(&["bash", "-lc", "pwd > out.txt"]))
+        );
+    }
+
+    #[test]
+    fn user_policies_extend_the_default_policy() {
+        let tmp = TempDir::new().unwrap();
+        write_user_policy(
+            tmp.path(),
+            "extra.policy",
+            r#"
+define_program(
+    program="true",
+)
+"#,
+        );
+        // Files without the `.policy` extension are ignored.
+        write_user_policy(tmp.path(), "notes.txt", "this is not starlark");
+
+        let (policy, _) = ExecPolicy::load(tmp.path(), tmp.path()).unwrap();
+        assert_eq!(
+            ExecPolicyDecision::Safe,
+            policy.evaluate(&vec_str(&["true"]))
+        );
+        assert_eq!(
+            ExecPolicyDecision::Safe,
+            policy.evaluate(&vec_str(&["pwd"]))
+        );
+    }
+
+    #[test]
+    fn invalid_policy_file_is_skipped() {
+        let tmp = TempDir::new().unwrap();
+        write_user_policy(tmp.path(), "a-broken.policy", "define_program(");
+        write_user_policy(
+            tmp.path(),
+            "b-deny.policy",
+            r#"
+forbid_program_regex(
+    regex="^rm$",
+    reason="no rm",
+)
+"#,
+        );
+
+        let (policy, errors) = ExecPolicy::load(tmp.path(), tmp.path()).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("a-broken.policy"), "{errors:?}");
+        assert_eq!(
+            ExecPolicyDecision::Forbidden {
+                reason: "no rm".to_string()
+            },
+            policy.evaluate(&vec_str(&["rm", "foo"]))
+        );
+        assert_eq!(
+            ExecPolicyDecision::Safe,
+            policy.evaluate(&vec_str(&["pwd"]))
+        );
+    }
+
+    #[test]
+    fn project_policy_can_forbid_but_not_unsandbox() {
+        let codex_home = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        std::fs::create_dir_all(project.path().join(".codex")).unwrap();
+        std::fs::write(
+            project.path().join(PROJECT_POLICY_FILE),
+            r#"
+define_program(
+    program="cargo",
+    args=["test"],
+)
+
+define_program(
+    program="git",
+    options=[flag("--force", required=True)],
+    args=["push", ARG_UNVERIFIED_VARARGS],
+    forbidden="do not force-push",
+)
+"#,
+        )
+        .unwrap();
+
+        let (policy, _) = ExecPolicy::load(codex_home.path(), project.path()).unwrap();
+        assert_eq!(
+            ExecPolicyDecision::Forbidden {
+                reason: "do not force-push".to_string()
+            },
+            policy.evaluate(&vec_str(&["git", "push", "--force", "origin", "main"]))
+        );
+        assert_eq!(
+            ExecPolicyDecision::Unverified,
+            policy.evaluate(&vec_str(&["git", "push", "origin", "main"]))
+        );
+        assert_eq!(
+            ExecPolicyDecision::SafeInSandbox,
+            policy.evaluate(&vec_str(&["cargo", "test"]))
+        );
+        assert_eq!(
+            ExecPolicyDecision::Safe,
+            policy.evaluate(&vec_str(&["pwd"]))
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! Git checkpoints of the workspace, taken before each turn so the turn can
+//! be undone.
+//!
+//! A checkpoint is a commit object whose tree is the repository's working
+//! tree, including untracked (but not ignored) files. It is built with a
+//! temporary index, so neither the user's index nor `HEAD` is touched, and is
+//! kept alive by a ref under `refs/codex/checkpoints/<conversation id>/`.
+//! Undoing a turn writes the checkpoint's files back and deletes the files
+//! created since, which also reverts the side effects of shell commands
+//! inside the repository.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use codex_protocol::mcp_protocol::ConversationId;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::Checkpoint;
+use codex_protocol::protocol::InputItem;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::git_info::get_git_repo_root;
+
+/// Snapshotting hashes every changed file, so allow more time than the quick
+/// queries in `git_info`.
+const CHECKPOINT_TIMEOUT: Duration = Duration::from_secs(30);
+
+const CHECKPOINT_REF_PREFIX: &str = "refs/codex/checkpoints";
+
+#[derive(Debug, Clone)]
+pub(crate) struct GitCheckpoint {
+    repo_root: PathBuf,
+    ref_name: String,
+    pub(crate) checkpoint: Checkpoint,
+}
+
+/// Snapshots the repository containing `cwd` as checkpoint `number` of the
+/// conversation. Returns `None` when `cwd` is not inside a git repository.
+pub(crate) async fn create_checkpoint(
+    cwd: &Path,
+    conversation_id: ConversationId,
+    number: usize,
+    summary: String,
+) -> Result<Option<GitCheckpoint>, String> {
+    if get_git_repo_root(cwd).is_none() {
+        return Ok(None);
+    }
+    let repo_root = PathBuf::from(git(cwd, None, &["rev-parse", "--show-toplevel"], None).await?);
+
+    let tree = snapshot_tree(&repo_root, conversation_id).await?;
+    let message = format!("codex checkpoint: {summary}");
+    let mut args = vec![
+        "commit-tree",
+        "--no-gpg-sign",
+        tree.as_str(),
+        "-m",
+        message.as_str(),
+    ];
+    let head = git(
+        &repo_root,
+        None,
+        &["rev-parse", "--verify", "-q", "HEAD"],
+        None,
+    )
+    .await
+    .ok();
+    if let Some(head) = &head {
+        args.extend(["-p", head.as_str()]);
+    }
+    let commit = git(&repo_root, None, &args, None).await?;
+
+    let ref_name = format!("{CHECKPOINT_REF_PREFIX}/{conversation_id}/{number}");
+    git(&repo_root, None, &["update-ref", &ref_name, &commit], None).await?;
+
+    Ok(Some(GitCheckpoint {
+        repo_root,
+        ref_name,
+        checkpoint: Checkpoint { commit, summary },
+    }))
+}
+
+/// Label for the checkpoint of a turn: the first line of the user's message.
+pub(crate) fn summarize_input(input: &[InputItem]) -> String {
+    input
+        .iter()
+        .find_map(|item| match item {
+            InputItem::Text { text } => text.lines().next(),
+            _ => None,
+        })
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Message recorded in the conversation after an undo. Only files are
+/// restored, so the model is told that what it saw since may be stale.
+pub(crate) fn undo_notice(checkpoint: &Checkpoint) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format!(
+                "<undo>The user undid the turn \"{}\": the workspace was restored to its state before that turn, reverting every file change made since. Re-read files before relying on earlier output.</undo>",
+                checkpoint.summary
+            ),
+        }],
+    }
+}
+
+/// Restores the working tree to `checkpoint` and deletes its ref.
+pub(crate) async fn restore_checkpoint(
+    checkpoint: &GitCheckpoint,
+    conversation_id: ConversationId,
+) -> Result<(), String> {
+    let repo_root = checkpoint.repo_root.as_path();
+    let commit = checkpoint.checkpoint.commit.as_str();
+    let current = snapshot_tree(repo_root, conversation_id).await?;
+
+    // Remove files created since the checkpoint first, in case a directory
+    // has to make way for a file of the same name.
+    let added = changed_paths(repo_root, commit, &current, "A").await?;
+    for path in added.split('\0').filter(|path| !path.is_empty()) {
+        let path = repo_root.join(path);
+        tokio::fs::remove_file(&path)
+            .await
+            .map_err(|e| format!("failed to remove {}: {e}", path.display()))?;
+        remove_empty_parents(repo_root, &path).await;
+    }
+
+    let changed = changed_paths(repo_root, commit, &current, "DMT").await?;
+    if !changed.is_empty() {
+        git(
+            repo_root,
+            None,
+            &[
+                "--literal-pathspecs",
+                "restore",
+                "--source",
+                commit,
+                "--worktree",
+                "--pathspec-from-file=-",
+                "--pathspec-file-nul",
+            ],
+            Some(changed.as_bytes()),
+        )
+        .await?;
+    }
+
+    delete_checkpoints(std::slice::from_ref(checkpoint)).await;
+    Ok(())
+}
+
+/// Deletes the refs that keep `checkpoints` alive. Failures are ignored: a
+/// leftover ref only costs disk space.
+pub(crate) async fn delete_checkpoints(checkpoints: &[GitCheckpoint]) {
+    for checkpoint in checkpoints {
+        let _ = git(
+            &checkpoint.repo_root,
+            None,
+            &["update-ref", "-d", &checkpoint.ref_name],
+            None,
+        )
+        .await;
+    }
+}
+
+/// Writes the working tree of `repo_root`, minus ignored files, as a tree
+/// object and returns its id.
+async fn snapshot_tree(
+    repo_root: &Path,
+    conversation_id: ConversationId,
+) -> Result<String, String> {
+    let index_name = format!("codex-checkpoint-{conversation_id}.index");
+    let index = repo_root.join(
+        git(
+            repo_root,
+            None,
+            &["rev-parse", "--git-path", &index_name],
+            None,
+        )
+        .await?,
+    );
+    let user_index =
+        repo_root.join(git(repo_root, None, &["rev-parse", "--git-path", "index"], None).await?);
+
+    // Starting from the user's index lets git reuse its cached file stats
+    // instead of hashing the whole tree.
+    if let Err(e) = tokio::fs::copy(&user_index, &index).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        return Err(format!("failed to copy {}: {e}", user_index.display()));
+    }
+    let tree = async {
+        git(repo_root, Some(&index), &["add", "--all", "--", ":/"], None).await?;
+        git(repo_root, Some(&index), &["write-tree"], None).await
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&index).await;
+    tree
+}
+
+/// NUL-separated paths whose status between `from` and `to` is one of
+/// `filter` (see `git diff --diff-filter`).
+async fn changed_paths(
+    repo_root: &Path,
+    from: &str,
+    to: &str,
+    filter: &str,
+) -> Result<String, String> {
+    let filter = format!("--diff-filter={filter}");
+    let args = [
+        "diff-tree",
+        "-r",
+        "-z",
+        "--name-only",
+        "--no-renames",
+        &filter,
+        from,
+        to,
+    ];
+    git_output(repo_root, None, &args, None).await
+}
+
+async fn remove_empty_parents(repo_root: &Path, path: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir
+        && current != repo_root
+        && tokio::fs::remove_dir(current).await.is_ok()
+    {
+        dir = current.parent();
+    }
+}
+
+/// Runs git in `cwd` and returns its stdout with surrounding whitespace
+/// removed, or its stderr on failure.
+async fn git(
+    cwd: &Path,
+    index: Option<&Path>,
+    args: &[&str],
+    stdin: Option<&[u8]>,
+) -> Result<String, String> {
+    git_output(cwd, index, args, stdin)
+        .await
+        .map(|stdout| stdout.trim().to_string())
+}
+
+/// Like [`git`], but returns stdout verbatim.
+async fn git_output(
+    cwd: &Path,
+    index: Option<&Path>,
+    args: &[&str],
+    stdin: Option<&[u8]>,
+) -> Result<String, String> {
+    let mut command = Command::new("git");
+    command
+        .args(args)
+        .current_dir(cwd)
+        // Checkpoints are internal commits; do not depend on the user's identity.
+        .env("GIT_AUTHOR_NAME", "Codex")
+        .env("GIT_AUTHOR_EMAIL", "codex@localhost")
+        .env("GIT_COMMITTER_NAME", "Codex")
+        .env("GIT_COMMITTER_EMAIL", "codex@localhost")
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(index) = index {
+        command.env("GIT_INDEX_FILE", index);
+    }
+
+    let run = async {
+        let mut child = command.spawn()?;
+        if let Some(input) = stdin
+            && let Some(mut child_stdin) = child.stdin.take()
+        {
+            child_stdin.write_all(input).await?;
+        }
+        child.wait_with_output().await
+    };
+    let output = match timeout(CHECKPOINT_TIMEOUT, run).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("failed to run git {}: {e}", args[0])),
+        Err(_) => return Err(format!("git {} timed out", args[0])),
+    };
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::TempDir;
+
+    async fn init_repo(dir: &Path) {
+        for args in [
+            &["init"][..],
+            &["config", "user.name", "Test User"],
+            &["config", "user.email", "test@example.com"],
+        ] {
+            git(dir, None, args, None).await.expect("git setup");
+        }
+        fs::write(dir.join(".gitignore"), "target/\n").unwrap();
+        fs::write(dir.join("tracked.txt"), "original\n").unwrap();
+        fs::write(dir.join("doomed.txt"), "delete me\n").unwrap();
+        git(dir, None, &["add", "."], None).await.unwrap();
+        git(dir, None, &["commit", "-m", "initial"], None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn restores_working_tree_to_checkpoint() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path();
+        init_repo(repo).await;
+        // Uncommitted work from before the turn is part of the checkpoint.
+        fs::write(repo.join("tracked.txt"), "edited by user\n").unwrap();
+        fs::write(repo.join("notes.txt"), "untracked\n").unwrap();
+        let status_before = git(repo, None, &["status", "--porcelain"], None)
+            .await
+            .unwrap();
+
+        let conversation_id = ConversationId::default();
+        let checkpoint = create_checkpoint(repo, conversation_id, 1, "turn".to_string())
+            .await
+            .unwrap()
+            .expect("inside a repo");
+        assert_eq!(
+            git(repo, None, &["status", "--porcelain"], None)
+                .await
+                .unwrap(),
+            status_before
+        );
+
+        // The turn edits, deletes and creates files.
+        fs::write(repo.join("tracked.txt"), "edited by agent\n").unwrap();
+        fs::remove_file(repo.join("doomed.txt")).unwrap();
+        fs::remove_file(repo.join("notes.txt")).unwrap();
+        fs::create_dir_all(repo.join("new/dir")).unwrap();
+        fs::write(repo.join("new/dir/file.txt"), "created\n").unwrap();
+        fs::create_dir(repo.join("target")).unwrap();
+        fs::write(repo.join("target/build.out"), "ignored\n").unwrap();
+
+        restore_checkpoint(&checkpoint, conversation_id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(repo.join("tracked.txt")).unwrap(),
+            "edited by user\n"
+        );
+        assert_eq!(
+            fs::read_to_string(repo.join("doomed.txt")).unwrap(),
+            "delete me\n"
+        );
+        assert_eq!(
+            fs::read_to_string(repo.join("notes.txt")).unwrap(),
+            "untracked\n"
+        );
+        assert!(!repo.join("new").exists());
+        // Ignored files are not part of checkpoints.
+        assert!(repo.join("target/build.out").exists());
+        assert_eq!(
+            git(repo, None, &["status", "--porcelain"], None)
+                .await
+                .unwrap(),
+            status_before
+        );
+        assert!(
+            git(
+                repo,
+                None,
+                &["rev-parse", "--verify", "-q", &checkpoint.ref_name],
+                None
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn no_checkpoint_outside_git_repo() {
+        let temp = TempDir::new().unwrap();
+        let checkpoint = create_checkpoint(
+            temp.path(),
+            ConversationId::default(),
+            1,
+            "turn".to_string(),
+        )
+        .await
+        .unwrap();
+        assert!(checkpoint.is_none());
+    }
+}
@@ -0,0 +1,512 @@
+//! Worktree isolation: each conversation runs in its own `git worktree` so
+//! parallel sessions on the same repository do not touch each other's files.
+//!
+//! Worktrees live in `$CODEX_HOME/worktrees/<name>` on a new branch
+//! `codex/<name>` that starts at the repository's `HEAD`. When the task is
+//! done, `codex worktree merge|diff|discard <name>` brings the changes back
+//! into the main checkout or throws them away. Resumed and forked sessions go
+//! back to the worktree their history was recorded in instead of creating a
+//! new one.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::WorktreeInfo;
+use tokio::process::Command;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::git_info::get_git_repo_root;
+use crate::git_info::resolve_root_git_project_for_trust;
+
+const WORKTREES_SUBDIR: &str = "worktrees";
+const BRANCH_PREFIX: &str = "codex/";
+
+/// Moves a new session into a fresh worktree unless its cwd already is in
+/// one: `cwd` and the sandbox's writable and readable roots inside the
+/// repository are pointed at the matching paths in the worktree. Resumed and
+/// forked sessions re-enter the worktree recorded in their history, if any.
+pub(crate) async fn enter_worktree(
+    config: &mut Config,
+    history: &InitialHistory,
+) -> io::Result<Option<WorktreeInfo>> {
+    let worktrees_dir = config.codex_home.join(WORKTREES_SUBDIR);
+    if !config.worktree {
+        return Ok(None);
+    }
+    if !matches!(history, InitialHistory::New) {
+        return Ok(restore_worktree(config, history, &worktrees_dir).await);
+    }
+    if config.cwd.starts_with(&worktrees_dir) {
+        return Ok(None);
+    }
+    let (worktree, repo_root) = create_worktree(&config.cwd, &config.codex_home).await?;
+    config.cwd = relocate(&config.cwd, &repo_root, &worktree.path);
+    relocate_sandbox_roots(&mut config.sandbox_policy, &repo_root, &worktree.path);
+    Ok(Some(worktree))
+}
+
+/// Points a resumed or forked session back at the worktree its history was
+/// recorded in. Without one, or once it has been merged or discarded, the
+/// session stays in `config.cwd`.
+async fn restore_worktree(
+    config: &mut Config,
+    history: &InitialHistory,
+    worktrees_dir: &Path,
+) -> Option<WorktreeInfo> {
+    let recorded_cwd = history
+        .get_rollout_items()
+        .into_iter()
+        .find_map(|item| match item {
+            RolloutItem::SessionMeta(meta_line) => Some(meta_line.meta.cwd),
+            _ => None,
+        })?;
+    let name = recorded_cwd
+        .strip_prefix(worktrees_dir)
+        .ok()?
+        .components()
+        .next()?
+        .as_os_str()
+        .to_string_lossy()
+        .into_owned();
+    let Some(worktree) = list_worktrees(&config.codex_home)
+        .await
+        .ok()
+        .and_then(|worktrees| worktrees.into_iter().find(|worktree| worktree.name == name))
+    else {
+        warn!("worktree {name} of the resumed session no longer exists");
+        return None;
+    };
+    if let Some(repo_root) = resolve_root_git_project_for_trust(&worktree.path) {
+        relocate_sandbox_roots(&mut config.sandbox_policy, &repo_root, &worktree.path);
+    }
+    config.cwd = recorded_cwd;
+    Some(worktree)
+}
+
+/// Points the sandbox's writable and readable roots inside `repo_root` at the
+/// matching paths in `worktree_root`.
+fn relocate_sandbox_roots(
+    sandbox_policy: &mut SandboxPolicy,
+    repo_root: &Path,
+    worktree_root: &Path,
+) {
+    let (writable_roots, readable_roots) = match sandbox_policy {
+        SandboxPolicy::DangerFullAccess => (None, None),
+        SandboxPolicy::ReadOnly { readable_roots } => (None, readable_roots.as_mut()),
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots,
+            readable_roots,
+            ..
+        } => (Some(writable_roots), readable_roots.as_mut()),
+    };
+    for root in writable_roots
+        .into_iter()
+        .chain(readable_roots)
+        .flat_map(|roots| roots.iter_mut())
+    {
+        *root = relocate(root, repo_root, worktree_root);
+    }
+}
+
+/// Creates a worktree of the repository containing `cwd`. Returns it along
+/// with the root of that repository.
+async fn create_worktree(cwd: &Path, codex_home: &Path) -> io::Result<(WorktreeInfo, PathBuf)> {
+    if get_git_repo_root(cwd).is_none() {
+        return Err(io::Error::other(format!(
+            "worktree mode requires a git repository, but {} is not in one",
+            cwd.display()
+        )));
+    }
+    let repo_root = PathBuf::from(git(cwd, &["rev-parse", "--show-toplevel"]).await?);
+
+    let repo_name = repo_root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "repo".to_string());
+    let id = Uuid::new_v4().simple().to_string();
+    let name = format!("{repo_name}-{}", &id[..8]);
+    let branch = format!("{BRANCH_PREFIX}{name}");
+    let path = codex_home.join(WORKTREES_SUBDIR).join(&name);
+    tokio::fs::create_dir_all(codex_home.join(WORKTREES_SUBDIR)).await?;
+
+    git(
+        &repo_root,
+        &[
+            "worktree",
+            "add",
+            "-b",
+            &branch,
+            &path.to_string_lossy(),
+            "HEAD",
+        ],
+    )
+    .await?;
+
+    Ok((WorktreeInfo { name, branch, path }, repo_root))
+}
+
+/// Maps `path` inside `repo_root` to the same place in `worktree_root`;
+/// other paths are returned unchanged.
+fn relocate(path: &Path, repo_root: &Path, worktree_root: &Path) -> PathBuf {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    match canonical.strip_prefix(repo_root) {
+        Ok(relative) => worktree_root.join(relative),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Worktrees created by Codex that still exist, sorted by name.
+pub async fn list_worktrees(codex_home: &Path) -> io::Result<Vec<WorktreeInfo>> {
+    let mut entries = match tokio::fs::read_dir(codex_home.join(WORKTREES_SUBDIR)).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut worktrees = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        // A linked worktree has a `.git` file pointing at the main repository.
+        if !path.join(".git").is_file() {
+            continue;
+        }
+        let Ok(branch) = git(&path, &["rev-parse", "--abbrev-ref", "HEAD"]).await else {
+            continue;
+        };
+        worktrees.push(WorktreeInfo {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            branch,
+            path,
+        });
+    }
+    worktrees.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(worktrees)
+}
+
+pub async fn find_worktree(codex_home: &Path, name: &str) -> io::Result<WorktreeInfo> {
+    list_worktrees(codex_home)
+        .await?
+        .into_iter()
+        .find(|worktree| worktree.name == name)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no Codex worktree named `{name}`; see `codex worktree list`"),
+            )
+        })
+}
+
+/// Changes in the worktree, committed or not, relative to the commit it
+/// started from.
+pub async fn worktree_diff(worktree: &WorktreeInfo) -> io::Result<String> {
+    let base = fork_point(worktree).await?;
+    // Stage everything into a throwaway copy of the worktree's index so new
+    // files show up without touching what is staged in the worktree.
+    let git_path = |name: String| async move {
+        git(&worktree.path, &["rev-parse", "--git-path", &name])
+            .await
+            .map(|path| worktree.path.join(path))
+    };
+    let index = git_path(format!("codex-diff-{}.index", Uuid::new_v4().simple())).await?;
+    let worktree_index = git_path("index".to_string()).await?;
+    if let Err(e) = tokio::fs::copy(&worktree_index, &index).await
+        && e.kind() != io::ErrorKind::NotFound
+    {
+        return Err(e);
+    }
+    let diff = async {
+        git_with_index(&worktree.path, Some(&index), &["add", "--all"]).await?;
+        git_with_index(&worktree.path, Some(&index), &["diff", "--cached", &base]).await
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&index).await;
+    diff
+}
+
+/// Commits any pending changes on the worktree's branch, merges the branch
+/// into the main checkout's current branch and removes the worktree. On a
+/// failed merge the worktree is kept so the changes are not lost.
+pub async fn merge_worktree(worktree: &WorktreeInfo) -> io::Result<()> {
+    let repo_root = main_repo_root(worktree)?;
+    git(&worktree.path, &["add", "--all"]).await?;
+    let staged = git(&worktree.path, &["diff", "--cached", "--name-only"]).await?;
+    if !staged.is_empty() {
+        let message = format!("Codex changes from worktree {}", worktree.name);
+        git(&worktree.path, &["commit", "-m", &message]).await?;
+    }
+    git(&repo_root, &["merge", "--no-edit", &worktree.branch])
+        .await
+        .map_err(|e| {
+            io::Error::other(format!(
+                "{e}\nthe worktree is kept at {}",
+                worktree.path.display()
+            ))
+        })?;
+    discard_worktree(worktree).await
+}
+
+/// Removes the worktree and deletes its branch, dropping all its changes.
+pub async fn discard_worktree(worktree: &WorktreeInfo) -> io::Result<()> {
+    let repo_root = main_repo_root(worktree)?;
+    git(
+        &repo_root,
+        &[
+            "worktree",
+            "remove",
+            "--force",
+            &worktree.path.to_string_lossy(),
+        ],
+    )
+    .await?;
+    git(&repo_root, &["branch", "-D", &worktree.branch]).await?;
+    Ok(())
+}
+
+fn main_repo_root(worktree: &WorktreeInfo) -> io::Result<PathBuf> {
+    resolve_root_git_project_for_trust(&worktree.path).ok_or_else(|| {
+        io::Error::other(format!(
+            "cannot find the repository of worktree {}",
+            worktree.path.display()
+        ))
+    })
+}
+
+/// The commit where the worktree's branch left the main checkout's `HEAD`.
+async fn fork_point(worktree: &WorktreeInfo) -> io::Result<String> {
+    let repo_root = main_repo_root(worktree)?;
+    let main_head = git(&repo_root, &["rev-parse", "HEAD"]).await?;
+    git(&worktree.path, &["merge-base", "HEAD", &main_head]).await
+}
+
+/// Runs git in `cwd` and returns its trimmed stdout, or an error carrying its
+/// stderr.
+async fn git(cwd: &Path, args: &[&str]) -> io::Result<String> {
+    git_with_index(cwd, None, args).await
+}
+
+/// Like [`git`], but with `GIT_INDEX_FILE` set to `index` when given.
+async fn git_with_index(cwd: &Path, index: Option<&Path>, args: &[&str]) -> io::Result<String> {
+    let mut command = Command::new("git");
+    command.args(args).current_dir(cwd);
+    if let Some(index) = index {
+        command.env("GIT_INDEX_FILE", index);
+    }
+    let output = command.output().await?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigOverrides;
+    use crate::config::ConfigToml;
+    use codex_protocol::mcp_protocol::ConversationId;
+    use codex_protocol::protocol::SessionMeta;
+    use codex_protocol::protocol::SessionMetaLine;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn worktree_config(cwd: &Path, codex_home: &Path) -> Config {
+        let mut config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides {
+                cwd: Some(cwd.to_path_buf()),
+                ..Default::default()
+            },
+            codex_home.to_path_buf(),
+        )
+        .expect("defaults for test should always succeed");
+        config.worktree = true;
+        config
+    }
+
+    async fn init_repo(dir: &Path) {
+        for args in [
+            &["init"][..],
+            &["config", "user.name", "Test User"],
+            &["config", "user.email", "test@example.com"],
+        ] {
+            git(dir, args).await.expect("git setup");
+        }
+        fs::create_dir(dir.join("src")).unwrap();
+        fs::write(dir.join("src/lib.rs"), "original\n").unwrap();
+        git(dir, &["add", "."]).await.unwrap();
+        git(dir, &["commit", "-m", "initial"]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn merge_brings_worktree_changes_back() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        let codex_home = temp.path().join("home");
+        fs::create_dir(&repo).unwrap();
+        init_repo(&repo).await;
+
+        let mut config = worktree_config(&repo.join("src"), &codex_home);
+        config.sandbox_policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![repo.join("src"), temp.path().join("elsewhere")],
+            network_access: false,
+            network_allowlist: Vec::new(),
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+            readable_roots: None,
+        };
+        let worktree = enter_worktree(&mut config, &InitialHistory::New)
+            .await
+            .unwrap()
+            .expect("worktree mode is on");
+        let cwd = config.cwd.clone();
+        assert_eq!(cwd, worktree.path.join("src"));
+        let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &config.sandbox_policy else {
+            panic!("sandbox policy changed");
+        };
+        assert_eq!(
+            writable_roots,
+            &vec![worktree.path.join("src"), temp.path().join("elsewhere")]
+        );
+        // A session started inside the worktree stays there.
+        assert_eq!(
+            enter_worktree(&mut config, &InitialHistory::New)
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(worktree.branch.starts_with("codex/repo-"));
+        assert_eq!(
+            list_worktrees(&codex_home).await.unwrap(),
+            vec![worktree.clone()]
+        );
+
+        fs::write(cwd.join("lib.rs"), "changed\n").unwrap();
+        fs::write(cwd.join("new.rs"), "new\n").unwrap();
+        // The main checkout is untouched until the merge.
+        assert_eq!(
+            fs::read_to_string(repo.join("src/lib.rs")).unwrap(),
+            "original\n"
+        );
+        let diff = worktree_diff(&worktree).await.unwrap();
+        assert!(diff.contains("+changed"), "diff: {diff}");
+        assert!(diff.contains("src/new.rs"), "diff: {diff}");
+        // Diffing leaves the worktree's index alone.
+        assert_eq!(
+            git(&worktree.path, &["diff", "--cached", "--name-only"])
+                .await
+                .unwrap(),
+            ""
+        );
+
+        merge_worktree(&worktree).await.unwrap();
+
+        assert_eq!(
+            fs::read_to_string(repo.join("src/lib.rs")).unwrap(),
+            "changed\n"
+        );
+        assert!(repo.join("src/new.rs").exists());
+        assert!(!worktree.path.exists());
+        assert!(list_worktrees(&codex_home).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn discard_drops_worktree_and_branch() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        let codex_home = temp.path().join("home");
+        fs::create_dir(&repo).unwrap();
+        init_repo(&repo).await;
+
+        let mut config = worktree_config(&repo, &codex_home);
+        let worktree = enter_worktree(&mut config, &InitialHistory::New)
+            .await
+            .unwrap()
+            .unwrap();
+        fs::write(config.cwd.join("src/lib.rs"), "changed\n").unwrap();
+
+        discard_worktree(&worktree).await.unwrap();
+
+        assert!(!worktree.path.exists());
+        assert_eq!(
+            fs::read_to_string(repo.join("src/lib.rs")).unwrap(),
+            "original\n"
+        );
+        let branches = git(&repo, &["branch", "--list", &worktree.branch])
+            .await
+            .unwrap();
+        assert_eq!(branches, "");
+    }
+
+    #[tokio::test]
+    async fn create_fails_outside_git_repo() {
+        let temp = TempDir::new().unwrap();
+        let mut config = worktree_config(temp.path(), &temp.path().join("home"));
+        let err = enter_worktree(&mut config, &InitialHistory::New)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("requires a git repository"));
+    }
+
+    fn history_recorded_in(cwd: &Path) -> InitialHistory {
+        InitialHistory::Forked(vec![RolloutItem::SessionMeta(SessionMetaLine {
+            meta: SessionMeta {
+                id: ConversationId::new(),
+                timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+                cwd: cwd.to_path_buf(),
+                originator: "test".to_string(),
+                cli_version: "0.0.0".to_string(),
+                instructions: None,
+            },
+            git: None,
+        })])
+    }
+
+    #[tokio::test]
+    async fn resumed_session_reenters_recorded_worktree() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        let codex_home = temp.path().join("home");
+        fs::create_dir(&repo).unwrap();
+        init_repo(&repo).await;
+        let mut config = worktree_config(&repo.join("src"), &codex_home);
+        let worktree = enter_worktree(&mut config, &InitialHistory::New)
+            .await
+            .unwrap()
+            .unwrap();
+        let recorded = history_recorded_in(&config.cwd);
+
+        let mut resumed = worktree_config(&repo.join("src"), &codex_home);
+        assert_eq!(
+            enter_worktree(&mut resumed, &recorded).await.unwrap(),
+            Some(worktree.clone())
+        );
+        assert_eq!(resumed.cwd, worktree.path.join("src"));
+
+        // A history recorded outside any worktree never creates one.
+        let mut resumed = worktree_config(&repo, &codex_home);
+        assert_eq!(
+            enter_worktree(&mut resumed, &history_recorded_in(&repo))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(resumed.cwd, repo);
+
+        // Once the worktree is gone the session stays in the main checkout.
+        discard_worktree(&worktree).await.unwrap();
+        let mut resumed = worktree_config(&repo.join("src"), &codex_home);
+        assert_eq!(enter_worktree(&mut resumed, &recorded).await.unwrap(), None);
+        assert_eq!(resumed.cwd, repo.join("src"));
+        assert_eq!(list_worktrees(&codex_home).await.unwrap(), Vec::new());
+    }
+}
@@ -8,6 +8,7 @@
 mod apply_patch;
 pub mod auth;
 pub mod bash;
+mod cassette;
 mod chat_completions;
 mod client;
 mod client_common;
@@ -19,22 +20,30 @@ pub mod config;
 pub mod config_edit;
 pub mod config_profile;
 pub mod config_types;
+pub mod container;
 mod conversation_history;
+mod delegate;
 pub mod custom_prompts;
 mod environment_context;
 pub mod error;
 pub mod exec;
 mod exec_command;
 pub mod exec_env;
+mod exec_policy;
 mod flags;
+mod git_checkpoint;
 pub mod git_info;
+pub mod git_worktree;
 pub mod internal_storage;
 mod is_safe_command;
 pub mod landlock;
 mod mcp_connection_manager;
+mod mcp_input;
 mod mcp_tool_call;
 mod message_history;
+mod messages_api;
 mod model_provider_info;
+pub mod network_proxy;
 pub mod parse_command;
 mod truncate;
 mod unified_exec;
@@ -58,6 +67,7 @@ mod openai_model_info;
 mod openai_tools;
 pub mod plan_tool;
 pub mod project_doc;
+mod redact;
 mod rollout;
 pub(crate) mod safety;
 pub mod seatbelt;
@@ -73,6 +83,10 @@ pub use rollout::SessionMeta;
 pub use rollout::list::ConversationItem;
 pub use rollout::list::ConversationsPage;
 pub use rollout::list::Cursor;
+pub use rollout::search::SearchEntry;
+pub use rollout::search::SearchEntryKind;
+pub use rollout::search::SearchHit;
+pub use rollout::search::SearchPage;
 mod user_notification;
 pub mod util;
 
@@ -4,30 +4,53 @@
 //! configured server (keyed by the *server name*). It offers convenience
 //! helpers to query the available tools across *all* servers and returns them
 //! in a single aggregated map using the fully-qualified tool name
-//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key. Resources and
+//! prompts are listed on demand from the servers that advertise them.
+//!
+//! Servers that exit unexpectedly are restarted in the background with
+//! backoff and tool lists are refreshed when a server sends
+//! `notifications/tools/list_changed`; see [`McpConnectionManager::refresh`].
 
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ffi::OsString;
 use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use codex_mcp_client::McpClient;
 use mcp_types::ClientCapabilities;
+use mcp_types::GetPromptResult;
 use mcp_types::Implementation;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::Prompt;
+use mcp_types::ReadResourceResult;
+use mcp_types::Resource;
+use mcp_types::ServerCapabilities;
 use mcp_types::Tool;
 
 use serde_json::json;
 use sha1::Digest;
 use sha1::Sha1;
+use tokio::sync::Notify;
 use tokio::task::JoinSet;
 use tracing::info;
 use tracing::warn;
 
+use crate::config_types::McpHttpTransport;
 use crate::config_types::McpServerConfig;
+use crate::config_types::McpServerTransportConfig;
+use crate::config_types::McpToolApproval;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -44,6 +67,14 @@ const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
 /// spawned successfully.
 pub type ClientStartErrors = HashMap<String, anyhow::Error>;
 
+/// Number of times a server that exited unexpectedly is restarted before
+/// giving up until the next reload.
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+
+/// Delay before retrying a failed restart; doubles with every attempt.
+const INITIAL_RESTART_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
 fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     let mut used_names = HashSet::new();
     let mut qualified_tools = HashMap::new();
@@ -76,36 +107,125 @@ fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     qualified_tools
 }
 
+#[derive(Clone)]
 struct ToolInfo {
     server_name: String,
     tool_name: String,
     tool: Tool,
 }
 
+#[derive(Clone)]
 struct ManagedClient {
     client: Arc<McpClient>,
     startup_timeout: Duration,
+    /// Capabilities the server reported in its `initialize` response.
+    capabilities: ServerCapabilities,
+    /// Configuration the server was started with.
+    config: McpServerConfig,
+}
+
+/// Bookkeeping for a server that exited unexpectedly and has not been
+/// restarted yet.
+struct RestartState {
+    /// Number of failed restart attempts so far.
+    attempts: u32,
+    /// Earliest time at which the next attempt may be made.
+    next_attempt: Instant,
+    /// Whether a restart task is currently running for the server.
+    in_progress: bool,
 }
 
-/// A thin wrapper around a set of running [`McpClient`] instances.
 #[derive(Default)]
-pub(crate) struct McpConnectionManager {
+struct ManagerState {
     /// Server-name -> client instance.
     ///
     /// The server name originates from the keys of the `mcp_servers` map in
     /// the user configuration.
     clients: HashMap<String, ManagedClient>,
 
+    /// Server-name -> tools most recently listed by that server.
+    server_tools: HashMap<String, Vec<ToolInfo>>,
+
     /// Fully qualified tool name -> tool instance.
     tools: HashMap<String, ToolInfo>,
+
+    /// Server-name -> restart bookkeeping for servers that exited.
+    restarts: HashMap<String, RestartState>,
+
+    /// Outcomes of background restarts, reported by the next
+    /// [`McpConnectionManager::refresh`].
+    restart_messages: Vec<String>,
+
+    /// Incremented by every reload so that restarts started before it do
+    /// not overwrite the reloaded servers.
+    generation: u64,
+}
+
+impl ManagerState {
+    /// Rebuild `tools` from the servers that are still running.
+    fn requalify_tools(&mut self) {
+        let tools = self
+            .server_tools
+            .iter()
+            .filter(|(server_name, _)| {
+                self.clients
+                    .get(*server_name)
+                    .is_some_and(|managed_client| !managed_client.client.is_closed())
+            })
+            .flat_map(|(_, tools)| tools.iter().cloned())
+            .collect();
+        self.tools = qualify_tools(tools);
+    }
+}
+
+/// Number of reloads requested and finished so far. Callers run reloads in the
+/// background; ops that must observe them wait on this instead of blocking
+/// the submission loop.
+#[derive(Default)]
+struct ReloadQueue {
+    requested: AtomicU64,
+    finished: AtomicU64,
+    notify: Notify,
+}
+
+/// A reload registered with [`McpConnectionManager::begin_reload`]. It counts
+/// as finished once dropped, whether or not it ran.
+pub(crate) struct PendingReload {
+    queue: Arc<ReloadQueue>,
+}
+
+impl Drop for PendingReload {
+    fn drop(&mut self) {
+        // Reloads may finish out of order, so count them rather than
+        // recording the latest one.
+        self.queue.finished.fetch_add(1, Ordering::SeqCst);
+        self.queue.notify.notify_waiters();
+    }
+}
+
+/// A thin wrapper around a set of running [`McpClient`] instances.
+///
+/// Servers that exit unexpectedly are restarted in the background by
+/// [`refresh`](Self::refresh), which also re-lists the tools of servers that
+/// announced a change; the whole set can be replaced with
+/// [`reload`](Self::reload).
+#[derive(Default)]
+pub(crate) struct McpConnectionManager {
+    /// Shared with the background restart tasks.
+    state: Arc<RwLock<ManagerState>>,
+
+    /// Serializes [`refresh`](Self::refresh) and [`reload`](Self::reload).
+    update_lock: tokio::sync::Mutex<()>,
+
+    reloads: Arc<ReloadQueue>,
 }
 
 impl McpConnectionManager {
-    /// Spawn a [`McpClient`] for each configured server.
+    /// Spawn or connect a [`McpClient`] for each configured server.
     ///
     /// * `mcp_servers` – Map loaded from the user configuration where *keys*
-    ///   are human-readable server identifiers and *values* are the spawn
-    ///   instructions.
+    ///   are human-readable server identifiers and *values* are the spawn or
+    ///   connection instructions.
     ///
     /// Servers that fail to start are reported in `ClientStartErrors`: the
     /// user should be informed about these errors.
@@ -117,121 +237,200 @@ impl McpConnectionManager {
             return Ok((Self::default(), ClientStartErrors::default()));
         }
 
-        // Launch all configured servers concurrently.
-        let mut join_set = JoinSet::new();
-        let mut errors = ClientStartErrors::new();
-
-        for (server_name, cfg) in mcp_servers {
-            // Validate server name before spawning
-            if !is_valid_mcp_server_name(&server_name) {
-                let error = anyhow::anyhow!(
-                    "invalid server name '{}': must match pattern ^[a-zA-Z0-9_-]+$",
-                    server_name
-                );
-                errors.insert(server_name, error);
-                continue;
-            }
+        let (clients, errors) = start_servers(mcp_servers).await;
+        let server_tools = list_all_tools(&clients).await;
 
-            let startup_timeout = cfg
-                .startup_timeout_ms
-                .map(Duration::from_millis)
-                .unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+        let mut state = ManagerState {
+            clients,
+            server_tools,
+            ..Default::default()
+        };
+        state.requalify_tools();
 
-            join_set.spawn(async move {
-                let McpServerConfig {
-                    command, args, env, ..
-                } = cfg;
-                let client_res = McpClient::new_stdio_client(
-                    command.into(),
-                    args.into_iter().map(OsString::from).collect(),
-                    env,
-                )
-                .await;
-                match client_res {
-                    Ok(client) => {
-                        // Initialize the client.
-                        let params = mcp_types::InitializeRequestParams {
-                            capabilities: ClientCapabilities {
-                                experimental: None,
-                                roots: None,
-                                sampling: None,
-                                // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
-                                // indicates this should be an empty object.
-                                elicitation: Some(json!({})),
-                            },
-                            client_info: Implementation {
-                                name: "codex-mcp-client".to_owned(),
-                                version: env!("CARGO_PKG_VERSION").to_owned(),
-                                title: Some("Codex".into()),
-                                // This field is used by Codex when it is an MCP
-                                // server: it should not be used when Codex is
-                                // an MCP client.
-                                user_agent: None,
-                            },
-                            protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
-                        };
-                        let initialize_notification_params = None;
-                        match client
-                            .initialize(
-                                params,
-                                initialize_notification_params,
-                                Some(startup_timeout),
-                            )
-                            .await
-                        {
-                            Ok(_response) => (server_name, Ok((client, startup_timeout))),
-                            Err(e) => (server_name, Err(e)),
-                        }
+        Ok((
+            Self {
+                state: Arc::new(RwLock::new(state)),
+                update_lock: tokio::sync::Mutex::new(()),
+                reloads: Arc::default(),
+            },
+            errors,
+        ))
+    }
+
+    fn read_state(&self) -> RwLockReadGuard<'_, ManagerState> {
+        self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_state(&self) -> RwLockWriteGuard<'_, ManagerState> {
+        write_state(&self.state)
+    }
+
+    fn client(&self, server: &str) -> Result<ManagedClient> {
+        self.read_state()
+            .clients
+            .get(server)
+            .cloned()
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))
+    }
+
+    /// Start restarting servers that exited unexpectedly and re-list the
+    /// tools of servers that sent `notifications/tools/list_changed`.
+    ///
+    /// Restarts run in the background so a server that is slow to come back
+    /// does not hold up the caller; until it is back, its tools are not
+    /// listed. Failed restarts are retried on later calls with exponential
+    /// backoff, up to [`MAX_RESTART_ATTEMPTS`] times. Returns a message for
+    /// every restart attempt that finished since the previous call so the
+    /// user can be informed.
+    pub async fn refresh(&self) -> Vec<String> {
+        let _guard = self.update_lock.lock().await;
+        let now = Instant::now();
+
+        let mut changed = HashMap::new();
+        {
+            let mut state = self.write_state();
+            let mut crashed = Vec::new();
+            for (server_name, managed_client) in &state.clients {
+                if managed_client.client.is_closed() {
+                    let attempts = match state.restarts.get(server_name) {
+                        None => Some(0),
+                        Some(restart) => (!restart.in_progress
+                            && restart.attempts < MAX_RESTART_ATTEMPTS
+                            && now >= restart.next_attempt)
+                            .then_some(restart.attempts),
+                    };
+                    if let Some(attempts) = attempts {
+                        crashed.push((
+                            server_name.clone(),
+                            managed_client.config.clone(),
+                            attempts,
+                        ));
                     }
-                    Err(e) => (server_name, Err(e.into())),
+                } else if managed_client.client.take_tools_list_changed() {
+                    info!("MCP server '{server_name}' reported that its tools changed");
+                    changed.insert(server_name.clone(), managed_client.clone());
                 }
-            });
+            }
+
+            let generation = state.generation;
+            for (server_name, config, attempts) in crashed {
+                state.restarts.insert(
+                    server_name.clone(),
+                    RestartState {
+                        attempts,
+                        next_attempt: now,
+                        in_progress: true,
+                    },
+                );
+                tokio::spawn(restart_server(
+                    Arc::clone(&self.state),
+                    generation,
+                    server_name,
+                    config,
+                    attempts,
+                ));
+            }
         }
 
-        let mut clients: HashMap<String, ManagedClient> = HashMap::with_capacity(join_set.len());
+        let server_tools = list_all_tools(&changed).await;
+        let mut state = self.write_state();
+        state.server_tools.extend(server_tools);
+        // Drops the tools of servers that exited.
+        state.requalify_tools();
+        std::mem::take(&mut state.restart_messages)
+    }
 
-        while let Some(res) = join_set.join_next().await {
-            let (server_name, client_res) = match res {
-                Ok((server_name, client_res)) => (server_name, client_res),
-                Err(e) => {
-                    warn!("Task panic when starting MCP server: {e:#}");
-                    continue;
-                }
-            };
+    /// Register a reload that is about to be run in the background with
+    /// [`reload`](Self::reload). Calls to [`wait_for_reloads`] made after
+    /// this one wait until it is done.
+    ///
+    /// [`wait_for_reloads`]: Self::wait_for_reloads
+    pub fn begin_reload(&self) -> PendingReload {
+        self.reloads.requested.fetch_add(1, Ordering::SeqCst);
+        PendingReload {
+            queue: Arc::clone(&self.reloads),
+        }
+    }
 
-            match client_res {
-                Ok((client, startup_timeout)) => {
-                    clients.insert(
-                        server_name,
-                        ManagedClient {
-                            client: Arc::new(client),
-                            startup_timeout,
-                        },
-                    );
-                }
-                Err(e) => {
-                    errors.insert(server_name, e);
+    /// Returns a future that resolves once every reload registered before
+    /// this call has finished. The registrations are read right away, so
+    /// the future can be awaited later from another task.
+    pub fn wait_for_reloads(&self) -> impl Future<Output = ()> + Send + 'static {
+        let reloads = Arc::clone(&self.reloads);
+        let requested = reloads.requested.load(Ordering::SeqCst);
+        async move {
+            loop {
+                // Created before the check so a reload finishing in between
+                // still wakes it.
+                let notified = reloads.notify.notified();
+                if reloads.finished.load(Ordering::SeqCst) >= requested {
+                    return;
                 }
+                notified.await;
             }
         }
+    }
 
-        let all_tools = match list_all_tools(&clients).await {
-            Ok(tools) => tools,
-            Err(e) => {
-                warn!("Failed to list tools from some MCP servers: {e:#}");
-                Vec::new()
-            }
+    /// Replace the running servers with `mcp_servers`. Servers whose
+    /// configuration is unchanged and that are still running are kept; all
+    /// others are stopped or (re)started. Servers that fail to start are
+    /// reported in `ClientStartErrors`. `pending` is marked finished once the
+    /// new servers are in place.
+    pub async fn reload(
+        &self,
+        pending: PendingReload,
+        mcp_servers: HashMap<String, McpServerConfig>,
+    ) -> ClientStartErrors {
+        let _pending = pending;
+        let _guard = self.update_lock.lock().await;
+
+        let to_start: HashMap<String, McpServerConfig> = {
+            let state = self.read_state();
+            mcp_servers
+                .into_iter()
+                .filter(|(server_name, config)| {
+                    !state
+                        .clients
+                        .get(server_name)
+                        .is_some_and(|managed_client| {
+                            managed_client.config == *config && !managed_client.client.is_closed()
+                        })
+                })
+                .collect()
+        };
+        let keep: HashSet<String> = {
+            let state = self.read_state();
+            state
+                .clients
+                .keys()
+                .filter(|server_name| !to_start.contains_key(*server_name))
+                .cloned()
+                .collect()
         };
 
-        let tools = qualify_tools(all_tools);
+        let (started, errors) = start_servers(to_start).await;
+        let server_tools = list_all_tools(&started).await;
 
-        Ok((Self { clients, tools }, errors))
+        let mut state = self.write_state();
+        state.generation += 1;
+        state
+            .clients
+            .retain(|server_name, _| keep.contains(server_name));
+        state
+            .server_tools
+            .retain(|server_name, _| keep.contains(server_name));
+        state.restarts.clear();
+        state.clients.extend(started);
+        state.server_tools.extend(server_tools);
+        state.requalify_tools();
+        errors
     }
 
     /// Returns a single map that contains **all** tools. Each key is the
     /// fully-qualified name for the tool.
     pub fn list_all_tools(&self) -> HashMap<String, Tool> {
-        self.tools
+        self.read_state()
+            .tools
             .iter()
             .map(|(name, tool)| (name.clone(), tool.tool.clone()))
             .collect()
@@ -245,12 +444,7 @@ impl McpConnectionManager {
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
     ) -> Result<mcp_types::CallToolResult> {
-        let client = self
-            .clients
-            .get(server)
-            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?
-            .client
-            .clone();
+        let client = self.client(server)?.client;
 
         client
             .call_tool(tool.to_string(), arguments, timeout)
@@ -258,16 +452,362 @@ impl McpConnectionManager {
             .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
+    /// Whether the user must approve calls to `tool` on `server` before they
+    /// are made, according to the server's per-tool `approval` setting.
+    pub fn tool_requires_approval(&self, server: &str, tool: &str) -> bool {
+        let state = self.read_state();
+        let Some(managed_client) = state.clients.get(server) else {
+            return false;
+        };
+        match managed_client.config.tool_approval(tool) {
+            McpToolApproval::Always => true,
+            McpToolApproval::Never => false,
+            McpToolApproval::OnRequest => {
+                let read_only = state
+                    .tools
+                    .values()
+                    .find(|info| info.server_name == server && info.tool_name == tool)
+                    .and_then(|info| info.tool.annotations.as_ref())
+                    .and_then(|annotations| annotations.read_only_hint)
+                    .unwrap_or(false);
+                !read_only
+            }
+        }
+    }
+
+    /// Returns the resources of every server that supports them, keyed by
+    /// server name. Servers that fail to list their resources are skipped.
+    pub async fn list_all_resources(&self) -> HashMap<String, Vec<Resource>> {
+        let clients = self.read_state().clients.clone();
+        let mut join_set = JoinSet::new();
+        for (server_name, managed_client) in clients {
+            if managed_client.capabilities.resources.is_none() {
+                continue;
+            }
+            let client = managed_client.client;
+            let timeout = managed_client.startup_timeout;
+            join_set.spawn(async move {
+                let mut resources = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let params = ListResourcesRequestParams { cursor };
+                    let result = client.list_resources(Some(params), Some(timeout)).await?;
+                    resources.extend(result.resources);
+                    cursor = result.next_cursor;
+                    if cursor.is_none() {
+                        break;
+                    }
+                }
+                Ok::<_, anyhow::Error>((server_name, resources))
+            });
+        }
+
+        let mut aggregated = HashMap::new();
+        while let Some(join_res) = join_set.join_next().await {
+            match join_res {
+                Ok(Ok((server_name, resources))) => {
+                    aggregated.insert(server_name, resources);
+                }
+                Ok(Err(e)) => warn!("Failed to list resources for MCP server: {e:#}"),
+                Err(e) => warn!("Task panic when listing resources for MCP server: {e:#}"),
+            }
+        }
+        aggregated
+    }
+
+    /// Returns the prompts of every server that supports them, keyed by
+    /// server name. Servers that fail to list their prompts are skipped.
+    pub async fn list_all_prompts(&self) -> HashMap<String, Vec<Prompt>> {
+        let clients = self.read_state().clients.clone();
+        let mut join_set = JoinSet::new();
+        for (server_name, managed_client) in clients {
+            if managed_client.capabilities.prompts.is_none() {
+                continue;
+            }
+            let client = managed_client.client;
+            let timeout = managed_client.startup_timeout;
+            join_set.spawn(async move {
+                let mut prompts = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let params = ListPromptsRequestParams { cursor };
+                    let result = client.list_prompts(Some(params), Some(timeout)).await?;
+                    prompts.extend(result.prompts);
+                    cursor = result.next_cursor;
+                    if cursor.is_none() {
+                        break;
+                    }
+                }
+                Ok::<_, anyhow::Error>((server_name, prompts))
+            });
+        }
+
+        let mut aggregated = HashMap::new();
+        while let Some(join_res) = join_set.join_next().await {
+            match join_res {
+                Ok(Ok((server_name, prompts))) => {
+                    aggregated.insert(server_name, prompts);
+                }
+                Ok(Err(e)) => warn!("Failed to list prompts for MCP server: {e:#}"),
+                Err(e) => warn!("Task panic when listing prompts for MCP server: {e:#}"),
+            }
+        }
+        aggregated
+    }
+
+    /// Read the resource at `uri` from `server`.
+    pub async fn read_resource(&self, server: &str, uri: &str) -> Result<ReadResourceResult> {
+        let managed_client = self.client(server)?;
+
+        managed_client
+            .client
+            .read_resource(uri.to_string(), Some(managed_client.startup_timeout))
+            .await
+            .with_context(|| format!("resources/read failed for `{server}:{uri}`"))
+    }
+
+    /// Fetch the prompt `name` from `server`, rendered with `arguments`.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        name: &str,
+        arguments: Option<HashMap<String, String>>,
+    ) -> Result<GetPromptResult> {
+        let managed_client = self.client(server)?;
+        let arguments = arguments.map(|args| json!(args));
+
+        managed_client
+            .client
+            .get_prompt(
+                name.to_string(),
+                arguments,
+                Some(managed_client.startup_timeout),
+            )
+            .await
+            .with_context(|| format!("prompts/get failed for `{server}/{name}`"))
+    }
+
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
-        self.tools
+        self.read_state()
+            .tools
             .get(tool_name)
             .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
     }
 }
 
-/// Query every server for its available tools and return a single map that
-/// contains **all** tools. Each key is the fully-qualified name for the tool.
-async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<ToolInfo>> {
+/// Start every server in `mcp_servers` concurrently. Servers that fail to
+/// start are reported in `ClientStartErrors`.
+async fn start_servers(
+    mcp_servers: HashMap<String, McpServerConfig>,
+) -> (HashMap<String, ManagedClient>, ClientStartErrors) {
+    let mut join_set = JoinSet::new();
+    let mut errors = ClientStartErrors::new();
+
+    for (server_name, cfg) in mcp_servers {
+        // Validate server name before spawning
+        if !is_valid_mcp_server_name(&server_name) {
+            let error = anyhow::anyhow!(
+                "invalid server name '{}': must match pattern ^[a-zA-Z0-9_-]+$",
+                server_name
+            );
+            errors.insert(server_name, error);
+            continue;
+        }
+
+        join_set.spawn(async move { (server_name, start_server(cfg).await) });
+    }
+
+    let mut clients: HashMap<String, ManagedClient> = HashMap::with_capacity(join_set.len());
+
+    while let Some(res) = join_set.join_next().await {
+        let (server_name, client_res) = match res {
+            Ok((server_name, client_res)) => (server_name, client_res),
+            Err(e) => {
+                warn!("Task panic when starting MCP server: {e:#}");
+                continue;
+            }
+        };
+
+        match client_res {
+            Ok(managed_client) => {
+                clients.insert(server_name, managed_client);
+            }
+            Err(e) => {
+                errors.insert(server_name, e);
+            }
+        }
+    }
+
+    (clients, errors)
+}
+
+/// Spawn or connect a single server and perform the `initialize` handshake.
+async fn start_server(cfg: McpServerConfig) -> Result<ManagedClient> {
+    let startup_timeout = cfg
+        .startup_timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+
+    let client = new_client(cfg.transport.clone(), startup_timeout).await?;
+
+    // Initialize the client.
+    let params = mcp_types::InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
+            // indicates this should be an empty object.
+            elicitation: Some(json!({})),
+        },
+        client_info: Implementation {
+            name: "codex-mcp-client".to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            title: Some("Codex".into()),
+            // This field is used by Codex when it is an MCP server: it should
+            // not be used when Codex is an MCP client.
+            user_agent: None,
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
+    };
+    let initialize_notification_params = None;
+    let response = client
+        .initialize(
+            params,
+            initialize_notification_params,
+            Some(startup_timeout),
+        )
+        .await?;
+
+    Ok(ManagedClient {
+        client: Arc::new(client),
+        startup_timeout,
+        capabilities: response.capabilities,
+        config: cfg,
+    })
+}
+
+/// Restart a server that exited, after `attempts` failed attempts, and
+/// record the outcome in `state` unless the servers were reloaded meanwhile.
+async fn restart_server(
+    state: Arc<RwLock<ManagerState>>,
+    generation: u64,
+    server_name: String,
+    config: McpServerConfig,
+    attempts: u32,
+) {
+    let result = match start_server(config).await {
+        Ok(managed_client) => {
+            let clients = HashMap::from([(server_name.clone(), managed_client)]);
+            let server_tools = list_all_tools(&clients).await;
+            Ok((clients, server_tools))
+        }
+        Err(e) => Err(e),
+    };
+
+    let mut state = write_state(&state);
+    if state.generation != generation {
+        return;
+    }
+    match result {
+        Ok((clients, server_tools)) => {
+            state.restart_messages.push(format!(
+                "MCP server `{server_name}` exited unexpectedly and was restarted"
+            ));
+            state.restarts.remove(&server_name);
+            state.clients.extend(clients);
+            state.server_tools.extend(server_tools);
+            state.requalify_tools();
+        }
+        Err(e) => {
+            let attempts = attempts + 1;
+            let backoff = restart_backoff(attempts);
+            let message = if attempts < MAX_RESTART_ATTEMPTS {
+                format!(
+                    "MCP server `{server_name}` exited unexpectedly and could not be restarted (attempt {attempts}/{MAX_RESTART_ATTEMPTS}, retrying in {}s): {e:#}",
+                    backoff.as_secs()
+                )
+            } else {
+                format!(
+                    "MCP server `{server_name}` exited unexpectedly and could not be restarted after {attempts} attempts; reload the MCP servers to try again: {e:#}"
+                )
+            };
+            state.restart_messages.push(message);
+            state.restarts.insert(
+                server_name,
+                RestartState {
+                    attempts,
+                    next_attempt: Instant::now() + backoff,
+                    in_progress: false,
+                },
+            );
+        }
+    }
+}
+
+fn write_state(state: &RwLock<ManagerState>) -> RwLockWriteGuard<'_, ManagerState> {
+    state.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Delay before the next restart attempt after `attempts` failed attempts:
+/// exponential, starting at [`INITIAL_RESTART_BACKOFF`] and capped at
+/// [`MAX_RESTART_BACKOFF`].
+fn restart_backoff(attempts: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+    INITIAL_RESTART_BACKOFF
+        .saturating_mul(factor)
+        .min(MAX_RESTART_BACKOFF)
+}
+
+/// Create a client for a single server. Connecting to an HTTP server is
+/// bounded by `startup_timeout` since the legacy SSE transport has to wait for
+/// the server to announce its endpoint.
+async fn new_client(
+    transport: McpServerTransportConfig,
+    startup_timeout: Duration,
+) -> Result<McpClient> {
+    match transport {
+        McpServerTransportConfig::Stdio { command, args, env } => Ok(McpClient::new_stdio_client(
+            command.into(),
+            args.into_iter().map(OsString::from).collect(),
+            env,
+        )
+        .await?),
+        McpServerTransportConfig::Http {
+            url,
+            transport,
+            bearer_token_env_var,
+            http_headers,
+        } => {
+            let bearer_token = match bearer_token_env_var {
+                Some(var) => Some(std::env::var(&var).with_context(|| {
+                    format!("environment variable `{var}` for the MCP bearer token is not set")
+                })?),
+                None => None,
+            };
+            let headers = http_headers.unwrap_or_default();
+            let connect = async {
+                match transport {
+                    McpHttpTransport::StreamableHttp => {
+                        McpClient::new_streamable_http_client(url, headers, bearer_token).await
+                    }
+                    McpHttpTransport::Sse => {
+                        McpClient::new_sse_client(url, headers, bearer_token).await
+                    }
+                }
+            };
+            tokio::time::timeout(startup_timeout, connect)
+                .await
+                .map_err(|_| anyhow!("timed out connecting to MCP server"))?
+        }
+    }
+}
+
+/// Query every server for its available tools and return them keyed by server
+/// name. Servers that fail to list their tools are omitted.
+async fn list_all_tools(
+    clients: &HashMap<String, ManagedClient>,
+) -> HashMap<String, Vec<ToolInfo>> {
     let mut join_set = JoinSet::new();
 
     // Spawn one task per server so we can query them concurrently. This
@@ -283,7 +823,7 @@ async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<
         });
     }
 
-    let mut aggregated: Vec<ToolInfo> = Vec::with_capacity(join_set.len());
+    let mut aggregated: HashMap<String, Vec<ToolInfo>> = HashMap::with_capacity(join_set.len());
 
     while let Some(join_res) = join_set.join_next().await {
         let (server_name, list_result) = if let Ok(result) = join_res {
@@ -300,23 +840,32 @@ async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<
             continue;
         };
 
+        let config = &clients[&server_name].config;
+        let server_tools = aggregated.entry(server_name.clone()).or_default();
         for tool in list_result.tools {
+            if !config.is_tool_enabled(&tool.name) {
+                info!(
+                    "skipping disabled tool '{}' from MCP server '{server_name}'",
+                    tool.name
+                );
+                continue;
+            }
             let tool_info = ToolInfo {
                 server_name: server_name.clone(),
                 tool_name: tool.name.clone(),
                 tool,
             };
-            aggregated.push(tool_info);
+            server_tools.push(tool_info);
         }
     }
 
     info!(
         "aggregated {} tools from {} servers",
-        aggregated.len(),
+        aggregated.values().map(Vec::len).sum::<usize>(),
         clients.len()
     );
 
-    Ok(aggregated)
+    aggregated
 }
 
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
@@ -330,6 +879,14 @@ fn is_valid_mcp_server_name(server_name: &str) -> bool {
 mod tests {
     use super::*;
     use mcp_types::ToolInputSchema;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::Request;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::method;
 
     fn create_test_tool(server_name: &str, tool_name: &str) -> ToolInfo {
         ToolInfo {
@@ -412,4 +969,130 @@ mod tests {
             "my_server__yet_another_e1c3987bd9c50b826cbe1687966f79f0c602d19ca"
         );
     }
+
+    #[test]
+    fn test_restart_backoff_doubles_and_is_capped() {
+        assert_eq!(restart_backoff(1), Duration::from_secs(1));
+        assert_eq!(restart_backoff(2), Duration::from_secs(2));
+        assert_eq!(restart_backoff(4), Duration::from_secs(8));
+        assert_eq!(restart_backoff(7), MAX_RESTART_BACKOFF);
+        assert_eq!(restart_backoff(u32::MAX), MAX_RESTART_BACKOFF);
+    }
+
+    /// Streamable HTTP MCP server with a single `ping` tool that hands out a
+    /// new session on every `initialize` and answers requests for the first
+    /// session with 404 once `expired` is set.
+    async fn start_session_server(expired: Arc<AtomicBool>) -> MockServer {
+        let sessions = AtomicU64::new(0);
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(move |request: &Request| {
+                let session = request
+                    .headers
+                    .get("mcp-session-id")
+                    .and_then(|value| value.to_str().ok());
+                if expired.load(Ordering::SeqCst) && session == Some("session-1") {
+                    return ResponseTemplate::new(404);
+                }
+
+                let message: serde_json::Value = request.body_json().unwrap();
+                let Some(id) = message.get("id") else {
+                    // Notifications are acknowledged without a body.
+                    return ResponseTemplate::new(202);
+                };
+                match message["method"].as_str() {
+                    Some("initialize") => {
+                        let session = sessions.fetch_add(1, Ordering::SeqCst) + 1;
+                        ResponseTemplate::new(200)
+                            .insert_header("mcp-session-id", format!("session-{session}"))
+                            .set_body_json(json!({
+                                "jsonrpc": "2.0",
+                                "id": id,
+                                "result": {
+                                    "protocolVersion": mcp_types::MCP_SCHEMA_VERSION,
+                                    "capabilities": { "tools": {} },
+                                    "serverInfo": { "name": "echo", "version": "1.0.0" },
+                                },
+                            }))
+                    }
+                    Some("tools/list") => ResponseTemplate::new(200).set_body_json(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "tools": [{ "name": "ping", "inputSchema": { "type": "object" } }],
+                        },
+                    })),
+                    _ => ResponseTemplate::new(200).set_body_json(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": { "content": [] },
+                    })),
+                }
+            })
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_refresh_restarts_closed_server_in_background() {
+        let expired = Arc::new(AtomicBool::new(false));
+        let server = start_session_server(Arc::clone(&expired)).await;
+        let config = McpServerConfig {
+            transport: McpServerTransportConfig::Http {
+                url: server.uri(),
+                transport: McpHttpTransport::StreamableHttp,
+                bearer_token_env_var: None,
+                http_headers: None,
+            },
+            startup_timeout_ms: None,
+            enabled_tools: None,
+            disabled_tools: None,
+            tools: HashMap::new(),
+        };
+        let (manager, errors) =
+            McpConnectionManager::new(HashMap::from([("echo".to_string(), config)]))
+                .await
+                .unwrap();
+        assert!(errors.is_empty());
+        assert!(manager.list_all_tools().contains_key("echo__ping"));
+
+        // The server ends the session, which the client reports as closed.
+        expired.store(true, Ordering::SeqCst);
+        manager
+            .call_tool("echo", "ping", None, Some(Duration::from_secs(5)))
+            .await
+            .unwrap_err();
+        let client = manager.client("echo").unwrap().client;
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !client.is_closed() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("client should be reported as closed");
+
+        let messages = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let messages = manager.refresh().await;
+                if !messages.is_empty() {
+                    return messages;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("server should be restarted");
+
+        assert_eq!(
+            messages,
+            vec!["MCP server `echo` exited unexpectedly and was restarted".to_string()]
+        );
+        assert!(manager.list_all_tools().contains_key("echo__ping"));
+        assert!(!manager.client("echo").unwrap().client.is_closed());
+        manager
+            .call_tool("echo", "ping", None, Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+    }
 }
@@ -0,0 +1,292 @@
+//! Resolution of MCP resources and prompts attached to user input.
+//!
+//! Front-ends reference MCP content with [`InputItem::McpResource`] and
+//! [`InputItem::McpPrompt`]. Before the input is handed to the model, those
+//! items are replaced by the text and images returned by `resources/read` and
+//! `prompts/get` respectively. Prompt messages keep their role, so a prompt
+//! that includes example assistant replies reaches the model as a
+//! conversation rather than as one user message.
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseInputItem;
+use mcp_types::BlobResourceContents;
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::GetPromptResult;
+use mcp_types::PromptMessage;
+use mcp_types::ReadResourceResult;
+use mcp_types::ReadResourceResultContents;
+use mcp_types::Role;
+use mcp_types::TextResourceContents;
+use tracing::warn;
+
+use crate::mcp_connection_manager::McpConnectionManager;
+use crate::protocol::InputItem;
+
+/// Replace every MCP item in `items` with the content it refers to and
+/// convert the result to model input: user content is grouped into user
+/// messages, and assistant messages from prompts become assistant messages.
+/// Items that cannot be resolved are dropped; a description of each failure
+/// is returned alongside the input so it can be shown to the user.
+pub(crate) async fn resolve_mcp_input_items(
+    manager: &McpConnectionManager,
+    items: Vec<InputItem>,
+) -> (Vec<ResponseInputItem>, Vec<String>) {
+    let mut input = TurnInputBuilder::default();
+    let mut errors = Vec::new();
+    for item in items {
+        match item {
+            InputItem::McpResource { server, uri } => {
+                match manager.read_resource(&server, &uri).await {
+                    Ok(result) => input.user_items(resource_to_input_items(&server, result)),
+                    Err(e) => {
+                        warn!("failed to read MCP resource: {e:#}");
+                        errors.push(format!("Failed to read MCP resource {server}:{uri}: {e:#}"));
+                    }
+                }
+            }
+            InputItem::McpPrompt {
+                server,
+                name,
+                arguments,
+            } => match manager.get_prompt(&server, &name, arguments).await {
+                Ok(result) => input.prompt(&server, result),
+                Err(e) => {
+                    warn!("failed to get MCP prompt: {e:#}");
+                    errors.push(format!("Failed to get MCP prompt {server}/{name}: {e:#}"));
+                }
+            },
+            other => input.user_items(vec![other]),
+        }
+    }
+    (input.finish(), errors)
+}
+
+/// Accumulates resolved input, starting a new message whenever the role
+/// changes.
+#[derive(Default)]
+struct TurnInputBuilder {
+    messages: Vec<ResponseInputItem>,
+    user_items: Vec<InputItem>,
+}
+
+impl TurnInputBuilder {
+    fn user_items(&mut self, items: Vec<InputItem>) {
+        self.user_items.extend(items);
+    }
+
+    fn prompt(&mut self, server: &str, result: GetPromptResult) {
+        for message in result.messages {
+            let PromptMessage { content, role } = message;
+            let Some(item) = prompt_content_to_input_item(server, content) else {
+                continue;
+            };
+            match role {
+                Role::User => self.user_items.push(item),
+                Role::Assistant => {
+                    // Only text can be replayed as model output.
+                    let InputItem::Text { text } = item else {
+                        warn!("skipping non-text assistant message in MCP prompt from `{server}`");
+                        continue;
+                    };
+                    self.flush_user_items();
+                    self.messages.push(ResponseInputItem::Message {
+                        role: "assistant".to_string(),
+                        content: vec![ContentItem::OutputText { text }],
+                    });
+                }
+            }
+        }
+    }
+
+    fn flush_user_items(&mut self) {
+        if !self.user_items.is_empty() {
+            let items = std::mem::take(&mut self.user_items);
+            self.messages.push(ResponseInputItem::from(items));
+        }
+    }
+
+    fn finish(mut self) -> Vec<ResponseInputItem> {
+        self.flush_user_items();
+        self.messages
+    }
+}
+
+fn resource_to_input_items(server: &str, result: ReadResourceResult) -> Vec<InputItem> {
+    result
+        .contents
+        .into_iter()
+        .map(|contents| match contents {
+            ReadResourceResultContents::TextResourceContents(text) => text_resource(server, text),
+            ReadResourceResultContents::BlobResourceContents(blob) => blob_resource(server, blob),
+        })
+        .collect()
+}
+
+fn prompt_content_to_input_item(server: &str, content: ContentBlock) -> Option<InputItem> {
+    match content {
+        ContentBlock::TextContent(text) => Some(InputItem::Text { text: text.text }),
+        ContentBlock::ImageContent(image) => Some(InputItem::Image {
+            image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+        }),
+        ContentBlock::EmbeddedResource(embedded) => Some(match embedded.resource {
+            EmbeddedResourceResource::TextResourceContents(text) => text_resource(server, text),
+            EmbeddedResourceResource::BlobResourceContents(blob) => blob_resource(server, blob),
+        }),
+        ContentBlock::ResourceLink(link) => Some(InputItem::Text {
+            text: format!("MCP resource available from `{server}`: {}", link.uri),
+        }),
+        // The model cannot consume audio input.
+        ContentBlock::AudioContent(_) => None,
+    }
+}
+
+fn text_resource(server: &str, contents: TextResourceContents) -> InputItem {
+    let TextResourceContents {
+        mime_type,
+        text,
+        uri,
+    } = contents;
+    let mime_type = mime_type
+        .map(|mime| format!(" mime_type=\"{mime}\""))
+        .unwrap_or_default();
+    InputItem::Text {
+        text: format!(
+            "<mcp_resource server=\"{server}\" uri=\"{uri}\"{mime_type}>\n{text}\n</mcp_resource>"
+        ),
+    }
+}
+
+fn blob_resource(server: &str, contents: BlobResourceContents) -> InputItem {
+    let BlobResourceContents {
+        blob,
+        mime_type,
+        uri,
+    } = contents;
+    match mime_type {
+        Some(mime) if mime.starts_with("image/") => InputItem::Image {
+            image_url: format!("data:{mime};base64,{blob}"),
+        },
+        mime_type => {
+            let mime_type = mime_type.unwrap_or_else(|| "unknown".to_string());
+            InputItem::Text {
+                text: format!(
+                    "<mcp_resource server=\"{server}\" uri=\"{uri}\" mime_type=\"{mime_type}\">\n(binary content omitted)\n</mcp_resource>"
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ImageContent;
+    use mcp_types::TextContent;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn resources_become_tagged_text_and_images() {
+        let result = ReadResourceResult {
+            contents: vec![
+                ReadResourceResultContents::TextResourceContents(TextResourceContents {
+                    mime_type: Some("text/markdown".to_string()),
+                    text: "# Runbook".to_string(),
+                    uri: "docs://runbook".to_string(),
+                }),
+                ReadResourceResultContents::BlobResourceContents(BlobResourceContents {
+                    blob: "aGk=".to_string(),
+                    mime_type: Some("image/png".to_string()),
+                    uri: "docs://diagram".to_string(),
+                }),
+                ReadResourceResultContents::BlobResourceContents(BlobResourceContents {
+                    blob: "aGk=".to_string(),
+                    mime_type: None,
+                    uri: "docs://archive".to_string(),
+                }),
+            ],
+        };
+
+        assert_eq!(
+            vec![
+                InputItem::Text {
+                    text: "<mcp_resource server=\"docs\" uri=\"docs://runbook\" mime_type=\"text/markdown\">\n# Runbook\n</mcp_resource>".to_string(),
+                },
+                InputItem::Image {
+                    image_url: "data:image/png;base64,aGk=".to_string(),
+                },
+                InputItem::Text {
+                    text: "<mcp_resource server=\"docs\" uri=\"docs://archive\" mime_type=\"unknown\">\n(binary content omitted)\n</mcp_resource>".to_string(),
+                },
+            ],
+            resource_to_input_items("docs", result)
+        );
+    }
+
+    #[test]
+    fn prompt_messages_keep_their_roles() {
+        let text = |role: Role, text: &str| PromptMessage {
+            content: ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text: text.to_string(),
+                r#type: "text".to_string(),
+            }),
+            role,
+        };
+        let result = GetPromptResult {
+            description: None,
+            messages: vec![
+                text(Role::User, "Review this change"),
+                PromptMessage {
+                    content: ContentBlock::ImageContent(ImageContent {
+                        annotations: None,
+                        data: "aGk=".to_string(),
+                        mime_type: "image/png".to_string(),
+                        r#type: "image".to_string(),
+                    }),
+                    role: Role::User,
+                },
+                text(Role::Assistant, "Looks good, but add a test."),
+                text(Role::User, "Now write the test"),
+            ],
+        };
+
+        let mut input = TurnInputBuilder::default();
+        input.user_items(vec![InputItem::Text {
+            text: "Context:".to_string(),
+        }]);
+        input.prompt("review", result);
+
+        assert_eq!(
+            vec![
+                ResponseInputItem::Message {
+                    role: "user".to_string(),
+                    content: vec![
+                        ContentItem::InputText {
+                            text: "Context:".to_string(),
+                        },
+                        ContentItem::InputText {
+                            text: "Review this change".to_string(),
+                        },
+                        ContentItem::InputImage {
+                            image_url: "data:image/png;base64,aGk=".to_string(),
+                        },
+                    ],
+                },
+                ResponseInputItem::Message {
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText {
+                        text: "Looks good, but add a test.".to_string(),
+                    }],
+                },
+                ResponseInputItem::Message {
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText {
+                        text: "Now write the test".to_string(),
+                    }],
+                },
+            ],
+            input.finish()
+        );
+    }
+}
@@ -4,16 +4,24 @@ use std::time::Instant;
 use tracing::error;
 
 use crate::codex::Session;
+use crate::protocol::AskForApproval;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::McpInvocation;
 use crate::protocol::McpToolCallBeginEvent;
 use crate::protocol::McpToolCallEndEvent;
+use crate::protocol::ReviewDecision;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::TextContent;
 
 /// Handles the specified tool call dispatches the appropriate
-/// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`.
+/// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`. Tools
+/// configured to require approval are only called once the user approves;
+/// under [`AskForApproval::Never`] they fail without asking.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn handle_mcp_tool_call(
     sess: &Session,
     sub_id: &str,
@@ -22,6 +30,7 @@ pub(crate) async fn handle_mcp_tool_call(
     tool_name: String,
     arguments: String,
     timeout: Option<Duration>,
+    approval_policy: AskForApproval,
 ) -> ResponseInputItem {
     // Parse the `arguments` as JSON. An empty string is OK, but invalid JSON
     // is not.
@@ -49,6 +58,46 @@ pub(crate) async fn handle_mcp_tool_call(
         arguments: arguments_value.clone(),
     };
 
+    if sess.mcp_tool_requires_approval(&server, &tool_name) {
+        // Nobody answers approval requests under `Never` (e.g. `codex exec`),
+        // so waiting for one would hang the turn.
+        if approval_policy == AskForApproval::Never {
+            let result = Ok(approval_required_result(&server, &tool_name));
+            let tool_call_end_event = EventMsg::McpToolCallEnd(McpToolCallEndEvent {
+                call_id: call_id.clone(),
+                invocation,
+                duration: Duration::ZERO,
+                result: result.clone(),
+            });
+            notify_mcp_tool_call_event(sess, sub_id, tool_call_end_event).await;
+            return ResponseInputItem::McpToolCallOutput { call_id, result };
+        }
+
+        let rx_approve = sess
+            .request_mcp_tool_call_approval(
+                sub_id.to_string(),
+                call_id.clone(),
+                invocation.clone(),
+                None,
+            )
+            .await;
+        match rx_approve.await.unwrap_or_default() {
+            ReviewDecision::Approved => (),
+            ReviewDecision::ApprovedForSession => {
+                sess.add_approved_mcp_tool(server.clone(), tool_name.clone());
+            }
+            ReviewDecision::Denied | ReviewDecision::Abort => {
+                return ResponseInputItem::FunctionCallOutput {
+                    call_id,
+                    output: FunctionCallOutputPayload {
+                        content: "MCP tool call rejected by user".to_string(),
+                        success: None,
+                    },
+                };
+            }
+        }
+    }
+
     let tool_call_begin_event = EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
         call_id: call_id.clone(),
         invocation: invocation.clone(),
@@ -73,6 +122,22 @@ pub(crate) async fn handle_mcp_tool_call(
     ResponseInputItem::McpToolCallOutput { call_id, result }
 }
 
+/// The failed result reported for a tool that requires approval when the
+/// approval policy does not allow asking the user.
+fn approval_required_result(server: &str, tool_name: &str) -> CallToolResult {
+    CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            annotations: None,
+            text: format!(
+                "MCP tool `{server}/{tool_name}` requires approval, but the approval policy is `never`; the call was not made"
+            ),
+            r#type: "text".to_string(),
+        })],
+        is_error: Some(true),
+        structured_content: None,
+    }
+}
+
 async fn notify_mcp_tool_call_event(sess: &Session, sub_id: &str, event: EventMsg) {
     sess.send_event(Event {
         id: sub_id.to_string(),
@@ -80,3 +145,24 @@ async fn notify_mcp_tool_call_event(sess: &Session, sub_id: &str, event: EventMs
     })
     .await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn approval_required_result_is_a_failed_call() {
+        let result = approval_required_result("github", "create_issue");
+        assert_eq!(result.is_error, Some(true));
+        let [ContentBlock::TextContent(text)] = result.content.as_slice() else {
+            panic!("expected a single text block: {result:?}");
+        };
+        assert!(
+            text.text
+                .contains("`github/create_issue` requires approval"),
+            "{}",
+            text.text
+        );
+    }
+}
@@ -5,7 +5,7 @@
 //! JSON-Lines tooling. Each record has the following schema:
 //!
 //! ````text
-//! {"conversation_id":"<uuid>","ts":<unix_seconds>,"text":"<message>"}
+//! {"conversation_id":"<uuid>","ts":<unix_seconds>,"text":"<message>","cwd":"<path>"}
 //! ````
 //!
 //! To minimise the chance of interleaved writes when multiple processes are
@@ -13,16 +13,32 @@
 //! trailing `\n`) and write it with a **single `write(2)` system call** while
 //! the file descriptor is opened with the `O_APPEND` flag. POSIX guarantees
 //! that writes up to `PIPE_BUF` bytes are atomic in that case.
+//!
+//! When `history.max_bytes` is set and an append pushes the file past that
+//! size, the file is compacted in place: it is truncated and rewritten with
+//! only the newest entries while the exclusive lock is held. The inode (and so
+//! the `log_id`) stays the same, and the total number of entries dropped so far
+//! is kept in `history.jsonl.dropped` so that offsets handed out before the
+//! compaction keep pointing at the same entry. [`lookup`] returns `None` for
+//! offsets whose entry was dropped.
 
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::io::Read;
 use std::io::Result;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::sync::Mutex;
 
+use regex_lite::Regex;
 use serde::Deserialize;
 use serde::Serialize;
 
+use std::collections::HashMap;
 use std::time::Duration;
 use tokio::fs;
 use tokio::io::AsyncReadExt;
@@ -42,11 +58,23 @@ const HISTORY_FILENAME: &str = "history.jsonl";
 const MAX_RETRIES: usize = 10;
 const RETRY_SLEEP: Duration = Duration::from_millis(100);
 
+/// Compaction keeps the newest entries that fit in this share of
+/// `max_bytes` (in percent), so it does not run again on the very next append.
+const COMPACTION_TARGET_PERCENT: usize = 75;
+
+/// Compiled `history.sensitive_patterns`, keyed by pattern, so each pattern is
+/// compiled once per process instead of on every append. Invalid patterns are
+/// cached as `None`.
+static SENSITIVE_PATTERNS: LazyLock<Mutex<HashMap<String, Option<Regex>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct HistoryEntry {
     pub session_id: String,
     pub ts: u64,
     pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
 }
 
 fn history_filepath(config: &Config) -> PathBuf {
@@ -55,6 +83,21 @@ fn history_filepath(config: &Config) -> PathBuf {
     path
 }
 
+/// File next to the history that records how many entries compaction has
+/// dropped from the front of it.
+fn dropped_entries_path(history_path: &Path) -> PathBuf {
+    history_path.with_extension("jsonl.dropped")
+}
+
+fn parse_dropped_entries(contents: &str) -> usize {
+    contents.trim().parse().unwrap_or(0)
+}
+
+fn read_dropped_entries(history_path: &Path) -> usize {
+    std::fs::read_to_string(dropped_entries_path(history_path))
+        .map_or(0, |contents| parse_dropped_entries(&contents))
+}
+
 /// Append a `text` entry associated with `conversation_id` to the history file. Uses
 /// advisory file locking to ensure that concurrent writes do not interleave,
 /// which entails a small amount of blocking I/O internally.
@@ -73,7 +116,9 @@ pub(crate) async fn append_entry(
         }
     }
 
-    // TODO: check `text` for sensitive patterns
+    if is_sensitive(text, &config.history.sensitive_patterns) {
+        return Ok(());
+    }
 
     // Resolve `~/.codex/history.jsonl` and ensure the parent directory exists.
     let path = history_filepath(config);
@@ -92,6 +137,7 @@ pub(crate) async fn append_entry(
         session_id: conversation_id.to_string(),
         ts,
         text: text.to_string(),
+        cwd: Some(config.cwd.clone()),
     };
     let mut line = serde_json::to_string(&entry)
         .map_err(|e| std::io::Error::other(format!("failed to serialise history entry: {e}")))?;
@@ -110,6 +156,8 @@ pub(crate) async fn append_entry(
     // Ensure permissions.
     ensure_owner_only_permissions(&history_file).await?;
 
+    let max_bytes = config.history.max_bytes;
+
     // Perform a blocking write under an advisory write lock using std::fs.
     tokio::task::spawn_blocking(move || -> Result<()> {
         // Retry a few times to avoid indefinite blocking when contended.
@@ -119,6 +167,9 @@ pub(crate) async fn append_entry(
                     // While holding the exclusive lock, write the full line.
                     history_file.write_all(line.as_bytes())?;
                     history_file.flush()?;
+                    if let Some(max_bytes) = max_bytes {
+                        compact_if_needed(&history_file, &path, max_bytes)?;
+                    }
                     return Ok(());
                 }
                 Err(std::fs::TryLockError::WouldBlock) => {
@@ -138,8 +189,75 @@ pub(crate) async fn append_entry(
     Ok(())
 }
 
+/// Returns true when `text` matches one of the configured secret patterns.
+/// Patterns are validated when the config is loaded.
+fn is_sensitive(text: &str, patterns: &[String]) -> bool {
+    let mut cache = SENSITIVE_PATTERNS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    patterns.iter().any(|pattern| {
+        cache
+            .entry(pattern.clone())
+            .or_insert_with(|| Regex::new(pattern).ok())
+            .as_ref()
+            .is_some_and(|re| re.is_match(text))
+    })
+}
+
+/// Rewrite the history file with only its newest entries once it is larger
+/// than `max_bytes`. Must be called while holding the exclusive lock on `file`.
+fn compact_if_needed(file: &File, path: &Path, max_bytes: usize) -> Result<()> {
+    if file.metadata()?.len() <= max_bytes as u64 {
+        return Ok(());
+    }
+
+    let mut contents = Vec::new();
+    let mut reader = file;
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_to_end(&mut contents)?;
+    let kept = newest_lines(
+        &contents,
+        max_bytes.saturating_mul(COMPACTION_TARGET_PERCENT) / 100,
+    );
+    let dropped = contents[..contents.len() - kept.len()]
+        .iter()
+        .filter(|&&b| b == b'\n')
+        .count();
+
+    // Rewrite in place rather than renaming a new file over the old one so the
+    // inode, and with it every `log_id` handed out so far, stays valid. Readers
+    // take the shared lock and so never observe the file half-written.
+    std::fs::write(
+        dropped_entries_path(path),
+        (read_dropped_entries(path) + dropped).to_string(),
+    )?;
+    file.set_len(0)?;
+    let mut writer = file;
+    writer.write_all(kept)?;
+    writer.flush()
+}
+
+/// The longest suffix of `contents` that starts at a line boundary and is at
+/// most `budget` bytes long.
+fn newest_lines(contents: &[u8], budget: usize) -> &[u8] {
+    let mut start = contents.len();
+    while start > 0 {
+        // Start of the line that ends right before `start`.
+        let line_start = contents[..start - 1]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        if contents.len() - line_start > budget {
+            break;
+        }
+        start = line_start;
+    }
+    &contents[start..]
+}
+
 /// Asynchronously fetch the history file's *identifier* (inode on Unix) and
-/// the current number of entries by counting newline characters.
+/// the current number of entries: the newline characters in the file plus the
+/// entries compaction has dropped from it.
 pub(crate) async fn history_metadata(config: &Config) -> (u64, usize) {
     let path = history_filepath(config);
 
@@ -176,7 +294,10 @@ pub(crate) async fn history_metadata(config: &Config) -> (u64, usize) {
         }
     }
 
-    (log_id, count)
+    let dropped = fs::read_to_string(dropped_entries_path(&path))
+        .await
+        .map_or(0, |contents| parse_dropped_entries(&contents));
+    (log_id, dropped + count)
 }
 
 /// Given a `log_id` (on Unix this is the file's inode number) and a zero-based
@@ -220,6 +341,9 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
 
         match lock_result {
             Ok(()) => {
+                // Offsets count entries since the file was created; skip the
+                // ones compaction has already dropped.
+                let offset = offset.checked_sub(read_dropped_entries(&path))?;
                 let reader = BufReader::new(&file);
                 for (idx, line_res) in reader.lines().enumerate() {
                     let line = match line_res {
@@ -256,6 +380,58 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
     None
 }
 
+/// Read every parseable entry of the history file, oldest first. Lines that
+/// fail to parse are skipped; I/O errors are logged and yield whatever was
+/// read so far.
+///
+/// Note this function is not async because it uses a sync advisory file
+/// locking API.
+pub(crate) fn load_entries(config: &Config) -> Vec<HistoryEntry> {
+    use std::io::BufRead;
+    use std::io::BufReader;
+
+    let path = history_filepath(config);
+    let file: File = match OpenOptions::new().read(true).open(&path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to open history file");
+            return Vec::new();
+        }
+    };
+
+    for _ in 0..MAX_RETRIES {
+        match file.try_lock_shared() {
+            Ok(()) => {
+                let mut entries = Vec::new();
+                for line_res in BufReader::new(&file).lines() {
+                    let line = match line_res {
+                        Ok(l) => l,
+                        Err(e) => {
+                            tracing::warn!(error = %e, "failed to read line from history file");
+                            break;
+                        }
+                    };
+                    match serde_json::from_str::<HistoryEntry>(&line) {
+                        Ok(entry) => entries.push(entry),
+                        Err(e) => tracing::warn!(error = %e, "failed to parse history entry"),
+                    }
+                }
+                return entries;
+            }
+            Err(std::fs::TryLockError::WouldBlock) => {
+                std::thread::sleep(RETRY_SLEEP);
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to acquire shared lock on history file");
+                return Vec::new();
+            }
+        }
+    }
+
+    Vec::new()
+}
+
 /// Fallback stub for non-Unix systems: currently always returns `None`.
 #[cfg(not(unix))]
 pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<HistoryEntry> {
@@ -284,3 +460,66 @@ async fn ensure_owner_only_permissions(_file: &File) -> Result<()> {
     // For now, on non-Unix, simply succeed.
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn newest_lines_keeps_whole_lines_within_budget() {
+        let contents = b"a\nbb\nccc\n";
+        assert_eq!(newest_lines(contents, 8), b"bb\nccc\n");
+        assert_eq!(newest_lines(contents, 9), contents);
+        assert_eq!(newest_lines(contents, 3), b"");
+    }
+
+    #[test]
+    fn sensitive_patterns_match_anywhere_in_text() {
+        let patterns = vec![r"sk-[A-Za-z0-9]{20,}".to_string()];
+        assert!(is_sensitive(
+            "use key sk-abcdefghijklmnopqrstuvwx please",
+            &patterns
+        ));
+        assert!(!is_sensitive("use my key please", &patterns));
+        assert!(!is_sensitive("anything", &[]));
+    }
+
+    #[tokio::test]
+    async fn compaction_keeps_offsets_of_live_sessions() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let config = Config::load_from_base_config_with_overrides(
+            crate::config::ConfigToml::default(),
+            crate::config::ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .expect("defaults for test should always succeed");
+        let path = history_filepath(&config);
+        let lines: Vec<String> = (0..10)
+            .map(|i| format!("{{\"session_id\":\"s\",\"ts\":{i},\"text\":\"entry {i}\"}}\n"))
+            .collect();
+        std::fs::write(&path, lines.concat()).expect("write history");
+        let (log_id, count) = history_metadata(&config).await;
+        assert_eq!(count, 10);
+
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&path)
+            .expect("open history");
+        let line_len = lines[0].len();
+        compact_if_needed(&file, &path, line_len * 4).expect("compact");
+
+        let compacted = std::fs::read_to_string(&path).expect("read history");
+        // 75% of a four-line budget leaves room for three entries.
+        assert_eq!(compacted, lines[7..].concat());
+        assert_eq!(history_metadata(&config).await, (log_id, 10));
+
+        #[cfg(unix)]
+        {
+            let entry = lookup(log_id, 8, &config).expect("kept entry");
+            assert_eq!(entry.text, "entry 8");
+            assert!(lookup(log_id, 2, &config).is_none());
+        }
+    }
+}
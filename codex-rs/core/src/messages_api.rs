@@ -0,0 +1,666 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::Bytes;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use reqwest::StatusCode;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+
+use crate::ModelProviderInfo;
+use crate::cassette::Cassette;
+use crate::cassette::CassetteRequest;
+use crate::cassette::SseByteStream;
+use crate::client_common::MAX_RETRY_AFTER_BEFORE_FAILOVER;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::model_family::ModelFamily;
+use crate::openai_tools::create_tools_json_for_messages_api;
+use crate::protocol::TokenUsage;
+use crate::util::backoff;
+use codex_protocol::config_types::ReasoningEffort as ReasoningEffortConfig;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ReasoningItemReasoningSummary;
+use codex_protocol::models::ResponseItem;
+
+/// Value of the `anthropic-version` header sent with every request.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// `max_tokens` is mandatory for the Messages API. Used when neither the
+/// config nor the model metadata provide a limit; every model that speaks
+/// this protocol supports at least this many output tokens.
+const DEFAULT_MAX_TOKENS: u64 = 8_192;
+
+/// Smallest thinking budget accepted by the Messages API.
+const MIN_THINKING_BUDGET_TOKENS: u64 = 1_024;
+
+/// Implementation for the Anthropic-style Messages API (`/v1/messages`).
+pub(crate) async fn stream_messages(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    max_output_tokens: Option<u64>,
+    effort: Option<ReasoningEffortConfig>,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+    failover_available: bool,
+    cassette: Option<&Cassette>,
+) -> Result<ResponseStream> {
+    // The Messages API has no structured output format.
+    if prompt.output_schema.is_some() {
+        return Err(CodexErr::UnsupportedByWireApi(
+            "an output schema is not supported by model providers that use the Messages API (`wire_api = \"messages\"`)"
+                .to_string(),
+        ));
+    }
+
+    let full_instructions = prompt.get_full_instructions(model_family);
+    let messages = build_messages(&prompt.get_formatted_input());
+    let tools_json = create_tools_json_for_messages_api(&prompt.tools)?;
+
+    let max_tokens = max_output_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+    let mut payload = json!({
+        "model": model_family.slug,
+        "system": full_instructions,
+        "messages": messages,
+        "max_tokens": max_tokens,
+        "stream": true,
+    });
+    if let Some(obj) = payload.as_object_mut() {
+        if !tools_json.is_empty() {
+            obj.insert("tools".to_string(), json!(tools_json));
+        }
+        if let Some(budget_tokens) = thinking_budget_tokens(effort, max_tokens) {
+            obj.insert(
+                "thinking".to_string(),
+                json!({"type": "enabled", "budget_tokens": budget_tokens}),
+            );
+        }
+    }
+
+    debug!(
+        "POST to {}: {}",
+        provider.get_full_url(&None),
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    let recording = match CassetteRequest::begin(cassette, "messages", &payload)? {
+        Some(CassetteRequest::Replay(stream)) => {
+            let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+            tokio::spawn(process_messages_sse(
+                stream,
+                tx_event,
+                provider.stream_idle_timeout(),
+            ));
+            return Ok(ResponseStream { rx_event });
+        }
+        Some(CassetteRequest::Record(recording)) => Some(recording),
+        None => None,
+    };
+
+    let mut attempt = 0;
+    let max_retries = provider.request_max_retries();
+    loop {
+        attempt += 1;
+
+        let req_builder = provider.create_request_builder(client, &None).await?;
+
+        let res = req_builder
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(&payload)
+            .send()
+            .await;
+
+        match res {
+            Ok(resp) if resp.status().is_success() => {
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                let stream: SseByteStream = match &recording {
+                    Some(recording) => recording.tee(stream)?,
+                    None => Box::pin(stream),
+                };
+                tokio::spawn(process_messages_sse(
+                    stream,
+                    tx_event,
+                    provider.stream_idle_timeout(),
+                ));
+                return Ok(ResponseStream { rx_event });
+            }
+            Ok(res) => {
+                let status = res.status();
+                if !(status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) {
+                    let body = (res.text().await).unwrap_or_default();
+                    return Err(CodexErr::UnexpectedStatus(status, body));
+                }
+
+                if attempt > max_retries {
+                    return Err(CodexErr::RetryLimit(status));
+                }
+
+                let retry_after_secs = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok());
+
+                // Rather than waiting out a long rate limit, let the turn
+                // fail over to the next provider.
+                if failover_available
+                    && retry_after_secs.is_some_and(|secs| {
+                        Duration::from_secs(secs) > MAX_RETRY_AFTER_BEFORE_FAILOVER
+                    })
+                {
+                    return Err(CodexErr::RetryLimit(status));
+                }
+
+                let delay = retry_after_secs
+                    .map(|s| Duration::from_millis(s * 1_000))
+                    .unwrap_or_else(|| backoff(attempt));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                if attempt > max_retries {
+                    return Err(e.into());
+                }
+                let delay = backoff(attempt);
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Extended thinking is enabled whenever a reasoning effort other than
+/// `minimal` is configured. The budget is a share of `max_tokens`, which it
+/// must not reach.
+fn thinking_budget_tokens(effort: Option<ReasoningEffortConfig>, max_tokens: u64) -> Option<u64> {
+    let budget = match effort? {
+        ReasoningEffortConfig::Minimal => return None,
+        ReasoningEffortConfig::Low => max_tokens / 4,
+        ReasoningEffortConfig::Medium => max_tokens / 2,
+        ReasoningEffortConfig::High => max_tokens / 4 * 3,
+    };
+    let budget = budget.max(MIN_THINKING_BUDGET_TOKENS);
+    (budget < max_tokens).then_some(budget)
+}
+
+/// Convert the conversation history into Messages API `messages`. Every item
+/// becomes a content block; consecutive blocks with the same role are merged
+/// into a single message because tool results must directly follow the
+/// assistant message that requested them.
+fn build_messages(input: &[ResponseItem]) -> Vec<Value> {
+    let mut messages: Vec<(&'static str, Vec<Value>)> = Vec::new();
+    let mut push = |role: &'static str, block: Value| match messages.last_mut() {
+        Some((last_role, blocks)) if *last_role == role => blocks.push(block),
+        _ => messages.push((role, vec![block])),
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                for c in content {
+                    match c {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            if !text.is_empty() {
+                                push(role, json!({"type": "text", "text": text}));
+                            }
+                        }
+                        ContentItem::InputImage { image_url } => {
+                            push(role, image_block(image_url));
+                        }
+                    }
+                }
+            }
+            ResponseItem::Reasoning {
+                summary,
+                content,
+                encrypted_content,
+                ..
+            } => {
+                // Thinking blocks can only be sent back together with the
+                // signature the API produced for them.
+                let Some(signature) = encrypted_content else {
+                    continue;
+                };
+                let mut thinking = String::new();
+                for ReasoningItemReasoningSummary::SummaryText { text } in summary {
+                    thinking.push_str(text);
+                }
+                for c in content.iter().flatten() {
+                    match c {
+                        ReasoningItemContent::ReasoningText { text }
+                        | ReasoningItemContent::Text { text } => thinking.push_str(text),
+                    }
+                }
+                if thinking.is_empty() {
+                    push(
+                        "assistant",
+                        json!({"type": "redacted_thinking", "data": signature}),
+                    );
+                } else {
+                    push(
+                        "assistant",
+                        json!({"type": "thinking", "thinking": thinking, "signature": signature}),
+                    );
+                }
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let input = serde_json::from_str::<Value>(arguments)
+                    .ok()
+                    .filter(Value::is_object)
+                    .unwrap_or_else(|| json!({}));
+                push(
+                    "assistant",
+                    json!({"type": "tool_use", "id": call_id, "name": name, "input": input}),
+                );
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let mut block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": call_id,
+                    "content": output.content,
+                });
+                if output.success == Some(false)
+                    && let Some(obj) = block.as_object_mut()
+                {
+                    obj.insert("is_error".to_string(), json!(true));
+                }
+                push("user", block);
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                push(
+                    "assistant",
+                    json!({"type": "tool_use", "id": call_id, "name": name, "input": {"input": input}}),
+                );
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                push(
+                    "user",
+                    json!({"type": "tool_result", "tool_use_id": call_id, "content": output}),
+                );
+            }
+            ResponseItem::LocalShellCall { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::Other => {
+                // Omit these items from the conversation history.
+                continue;
+            }
+        }
+    }
+
+    messages
+        .into_iter()
+        .map(|(role, content)| json!({"role": role, "content": content}))
+        .collect()
+}
+
+/// Images are sent inline when they are `data:` URLs and by reference
+/// otherwise.
+fn image_block(image_url: &str) -> Value {
+    if let Some(rest) = image_url.strip_prefix("data:")
+        && let Some((media_type, data)) = rest.split_once(";base64,")
+    {
+        return json!({
+            "type": "image",
+            "source": {"type": "base64", "media_type": media_type, "data": data},
+        });
+    }
+    json!({
+        "type": "image",
+        "source": {"type": "url", "url": image_url},
+    })
+}
+
+/// A content block that is still being streamed.
+enum ContentBlock {
+    Text(String),
+    Thinking {
+        text: String,
+        signature: String,
+    },
+    RedactedThinking(String),
+    ToolUse {
+        id: String,
+        name: String,
+        input: String,
+    },
+}
+
+impl ContentBlock {
+    fn from_start(block: &Value) -> Option<Self> {
+        let str_field = |name: &str| {
+            block
+                .get(name)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        match block.get("type").and_then(Value::as_str)? {
+            "text" => Some(ContentBlock::Text(str_field("text"))),
+            "thinking" => Some(ContentBlock::Thinking {
+                text: str_field("thinking"),
+                signature: str_field("signature"),
+            }),
+            "redacted_thinking" => Some(ContentBlock::RedactedThinking(str_field("data"))),
+            "tool_use" => Some(ContentBlock::ToolUse {
+                id: str_field("id"),
+                name: str_field("name"),
+                // The input is streamed as `input_json_delta` fragments; the
+                // `input` object in `content_block_start` is always empty.
+                input: String::new(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn into_response_item(self) -> Option<ResponseItem> {
+        match self {
+            ContentBlock::Text(text) => {
+                if text.is_empty() {
+                    return None;
+                }
+                Some(ResponseItem::Message {
+                    id: None,
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText { text }],
+                })
+            }
+            ContentBlock::Thinking { text, signature } => Some(ResponseItem::Reasoning {
+                id: String::new(),
+                summary: vec![ReasoningItemReasoningSummary::SummaryText { text }],
+                content: None,
+                encrypted_content: Some(signature).filter(|s| !s.is_empty()),
+            }),
+            ContentBlock::RedactedThinking(data) => Some(ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some(data),
+            }),
+            ContentBlock::ToolUse { id, name, input } => Some(ResponseItem::FunctionCall {
+                id: None,
+                name,
+                arguments: if input.is_empty() {
+                    "{}".to_string()
+                } else {
+                    input
+                },
+                call_id: id,
+            }),
+        }
+    }
+}
+
+/// SSE processor for the Messages streaming format. Content blocks are
+/// accumulated until `content_block_stop` and then forwarded as a single
+/// [`ResponseItem`], so the rest of the pipeline can stay agnostic of the
+/// underlying wire format.
+async fn process_messages_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    idle_timeout: Duration,
+) where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+
+    let mut blocks: HashMap<u64, ContentBlock> = HashMap::new();
+    let mut response_id = String::new();
+    let mut usage = TokenUsage::default();
+
+    loop {
+        let sse = match timeout(idle_timeout, stream.next()).await {
+            Ok(Some(Ok(ev))) => ev,
+            Ok(Some(Err(e))) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(e.to_string(), None)))
+                    .await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before message_stop".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "idle timeout waiting for SSE".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        let event: Value = match serde_json::from_str(&sse.data) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        trace!("messages received SSE event: {event:?}");
+
+        let index = event.get("index").and_then(Value::as_u64);
+        match event
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+        {
+            "message_start" => {
+                let message = event.get("message");
+                if let Some(id) = message.and_then(|m| m.get("id")).and_then(Value::as_str) {
+                    response_id = id.to_string();
+                }
+                if let Some(start_usage) = message.and_then(|m| m.get("usage")) {
+                    update_usage(&mut usage, start_usage);
+                }
+                let _ = tx_event.send(Ok(ResponseEvent::Created)).await;
+            }
+            "content_block_start" => {
+                if let (Some(index), Some(block)) = (
+                    index,
+                    event
+                        .get("content_block")
+                        .and_then(ContentBlock::from_start),
+                ) {
+                    blocks.insert(index, block);
+                }
+            }
+            "content_block_delta" => {
+                let (Some(index), Some(delta)) = (index, event.get("delta")) else {
+                    continue;
+                };
+                let Some(block) = blocks.get_mut(&index) else {
+                    continue;
+                };
+                let delta_str = |name: &str| delta.get(name).and_then(Value::as_str);
+                match (block, delta_str("type").unwrap_or_default()) {
+                    (ContentBlock::Text(text), "text_delta") => {
+                        if let Some(fragment) = delta_str("text").filter(|s| !s.is_empty()) {
+                            text.push_str(fragment);
+                            let _ = tx_event
+                                .send(Ok(ResponseEvent::OutputTextDelta(fragment.to_string())))
+                                .await;
+                        }
+                    }
+                    (ContentBlock::Thinking { text, .. }, "thinking_delta") => {
+                        if let Some(fragment) = delta_str("thinking").filter(|s| !s.is_empty()) {
+                            text.push_str(fragment);
+                            let _ = tx_event
+                                .send(Ok(ResponseEvent::ReasoningSummaryDelta(
+                                    fragment.to_string(),
+                                )))
+                                .await;
+                        }
+                    }
+                    (ContentBlock::Thinking { signature, .. }, "signature_delta") => {
+                        if let Some(fragment) = delta_str("signature") {
+                            signature.push_str(fragment);
+                        }
+                    }
+                    (ContentBlock::ToolUse { input, .. }, "input_json_delta") => {
+                        if let Some(fragment) = delta_str("partial_json") {
+                            input.push_str(fragment);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => {
+                if let Some(item) = index
+                    .and_then(|index| blocks.remove(&index))
+                    .and_then(ContentBlock::into_response_item)
+                {
+                    let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+                }
+            }
+            "message_delta" => {
+                if let Some(delta_usage) = event.get("usage") {
+                    update_usage(&mut usage, delta_usage);
+                }
+            }
+            "message_stop" => {
+                usage.total_tokens = usage.input_tokens + usage.output_tokens;
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id,
+                        token_usage: Some(usage),
+                    }))
+                    .await;
+                return;
+            }
+            "error" => {
+                let message = event
+                    .get("error")
+                    .and_then(|e| e.get("message"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+                    .to_string();
+                let _ = tx_event.send(Err(CodexErr::Stream(message, None))).await;
+                return;
+            }
+            // `ping` and any event types added in the future.
+            _ => {}
+        }
+    }
+}
+
+/// Fold a Messages API `usage` object into `usage`. Counts are cumulative, so
+/// later values replace earlier ones. Cache reads and writes are part of the
+/// prompt and therefore count as input tokens.
+fn update_usage(usage: &mut TokenUsage, value: &Value) {
+    let count = |name: &str| value.get(name).and_then(Value::as_u64);
+    if let Some(input_tokens) = count("input_tokens") {
+        let cache_read = count("cache_read_input_tokens").unwrap_or(0);
+        let cache_creation = count("cache_creation_input_tokens").unwrap_or(0);
+        usage.input_tokens = input_tokens + cache_read + cache_creation;
+        usage.cached_input_tokens = cache_read;
+    }
+    if let Some(output_tokens) = count("output_tokens") {
+        usage.output_tokens = output_tokens;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn merges_tool_calls_and_results_into_alternating_messages() {
+        let input = vec![
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "list files".to_string(),
+                }],
+            },
+            ResponseItem::Reasoning {
+                id: String::new(),
+                summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                    text: "use ls".to_string(),
+                }],
+                content: None,
+                encrypted_content: Some("sig".to_string()),
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{\"command\":[\"ls\"]}".to_string(),
+                call_id: "toolu_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "boom".to_string(),
+                    success: Some(false),
+                },
+            },
+        ];
+
+        assert_eq!(
+            build_messages(&input),
+            vec![
+                json!({"role": "user", "content": [{"type": "text", "text": "list files"}]}),
+                json!({"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "use ls", "signature": "sig"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {"command": ["ls"]}},
+                ]}),
+                json!({"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "boom", "is_error": true},
+                ]}),
+            ]
+        );
+    }
+
+    #[test]
+    fn thinking_budget_follows_effort() {
+        assert_eq!(thinking_budget_tokens(None, 8_192), None);
+        assert_eq!(
+            thinking_budget_tokens(Some(ReasoningEffortConfig::Minimal), 8_192),
+            None
+        );
+        assert_eq!(
+            thinking_budget_tokens(Some(ReasoningEffortConfig::Medium), 8_192),
+            Some(4_096)
+        );
+        assert_eq!(
+            thinking_budget_tokens(Some(ReasoningEffortConfig::Low), 2_048),
+            Some(1_024)
+        );
+        assert_eq!(
+            thinking_budget_tokens(Some(ReasoningEffortConfig::Low), 1_024),
+            None
+        );
+    }
+}
@@ -118,3 +118,17 @@ pub fn find_family_for_model(slug: &str) -> Option<ModelFamily> {
         None
     }
 }
+
+/// Returns the `ModelFamily` for `slug`, falling back to conservative
+/// defaults when the slug does not match any known model family.
+pub fn derive_model_family(slug: &str) -> ModelFamily {
+    find_family_for_model(slug).unwrap_or_else(|| ModelFamily {
+        slug: slug.to_string(),
+        family: slug.to_string(),
+        needs_special_apply_patch_instructions: false,
+        supports_reasoning_summaries: false,
+        reasoning_summary_format: ReasoningSummaryFormat::None,
+        uses_local_shell_tool: false,
+        apply_patch_tool_type: None,
+    })
+}
@@ -25,9 +25,10 @@ const MAX_REQUEST_MAX_RETRIES: u64 = 100;
 /// Wire protocol that the provider speaks. Most third-party services only
 /// implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI
 /// itself (and a handful of others) additionally expose the more modern
-/// *Responses* API. The two protocols use different request/response shapes
-/// and *cannot* be auto-detected at runtime, therefore each provider entry
-/// must declare which one it expects.
+/// *Responses* API, and Anthropic-compatible services speak the *Messages*
+/// API. The protocols use different request/response shapes and *cannot* be
+/// auto-detected at runtime, therefore each provider entry must declare which
+/// one it expects.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum WireApi {
@@ -37,6 +38,9 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// The Anthropic-style Messages API at `/v1/messages`.
+    Messages,
 }
 
 /// Serializable representation of a provider definition.
@@ -92,7 +96,8 @@ impl ModelProviderInfo {
     /// Construct a `POST` RequestBuilder for the given URL using the provided
     /// reqwest Client applying:
     ///   • provider-specific headers (static + env based)
-    ///   • Bearer auth header when an API key is available.
+    ///   • Bearer auth header (`x-api-key` for the Messages API) when an API
+    ///     key is available.
     ///   • Auth token for OAuth.
     ///
     /// If the provider declares an `env_key` but the variable is missing/empty, returns an [`Err`] identical to the
@@ -119,7 +124,13 @@ impl ModelProviderInfo {
         let mut builder = client.post(url);
 
         if let Some(auth) = effective_auth.as_ref() {
-            builder = builder.bearer_auth(auth.get_token().await?);
+            let token = auth.get_token().await?;
+            // The Messages API expects the key in `x-api-key` rather than as
+            // a bearer token.
+            builder = match self.wire_api {
+                WireApi::Messages => builder.header("x-api-key", token),
+                WireApi::Responses | WireApi::Chat => builder.bearer_auth(token),
+            };
         }
 
         Ok(self.apply_http_headers(builder))
@@ -159,6 +170,7 @@ impl ModelProviderInfo {
         match self.wire_api {
             WireApi::Responses => format!("{base_url}/responses{query_string}"),
             WireApi::Chat => format!("{base_url}/chat/completions{query_string}"),
+            WireApi::Messages => format!("{base_url}/messages{query_string}"),
         }
     }
 
@@ -0,0 +1,527 @@
+//! Local HTTP/CONNECT proxy used to give sandboxed commands access to the
+//! hosts in `sandbox_workspace_write.network_allowlist` (and nothing else).
+//!
+//! The sandbox only lets commands open TCP connections to the proxy port; the
+//! proxy then checks the requested host against the allowlist before dialing
+//! out. Commands find the proxy through the `HTTP(S)_PROXY` variables that
+//! [`crate::exec_env::create_env`] injects. Plain HTTP requests are forwarded
+//! with `Connection: close` on both sides so that every request arrives on a
+//! new connection and is checked again.
+//!
+//! Only the Linux sandbox can confine commands to the proxy; the other
+//! sandboxes refuse allowlist policies, see [`reject_network_allowlist`].
+
+use std::collections::HashMap;
+use std::io;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::warn;
+
+use crate::protocol::SandboxPolicy;
+
+/// Upper bound on the size of a request line plus headers.
+const MAX_REQUEST_HEAD_BYTES: usize = 64 * 1024;
+
+/// Environment variables pointing clients at the proxy. Both spellings are set
+/// because tools disagree on which one they read.
+const PROXY_ENV_VARS: &[&str] = &[
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// Proxies started so far, keyed by their (normalized) allowlist. Proxies live
+/// for the rest of the process so that every command run under the same policy
+/// shares one listener.
+static PROXIES: OnceLock<Mutex<HashMap<Vec<String>, Arc<NetworkProxy>>>> = OnceLock::new();
+
+/// Returns the proxy for `sandbox_policy`, starting it on first use, or `None`
+/// if the policy does not restrict the network to an allowlist.
+pub fn network_proxy_for_policy(sandbox_policy: &SandboxPolicy) -> Option<Arc<NetworkProxy>> {
+    let allowlist = normalize_allowlist(sandbox_policy.network_allowlist());
+    if allowlist.is_empty() {
+        return None;
+    }
+
+    let proxies = PROXIES.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut proxies = match proxies.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    // A proxy whose runtime has shut down can no longer accept connections.
+    if let Some(proxy) = proxies.get(&allowlist)
+        && !proxy.task.is_finished()
+    {
+        return Some(proxy.clone());
+    }
+
+    match NetworkProxy::start(allowlist.clone()) {
+        Ok(proxy) => {
+            let proxy = Arc::new(proxy);
+            proxies.insert(allowlist, proxy.clone());
+            Some(proxy)
+        }
+        Err(e) => {
+            warn!("failed to start network proxy, network stays disabled: {e}");
+            None
+        }
+    }
+}
+
+/// Fails when `sandbox_policy` limits the network to an allowlist. Called by
+/// sandboxes that cannot confine commands to the proxy, which would otherwise
+/// run them with no network at all.
+pub(crate) fn reject_network_allowlist(
+    sandbox_policy: &SandboxPolicy,
+    sandbox_name: &str,
+) -> io::Result<()> {
+    if sandbox_policy.network_allowlist().is_empty() {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "`network_allowlist` is not supported by the {sandbox_name} sandbox; remove it or set `network_access = true`"
+        ),
+    ))
+}
+
+#[derive(Debug)]
+pub struct NetworkProxy {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl NetworkProxy {
+    /// Binds a listener on an ephemeral loopback port and serves it on the
+    /// current Tokio runtime.
+    pub fn start(allowlist: Vec<String>) -> io::Result<Self> {
+        let handle = tokio::runtime::Handle::try_current().map_err(io::Error::other)?;
+        let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let allowlist: Arc<[String]> = normalize_allowlist(&allowlist).into();
+        let task = handle.spawn(async move {
+            let listener = match TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    warn!("network proxy failed to listen: {e}");
+                    return;
+                }
+            };
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        warn!("network proxy failed to accept connection: {e}");
+                        continue;
+                    }
+                };
+                let allowlist = allowlist.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, &allowlist).await {
+                        debug!("network proxy connection from {peer} failed: {e}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, task })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// Environment variables that route HTTP(S) clients through this proxy.
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        let url = format!("http://{}", self.addr);
+        let mut vars: Vec<(String, String)> = PROXY_ENV_VARS
+            .iter()
+            .map(|name| (name.to_string(), url.clone()))
+            .collect();
+        // A user-level NO_PROXY could otherwise send some hosts around the
+        // proxy, where the sandbox would reject them anyway.
+        vars.push(("NO_PROXY".to_string(), String::new()));
+        vars.push(("no_proxy".to_string(), String::new()));
+        vars
+    }
+}
+
+impl Drop for NetworkProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn normalize_allowlist(allowlist: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = allowlist
+        .iter()
+        .map(|entry| {
+            let entry = entry.trim().trim_end_matches('.').to_ascii_lowercase();
+            entry
+                .strip_prefix("*.")
+                .map(str::to_string)
+                .unwrap_or(entry)
+        })
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+/// `host` is allowed if it equals an allowlist entry or is a subdomain of one.
+fn is_host_allowed(host: &str, allowlist: &[String]) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    allowlist.iter().any(|entry| {
+        host == *entry
+            || host
+                .strip_suffix(entry.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct ProxyRequest {
+    host: String,
+    port: u16,
+    /// Request head to send upstream for plain HTTP requests. `None` for
+    /// `CONNECT`, where the client speaks directly to the upstream once the
+    /// tunnel is established.
+    upstream_head: Option<String>,
+}
+
+/// Parses the request line and headers (without the terminating blank line).
+fn parse_request_head(head: &str) -> Option<ProxyRequest> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?;
+    let target = request_line.next()?;
+    let version = request_line.next()?;
+
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) = split_host_port(target, None)?;
+        return Some(ProxyRequest {
+            host,
+            port,
+            upstream_head: None,
+        });
+    }
+
+    // Plain HTTP requests use the absolute form, e.g. `GET http://host/path`.
+    let scheme_len = "http://".len();
+    if !target
+        .get(..scheme_len)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("http://"))
+    {
+        return None;
+    }
+    let rest = &target[scheme_len..];
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = split_host_port(authority, Some(80))?;
+
+    // Proxy-specific headers are meant for us, not the origin server.
+    let headers = lines.filter(|line| {
+        !line
+            .get(.."proxy-".len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("proxy-"))
+    });
+    let upstream_head = with_connection_close(format!("{method} {path} {version}"), headers);
+
+    Some(ProxyRequest {
+        host,
+        port,
+        upstream_head: Some(upstream_head),
+    })
+}
+
+/// Builds a head (including the terminating blank line) from `first_line`
+/// and `headers`, replacing any `Connection` and `Keep-Alive` headers with
+/// `Connection: close`.
+fn with_connection_close<'a>(first_line: String, headers: impl Iterator<Item = &'a str>) -> String {
+    let mut head = first_line;
+    head.push_str("\r\n");
+    for line in headers {
+        let name = line.split(':').next().unwrap_or_default().trim();
+        if name.eq_ignore_ascii_case("connection") || name.eq_ignore_ascii_case("keep-alive") {
+            continue;
+        }
+        head.push_str(line);
+        head.push_str("\r\n");
+    }
+    head.push_str("Connection: close\r\n\r\n");
+    head
+}
+
+/// Splits `host:port` (or `[v6]:port`), falling back to `default_port` when no
+/// port is present.
+fn split_host_port(authority: &str, default_port: Option<u16>) -> Option<(String, u16)> {
+    if authority.is_empty() || authority.contains('@') {
+        return None;
+    }
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        match rest.strip_prefix(':') {
+            Some(port) => (host, Some(port)),
+            None if rest.is_empty() => (host, None),
+            None => return None,
+        }
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port?,
+    };
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_string(), port))
+}
+
+/// Reads until the end of a request or response head. Returns the head
+/// (without the blank line) and any bytes the peer already sent after it.
+async fn read_head(stream: &mut TcpStream) -> io::Result<(String, Vec<u8>)> {
+    let mut buf = Vec::with_capacity(4096);
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "connection closed before the end of the head",
+            ));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let rest = buf.split_off(end + 4);
+            buf.truncate(end);
+            let head = String::from_utf8(buf)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            return Ok((head, rest));
+        }
+        if buf.len() > MAX_REQUEST_HEAD_BYTES {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "head too large"));
+        }
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str) -> io::Result<()> {
+    let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn handle_connection(mut client: TcpStream, allowlist: &[String]) -> io::Result<()> {
+    let (head, rest) = read_head(&mut client).await?;
+    let Some(request) = parse_request_head(&head) else {
+        return respond(&mut client, "400 Bad Request").await;
+    };
+    if !is_host_allowed(&request.host, allowlist) {
+        debug!("network proxy denied {}:{}", request.host, request.port);
+        return respond(&mut client, "403 Forbidden").await;
+    }
+
+    let mut upstream = match TcpStream::connect((request.host.as_str(), request.port)).await {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            debug!(
+                "network proxy failed to connect to {}:{}: {e}",
+                request.host, request.port
+            );
+            return respond(&mut client, "502 Bad Gateway").await;
+        }
+    };
+    match &request.upstream_head {
+        Some(upstream_head) => {
+            upstream.write_all(upstream_head.as_bytes()).await?;
+            upstream.write_all(&rest).await?;
+            // Without keep-alive, a second request on this connection (which
+            // could name another host) never reaches the upstream: the client
+            // has to reconnect and go through the allowlist check again.
+            let (response_head, response_rest) = read_head(&mut upstream).await?;
+            let mut lines = response_head.split("\r\n");
+            let status_line = lines.next().unwrap_or_default().to_string();
+            client
+                .write_all(with_connection_close(status_line, lines).as_bytes())
+                .await?;
+            client.write_all(&response_rest).await?;
+        }
+        None => {
+            client
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?;
+            upstream.write_all(&rest).await?;
+        }
+    }
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn allowlist(entries: &[&str]) -> Vec<String> {
+        normalize_allowlist(&entries.iter().map(|e| e.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn host_matching_includes_subdomains_only() {
+        let allowlist = allowlist(&["crates.io", "*.npmjs.org", "Example.COM."]);
+        assert!(is_host_allowed("crates.io", &allowlist));
+        assert!(is_host_allowed("static.crates.io", &allowlist));
+        assert!(is_host_allowed("registry.npmjs.org", &allowlist));
+        assert!(is_host_allowed("EXAMPLE.com", &allowlist));
+        assert!(!is_host_allowed("evilcrates.io", &allowlist));
+        assert!(!is_host_allowed("crates.io.evil.com", &allowlist));
+        assert!(!is_host_allowed("github.com", &allowlist));
+    }
+
+    #[test]
+    fn parses_connect_and_absolute_form_requests() {
+        assert_eq!(
+            parse_request_head("CONNECT index.crates.io:443 HTTP/1.1\r\nHost: index.crates.io:443"),
+            Some(ProxyRequest {
+                host: "index.crates.io".to_string(),
+                port: 443,
+                upstream_head: None,
+            })
+        );
+        assert_eq!(
+            parse_request_head(
+                "GET http://example.com/a?b HTTP/1.1\r\nHost: example.com\r\nProxy-Connection: keep-alive"
+            ),
+            Some(ProxyRequest {
+                host: "example.com".to_string(),
+                port: 80,
+                upstream_head: Some(
+                    "GET /a?b HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                ),
+            })
+        );
+        assert_eq!(
+            parse_request_head("CONNECT [::1]:8443 HTTP/1.1").map(|r| (r.host, r.port)),
+            Some(("::1".to_string(), 8443))
+        );
+        // Origin-form requests are not proxy requests.
+        assert_eq!(parse_request_head("GET /index.html HTTP/1.1"), None);
+        assert_eq!(parse_request_head("CONNECT example.com HTTP/1.1"), None);
+    }
+
+    #[tokio::test]
+    async fn tunnels_allowed_hosts_and_rejects_others() {
+        let upstream = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            stream.write_all(b"hello").await.unwrap();
+        });
+
+        let proxy = NetworkProxy::start(vec!["127.0.0.1".to_string()]).unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", proxy.port()))
+            .await
+            .unwrap();
+        client
+            .write_all(format!("CONNECT 127.0.0.1:{upstream_port} HTTP/1.1\r\n\r\n").as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert_eq!(response, "HTTP/1.1 200 Connection Established\r\n\r\nhello");
+
+        let mut client = TcpStream::connect(("127.0.0.1", proxy.port()))
+            .await
+            .unwrap();
+        client
+            .write_all(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+    }
+
+    #[tokio::test]
+    async fn plain_http_requests_are_not_kept_alive() {
+        let upstream = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        let upstream_task = tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let (head, _) = read_head(&mut stream).await.unwrap();
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nConnection: keep-alive\r\nContent-Length: 2\r\n\r\nok",
+                )
+                .await
+                .unwrap();
+            head
+        });
+
+        let proxy = NetworkProxy::start(vec!["127.0.0.1".to_string()]).unwrap();
+        let mut client = TcpStream::connect(("127.0.0.1", proxy.port()))
+            .await
+            .unwrap();
+        client
+            .write_all(
+                format!(
+                    "GET http://127.0.0.1:{upstream_port}/ HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: keep-alive\r\n\r\n"
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert_eq!(
+            response,
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok"
+        );
+        assert_eq!(
+            upstream_task.await.unwrap(),
+            "GET / HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close"
+        );
+    }
+
+    #[test]
+    fn allowlist_policies_are_rejected_where_unsupported() {
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: Vec::new(),
+            network_access: false,
+            network_allowlist: vec!["crates.io".to_string()],
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+            readable_roots: None,
+        };
+        let err = reject_network_allowlist(&policy, "container").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(
+            reject_network_allowlist(&SandboxPolicy::new_read_only_policy(), "container").is_ok()
+        );
+    }
+}
@@ -1,10 +1,7 @@
+use crate::config_types::ModelPricing;
 use crate::model_family::ModelFamily;
 
 /// Metadata about a model, particularly OpenAI models.
-/// We may want to consider including details like the pricing for
-/// input tokens, output tokens, etc., though users will need to be able to
-/// override this in config.toml, as this information can get out of date.
-/// Though this would help present more accurate pricing information in the UI.
 #[derive(Debug)]
 pub(crate) struct ModelInfo {
     /// Size of the context window in tokens.
@@ -68,3 +65,34 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
         _ => None,
     }
 }
+
+/// Built-in pricing for known models. This can get out of date, so users can
+/// override it per model with `model_pricing` in config.toml.
+/// https://platform.openai.com/docs/pricing
+pub(crate) fn get_model_pricing(model_family: &ModelFamily) -> Option<ModelPricing> {
+    const fn pricing(input: f64, cached_input: Option<f64>, output: f64) -> ModelPricing {
+        ModelPricing {
+            input,
+            cached_input,
+            output,
+        }
+    }
+
+    let slug = model_family.slug.as_str();
+    match slug {
+        "o3" => Some(pricing(2.0, Some(0.5), 8.0)),
+        "o4-mini" => Some(pricing(1.1, Some(0.275), 4.4)),
+        "codex-mini-latest" => Some(pricing(1.5, Some(0.375), 6.0)),
+        "gpt-4.1" | "gpt-4.1-2025-04-14" => Some(pricing(2.0, Some(0.5), 8.0)),
+        "gpt-4o" | "gpt-4o-2024-08-06" | "gpt-4o-2024-11-20" => {
+            Some(pricing(2.5, Some(1.25), 10.0))
+        }
+        "gpt-4o-2024-05-13" => Some(pricing(5.0, None, 15.0)),
+        "gpt-3.5-turbo" => Some(pricing(0.5, None, 1.5)),
+        _ if slug.starts_with("gpt-5-mini") => Some(pricing(0.25, Some(0.025), 2.0)),
+        _ if slug.starts_with("gpt-5-nano") => Some(pricing(0.05, Some(0.005), 0.4)),
+        _ if slug.starts_with("gpt-5") => Some(pricing(1.25, Some(0.125), 10.0)),
+        _ if slug.starts_with("codex-") => Some(pricing(1.25, Some(0.125), 10.0)),
+        _ => None,
+    }
+}
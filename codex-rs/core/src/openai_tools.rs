@@ -71,6 +71,7 @@ pub(crate) struct ToolsConfig {
     pub web_search_request: bool,
     pub include_view_image_tool: bool,
     pub experimental_unified_exec_tool: bool,
+    pub delegate_tool: bool,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -83,6 +84,7 @@ pub(crate) struct ToolsConfigParams<'a> {
     pub(crate) use_streamable_shell_tool: bool,
     pub(crate) include_view_image_tool: bool,
     pub(crate) experimental_unified_exec_tool: bool,
+    pub(crate) include_delegate_tool: bool,
 }
 
 impl ToolsConfig {
@@ -97,6 +99,7 @@ impl ToolsConfig {
             use_streamable_shell_tool,
             include_view_image_tool,
             experimental_unified_exec_tool,
+            include_delegate_tool,
         } = params;
         let mut shell_type = if *use_streamable_shell_tool {
             ConfigShellToolType::StreamableShell
@@ -111,25 +114,56 @@ impl ToolsConfig {
             }
         }
 
-        let apply_patch_tool_type = match model_family.apply_patch_tool_type {
-            Some(ApplyPatchToolType::Freeform) => Some(ApplyPatchToolType::Freeform),
-            Some(ApplyPatchToolType::Function) => Some(ApplyPatchToolType::Function),
-            None => {
-                if *include_apply_patch_tool {
-                    Some(ApplyPatchToolType::Freeform)
-                } else {
-                    None
-                }
-            }
-        };
-
         Self {
             shell_type,
             plan_tool: *include_plan_tool,
-            apply_patch_tool_type,
+            apply_patch_tool_type: apply_patch_tool_type(model_family, *include_apply_patch_tool),
             web_search_request: *include_web_search_request,
             include_view_image_tool: *include_view_image_tool,
             experimental_unified_exec_tool: *experimental_unified_exec_tool,
+            delegate_tool: *include_delegate_tool,
+        }
+    }
+
+    /// Returns this config with the tools that depend on the model (the
+    /// shell and `apply_patch` tools) chosen for `model_family` instead, e.g.
+    /// after a turn failed over to another model.
+    pub fn for_model_family(
+        &self,
+        model_family: &ModelFamily,
+        include_apply_patch_tool: bool,
+    ) -> Self {
+        let shell_type = match &self.shell_type {
+            ConfigShellToolType::DefaultShell | ConfigShellToolType::LocalShell => {
+                if model_family.uses_local_shell_tool {
+                    ConfigShellToolType::LocalShell
+                } else {
+                    ConfigShellToolType::DefaultShell
+                }
+            }
+            shell_type => shell_type.clone(),
+        };
+        Self {
+            shell_type,
+            apply_patch_tool_type: apply_patch_tool_type(model_family, include_apply_patch_tool),
+            ..self.clone()
+        }
+    }
+}
+
+fn apply_patch_tool_type(
+    model_family: &ModelFamily,
+    include_apply_patch_tool: bool,
+) -> Option<ApplyPatchToolType> {
+    match model_family.apply_patch_tool_type {
+        Some(ApplyPatchToolType::Freeform) => Some(ApplyPatchToolType::Freeform),
+        Some(ApplyPatchToolType::Function) => Some(ApplyPatchToolType::Function),
+        None => {
+            if include_apply_patch_tool {
+                Some(ApplyPatchToolType::Freeform)
+            } else {
+                None
+            }
         }
     }
 }
@@ -323,6 +357,75 @@ fn create_view_image_tool() -> OpenAiTool {
         },
     })
 }
+pub(crate) const DELEGATE_TOOL_NAME: &str = "delegate";
+
+fn create_delegate_tool() -> OpenAiTool {
+    let mut task_properties = BTreeMap::new();
+    task_properties.insert(
+        "prompt".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Self-contained instructions for the sub-agent; it does not see this conversation."
+                    .to_string(),
+            ),
+        },
+    );
+    task_properties.insert(
+        "cwd".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Working directory for the sub-agent, relative to the current one. Defaults to the current working directory."
+                    .to_string(),
+            ),
+        },
+    );
+    task_properties.insert(
+        "sandbox".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "One of: read-only, workspace-write, danger-full-access. Defaults to the current sandbox and cannot be less restrictive than it."
+                    .to_string(),
+            ),
+        },
+    );
+    task_properties.insert(
+        "model".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Model for the sub-agent. Defaults to the current model.".to_string(),
+            ),
+        },
+    );
+
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "tasks".to_string(),
+        JsonSchema::Array {
+            description: Some("Sub-tasks to run; they run in parallel.".to_string()),
+            items: Box::new(JsonSchema::Object {
+                properties: task_properties,
+                required: Some(vec!["prompt".to_string()]),
+                additional_properties: Some(false),
+            }),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: DELEGATE_TOOL_NAME.to_string(),
+        description: r#"Hands independent sub-tasks to sub-agents, each running in its own conversation with its own working directory, sandbox and model.
+The tool returns once every sub-agent has finished, with each sub-agent's final message.
+Give parallel sub-agents disjoint files or directories to work on.
+"#
+        .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["tasks".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
 /// TODO(dylan): deprecate once we get rid of json tool
 #[derive(Serialize, Deserialize)]
 pub(crate) struct ApplyPatchToolArgs {
@@ -375,6 +478,38 @@ pub(crate) fn create_tools_json_for_chat_completions_api(
     Ok(tools_json)
 }
 
+/// Returns JSON values that are compatible with the Messages API: only
+/// function tools are supported and their `parameters` become `input_schema`.
+/// The freeform `apply_patch` and `local_shell` tools are sent as their
+/// function equivalents.
+pub(crate) fn create_tools_json_for_messages_api(
+    tools: &Vec<OpenAiTool>,
+) -> crate::error::Result<Vec<serde_json::Value>> {
+    let tools = tools
+        .iter()
+        .map(|tool| match tool {
+            OpenAiTool::Freeform(FreeformTool { name, .. }) if name == "apply_patch" => {
+                create_apply_patch_json_tool()
+            }
+            OpenAiTool::LocalShell {} => create_shell_tool(),
+            tool => tool.clone(),
+        })
+        .collect();
+    let responses_api_tools_json = create_tools_json_for_responses_api(&tools)?;
+    let tools_json = responses_api_tools_json
+        .into_iter()
+        .filter(|tool| tool.get("type") == Some(&serde_json::Value::String("function".to_string())))
+        .map(|tool| {
+            json!({
+                "name": tool.get("name"),
+                "description": tool.get("description"),
+                "input_schema": tool.get("parameters"),
+            })
+        })
+        .collect::<Vec<serde_json::Value>>();
+    Ok(tools_json)
+}
+
 pub(crate) fn mcp_tool_to_openai_tool(
     fully_qualified_name: String,
     tool: mcp_types::Tool,
@@ -580,6 +715,10 @@ pub(crate) fn get_openai_tools(
     if config.include_view_image_tool {
         tools.push(create_view_image_tool());
     }
+
+    if config.delegate_tool {
+        tools.push(create_delegate_tool());
+    }
     if let Some(mcp_tools) = mcp_tools {
         // Ensure deterministic ordering to maximize prompt cache hits.
         let mut entries: Vec<(String, mcp_types::Tool)> = mcp_tools.into_iter().collect();
@@ -637,13 +776,14 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: true,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
             experimental_unified_exec_tool: true,
+            include_delegate_tool: false,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
 
@@ -653,19 +793,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tools_config_for_fallback_model_family() {
+        let model_family = find_family_for_model("codex-mini-latest")
+            .expect("codex-mini-latest should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            include_plan_tool: true,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: false,
+            experimental_unified_exec_tool: false,
+            include_delegate_tool: false,
+        });
+        assert_eq_tool_names(
+            &get_openai_tools(&config, Some(HashMap::new())),
+            &["local_shell", "update_plan"],
+        );
+
+        let fallback_family =
+            find_family_for_model("gpt-oss-120b").expect("gpt-oss should be a valid model family");
+        let fallback_config = config.for_model_family(&fallback_family, false);
+        assert_eq_tool_names(
+            &get_openai_tools(&fallback_config, Some(HashMap::new())),
+            &["shell", "update_plan", "apply_patch"],
+        );
+    }
+
     #[test]
     fn test_get_openai_tools_default_shell() {
         let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: true,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
             experimental_unified_exec_tool: true,
+            include_delegate_tool: false,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
 
@@ -675,19 +846,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_openai_tools_with_delegate() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: false,
+            experimental_unified_exec_tool: true,
+            include_delegate_tool: true,
+        });
+        let tools = get_openai_tools(&config, Some(HashMap::new()));
+
+        assert_eq_tool_names(&tools, &["unified_exec", "delegate"]);
+    }
+
     #[test]
     fn test_get_openai_tools_mcp_tools() {
         let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
             experimental_unified_exec_tool: true,
+            include_delegate_tool: false,
         });
         let tools = get_openai_tools(
             &config,
@@ -787,13 +979,14 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: false,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
             experimental_unified_exec_tool: true,
+            include_delegate_tool: false,
         });
 
         // Intentionally construct a map with keys that would sort alphabetically.
@@ -865,13 +1058,14 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
             experimental_unified_exec_tool: true,
+            include_delegate_tool: false,
         });
 
         let tools = get_openai_tools(
@@ -928,13 +1122,14 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
             experimental_unified_exec_tool: true,
+            include_delegate_tool: false,
         });
 
         let tools = get_openai_tools(
@@ -986,13 +1181,14 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
             experimental_unified_exec_tool: true,
+            include_delegate_tool: false,
         });
 
         let tools = get_openai_tools(
@@ -1047,13 +1243,14 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
             experimental_unified_exec_tool: true,
+            include_delegate_tool: false,
         });
 
         let tools = get_openai_tools(
@@ -1104,8 +1301,10 @@ mod tests {
         let sandbox_policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec!["workspace".into()],
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            readable_roots: None,
         };
         let tool = super::create_shell_tool_for_sandbox(&sandbox_policy);
         let OpenAiTool::Function(ResponsesApiTool {
@@ -1122,7 +1321,7 @@ mod tests {
 
     #[test]
     fn test_shell_tool_for_sandbox_readonly() {
-        let tool = super::create_shell_tool_for_sandbox(&SandboxPolicy::ReadOnly);
+        let tool = super::create_shell_tool_for_sandbox(&SandboxPolicy::new_read_only_policy());
         let OpenAiTool::Function(ResponsesApiTool {
             description, name, ..
         }) = &tool
@@ -1135,6 +1334,27 @@ mod tests {
         assert_eq!(description, expected);
     }
 
+    #[test]
+    fn test_messages_api_tools_replace_freeform_apply_patch_and_local_shell() {
+        let tools = vec![
+            OpenAiTool::LocalShell {},
+            create_apply_patch_freeform_tool(),
+            OpenAiTool::WebSearch {},
+        ];
+
+        let tools_json = create_tools_json_for_messages_api(&tools).unwrap();
+
+        let names = tools_json
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["shell", "apply_patch"]);
+        assert_eq!(
+            tools_json[1]["input_schema"]["properties"]["input"]["type"],
+            "string"
+        );
+    }
+
     #[test]
     fn test_shell_tool_for_sandbox_danger_full_access() {
         let tool = super::create_shell_tool_for_sandbox(&SandboxPolicy::DangerFullAccess);
@@ -38,17 +38,17 @@ pub struct ConversationItem {
 
 /// Hard cap to bound worst‑case work per request.
 const MAX_SCAN_FILES: usize = 100;
-const HEAD_RECORD_LIMIT: usize = 10;
+pub(super) const HEAD_RECORD_LIMIT: usize = 10;
 
 /// Pagination cursor identifying a file by timestamp and UUID.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Cursor {
-    ts: OffsetDateTime,
-    id: Uuid,
+    pub(super) ts: OffsetDateTime,
+    pub(super) id: Uuid,
 }
 
 impl Cursor {
-    fn new(ts: OffsetDateTime, id: Uuid) -> Self {
+    pub(super) fn new(ts: OffsetDateTime, id: Uuid) -> Self {
         Self { ts, id }
     }
 }
@@ -104,6 +104,37 @@ pub(crate) async fn get_conversations(
     Ok(result)
 }
 
+/// Locate the rollout file for the conversation with the given `id`, searching
+/// the newest sessions first. Returns `None` if no such file exists.
+pub(crate) async fn find_conversation_path_by_id(
+    codex_home: &Path,
+    id: Uuid,
+) -> io::Result<Option<PathBuf>> {
+    let mut root = codex_home.to_path_buf();
+    root.push(SESSIONS_SUBDIR);
+    if !root.exists() {
+        return Ok(None);
+    }
+
+    for (_year, year_path) in collect_dirs_desc(&root, |s| s.parse::<u16>().ok()).await? {
+        for (_month, month_path) in collect_dirs_desc(&year_path, |s| s.parse::<u8>().ok()).await? {
+            for (_day, day_path) in collect_dirs_desc(&month_path, |s| s.parse::<u8>().ok()).await?
+            {
+                let mut matches = collect_files(&day_path, |name_str, path| {
+                    parse_timestamp_uuid_from_filename(name_str)
+                        .filter(|(_ts, file_id)| *file_id == id)
+                        .map(|_| path.to_path_buf())
+                })
+                .await?;
+                if let Some(path) = matches.pop() {
+                    return Ok(Some(path));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
 /// Load the full contents of a single conversation session file at `path`.
 /// Returns the entire file contents as a String.
 #[allow(dead_code)]
@@ -220,7 +251,10 @@ fn build_next_cursor(items: &[ConversationItem]) -> Option<Cursor> {
 
 /// Collects immediate subdirectories of `parent`, parses their (string) names with `parse`,
 /// and returns them sorted descending by the parsed key.
-async fn collect_dirs_desc<T, F>(parent: &Path, parse: F) -> io::Result<Vec<(T, PathBuf)>>
+pub(super) async fn collect_dirs_desc<T, F>(
+    parent: &Path,
+    parse: F,
+) -> io::Result<Vec<(T, PathBuf)>>
 where
     T: Ord + Copy,
     F: Fn(&str) -> Option<T>,
@@ -244,7 +278,7 @@ where
 }
 
 /// Collects files in a directory and parses them with `parse`.
-async fn collect_files<T, F>(parent: &Path, parse: F) -> io::Result<Vec<T>>
+pub(super) async fn collect_files<T, F>(parent: &Path, parse: F) -> io::Result<Vec<T>>
 where
     F: Fn(&str, &Path) -> Option<T>,
 {
@@ -265,7 +299,7 @@ where
     Ok(collected)
 }
 
-fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uuid)> {
+pub(super) fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uuid)> {
     // Expected: rollout-YYYY-MM-DDThh-mm-ss-<uuid>.jsonl
     let core = name.strip_prefix("rollout-")?.strip_suffix(".jsonl")?;
 
@@ -282,7 +316,7 @@ fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uui
     Some((ts, uuid))
 }
 
-async fn read_head_and_flags(
+pub(super) async fn read_head_and_flags(
     path: &Path,
     max_records: usize,
 ) -> io::Result<(Vec<serde_json::Value>, bool, bool)> {
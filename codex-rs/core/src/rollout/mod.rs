@@ -6,6 +6,7 @@ pub const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
 pub mod list;
 pub(crate) mod policy;
 pub mod recorder;
+pub mod search;
 
 pub use codex_protocol::protocol::SessionMeta;
 pub use recorder::RolloutRecorder;
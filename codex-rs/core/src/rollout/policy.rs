@@ -58,14 +58,25 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::ExecCommandEnd(_)
         | EventMsg::ExecApprovalRequest(_)
         | EventMsg::ApplyPatchApprovalRequest(_)
+        | EventMsg::McpToolCallApprovalRequest(_)
         | EventMsg::BackgroundEvent(_)
         | EventMsg::StreamError(_)
+        | EventMsg::SessionBudgetExceeded(_)
+        | EventMsg::SecretsRedacted(_)
+        | EventMsg::SubAgentBegin(_)
+        | EventMsg::SubAgent(_)
+        | EventMsg::SubAgentEnd(_)
         | EventMsg::PatchApplyBegin(_)
         | EventMsg::PatchApplyEnd(_)
         | EventMsg::TurnDiff(_)
+        | EventMsg::CheckpointCreated(_)
+        | EventMsg::TurnUndone(_)
         | EventMsg::GetHistoryEntryResponse(_)
+        | EventMsg::ListHistoryEntriesResponse(_)
         | EventMsg::McpListToolsResponse(_)
         | EventMsg::ListCustomPromptsResponse(_)
+        | EventMsg::McpListResourcesResponse(_)
+        | EventMsg::McpListPromptsResponse(_)
         | EventMsg::PlanUpdate(_)
         | EventMsg::TurnAborted(_)
         | EventMsg::ShutdownComplete
@@ -19,12 +19,16 @@ use tokio::sync::mpsc::{self};
 use tokio::sync::oneshot;
 use tracing::info;
 use tracing::warn;
+use uuid::Uuid;
 
 use super::SESSIONS_SUBDIR;
 use super::list::ConversationsPage;
 use super::list::Cursor;
+use super::list::find_conversation_path_by_id;
 use super::list::get_conversations;
 use super::policy::is_persisted_response_item;
+use super::search::SearchPage;
+use super::search::search_conversations;
 use crate::config::Config;
 use crate::default_client::ORIGINATOR;
 use crate::git_info::collect_git_info;
@@ -109,6 +113,32 @@ impl RolloutRecorder {
         get_conversations(codex_home, page_size, cursor).await
     }
 
+    /// Find the rollout file recorded for the conversation whose id is `id`
+    /// under the provided Codex home directory.
+    pub async fn find_conversation_path_by_id(
+        codex_home: &Path,
+        id: &str,
+    ) -> std::io::Result<Option<PathBuf>> {
+        let id = Uuid::parse_str(id.trim()).map_err(|e| {
+            IoError::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid session id `{id}`: {e}"),
+            )
+        })?;
+        find_conversation_path_by_id(codex_home, id).await
+    }
+
+    /// Search conversations under the provided Codex home directory for
+    /// `query`, updating the persisted search index as needed.
+    pub async fn search_conversations(
+        codex_home: &Path,
+        query: &str,
+        page_size: usize,
+        cursor: Option<&Cursor>,
+    ) -> std::io::Result<SearchPage> {
+        search_conversations(codex_home, query, page_size, cursor).await
+    }
+
     /// Attempt to create a new [`RolloutRecorder`]. If the sessions directory
     /// cannot be created or the rollout file cannot be opened we return the
     /// error so the caller can decide whether to disable persistence.
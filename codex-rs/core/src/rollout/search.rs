@@ -0,0 +1,404 @@
+//! Full-text search across session rollouts.
+//!
+//! Searching reads every rollout under `~/.codex/sessions`, so the text that
+//! can be matched (messages, commands run and files touched) is kept in a
+//! persisted index next to the sessions. Rollouts are append-only, so the
+//! index remembers how many bytes of each file it has seen and only the new
+//! tail of a rollout is parsed on the next search.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::io::{self};
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_apply_patch::Hunk;
+use codex_apply_patch::MaybeApplyPatch;
+use codex_apply_patch::maybe_parse_apply_patch;
+use codex_apply_patch::parse_patch;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::SESSIONS_SUBDIR;
+use super::list::Cursor;
+use super::list::HEAD_RECORD_LIMIT;
+use super::list::collect_dirs_desc;
+use super::list::collect_files;
+use super::list::parse_timestamp_uuid_from_filename;
+use super::list::read_head_and_flags;
+use crate::protocol::EventMsg;
+use crate::protocol::InputMessageKind;
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::models::ShellToolCallParams;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+
+/// Name of the index file, stored in the sessions directory.
+pub const SEARCH_INDEX_FILE: &str = "search_index.json";
+
+/// Bump when the shape of [`IndexedSession`] or the extraction rules change
+/// so stale indexes are rebuilt.
+const SEARCH_INDEX_VERSION: u32 = 1;
+
+/// Longest text kept for a single indexed entry.
+const MAX_ENTRY_CHARS: usize = 4096;
+
+/// Most matching entries reported for a single session.
+const MAX_MATCHES_PER_HIT: usize = 5;
+
+/// Returned page of search results.
+#[derive(Debug, Default, PartialEq)]
+pub struct SearchPage {
+    /// Matching conversations ordered newest first.
+    pub items: Vec<SearchHit>,
+    /// Cursor to resume after the last item, or `None` if there are no more.
+    pub next_cursor: Option<Cursor>,
+}
+
+/// A conversation rollout that matched a search query.
+#[derive(Debug, PartialEq)]
+pub struct SearchHit {
+    /// Absolute path to the rollout file.
+    pub path: PathBuf,
+    /// First JSONL records of the rollout, as in [`super::list::ConversationItem`].
+    pub head: Vec<serde_json::Value>,
+    /// Entries of the conversation that contain at least one query term.
+    pub matches: Vec<SearchEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchEntryKind {
+    UserMessage,
+    AgentMessage,
+    Command,
+    File,
+}
+
+/// A piece of searchable text extracted from a rollout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchEntry {
+    pub kind: SearchEntryKind,
+    pub text: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SearchIndex {
+    version: u32,
+    /// Keyed by the rollout path relative to the sessions directory.
+    sessions: HashMap<String, IndexedSession>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct IndexedSession {
+    /// Number of bytes of the rollout that have been indexed. Always ends on
+    /// a line boundary.
+    indexed_bytes: u64,
+    has_session_meta: bool,
+    has_user_message: bool,
+    entries: Vec<SearchEntry>,
+}
+
+impl IndexedSession {
+    fn push(&mut self, kind: SearchEntryKind, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        let text: String = text.chars().take(MAX_ENTRY_CHARS).collect();
+        // Commands are often retried and files touched repeatedly.
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.kind == kind && entry.text == text)
+        {
+            return;
+        }
+        self.entries.push(SearchEntry { kind, text });
+    }
+
+    fn index_line(&mut self, line: &str) {
+        let Ok(rollout_line) = serde_json::from_str::<RolloutLine>(line) else {
+            return;
+        };
+        match rollout_line.item {
+            RolloutItem::SessionMeta(_) => self.has_session_meta = true,
+            RolloutItem::EventMsg(EventMsg::UserMessage(ev)) => {
+                self.has_user_message = true;
+                if matches!(ev.kind, None | Some(InputMessageKind::Plain)) {
+                    self.push(SearchEntryKind::UserMessage, &ev.message);
+                }
+            }
+            RolloutItem::EventMsg(EventMsg::AgentMessage(ev)) => {
+                self.push(SearchEntryKind::AgentMessage, &ev.message);
+            }
+            RolloutItem::ResponseItem(item) => self.index_response_item(item),
+            RolloutItem::EventMsg(_) | RolloutItem::TurnContext(_) | RolloutItem::Compacted(_) => {}
+        }
+    }
+
+    fn index_response_item(&mut self, item: ResponseItem) {
+        match item {
+            ResponseItem::FunctionCall {
+                name, arguments, ..
+            } => match name.as_str() {
+                "shell" | "container.exec" => {
+                    if let Ok(params) = serde_json::from_str::<ShellToolCallParams>(&arguments) {
+                        self.index_command(&params.command);
+                    }
+                }
+                "apply_patch" => {
+                    #[derive(Deserialize)]
+                    struct ApplyPatchToolArgs {
+                        input: String,
+                    }
+                    if let Ok(args) = serde_json::from_str::<ApplyPatchToolArgs>(&arguments) {
+                        self.index_patch(&args.input);
+                    }
+                }
+                _ => {}
+            },
+            ResponseItem::CustomToolCall { name, input, .. } if name == "apply_patch" => {
+                self.index_patch(&input);
+            }
+            ResponseItem::LocalShellCall {
+                action: LocalShellAction::Exec(action),
+                ..
+            } => self.index_command(&action.command),
+            _ => {}
+        }
+    }
+
+    fn index_command(&mut self, command: &[String]) {
+        if let MaybeApplyPatch::Body(args) = maybe_parse_apply_patch(command) {
+            self.index_hunks(&args.hunks);
+            return;
+        }
+        let text = match command {
+            [shell, flag, script] if shell == "bash" && flag == "-lc" => script.clone(),
+            _ => shlex::try_join(command.iter().map(String::as_str))
+                .unwrap_or_else(|_| command.join(" ")),
+        };
+        self.push(SearchEntryKind::Command, &text);
+    }
+
+    fn index_patch(&mut self, patch: &str) {
+        if let Ok(args) = parse_patch(patch) {
+            self.index_hunks(&args.hunks);
+        }
+    }
+
+    fn index_hunks(&mut self, hunks: &[Hunk]) {
+        for hunk in hunks {
+            match hunk {
+                Hunk::AddFile { path, .. } | Hunk::DeleteFile { path } => {
+                    self.push(SearchEntryKind::File, &path.to_string_lossy());
+                }
+                Hunk::UpdateFile {
+                    path, move_path, ..
+                } => {
+                    self.push(SearchEntryKind::File, &path.to_string_lossy());
+                    if let Some(move_path) = move_path {
+                        self.push(SearchEntryKind::File, &move_path.to_string_lossy());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Entries containing any of `terms`, or `None` unless every term is found
+    /// somewhere in the session. Terms must already be lowercase.
+    fn matches(&self, terms: &[String]) -> Option<Vec<SearchEntry>> {
+        let lowered: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| entry.text.to_lowercase())
+            .collect();
+        let all_found = terms
+            .iter()
+            .all(|term| lowered.iter().any(|text| text.contains(term.as_str())));
+        if !all_found {
+            return None;
+        }
+        Some(
+            self.entries
+                .iter()
+                .zip(lowered.iter())
+                .filter(|(_, text)| terms.iter().any(|term| text.contains(term.as_str())))
+                .map(|(entry, _)| entry.clone())
+                .take(MAX_MATCHES_PER_HIT)
+                .collect(),
+        )
+    }
+}
+
+/// Search all rollouts under `codex_home` for conversations whose user or
+/// agent messages, commands or touched files contain every whitespace
+/// separated term of `query` (case-insensitive). Results are ordered like
+/// [`super::list::get_conversations`] and paginated with the same cursor.
+pub(crate) async fn search_conversations(
+    codex_home: &Path,
+    query: &str,
+    page_size: usize,
+    cursor: Option<&Cursor>,
+) -> io::Result<SearchPage> {
+    let root = codex_home.join(SESSIONS_SUBDIR);
+    if !root.exists() {
+        return Ok(SearchPage::default());
+    }
+
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut files = collect_rollout_files(&root).await?;
+    files.sort_by_key(|(ts, id, _)| (Reverse(*ts), Reverse(*id)));
+
+    let index = update_index(&root, &files).await?;
+
+    let mut items = Vec::new();
+    let mut next_cursor = None;
+    for (ts, id, path) in files {
+        if let Some(cursor) = cursor
+            && (ts, id) >= (cursor.ts, cursor.id)
+        {
+            continue;
+        }
+        let Some(session) = index.sessions.get(&index_key(&root, &path)) else {
+            continue;
+        };
+        if !session.has_session_meta || !session.has_user_message {
+            continue;
+        }
+        let Some(matches) = session.matches(&terms) else {
+            continue;
+        };
+        if items.len() == page_size {
+            break;
+        }
+        let (head, _, _) = read_head_and_flags(&path, HEAD_RECORD_LIMIT)
+            .await
+            .unwrap_or_default();
+        items.push(SearchHit {
+            path,
+            head,
+            matches,
+        });
+        if items.len() == page_size {
+            next_cursor = Some(Cursor::new(ts, id));
+        }
+    }
+
+    Ok(SearchPage { items, next_cursor })
+}
+
+/// All rollout files under the sessions directory, in no particular order.
+async fn collect_rollout_files(root: &Path) -> io::Result<Vec<(OffsetDateTime, Uuid, PathBuf)>> {
+    let mut files = Vec::new();
+    for (_year, year_path) in collect_dirs_desc(root, |s| s.parse::<u16>().ok()).await? {
+        for (_month, month_path) in collect_dirs_desc(&year_path, |s| s.parse::<u8>().ok()).await? {
+            for (_day, day_path) in collect_dirs_desc(&month_path, |s| s.parse::<u8>().ok()).await?
+            {
+                let day_files = collect_files(&day_path, |name_str, path| {
+                    if !name_str.starts_with("rollout-") || !name_str.ends_with(".jsonl") {
+                        return None;
+                    }
+                    parse_timestamp_uuid_from_filename(name_str)
+                        .map(|(ts, id)| (ts, id, path.to_path_buf()))
+                })
+                .await?;
+                files.extend(day_files);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn index_key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Load the persisted index, bring it up to date with `files` and write it
+/// back if anything changed. Sessions whose rollouts were removed are dropped.
+async fn update_index(
+    root: &Path,
+    files: &[(OffsetDateTime, Uuid, PathBuf)],
+) -> io::Result<SearchIndex> {
+    let index_path = root.join(SEARCH_INDEX_FILE);
+    let mut previous = match tokio::fs::read(&index_path).await {
+        Ok(bytes) => serde_json::from_slice::<SearchIndex>(&bytes)
+            .ok()
+            .filter(|index| index.version == SEARCH_INDEX_VERSION)
+            .unwrap_or_default(),
+        Err(_) => SearchIndex::default(),
+    };
+    let mut changed = previous.version != SEARCH_INDEX_VERSION;
+
+    let mut index = SearchIndex {
+        version: SEARCH_INDEX_VERSION,
+        sessions: HashMap::with_capacity(files.len()),
+    };
+    for (_, _, path) in files {
+        let key = index_key(root, path);
+        let mut session = previous.sessions.remove(&key).unwrap_or_default();
+        match index_rollout_tail(path, &mut session).await {
+            Ok(updated) => changed |= updated,
+            Err(e) => {
+                warn!("failed to index rollout {}: {e}", path.display());
+                continue;
+            }
+        }
+        index.sessions.insert(key, session);
+    }
+    changed |= !previous.sessions.is_empty();
+
+    if changed && let Err(e) = write_index(&index_path, &index) {
+        warn!("failed to write search index {}: {e}", index_path.display());
+    }
+    Ok(index)
+}
+
+/// Index the lines of `path` past `session.indexed_bytes`. Returns whether the
+/// session changed.
+async fn index_rollout_tail(path: &Path, session: &mut IndexedSession) -> io::Result<bool> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+    if len == session.indexed_bytes {
+        return Ok(false);
+    }
+    if len < session.indexed_bytes {
+        // The rollout was rewritten rather than appended to; start over.
+        *session = IndexedSession::default();
+    }
+
+    file.seek(SeekFrom::Start(session.indexed_bytes)).await?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).await?;
+    // Leave a partially written last line for the next search.
+    let Some(end) = tail.iter().rposition(|b| *b == b'\n') else {
+        return Ok(false);
+    };
+    for line in String::from_utf8_lossy(&tail[..=end]).lines() {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            session.index_line(trimmed);
+        }
+    }
+    session.indexed_bytes += end as u64 + 1;
+    Ok(true)
+}
+
+fn write_index(index_path: &Path, index: &SearchIndex) -> io::Result<()> {
+    let parent = index_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut tmp = tempfile::NamedTempFile::new_in(parent)?;
+    serde_json::to_writer(&mut tmp, index)?;
+    tmp.persist(index_path).map_err(|e| e.error)?;
+    Ok(())
+}
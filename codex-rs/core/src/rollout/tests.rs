@@ -15,8 +15,13 @@ use uuid::Uuid;
 use crate::rollout::list::ConversationItem;
 use crate::rollout::list::ConversationsPage;
 use crate::rollout::list::Cursor;
+use crate::rollout::list::find_conversation_path_by_id;
 use crate::rollout::list::get_conversation;
 use crate::rollout::list::get_conversations;
+use crate::rollout::search::SEARCH_INDEX_FILE;
+use crate::rollout::search::SearchEntry;
+use crate::rollout::search::SearchEntryKind;
+use crate::rollout::search::search_conversations;
 
 fn write_session_file(
     root: &Path,
@@ -366,6 +371,31 @@ async fn test_get_conversation_contents() {
     assert_eq!(content, expected_content);
 }
 
+#[tokio::test]
+async fn test_find_conversation_path_by_id() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let older = Uuid::new_v4();
+    let newer = Uuid::new_v4();
+    write_session_file(home, "2025-03-31T23-59-00", older, 0).unwrap();
+    write_session_file(home, "2025-04-01T10-30-00", newer, 0).unwrap();
+
+    let found = find_conversation_path_by_id(home, older).await.unwrap();
+    let expected = home
+        .join("sessions")
+        .join("2025")
+        .join("03")
+        .join("31")
+        .join(format!("rollout-2025-03-31T23-59-00-{older}.jsonl"));
+    assert_eq!(found, Some(expected));
+
+    let missing = find_conversation_path_by_id(home, Uuid::new_v4())
+        .await
+        .unwrap();
+    assert_eq!(missing, None);
+}
+
 #[tokio::test]
 async fn test_stable_ordering_same_second_pagination() {
     let temp = TempDir::new().unwrap();
@@ -443,3 +473,187 @@ async fn test_stable_ordering_same_second_pagination() {
     };
     assert_eq!(page2, expected_page2);
 }
+
+fn append_rollout_items(
+    root: &Path,
+    ts_str: &str,
+    uuid: Uuid,
+    items: &[serde_json::Value],
+) -> std::io::Result<()> {
+    let path = root
+        .join("sessions")
+        .join(&ts_str[0..4])
+        .join(&ts_str[5..7])
+        .join(&ts_str[8..10])
+        .join(format!("rollout-{ts_str}-{uuid}.jsonl"));
+    let mut file = fs::OpenOptions::new().append(true).open(path)?;
+    for item in items {
+        let mut line = serde_json::json!({ "timestamp": ts_str });
+        if let (Some(line), Some(item)) = (line.as_object_mut(), item.as_object()) {
+            line.extend(item.clone());
+        }
+        writeln!(file, "{line}")?;
+    }
+    Ok(())
+}
+
+fn search_matches(page: &crate::rollout::search::SearchPage) -> Vec<Vec<SearchEntry>> {
+    page.items.iter().map(|hit| hit.matches.clone()).collect()
+}
+
+#[tokio::test]
+async fn test_search_matches_messages_commands_and_files() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let ts_a = "2025-03-01T09-00-00";
+    let ts_b = "2025-03-02T09-00-00";
+    let u_a = Uuid::from_u128(1);
+    let u_b = Uuid::from_u128(2);
+    write_session_file(home, ts_a, u_a, 0).unwrap();
+    write_session_file(home, ts_b, u_b, 0).unwrap();
+
+    append_rollout_items(
+        home,
+        ts_a,
+        u_a,
+        &[
+            serde_json::json!({
+                "type": "response_item",
+                "payload": {
+                    "type": "function_call",
+                    "name": "shell",
+                    "arguments": "{\"command\":[\"bash\",\"-lc\",\"cargo test -p codex-core\"]}",
+                    "call_id": "call-1"
+                }
+            }),
+            serde_json::json!({
+                "type": "response_item",
+                "payload": {
+                    "type": "custom_tool_call",
+                    "name": "apply_patch",
+                    "input": "*** Begin Patch\n*** Add File: src/widget.rs\n+fn widget() {}\n*** End Patch",
+                    "call_id": "call-2"
+                }
+            }),
+        ],
+    )
+    .unwrap();
+    append_rollout_items(
+        home,
+        ts_b,
+        u_b,
+        &[serde_json::json!({
+            "type": "event_msg",
+            "payload": { "type": "agent_message", "message": "All Done" }
+        })],
+    )
+    .unwrap();
+
+    let page = search_conversations(home, "CARGO", 10, None).await.unwrap();
+    assert_eq!(
+        search_matches(&page),
+        vec![vec![SearchEntry {
+            kind: SearchEntryKind::Command,
+            text: "cargo test -p codex-core".to_string(),
+        }]]
+    );
+
+    let page = search_conversations(home, "widget.rs", 10, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        search_matches(&page),
+        vec![vec![SearchEntry {
+            kind: SearchEntryKind::File,
+            text: "src/widget.rs".to_string(),
+        }]]
+    );
+
+    // Every term must match somewhere in the session.
+    let page = search_conversations(home, "hello done", 10, None)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert!(
+        page.items[0]
+            .path
+            .ends_with(format!("rollout-{ts_b}-{u_b}.jsonl"))
+    );
+    assert_eq!(
+        page.items[0].matches,
+        vec![
+            SearchEntry {
+                kind: SearchEntryKind::UserMessage,
+                text: "Hello from user".to_string(),
+            },
+            SearchEntry {
+                kind: SearchEntryKind::AgentMessage,
+                text: "All Done".to_string(),
+            },
+        ]
+    );
+
+    // Newest first, paginated with the same cursor as listing.
+    let page1 = search_conversations(home, "hello", 1, None).await.unwrap();
+    assert_eq!(page1.items.len(), 1);
+    assert!(
+        page1.items[0]
+            .path
+            .ends_with(format!("rollout-{ts_b}-{u_b}.jsonl"))
+    );
+    let page2 = search_conversations(home, "hello", 1, page1.next_cursor.as_ref())
+        .await
+        .unwrap();
+    assert_eq!(page2.items.len(), 1);
+    assert!(
+        page2.items[0]
+            .path
+            .ends_with(format!("rollout-{ts_a}-{u_a}.jsonl"))
+    );
+
+    let page = search_conversations(home, "nothing-matches", 10, None)
+        .await
+        .unwrap();
+    assert!(page.items.is_empty());
+    assert_eq!(page.next_cursor, None);
+}
+
+#[tokio::test]
+async fn test_search_index_picks_up_appended_lines() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let ts = "2025-04-01T10-00-00";
+    let uuid = Uuid::from_u128(7);
+    write_session_file(home, ts, uuid, 0).unwrap();
+
+    let page = search_conversations(home, "flamingo", 10, None)
+        .await
+        .unwrap();
+    assert!(page.items.is_empty());
+    assert!(home.join("sessions").join(SEARCH_INDEX_FILE).exists());
+
+    append_rollout_items(
+        home,
+        ts,
+        uuid,
+        &[serde_json::json!({
+            "type": "event_msg",
+            "payload": { "type": "user_message", "message": "add a flamingo", "kind": "plain" }
+        })],
+    )
+    .unwrap();
+
+    let page = search_conversations(home, "flamingo", 10, None)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(
+        page.items[0].matches,
+        vec![SearchEntry {
+            kind: SearchEntryKind::UserMessage,
+            text: "add a flamingo".to_string(),
+        }]
+    );
+}
@@ -7,15 +7,25 @@ use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
 
 use crate::exec::SandboxType;
+use crate::exec_policy::ExecPolicy;
+use crate::exec_policy::ExecPolicyDecision;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
 
 #[derive(Debug, PartialEq)]
 pub enum SafetyCheck {
-    AutoApprove { sandbox_type: SandboxType },
+    AutoApprove {
+        sandbox_type: SandboxType,
+    },
     AskUser,
-    Reject { reason: String },
+    Reject {
+        reason: String,
+    },
+    /// The exec policy forbids the command; `reason` comes from the policy.
+    Forbidden {
+        reason: String,
+    },
 }
 
 pub fn assess_patch_safety(
@@ -72,20 +82,36 @@ pub fn assess_patch_safety(
     }
 }
 
+/// Commands forbidden by the exec policy are always rejected with the
+/// policy's reason. Commands that only the project policy verifies as safe are
+/// auto-approved only if they can run in a sandbox.
+///
 /// For a command to be run _without_ a sandbox, one of the following must be
 /// true:
 ///
 /// - the user has explicitly approved the command
+/// - the command is verified as safe by the exec policy
 /// - the command is on the "known safe" list
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
-pub fn assess_command_safety(
+///
+/// The first three only skip the sandbox while reads are unrestricted: with
+/// `readable_roots` configured, a trusted command such as `cat` could still
+/// read any file, so it runs in the platform sandbox when one is available.
+pub(crate) fn assess_command_safety(
     command: &[String],
+    exec_policy: &ExecPolicy,
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<Vec<String>>,
     with_escalated_permissions: bool,
 ) -> SafetyCheck {
+    let policy_decision = exec_policy.evaluate(command);
+    if let ExecPolicyDecision::Forbidden { reason } = policy_decision {
+        return SafetyCheck::Forbidden { reason };
+    }
+
     // A command is "trusted" because either:
+    // - the exec policy verified it as safe,
     // - it belongs to a set of commands we consider "safe" by default, or
     // - the user has explicitly approved the command for this session
     //
@@ -98,10 +124,23 @@ pub fn assess_command_safety(
     // would probably be fine to run the command in a sandbox, but when
     // `approved.contains(command)` is `true`, the user may have approved it for
     // the session _because_ they know it needs to run outside a sandbox.
-    if is_known_safe_command(command) || approved.contains(command) {
-        return SafetyCheck::AutoApprove {
-            sandbox_type: SandboxType::None,
+    if policy_decision == ExecPolicyDecision::Safe
+        || is_known_safe_command(command)
+        || approved.contains(command)
+    {
+        let sandbox_type = if sandbox_policy.readable_roots().is_some() {
+            get_platform_sandbox().unwrap_or(SandboxType::None)
+        } else {
+            SandboxType::None
         };
+        return SafetyCheck::AutoApprove { sandbox_type };
+    }
+
+    if policy_decision == ExecPolicyDecision::SafeInSandbox
+        && !with_escalated_permissions
+        && let Some(sandbox_type) = get_platform_sandbox()
+    {
+        return SafetyCheck::AutoApprove { sandbox_type };
     }
 
     assess_safety_for_untrusted_command(approval_policy, sandbox_policy, with_escalated_permissions)
@@ -127,7 +166,7 @@ pub(crate) fn assess_safety_for_untrusted_command(
         | (OnRequest, DangerFullAccess) => SafetyCheck::AutoApprove {
             sandbox_type: SandboxType::None,
         },
-        (OnRequest, ReadOnly) | (OnRequest, WorkspaceWrite { .. }) => {
+        (OnRequest, ReadOnly { .. }) | (OnRequest, WorkspaceWrite { .. }) => {
             if with_escalated_permissions {
                 SafetyCheck::AskUser
             } else {
@@ -139,9 +178,9 @@ pub(crate) fn assess_safety_for_untrusted_command(
                 }
             }
         }
-        (Never, ReadOnly)
+        (Never, ReadOnly { .. })
         | (Never, WorkspaceWrite { .. })
-        | (OnFailure, ReadOnly)
+        | (OnFailure, ReadOnly { .. })
         | (OnFailure, WorkspaceWrite { .. }) => {
             match get_platform_sandbox() {
                 Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
@@ -183,7 +222,7 @@ fn is_write_patch_constrained_to_writable_paths(
 ) -> bool {
     // Early‑exit if there are no declared writable roots.
     let writable_roots = match sandbox_policy {
-        SandboxPolicy::ReadOnly => {
+        SandboxPolicy::ReadOnly { .. } => {
             return false;
         }
         SandboxPolicy::DangerFullAccess => {
@@ -274,8 +313,10 @@ mod tests {
         let policy_workspace_only = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            readable_roots: None,
         };
 
         assert!(is_write_patch_constrained_to_writable_paths(
@@ -295,8 +336,10 @@ mod tests {
         let policy_with_parent = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![parent],
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            readable_roots: None,
         };
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_outside,
@@ -310,12 +353,13 @@ mod tests {
         // Should not be a trusted command
         let command = vec!["git commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = true;
 
         let safety_check = assess_command_safety(
             &command,
+            &ExecPolicy::default_policy().unwrap(),
             approval_policy,
             &sandbox_policy,
             &approved,
@@ -329,12 +373,13 @@ mod tests {
     fn test_request_escalated_privileges_no_sandbox_fallback() {
         let command = vec!["git".to_string(), "commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = false;
 
         let safety_check = assess_command_safety(
             &command,
+            &ExecPolicy::default_policy().unwrap(),
             approval_policy,
             &sandbox_policy,
             &approved,
@@ -347,4 +392,124 @@ mod tests {
         };
         assert_eq!(safety_check, expected);
     }
+
+    #[test]
+    fn test_exec_policy_forbidden_command_is_forbidden() {
+        let codex_home = TempDir::new().unwrap();
+        let policy_dir = codex_home.path().join("policy");
+        std::fs::create_dir_all(&policy_dir).unwrap();
+        std::fs::write(
+            policy_dir.join("forbid.policy"),
+            r#"
+forbid_program_regex(
+    regex="^shutdown$",
+    reason="shutting down the machine is not allowed",
+)
+"#,
+        )
+        .unwrap();
+        let (exec_policy, _) = ExecPolicy::load(codex_home.path(), codex_home.path()).unwrap();
+
+        let command = vec!["shutdown".to_string(), "now".to_string()];
+        // Even a command the user approved for the session is rejected.
+        let approved: HashSet<Vec<String>> = HashSet::from([command.clone()]);
+        let safety_check = assess_command_safety(
+            &command,
+            &exec_policy,
+            AskForApproval::OnRequest,
+            &SandboxPolicy::DangerFullAccess,
+            &approved,
+            false,
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::Forbidden {
+                reason: "shutting down the machine is not allowed".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_exec_policy_safe_command_is_auto_approved() {
+        let exec_policy = ExecPolicy::default_policy().unwrap();
+        let command = vec![
+            "head".to_string(),
+            "-n".to_string(),
+            "5".to_string(),
+            "README.md".to_string(),
+        ];
+        let safety_check = assess_command_safety(
+            &command,
+            &exec_policy,
+            AskForApproval::UnlessTrusted,
+            &SandboxPolicy::new_read_only_policy(),
+            &HashSet::new(),
+            false,
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        );
+    }
+
+    #[test]
+    fn test_known_safe_command_is_sandboxed_when_reads_are_restricted() {
+        let workspace = TempDir::new().unwrap();
+        let command = vec!["cat".to_string(), "/root/.ssh/id_rsa".to_string()];
+        let sandbox_policy = SandboxPolicy::new_read_only_policy()
+            .with_readable_roots(Some(vec![workspace.path().to_path_buf()]));
+
+        let safety_check = assess_command_safety(
+            &command,
+            &ExecPolicy::default_policy().unwrap(),
+            AskForApproval::UnlessTrusted,
+            &sandbox_policy,
+            &HashSet::new(),
+            false,
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::AutoApprove {
+                sandbox_type: get_platform_sandbox().unwrap_or(SandboxType::None)
+            }
+        );
+    }
+
+    #[test]
+    fn test_project_policy_safe_command_stays_sandboxed() {
+        let codex_home = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        std::fs::create_dir_all(project.path().join(".codex")).unwrap();
+        std::fs::write(
+            project.path().join(".codex/policy.star"),
+            r#"
+define_program(
+    program="cargo",
+    args=["test"],
+)
+"#,
+        )
+        .unwrap();
+        let (exec_policy, _) = ExecPolicy::load(codex_home.path(), project.path()).unwrap();
+
+        let safety_check = assess_command_safety(
+            &["cargo".to_string(), "test".to_string()],
+            &exec_policy,
+            AskForApproval::UnlessTrusted,
+            &SandboxPolicy::new_read_only_policy(),
+            &HashSet::new(),
+            false,
+        );
+
+        let expected = match get_platform_sandbox() {
+            Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+            None => SafetyCheck::AskUser,
+        };
+        assert_eq!(safety_check, expected);
+    }
 }
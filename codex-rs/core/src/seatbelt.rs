@@ -3,6 +3,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
+use crate::network_proxy::reject_network_allowlist;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use crate::spawn::StdioPolicy;
@@ -23,6 +24,7 @@ pub async fn spawn_command_under_seatbelt(
     stdio_policy: StdioPolicy,
     mut env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
+    reject_network_allowlist(sandbox_policy, "macOS Seatbelt")?;
     let args = create_seatbelt_command_args(command, sandbox_policy, &cwd);
     let arg0 = None;
     env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "seatbelt".to_string());
@@ -96,11 +98,9 @@ fn create_seatbelt_command_args(
         }
     };
 
-    let file_read_policy = if sandbox_policy.has_full_disk_read_access() {
-        "; allow read-only file operations\n(allow file-read*)"
-    } else {
-        ""
-    };
+    // `readable_roots` is only enforced by Landlock on Linux for now, so reads
+    // stay unrestricted under Seatbelt.
+    let file_read_policy = "; allow read-only file operations\n(allow file-read*)";
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
     let network_policy = if sandbox_policy.has_full_network_access() {
@@ -155,8 +155,10 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![root_with_git, root_without_git],
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            readable_roots: None,
         };
 
         let args = create_seatbelt_command_args(
@@ -230,8 +232,10 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            readable_roots: None,
         };
 
         let args = create_seatbelt_command_args(
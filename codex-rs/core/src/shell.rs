@@ -21,10 +21,26 @@ pub struct PowerShellConfig {
     bash_exe_fallback: Option<PathBuf>, // In case the model generates a bash command.
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct FishShell {
+    shell_path: String,
+    bash_exe_fallback: Option<PathBuf>, // Runs the model's bash scripts.
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct NushellShell {
+    shell_path: String,
+    config_path: String,
+    env_config_path: String,
+    bash_exe_fallback: Option<PathBuf>, // Runs the model's bash scripts.
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum Shell {
     Zsh(ZshShell),
     Bash(BashShell),
+    Fish(FishShell),
+    Nushell(NushellShell),
     PowerShell(PowerShellConfig),
     Unknown,
 }
@@ -38,6 +54,70 @@ impl Shell {
             Shell::Bash(bash) => {
                 format_shell_invocation_with_rc(&command, &bash.shell_path, &bash.bashrc_path)
             }
+            Shell::Fish(fish) => {
+                // fish reads config.fish on every start, so a login shell is
+                // all it takes to pick up the user's profile.
+                let script = match strip_bash_lc(&command) {
+                    Some(script) => match &fish.bash_exe_fallback {
+                        // Let bash run the model's bash syntax, but from inside
+                        // fish so it inherits the environment fish set up.
+                        Some(bash) => format!(
+                            "exec {} -c {}",
+                            fish_quote(&bash.to_string_lossy()),
+                            fish_quote(&script)
+                        ),
+                        // No bash → fish understands simple scripts, and the
+                        // error should tell the model it is running under fish.
+                        None => script,
+                    },
+                    None => command
+                        .iter()
+                        .map(|arg| fish_quote(arg))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                };
+                Some(vec![
+                    fish.shell_path.clone(),
+                    "-l".to_string(),
+                    "-c".to_string(),
+                    script,
+                ])
+            }
+            Shell::Nushell(nu) => {
+                let script = match strip_bash_lc(&command) {
+                    Some(script) => match &nu.bash_exe_fallback {
+                        Some(bash) => format!(
+                            "^{} -c {}",
+                            nu_quote(&bash.to_string_lossy()),
+                            nu_quote(&script)
+                        ),
+                        None => script,
+                    },
+                    // `^` makes nu run the external program rather than a
+                    // builtin of the same name (e.g. `ls`).
+                    None => {
+                        let (program, args) = command.split_first()?;
+                        std::iter::once(format!("^{}", nu_quote(program)))
+                            .chain(args.iter().map(|arg| nu_quote(arg)))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    }
+                };
+                // `nu -c` skips the user's config files unless they are passed
+                // explicitly.
+                let mut invocation = vec![nu.shell_path.clone(), "--login".to_string()];
+                if std::path::Path::new(&nu.env_config_path).exists() {
+                    invocation.push("--env-config".to_string());
+                    invocation.push(nu.env_config_path.clone());
+                }
+                if std::path::Path::new(&nu.config_path).exists() {
+                    invocation.push("--config".to_string());
+                    invocation.push(nu.config_path.clone());
+                }
+                invocation.push("-c".to_string());
+                invocation.push(script);
+                Some(invocation)
+            }
             Shell::PowerShell(ps) => {
                 // If model generated a bash command, prefer a detected bash fallback
                 if let Some(script) = strip_bash_lc(&command) {
@@ -95,6 +175,12 @@ impl Shell {
             Shell::Bash(bash) => std::path::Path::new(&bash.shell_path)
                 .file_name()
                 .map(|s| s.to_string_lossy().to_string()),
+            Shell::Fish(fish) => std::path::Path::new(&fish.shell_path)
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string()),
+            Shell::Nushell(nu) => std::path::Path::new(&nu.shell_path)
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string()),
             Shell::PowerShell(ps) => Some(ps.exe.clone()),
             Shell::Unknown => None,
         }
@@ -118,6 +204,30 @@ fn format_shell_invocation_with_rc(
     Some(vec![shell_path.to_string(), "-lc".to_string(), rc_command])
 }
 
+/// Quotes `arg` for fish, where `\\` and `\'` are the only escapes inside
+/// single quotes.
+fn fish_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Quotes `arg` as a nu double-quoted string.
+fn nu_quote(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for ch in arg.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 fn strip_bash_lc(command: &Vec<String>) -> Option<String> {
     match command.as_slice() {
         // exactly three items
@@ -160,6 +270,29 @@ fn detect_default_user_shell() -> Shell {
                     bashrc_path: format!("{home_path}/.bashrc"),
                 });
             }
+
+            if shell_path.ends_with("/fish") {
+                return Shell::Fish(FishShell {
+                    shell_path,
+                    bash_exe_fallback: which::which("bash").ok(),
+                });
+            }
+
+            if shell_path.ends_with("/nu") {
+                let config_dir = match std::env::var("XDG_CONFIG_HOME") {
+                    Ok(dir) if !dir.is_empty() => format!("{dir}/nushell"),
+                    _ if cfg!(target_os = "macos") => {
+                        format!("{home_path}/Library/Application Support/nushell")
+                    }
+                    _ => format!("{home_path}/.config/nushell"),
+                };
+                return Shell::Nushell(NushellShell {
+                    shell_path,
+                    config_path: format!("{config_dir}/config.nu"),
+                    env_config_path: format!("{config_dir}/env.nu"),
+                    bash_exe_fallback: which::which("bash").ok(),
+                });
+            }
         }
     }
     Shell::Unknown
@@ -347,6 +480,7 @@ mod tests {
                 &SandboxPolicy::DangerFullAccess,
                 &None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -360,6 +494,98 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_fish_invocation() {
+        let shell = Shell::Fish(FishShell {
+            shell_path: "/usr/bin/fish".to_string(),
+            bash_exe_fallback: Some(PathBuf::from("/bin/bash")),
+        });
+        assert_eq!(
+            shell.format_default_shell_invocation(vec![
+                "bash".to_string(),
+                "-lc".to_string(),
+                "echo \"$(pwd)\" && echo 'it'\\''s'".to_string(),
+            ]),
+            Some(vec![
+                "/usr/bin/fish".to_string(),
+                "-l".to_string(),
+                "-c".to_string(),
+                "exec '/bin/bash' -c 'echo \"$(pwd)\" && echo \\'it\\'\\\\\\'\\'s\\''".to_string(),
+            ])
+        );
+        assert_eq!(
+            shell.format_default_shell_invocation(vec![
+                "rg".to_string(),
+                "foo bar".to_string(),
+                "it's".to_string(),
+            ]),
+            Some(vec![
+                "/usr/bin/fish".to_string(),
+                "-l".to_string(),
+                "-c".to_string(),
+                "'rg' 'foo bar' 'it\\'s'".to_string(),
+            ])
+        );
+
+        let shell = Shell::Fish(FishShell {
+            shell_path: "/usr/bin/fish".to_string(),
+            bash_exe_fallback: None,
+        });
+        assert_eq!(
+            shell.format_default_shell_invocation(vec![
+                "bash".to_string(),
+                "-lc".to_string(),
+                "ls && pwd".to_string(),
+            ]),
+            Some(vec![
+                "/usr/bin/fish".to_string(),
+                "-l".to_string(),
+                "-c".to_string(),
+                "ls && pwd".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_nushell_invocation() {
+        let temp_home = tempfile::tempdir().unwrap();
+        let config_path = temp_home.path().join("config.nu");
+        std::fs::write(&config_path, "$env.config = {}").unwrap();
+        let config_path = config_path.to_str().unwrap().to_string();
+        let shell = Shell::Nushell(NushellShell {
+            shell_path: "/usr/bin/nu".to_string(),
+            config_path: config_path.clone(),
+            env_config_path: "/does/not/exist/env.nu".to_string(),
+            bash_exe_fallback: Some(PathBuf::from("/bin/bash")),
+        });
+        assert_eq!(
+            shell.format_default_shell_invocation(vec![
+                "bash".to_string(),
+                "-lc".to_string(),
+                "echo \"hi\"\nls".to_string(),
+            ]),
+            Some(vec![
+                "/usr/bin/nu".to_string(),
+                "--login".to_string(),
+                "--config".to_string(),
+                config_path.clone(),
+                "-c".to_string(),
+                "^\"/bin/bash\" -c \"echo \\\"hi\\\"\\nls\"".to_string(),
+            ])
+        );
+        assert_eq!(
+            shell.format_default_shell_invocation(vec!["ls".to_string(), "-la".to_string()]),
+            Some(vec![
+                "/usr/bin/nu".to_string(),
+                "--login".to_string(),
+                "--config".to_string(),
+                config_path,
+                "-c".to_string(),
+                "^\"ls\" \"-la\"".to_string(),
+            ])
+        );
+    }
 }
 
 #[cfg(test)]
@@ -453,6 +679,7 @@ mod macos_tests {
                 &SandboxPolicy::DangerFullAccess,
                 &None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -17,6 +17,11 @@ use crate::protocol::SandboxPolicy;
 /// attributes, so this may change in the future.
 pub const CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR: &str = "CODEX_SANDBOX_NETWORK_DISABLED";
 
+/// Set to the port of the local network proxy when the sandbox policy limits
+/// the network to an allowlist. The Linux sandbox only permits TCP connections
+/// to this port.
+pub const CODEX_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR: &str = "CODEX_SANDBOX_NETWORK_PROXY_PORT";
+
 /// Should be set when the process is spawned under a sandbox. Currently, the
 /// value is "seatbelt" for macOS, but it may change in the future to
 /// accommodate sandboxing configuration and other sandboxing mechanisms.
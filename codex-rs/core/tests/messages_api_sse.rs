@@ -0,0 +1,275 @@
+use std::sync::Arc;
+
+use codex_core::ContentItem;
+use codex_core::ModelClient;
+use codex_core::ModelProviderInfo;
+use codex_core::Prompt;
+use codex_core::ResponseEvent;
+use codex_core::ResponseItem;
+use codex_core::WireApi;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_protocol::mcp_protocol::ConversationId;
+use core_test_support::load_default_config_for_test;
+use futures::StreamExt;
+use serde_json::Value;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::header;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn network_disabled() -> bool {
+    std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok()
+}
+
+fn sse(events: &[Value]) -> String {
+    events
+        .iter()
+        .map(|event| {
+            let kind = event
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            format!("event: {kind}\ndata: {event}\n\n")
+        })
+        .collect()
+}
+
+/// Client for a provider that speaks the Messages API at `base_url`.
+fn messages_client(base_url: String) -> (ModelClient, TempDir) {
+    let provider = ModelProviderInfo {
+        name: "mock".into(),
+        base_url: Some(base_url),
+        env_key: None,
+        env_key_instructions: None,
+        wire_api: WireApi::Messages,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: Some(5_000),
+        requires_openai_auth: false,
+    };
+
+    let codex_home = match TempDir::new() {
+        Ok(dir) => dir,
+        Err(e) => panic!("failed to create TempDir: {e}"),
+    };
+    let mut config = load_default_config_for_test(&codex_home);
+    config.model_provider_id = provider.name.clone();
+    config.model_provider = provider.clone();
+    let effort = config.model_reasoning_effort;
+    let summary = config.model_reasoning_summary;
+    let config = Arc::new(config);
+
+    let client = ModelClient::new(
+        Arc::clone(&config),
+        None,
+        provider,
+        effort,
+        summary,
+        ConversationId::new(),
+    );
+    (client, codex_home)
+}
+
+async fn run_stream(sse_body: String) -> (Vec<ResponseEvent>, Value) {
+    let server = MockServer::start().await;
+
+    let template = ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_raw(sse_body, "text/event-stream");
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .respond_with(template)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (client, _codex_home) = messages_client(format!("{}/v1", server.uri()));
+
+    let mut prompt = Prompt::default();
+    prompt.input = vec![ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: "hello".to_string(),
+        }],
+    }];
+
+    let mut stream = match client.stream(&prompt).await {
+        Ok(s) => s,
+        Err(e) => panic!("stream messages failed: {e}"),
+    };
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(ev) => events.push(ev),
+            Err(e) => panic!("stream event error: {e}"),
+        }
+    }
+
+    let requests = match server.received_requests().await {
+        Some(reqs) => reqs,
+        None => panic!("request not made"),
+    };
+    let body = match requests[0].body_json() {
+        Ok(v) => v,
+        Err(e) => panic!("invalid json body: {e}"),
+    };
+    (events, body)
+}
+
+fn message_start() -> Value {
+    serde_json::json!({
+        "type": "message_start",
+        "message": {"id": "msg_1", "usage": {"input_tokens": 10, "cache_read_input_tokens": 5, "output_tokens": 1}},
+    })
+}
+
+fn message_stop() -> Vec<Value> {
+    vec![
+        serde_json::json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 7}}),
+        serde_json::json!({"type": "message_stop"}),
+    ]
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn streams_text_blocks() {
+    if network_disabled() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let mut events = vec![
+        message_start(),
+        serde_json::json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+        serde_json::json!({"type": "ping"}),
+        serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "hi "}}),
+        serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "there"}}),
+        serde_json::json!({"type": "content_block_stop", "index": 0}),
+    ];
+    events.extend(message_stop());
+
+    let (events, body) = run_stream(sse(&events)).await;
+    assert_eq!(events.len(), 5, "unexpected events: {events:?}");
+
+    assert!(matches!(events[0], ResponseEvent::Created));
+    match (&events[1], &events[2]) {
+        (ResponseEvent::OutputTextDelta(a), ResponseEvent::OutputTextDelta(b)) => {
+            assert_eq!(a, "hi ");
+            assert_eq!(b, "there");
+        }
+        other => panic!("expected text deltas, got {other:?}"),
+    }
+    match &events[3] {
+        ResponseEvent::OutputItemDone(ResponseItem::Message { role, content, .. }) => {
+            assert_eq!(role, "assistant");
+            assert_eq!(
+                content,
+                &vec![ContentItem::OutputText {
+                    text: "hi there".to_string()
+                }]
+            );
+        }
+        other => panic!("expected assistant message, got {other:?}"),
+    }
+    match &events[4] {
+        ResponseEvent::Completed {
+            response_id,
+            token_usage: Some(usage),
+        } => {
+            assert_eq!(response_id, "msg_1");
+            assert_eq!(usage.input_tokens, 15);
+            assert_eq!(usage.cached_input_tokens, 5);
+            assert_eq!(usage.output_tokens, 7);
+            assert_eq!(usage.total_tokens, 22);
+        }
+        other => panic!("expected completion with usage, got {other:?}"),
+    }
+
+    assert_eq!(body["stream"], Value::Bool(true));
+    assert!(body["system"].is_string());
+    assert_eq!(
+        body["messages"],
+        serde_json::json!([{"role": "user", "content": [{"type": "text", "text": "hello"}]}])
+    );
+    assert!(body["max_tokens"].is_u64());
+    assert!(body.get("thinking").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn streams_thinking_before_tool_use() {
+    if network_disabled() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let mut events = vec![
+        message_start(),
+        serde_json::json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+        serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "need ls"}}),
+        serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig-1"}}),
+        serde_json::json!({"type": "content_block_stop", "index": 0}),
+        serde_json::json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {}}}),
+        serde_json::json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{\"command\":"}}),
+        serde_json::json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "[\"ls\"]}"}}),
+        serde_json::json!({"type": "content_block_stop", "index": 1}),
+    ];
+    events.extend(message_stop());
+
+    let (events, _) = run_stream(sse(&events)).await;
+    assert_eq!(events.len(), 5, "unexpected events: {events:?}");
+
+    match &events[1] {
+        ResponseEvent::ReasoningSummaryDelta(text) => assert_eq!(text, "need ls"),
+        other => panic!("expected reasoning delta, got {other:?}"),
+    }
+    match &events[2] {
+        ResponseEvent::OutputItemDone(ResponseItem::Reasoning {
+            summary,
+            encrypted_content,
+            ..
+        }) => {
+            assert_eq!(summary.len(), 1);
+            assert_eq!(encrypted_content.as_deref(), Some("sig-1"));
+        }
+        other => panic!("expected reasoning item, got {other:?}"),
+    }
+    match &events[3] {
+        ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+            name,
+            arguments,
+            call_id,
+            ..
+        }) => {
+            assert_eq!(name, "shell");
+            assert_eq!(arguments, "{\"command\":[\"ls\"]}");
+            assert_eq!(call_id, "toolu_1");
+        }
+        other => panic!("expected function call, got {other:?}"),
+    }
+    assert!(matches!(events[4], ResponseEvent::Completed { .. }));
+}
+
+#[tokio::test]
+async fn output_schema_is_rejected() {
+    // The request is rejected before anything is sent.
+    let (client, _codex_home) = messages_client("http://127.0.0.1:9/v1".to_string());
+    let mut prompt = Prompt::default();
+    prompt.output_schema = Some(serde_json::json!({"type": "object"}));
+
+    let Err(err) = client.stream(&prompt).await else {
+        panic!("expected the output schema to be rejected");
+    };
+    assert!(err.to_string().contains("output schema"), "{err}");
+}
@@ -0,0 +1,236 @@
+#![expect(clippy::unwrap_used)]
+
+use codex_core::CodexAuth;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::SubAgentEndEvent;
+use codex_core::protocol::SubAgentEvent;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use core_test_support::load_default_config_for_test;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::Request;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Build an SSE stream body from a list of JSON events.
+fn sse(events: Vec<Value>) -> String {
+    use std::fmt::Write as _;
+    let mut out = String::new();
+    for ev in events {
+        let kind = ev.get("type").and_then(|v| v.as_str()).unwrap();
+        writeln!(&mut out, "event: {kind}").unwrap();
+        write!(&mut out, "data: {ev}\n\n").unwrap();
+    }
+    out
+}
+
+fn ev_completed_with_tokens(id: &str, total_tokens: u64) -> Value {
+    json!({
+        "type": "response.completed",
+        "response": {
+            "id": id,
+            "usage": {
+                "input_tokens": total_tokens,
+                "input_tokens_details": null,
+                "output_tokens": 0,
+                "output_tokens_details": null,
+                "total_tokens": total_tokens
+            }
+        }
+    })
+}
+
+fn ev_assistant_message(id: &str, text: &str) -> Value {
+    json!({
+        "type": "response.output_item.done",
+        "item": {
+            "type": "message",
+            "role": "assistant",
+            "id": id,
+            "content": [{"type": "output_text", "text": text}]
+        }
+    })
+}
+
+fn ev_function_call(call_id: &str, name: &str, arguments: Value) -> Value {
+    json!({
+        "type": "response.output_item.done",
+        "item": {
+            "type": "function_call",
+            "call_id": call_id,
+            "name": name,
+            "arguments": arguments.to_string()
+        }
+    })
+}
+
+const SUB_AGENT_REPLY: &str = "SUB_AGENT_DONE";
+const PARENT_REPLY: &str = "PARENT_DONE";
+
+/// The parent delegates one task; the sub-agent runs a command that needs
+/// approval and then answers. Checks that the approval is answered through
+/// the parent, the sub-agent's events are forwarded, its final message is
+/// returned to the parent model and its tokens count towards the parent's
+/// totals.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn delegate_forwards_sub_agent_events_and_approvals() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    // Requests arrive strictly in order: the parent's first turn, the
+    // sub-agent's two turns, then the parent's follow-up.
+    let bodies = vec![
+        sse(vec![
+            ev_function_call(
+                "delegate-1",
+                "delegate",
+                json!({ "tasks": [{ "prompt": "create the marker file" }] }),
+            ),
+            ev_completed_with_tokens("r1", 10),
+        ]),
+        sse(vec![
+            ev_function_call(
+                "shell-1",
+                "shell",
+                json!({ "command": ["touch", "marker.txt"] }),
+            ),
+            ev_completed_with_tokens("r2", 20),
+        ]),
+        sse(vec![
+            ev_assistant_message("m3", SUB_AGENT_REPLY),
+            ev_completed_with_tokens("r3", 30),
+        ]),
+        sse(vec![
+            ev_assistant_message("m4", PARENT_REPLY),
+            ev_completed_with_tokens("r4", 40),
+        ]),
+    ];
+    let server = MockServer::start().await;
+    let request_count = Arc::new(AtomicUsize::new(0));
+    let responder = {
+        let request_count = request_count.clone();
+        move |_: &Request| {
+            let idx = request_count.fetch_add(1, Ordering::SeqCst);
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(bodies[idx].clone(), "text/event-stream")
+        }
+    };
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(responder)
+        .expect(4)
+        .mount(&server)
+        .await;
+
+    let codex_home = TempDir::new().unwrap();
+    let cwd = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&codex_home);
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.cwd = cwd.path().to_path_buf();
+    config.include_delegate_tool = true;
+    config.approval_policy = AskForApproval::UnlessTrusted;
+    config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("Test API Key"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create conversation")
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "split this up".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let mut approvals = 0;
+    let mut forwarded_exec_end = false;
+    let mut sub_agent_result = None;
+    let mut total_tokens = None;
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(10), codex.next_event())
+            .await
+            .expect("timeout waiting for event")
+            .expect("stream ended unexpectedly");
+        match event.msg {
+            EventMsg::ExecApprovalRequest(request) => {
+                assert_eq!(request.command, vec!["touch", "marker.txt"]);
+                approvals += 1;
+                codex
+                    .submit(Op::ExecApproval {
+                        id: event.id,
+                        decision: ReviewDecision::Approved,
+                    })
+                    .await
+                    .unwrap();
+            }
+            EventMsg::SubAgent(SubAgentEvent { msg, .. }) => {
+                if matches!(*msg, EventMsg::ExecCommandEnd(ref end) if end.exit_code == 0) {
+                    forwarded_exec_end = true;
+                }
+            }
+            EventMsg::SubAgentEnd(SubAgentEndEvent { result, .. }) => {
+                sub_agent_result = Some(result);
+            }
+            EventMsg::TokenCount(token_count) => {
+                total_tokens = token_count
+                    .info
+                    .map(|info| info.total_token_usage.total_tokens);
+            }
+            EventMsg::TaskComplete(_) => break,
+            _ => {}
+        }
+    }
+
+    assert_eq!(approvals, 1);
+    assert!(
+        forwarded_exec_end,
+        "sub-agent exec events were not forwarded"
+    );
+    assert!(cwd.path().join("marker.txt").exists());
+    assert_eq!(sub_agent_result, Some(Ok(SUB_AGENT_REPLY.to_string())));
+    // Parent turns (10 + 40) plus sub-agent turns (20 + 30).
+    assert_eq!(total_tokens, Some(100));
+
+    // The parent's follow-up request carries the sub-agent's final message
+    // as the output of the delegate call.
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 4);
+    let body = requests[3].body_json::<Value>().unwrap();
+    let output = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["type"] == "function_call_output" && item["call_id"] == "delegate-1")
+        .expect("delegate output in follow-up request");
+    assert_eq!(output["output"], SUB_AGENT_REPLY);
+}
@@ -62,6 +62,7 @@ async fn test_exec_stdout_stream_events_echo() {
         SandboxType::None,
         &policy,
         &None,
+        None,
         Some(stdout_stream),
     )
     .await;
@@ -112,6 +113,7 @@ async fn test_exec_stderr_stream_events_echo() {
         SandboxType::None,
         &policy,
         &None,
+        None,
         Some(stdout_stream),
     )
     .await;
@@ -160,7 +162,7 @@ async fn test_aggregated_output_interleaves_in_order() {
 
     let policy = SandboxPolicy::new_read_only_policy();
 
-    let result = process_exec_tool_call(params, SandboxType::None, &policy, &None, None)
+    let result = process_exec_tool_call(params, SandboxType::None, &policy, &None, None, None)
         .await
         .expect("process_exec_tool_call");
 
@@ -3,10 +3,12 @@
 mod cli_stream;
 mod client;
 mod compact;
+mod delegate;
 mod exec;
 mod exec_stream_events;
 mod fork_conversation;
 mod live_cli;
+mod mcp_input;
 mod model_overrides;
 mod prompt_caching;
 mod review;
@@ -383,8 +383,10 @@ async fn overrides_turn_context_but_keeps_cached_prefix_and_key_constant() {
             sandbox_policy: Some(SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![writable.path().to_path_buf()],
                 network_access: true,
+                network_allowlist: Vec::new(),
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                readable_roots: None,
             }),
             model: Some("o3".to_string()),
             effort: Some(Some(ReasoningEffort::High)),
@@ -515,8 +517,10 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() {
             sandbox_policy: SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![writable.path().to_path_buf()],
                 network_access: true,
+                network_allowlist: Vec::new(),
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                readable_roots: None,
             },
             model: "o3".to_string(),
             effort: Some(ReasoningEffort::High),
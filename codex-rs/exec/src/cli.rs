@@ -6,6 +6,10 @@ use std::path::PathBuf;
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Cli {
+    /// Action to perform. If omitted, runs a new non-interactive session.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Optional image(s) to attach to the initial prompt.
     #[arg(long = "image", short = 'i', value_name = "FILE", value_delimiter = ',', num_args = 1..)]
     pub images: Vec<PathBuf>,
@@ -44,6 +48,11 @@ pub struct Cli {
     #[clap(long = "cd", short = 'C', value_name = "DIR")]
     pub cwd: Option<PathBuf>,
 
+    /// Run the session in a new git worktree on its own branch, leaving the
+    /// current checkout untouched. See `codex worktree`.
+    #[arg(long = "worktree", default_value_t = false)]
+    pub worktree: bool,
+
     /// Allow running Codex outside a Git repository.
     #[arg(long = "skip-git-repo-check", default_value_t = false)]
     pub skip_git_repo_check: bool,
@@ -63,12 +72,53 @@ pub struct Cli {
     #[arg(long = "output-last-message")]
     pub last_message_file: Option<PathBuf>,
 
+    /// Path to a JSON Schema file describing the shape of the agent's final
+    /// response. The response is validated against it and the parsed JSON is
+    /// written to stdout, or to `--output-last-message` if given.
+    #[arg(long = "output-schema", value_name = "FILE")]
+    pub output_schema: Option<PathBuf>,
+
+    /// Save every model request and streamed response in DIR so the session
+    /// can be replayed offline with `--replay`.
+    #[arg(long = "record", value_name = "DIR", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Serve model responses from a cassette written by `--record` instead of
+    /// calling the model provider. Fails if a request differs from the
+    /// recorded one.
+    #[arg(long = "replay", value_name = "DIR")]
+    pub replay: Option<PathBuf>,
+
     /// Initial instructions for the agent. If not provided as an argument (or
     /// if `-` is used), instructions are read from stdin.
     #[arg(value_name = "PROMPT")]
     pub prompt: Option<String>,
 }
 
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Resume a previous session by id, or the most recent one with `--last`.
+    Resume(ResumeArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct ResumeArgs {
+    /// Id of the session to resume, as printed when that session started.
+    /// Omit it when using `--last`.
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: Option<String>,
+
+    /// Resume the most recent recorded session instead of a specific one.
+    /// When set, the first positional argument is treated as the prompt.
+    #[arg(long = "last", default_value_t = false)]
+    pub last: bool,
+
+    /// Follow-up prompt for the resumed session. If not provided as an
+    /// argument (or if `-` is used), it is read from stdin.
+    #[arg(value_name = "PROMPT")]
+    pub prompt: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
 #[value(rename_all = "kebab-case")]
 pub enum Color {
@@ -19,11 +19,17 @@ use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::SecretsRedactedEvent;
+use codex_core::protocol::SessionBudgetExceededEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
+use codex_core::protocol::SubAgentEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnUndoneEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_protocol::num_format::format_with_separators;
@@ -181,6 +187,67 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::StreamError(StreamErrorEvent { message }) => {
                 ts_println!(self, "{}", message.style(self.dimmed));
             }
+            EventMsg::SessionBudgetExceeded(SessionBudgetExceededEvent { message }) => {
+                let prefix = "ERROR:".style(self.red);
+                ts_println!(self, "{prefix} {message}");
+            }
+            EventMsg::SecretsRedacted(SecretsRedactedEvent { count }) => {
+                ts_println!(
+                    self,
+                    "{}",
+                    format!("redacted {count} secret(s) from tool output").style(self.dimmed)
+                );
+            }
+            EventMsg::SubAgentBegin(SubAgentBeginEvent {
+                task,
+                prompt,
+                cwd,
+                model,
+                ..
+            }) => {
+                let prompt = prompt.lines().next().unwrap_or_default().to_string();
+                ts_println!(
+                    self,
+                    "{} {} in {} ({model})",
+                    format!("sub-agent {task}").style(self.magenta),
+                    prompt.style(self.bold),
+                    cwd.to_string_lossy(),
+                );
+            }
+            EventMsg::SubAgent(SubAgentEvent { task, msg, .. }) => {
+                let prefix = format!("sub-agent {task}");
+                match *msg {
+                    EventMsg::ExecCommandBegin(ExecCommandBeginEvent { command, .. }) => {
+                        ts_println!(
+                            self,
+                            "{} {} {}",
+                            prefix.style(self.magenta),
+                            "exec".style(self.magenta),
+                            escape_command(&command).style(self.bold),
+                        );
+                    }
+                    EventMsg::Error(ErrorEvent { message }) => {
+                        ts_println!(
+                            self,
+                            "{} {} {message}",
+                            prefix.style(self.magenta),
+                            "ERROR:".style(self.red),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            EventMsg::SubAgentEnd(SubAgentEndEvent { task, result, .. }) => match result {
+                Ok(message) => {
+                    let title = format!("sub-agent {task} finished:");
+                    ts_println!(self, "{}", title.style(self.green));
+                    println!("{}", message.style(self.dimmed));
+                }
+                Err(e) => {
+                    let title = format!("sub-agent {task} failed: {e}");
+                    ts_println!(self, "{}", title.style(self.red));
+                }
+            },
             EventMsg::TaskStarted(_) => {
                 // Ignore.
             }
@@ -192,11 +259,12 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             }
             EventMsg::TokenCount(ev) => {
                 if let Some(usage_info) = ev.info {
-                    ts_println!(
-                        self,
-                        "tokens used: {}",
-                        format_with_separators(usage_info.total_token_usage.blended_total())
-                    );
+                    let tokens =
+                        format_with_separators(usage_info.total_token_usage.blended_total());
+                    match usage_info.total_cost_usd {
+                        Some(cost) => ts_println!(self, "tokens used: {tokens} (~${cost:.2})"),
+                        None => ts_println!(self, "tokens used: {tokens}"),
+                    }
                 }
             }
             EventMsg::AgentMessageDelta(AgentMessageDeltaEvent { delta }) => {
@@ -495,12 +563,23 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 ts_println!(self, "{}", "turn diff:".style(self.magenta));
                 println!("{unified_diff}");
             }
+            EventMsg::TurnUndone(TurnUndoneEvent { checkpoint }) => {
+                ts_println!(
+                    self,
+                    "{} {}",
+                    "restored checkpoint".style(self.magenta),
+                    checkpoint.commit.style(self.dimmed)
+                );
+            }
             EventMsg::ExecApprovalRequest(_) => {
                 // Should we exit?
             }
             EventMsg::ApplyPatchApprovalRequest(_) => {
                 // Should we exit?
             }
+            EventMsg::McpToolCallApprovalRequest(_) => {
+                // Should we exit?
+            }
             EventMsg::AgentReasoning(agent_reasoning_event) => {
                 if self.show_agent_reasoning {
                     if !self.reasoning_started {
@@ -525,6 +604,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     history_entry_count: _,
                     initial_messages: _,
                     rollout_path: _,
+                    worktree,
                 } = session_configured_event;
 
                 ts_println!(
@@ -535,6 +615,14 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 );
 
                 ts_println!(self, "model: {}", model);
+                if let Some(worktree) = worktree {
+                    ts_println!(
+                        self,
+                        "worktree: {} (branch {})",
+                        worktree.path.display(),
+                        worktree.branch
+                    );
+                }
                 println!();
             }
             EventMsg::PlanUpdate(plan_update_event) => {
@@ -542,7 +630,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 ts_println!(self, "explanation: {explanation:?}");
                 ts_println!(self, "plan: {plan:?}");
             }
-            EventMsg::GetHistoryEntryResponse(_) => {
+            EventMsg::GetHistoryEntryResponse(_) | EventMsg::ListHistoryEntriesResponse(_) => {
                 // Currently ignored in exec output.
             }
             EventMsg::McpListToolsResponse(_) => {
@@ -551,6 +639,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ListCustomPromptsResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::McpListResourcesResponse(_) | EventMsg::McpListPromptsResponse(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::TurnAborted(abort_reason) => match abort_reason.reason {
                 TurnAbortReason::Interrupted => {
                     ts_println!(self, "task interrupted");
@@ -561,6 +652,8 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             },
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::ConversationPath(_) => {}
+            // `codex exec` cannot undo turns, so checkpoints are not shown.
+            EventMsg::CheckpointCreated(_) => {}
             EventMsg::UserMessage(_) => {}
             EventMsg::EnteredReviewMode(_) => {}
             EventMsg::ExitedReviewMode(_) => {}
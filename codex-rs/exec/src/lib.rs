@@ -2,24 +2,31 @@ mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
 mod event_processor_with_json_output;
+mod output_schema;
 
 use std::io::IsTerminal;
 use std::io::Read;
+use std::path::Path;
 use std::path::PathBuf;
 
 pub use cli::Cli;
+use cli::Command;
+use cli::ResumeArgs;
 use codex_core::AuthManager;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::ConversationManager;
 use codex_core::NewConversation;
+use codex_core::RolloutRecorder;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::config_types::CassetteMode;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
+use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_ollama::DEFAULT_OSS_MODEL;
 use codex_protocol::config_types::SandboxMode;
@@ -32,9 +39,13 @@ use tracing_subscriber::EnvFilter;
 
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
+use crate::output_schema::load_output_schema;
+use crate::output_schema::parse_structured_output;
+use crate::output_schema::write_structured_output;
 
 pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
     let Cli {
+        command,
         images,
         model: model_cli_arg,
         oss,
@@ -42,15 +53,48 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         full_auto,
         dangerously_bypass_approvals_and_sandbox,
         cwd,
+        worktree,
         skip_git_repo_check,
         color,
         last_message_file,
+        output_schema: output_schema_path,
+        record,
+        replay,
         json: json_mode,
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
         config_overrides,
     } = cli;
 
+    let (resume_target, prompt) = match command {
+        Some(Command::Resume(ResumeArgs {
+            session_id,
+            last: true,
+            prompt: resume_prompt,
+        })) => {
+            if session_id.is_some() && resume_prompt.is_some() {
+                eprintln!("`codex exec resume --last` does not take a session id.");
+                std::process::exit(1);
+            }
+            // Without an id, the first positional argument is the prompt.
+            (Some(ResumeTarget::Last), session_id.or(resume_prompt))
+        }
+        Some(Command::Resume(ResumeArgs {
+            session_id: Some(session_id),
+            last: false,
+            prompt: resume_prompt,
+        })) => (Some(ResumeTarget::Id(session_id)), resume_prompt),
+        Some(Command::Resume(ResumeArgs {
+            session_id: None,
+            last: false,
+            ..
+        })) => {
+            eprintln!("Specify the session to resume with SESSION_ID or `--last`.");
+            std::process::exit(1);
+        }
+        None => (None, prompt),
+    };
+
     // Determine the prompt based on CLI arg and/or stdin.
     let prompt = match prompt {
         Some(p) if p != "-" => p,
@@ -134,6 +178,17 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         None // No specific model provider override.
     };
 
+    let output_schema = output_schema_path
+        .as_deref()
+        .map(load_output_schema)
+        .transpose()?;
+
+    let cassette = match (record, replay) {
+        (Some(dir), _) => Some(CassetteMode::Record(dir)),
+        (None, Some(dir)) => Some(CassetteMode::Replay(dir)),
+        (None, None) => None,
+    };
+
     // Load configuration and determine approval policy
     let overrides = ConfigOverrides {
         model,
@@ -152,6 +207,9 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         include_view_image_tool: None,
         show_raw_agent_reasoning: oss.then_some(true),
         tools_web_search_request: None,
+        output_schema: output_schema.clone(),
+        cassette,
+        worktree: worktree.then_some(true),
     };
     // Parse `-c` overrides.
     let cli_kv_overrides = match config_overrides.parse_overrides() {
@@ -163,13 +221,22 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     };
 
     let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
+    // With an output schema the structured result replaces the raw last
+    // message, so the event processors must not write it.
+    let processor_last_message_file = if output_schema.is_some() {
+        None
+    } else {
+        last_message_file.clone()
+    };
     let mut event_processor: Box<dyn EventProcessor> = if json_mode {
-        Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone()))
+        Box::new(EventProcessorWithJsonOutput::new(
+            processor_last_message_file,
+        ))
     } else {
         Box::new(EventProcessorWithHumanOutput::create_with_ansi(
             stdout_with_ansi,
             &config,
-            last_message_file.clone(),
+            processor_last_message_file,
         ))
     };
 
@@ -188,15 +255,37 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         std::process::exit(1);
     }
 
-    let conversation_manager =
-        ConversationManager::new(AuthManager::shared(config.codex_home.clone()));
+    let resume_path = match resume_target {
+        Some(target) => resolve_resume_path(&config.codex_home, target).await?,
+        None => None,
+    };
+
+    let auth_manager = AuthManager::shared(config.codex_home.clone());
+    let conversation_manager = ConversationManager::new(auth_manager.clone());
     let NewConversation {
         conversation_id: _,
         conversation,
         session_configured,
-    } = conversation_manager.new_conversation(config).await?;
+    } = match resume_path {
+        Some(rollout_path) => {
+            conversation_manager
+                .resume_conversation_from_rollout(config, rollout_path, auth_manager)
+                .await?
+        }
+        None => conversation_manager.new_conversation(config).await?,
+    };
     info!("Codex initialized with event: {session_configured:?}");
 
+    // Report the session id up front so scripts can resume this session in a
+    // later `codex exec resume` call. The replayed history is not echoed.
+    event_processor.process_event(Event {
+        id: String::new(),
+        msg: EventMsg::SessionConfigured(SessionConfiguredEvent {
+            initial_messages: None,
+            ..session_configured
+        }),
+    });
+
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
     {
         let conversation = conversation.clone();
@@ -264,7 +353,18 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     info!("Sent prompt with event ID: {initial_prompt_task_id}");
 
     // Run the loop until the task is complete.
+    let mut structured_output_error = None;
     while let Some(event) = rx.recv().await {
+        if let Some(schema) = &output_schema
+            && let EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) = &event.msg
+        {
+            match parse_structured_output(schema, last_agent_message.as_deref()) {
+                Ok(value) => {
+                    write_structured_output(&value, last_message_file.as_deref(), json_mode)
+                }
+                Err(e) => structured_output_error = Some(e),
+            }
+        }
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
             CodexStatus::Running => continue,
@@ -277,5 +377,38 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     }
 
+    if let Some(e) = structured_output_error {
+        anyhow::bail!("{e}");
+    }
+
     Ok(())
 }
+
+enum ResumeTarget {
+    Id(String),
+    Last,
+}
+
+/// Finds the rollout file of the session to resume. `--last` with no recorded
+/// sessions starts a fresh session, like `--continue` in the TUI.
+async fn resolve_resume_path(
+    codex_home: &Path,
+    target: ResumeTarget,
+) -> anyhow::Result<Option<PathBuf>> {
+    match target {
+        ResumeTarget::Id(id) => {
+            match RolloutRecorder::find_conversation_path_by_id(codex_home, &id).await? {
+                Some(path) => Ok(Some(path)),
+                None => anyhow::bail!("No recorded session found with id {id}"),
+            }
+        }
+        ResumeTarget::Last => {
+            let page = RolloutRecorder::list_conversations(codex_home, 1, None).await?;
+            let path = page.items.into_iter().next().map(|item| item.path);
+            if path.is_none() {
+                eprintln!("No recorded sessions found; starting a new session.");
+            }
+            Ok(path)
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! Support for `codex exec --output-schema`: loading the schema, checking the
+//! final agent message against it and writing the parsed result.
+//!
+//! The validator checks the keywords in [`VALIDATED_KEYWORDS`] (only local
+//! `$ref`s are resolved). Annotations such as `description` or `$defs` do not
+//! constrain values. Any other keyword, e.g. `pattern`, `format`, `minimum` or
+//! `minItems`, is *not* checked locally and is reported as a warning when the
+//! schema is loaded; the model provider may still enforce it.
+
+use std::path::Path;
+
+use anyhow::Context;
+use serde_json::Map;
+use serde_json::Value;
+use serde_json::json;
+
+/// Keywords the validator enforces.
+pub(crate) const VALIDATED_KEYWORDS: &[&str] = &[
+    "type",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "enum",
+    "const",
+    "anyOf",
+    "$ref",
+];
+
+/// Keywords that carry no constraint of their own.
+const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "$defs",
+    "definitions",
+    "title",
+    "description",
+    "default",
+    "examples",
+];
+
+pub(crate) fn load_output_schema(path: &Path) -> anyhow::Result<Value> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read output schema {}", path.display()))?;
+    let schema: Value = serde_json::from_str(&contents)
+        .with_context(|| format!("output schema {} is not valid JSON", path.display()))?;
+    if !schema.is_object() {
+        anyhow::bail!("output schema {} must be a JSON object", path.display());
+    }
+    let unsupported = unsupported_keywords(&schema);
+    if !unsupported.is_empty() {
+        eprintln!(
+            "Warning: output schema {} uses keywords that are not validated locally: {}",
+            path.display(),
+            unsupported.join(", ")
+        );
+    }
+    Ok(schema)
+}
+
+/// Parses the final agent message as JSON and validates it against `schema`.
+/// A surrounding Markdown code fence is tolerated for providers that do not
+/// support structured outputs.
+pub(crate) fn parse_structured_output(
+    schema: &Value,
+    last_agent_message: Option<&str>,
+) -> Result<Value, String> {
+    let message = last_agent_message.ok_or("the agent did not produce a final message")?;
+    let value: Value = serde_json::from_str(strip_code_fence(message))
+        .map_err(|e| format!("the final agent message is not valid JSON: {e}"))?;
+    let errors = validate(schema, &value);
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(format!(
+            "the final agent message does not match the output schema:\n  {}",
+            errors.join("\n  ")
+        ))
+    }
+}
+
+/// Writes `value` to `output_file`, or to stdout when no file was given. In
+/// `--json` mode stdout is a JSONL stream, so the value is wrapped in a
+/// `{"structured_output": ...}` line rather than printed bare.
+pub(crate) fn write_structured_output(value: &Value, output_file: Option<&Path>, json_mode: bool) {
+    match output_file {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, value.to_string()) {
+                eprintln!("Failed to write last message file {path:?}: {e}");
+            }
+        }
+        None if json_mode => println!("{}", json!({ "structured_output": value })),
+        None => println!("{value}"),
+    }
+}
+
+/// Sorted, de-duplicated keywords in `schema` (including nested schemas)
+/// that are neither validated nor pure annotations.
+pub(crate) fn unsupported_keywords(schema: &Value) -> Vec<String> {
+    let mut found = Vec::new();
+    collect_unsupported_keywords(schema, &mut found);
+    found.sort();
+    found.dedup();
+    found
+}
+
+fn collect_unsupported_keywords(schema: &Value, found: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    for (keyword, value) in schema {
+        match keyword.as_str() {
+            "properties" | "$defs" | "definitions" => {
+                for subschema in value.as_object().into_iter().flat_map(Map::values) {
+                    collect_unsupported_keywords(subschema, found);
+                }
+            }
+            "anyOf" => {
+                for subschema in value.as_array().into_iter().flatten() {
+                    collect_unsupported_keywords(subschema, found);
+                }
+            }
+            "items" | "additionalProperties" => collect_unsupported_keywords(value, found),
+            keyword
+                if VALIDATED_KEYWORDS.contains(&keyword)
+                    || ANNOTATION_KEYWORDS.contains(&keyword) => {}
+            keyword => found.push(keyword.to_string()),
+        }
+    }
+}
+
+fn strip_code_fence(message: &str) -> &str {
+    let trimmed = message.trim();
+    trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|body| {
+            // Drop the info string (e.g. `json`) on the opening fence line.
+            body.split_once('\n').map_or(body, |(_, code)| code).trim()
+        })
+        .unwrap_or(trimmed)
+}
+
+/// Returns one message per violation; empty when `instance` matches.
+pub(crate) fn validate(schema: &Value, instance: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(schema, schema, instance, "$", &mut errors);
+    errors
+}
+
+fn validate_at(
+    root: &Value,
+    schema: &Value,
+    instance: &Value,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let Some(schema) = schema.as_object() else {
+        // `true`/`false` schemas.
+        if schema == &Value::Bool(false) {
+            errors.push(format!("{path}: no value is allowed here"));
+        }
+        return;
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+        {
+            Some(target) => validate_at(root, target, instance, path, errors),
+            None => errors.push(format!("{path}: unresolvable $ref `{reference}`")),
+        }
+        return;
+    }
+
+    if let Some(any_of) = schema.get("anyOf").and_then(Value::as_array)
+        && !any_of.iter().any(|option| {
+            let mut option_errors = Vec::new();
+            validate_at(root, option, instance, path, &mut option_errors);
+            option_errors.is_empty()
+        })
+    {
+        errors.push(format!("{path}: does not match any of the allowed schemas"));
+        return;
+    }
+
+    if let Some(expected) = schema.get("type")
+        && !type_matches(expected, instance)
+    {
+        let expected = expected
+            .as_str()
+            .map_or_else(|| expected.to_string(), str::to_string);
+        errors.push(format!(
+            "{path}: expected type {expected}, found {}",
+            type_name(instance)
+        ));
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(instance)
+    {
+        errors.push(format!(
+            "{path}: {instance} is not one of {}",
+            Value::from(allowed.clone())
+        ));
+    }
+
+    if let Some(expected) = schema.get("const")
+        && expected != instance
+    {
+        errors.push(format!("{path}: expected {expected}, found {instance}"));
+    }
+
+    match instance {
+        Value::Object(object) => validate_object(root, schema, object, path, errors),
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (idx, item) in items.iter().enumerate() {
+                    validate_at(root, item_schema, item, &format!("{path}[{idx}]"), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_object(
+    root: &Value,
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for key in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(key) {
+                errors.push(format!("{path}: missing required property `{key}`"));
+            }
+        }
+    }
+
+    for (key, value) in object {
+        let child_path = format!("{path}.{key}");
+        match properties.and_then(|properties| properties.get(key)) {
+            Some(property_schema) => validate_at(root, property_schema, value, &child_path, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(format!("{path}: unexpected property `{key}`"));
+                }
+                Some(additional) => validate_at(root, additional, value, &child_path, errors),
+                None => {}
+            },
+        }
+    }
+}
+
+fn type_matches(expected: &Value, instance: &Value) -> bool {
+    match expected {
+        Value::String(name) => type_name_matches(name, instance),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| type_name_matches(name, instance)),
+        _ => true,
+    }
+}
+
+fn type_name_matches(name: &str, instance: &Value) -> bool {
+    match name {
+        "integer" => instance.as_i64().is_some() || instance.as_u64().is_some(),
+        "number" => instance.is_number(),
+        other => type_name(instance) == other,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "status": { "type": "string", "enum": ["pass", "fail"] },
+                "failures": { "type": "array", "items": { "$ref": "#/$defs/failure" } },
+                "note": { "type": ["string", "null"] },
+            },
+            "required": ["status", "failures"],
+            "additionalProperties": false,
+            "$defs": {
+                "failure": {
+                    "type": "object",
+                    "properties": { "test": { "type": "string" }, "line": { "type": "integer" } },
+                    "required": ["test"],
+                },
+            },
+        })
+    }
+
+    #[test]
+    fn accepts_matching_output_in_code_fence() {
+        let message = "```json\n{\"status\": \"fail\", \"failures\": [{\"test\": \"a\", \"line\": 3}], \"note\": null}\n```";
+
+        assert_eq!(
+            parse_structured_output(&schema(), Some(message)),
+            Ok(json!({
+                "status": "fail",
+                "failures": [{ "test": "a", "line": 3 }],
+                "note": null,
+            }))
+        );
+    }
+
+    #[test]
+    fn reports_each_violation() {
+        let instance = json!({
+            "status": "flaky",
+            "failures": [{ "line": 1.5 }],
+            "extra": true,
+        });
+
+        // Property order depends on serde_json features, so compare sorted.
+        let mut errors = validate(&schema(), &instance);
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "$.failures[0].line: expected type integer, found number".to_string(),
+                "$.failures[0]: missing required property `test`".to_string(),
+                "$.status: \"flaky\" is not one of [\"pass\",\"fail\"]".to_string(),
+                "$: unexpected property `extra`".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_keywords_that_are_not_validated() {
+        let report_schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "description": "test report",
+            "properties": {
+                "name": { "type": "string", "pattern": "^[a-z]+$", "minLength": 1 },
+                "tags": { "type": "array", "items": { "format": "uuid" }, "minItems": 1 },
+                "count": { "anyOf": [{ "type": "integer", "minimum": 0 }, { "type": "null" }] },
+            },
+            "$defs": { "unused": { "oneOf": [] } },
+            "additionalProperties": false,
+        });
+
+        assert_eq!(
+            unsupported_keywords(&report_schema),
+            vec![
+                "format",
+                "minItems",
+                "minLength",
+                "minimum",
+                "oneOf",
+                "pattern"
+            ]
+        );
+        assert_eq!(unsupported_keywords(&schema()), Vec::<String>::new());
+        // Unsupported keywords are ignored rather than enforced.
+        assert_eq!(
+            validate(
+                &report_schema,
+                &json!({ "name": "NOT lowercase", "tags": [] })
+            ),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn rejects_non_json_message() {
+        let err = parse_structured_output(&schema(), Some("All tests passed!"))
+            .expect_err("plain text should be rejected");
+        assert!(err.starts_with("the final agent message is not valid JSON"));
+    }
+}
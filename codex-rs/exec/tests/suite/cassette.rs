@@ -0,0 +1,106 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use assert_cmd::prelude::*;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use std::path::Path;
+use std::process::Command;
+use std::process::Output;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const SSE_ASSISTANT_REPLY: &str = r#"[
+  {
+    "type": "response.output_item.done",
+    "item": {
+      "type": "message",
+      "role": "assistant",
+      "content": [{ "type": "output_text", "text": "recorded reply" }]
+    }
+  },
+  {
+    "type": "response.completed",
+    "response": { "id": "__ID__", "output": [] }
+  }
+]"#;
+
+/// Nothing listens here, so a replay that reaches the network fails.
+const UNREACHABLE_BASE_URL: &str = "http://127.0.0.1:9/v1";
+
+fn run_exec(home: &Path, base_url: &str, args: &[&str]) -> Output {
+    Command::cargo_bin("codex-exec")
+        .expect("should find binary for codex-exec")
+        .current_dir(home)
+        .env("CODEX_HOME", home)
+        .env("OPENAI_API_KEY", "dummy")
+        .env("OPENAI_BASE_URL", base_url)
+        .arg("--skip-git-repo-check")
+        .args(args)
+        .output()
+        .expect("run codex-exec")
+}
+
+async fn record(home: &Path, cassette: &Path, prompt: &str) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(SSE_ASSISTANT_REPLY, "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let output = run_exec(
+        home,
+        &format!("{}/v1", server.uri()),
+        &["--record", cassette.to_str().unwrap(), prompt],
+    );
+    assert!(output.status.success(), "recording failed: {output:?}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn replay_serves_recorded_responses_offline() {
+    let home = TempDir::new().unwrap();
+    let cassette = home.path().join("cassette");
+    record(home.path(), &cassette, "say something").await;
+
+    assert!(cassette.join("0001.request.json").is_file());
+    assert!(cassette.join("0001.sse").is_file());
+
+    let output = run_exec(
+        home.path(),
+        UNREACHABLE_BASE_URL,
+        &["--replay", cassette.to_str().unwrap(), "say something"],
+    );
+    assert!(output.status.success(), "replay failed: {output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("recorded reply"), "missing reply: {stdout}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn replay_fails_when_prompt_changes() {
+    let home = TempDir::new().unwrap();
+    let cassette = home.path().join("cassette");
+    record(home.path(), &cassette, "say something").await;
+
+    let output = run_exec(
+        home.path(),
+        UNREACHABLE_BASE_URL,
+        &["--replay", cassette.to_str().unwrap(), "say something else"],
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("cassette replay failed: request 1 does not match"),
+        "unexpected output: {stdout}"
+    );
+}
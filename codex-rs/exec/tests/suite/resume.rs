@@ -0,0 +1,134 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use assert_cmd::prelude::*;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const SSE_ASSISTANT_REPLY: &str = r#"[
+  {
+    "type": "response.output_item.done",
+    "item": {
+      "type": "message",
+      "role": "assistant",
+      "content": [{ "type": "output_text", "text": "done" }]
+    }
+  },
+  {
+    "type": "response.completed",
+    "response": { "id": "__ID__", "output": [] }
+  }
+]"#;
+
+async fn start_mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(SSE_ASSISTANT_REPLY, "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Runs `codex-exec --json <args>` and returns its stdout.
+fn run_exec(home: &Path, server: &MockServer, args: &[&str]) -> String {
+    let output = Command::cargo_bin("codex-exec")
+        .expect("should find binary for codex-exec")
+        .current_dir(home)
+        .env("CODEX_HOME", home)
+        .env("OPENAI_API_KEY", "dummy")
+        .env("OPENAI_BASE_URL", format!("{}/v1", server.uri()))
+        .arg("--skip-git-repo-check")
+        .arg("--json")
+        .args(args)
+        .output()
+        .expect("run codex-exec");
+    assert!(output.status.success(), "codex-exec failed: {output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn session_id(stdout: &str) -> String {
+    stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find(|event| event["msg"]["type"] == "session_configured")
+        .and_then(|event| event["msg"]["session_id"].as_str().map(str::to_string))
+        .expect("session id printed at start")
+}
+
+async fn last_request_body(server: &MockServer) -> String {
+    let requests = server.received_requests().await.unwrap();
+    String::from_utf8(requests.last().unwrap().body.clone()).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resume_by_id_continues_previous_session() {
+    let home = TempDir::new().unwrap();
+    let server = start_mock_server().await;
+
+    let first_id = session_id(&run_exec(home.path(), &server, &["first prompt"]));
+    let resumed_id = session_id(&run_exec(
+        home.path(),
+        &server,
+        &["resume", &first_id, "second prompt"],
+    ));
+
+    assert_eq!(resumed_id, first_id);
+    let body = last_request_body(&server).await;
+    assert!(body.contains("first prompt"), "history missing: {body}");
+    assert!(body.contains("second prompt"), "follow-up missing: {body}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resume_last_picks_most_recent_session() {
+    let home = TempDir::new().unwrap();
+    let server = start_mock_server().await;
+
+    run_exec(home.path(), &server, &["older prompt"]);
+    // Rollout file names have one-second resolution.
+    std::thread::sleep(Duration::from_millis(1100));
+    let newest_id = session_id(&run_exec(home.path(), &server, &["newest prompt"]));
+    let resumed_id = session_id(&run_exec(
+        home.path(),
+        &server,
+        &["resume", "--last", "follow-up prompt"],
+    ));
+
+    assert_eq!(resumed_id, newest_id);
+    let body = last_request_body(&server).await;
+    assert!(body.contains("newest prompt"), "history missing: {body}");
+    assert!(!body.contains("older prompt"), "wrong session: {body}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resume_unknown_id_fails() {
+    let home = TempDir::new().unwrap();
+    let server = start_mock_server().await;
+
+    Command::cargo_bin("codex-exec")
+        .expect("should find binary for codex-exec")
+        .current_dir(home.path())
+        .env("CODEX_HOME", home.path())
+        .env("OPENAI_API_KEY", "dummy")
+        .env("OPENAI_BASE_URL", format!("{}/v1", server.uri()))
+        .arg("--skip-git-repo-check")
+        .args(["resume", "00000000-0000-0000-0000-000000000000", "prompt"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("No recorded session found"));
+}
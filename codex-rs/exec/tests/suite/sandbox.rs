@@ -56,8 +56,10 @@ async fn python_multiprocessing_lock_works_under_sandbox() {
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots,
         network_access: false,
+        network_allowlist: Vec::new(),
         exclude_tmpdir_env_var: false,
         exclude_slash_tmp: false,
+        readable_roots: None,
     };
 
     let python_code = r#"import multiprocessing
@@ -169,7 +171,7 @@ fn unix_sock_body() {
 async fn allow_unix_socketpair_recvfrom() {
     run_code_under_sandbox(
         "allow_unix_socketpair_recvfrom",
-        &SandboxPolicy::ReadOnly,
+        &SandboxPolicy::new_read_only_policy(),
         || async { unix_sock_body() },
     )
     .await
@@ -39,7 +39,12 @@ pub use valid_exec::ValidExec;
 
 const DEFAULT_POLICY: &str = include_str!("default.policy");
 
+/// Parser for the policy that ships with this crate. Combine it with other
+/// parsers via [`PolicyParser::parse_all`] to extend the default policy.
+pub fn default_policy_parser() -> PolicyParser {
+    PolicyParser::new("#default", DEFAULT_POLICY)
+}
+
 pub fn get_default_policy() -> starlark::Result<Policy> {
-    let parser = PolicyParser::new("#default", DEFAULT_POLICY);
-    parser.parse()
+    default_policy_parser().parse()
 }
@@ -35,6 +35,23 @@ impl PolicyParser {
     }
 
     pub fn parse(&self) -> starlark::Result<Policy> {
+        Self::parse_all(std::slice::from_ref(self))
+    }
+
+    /// Evaluates each policy in order against a single shared builder so that
+    /// later policies extend, rather than replace, the ones before them.
+    /// Program specs for the same program accumulate and are tried in the
+    /// order they were defined; forbidden regexes and substrings are unioned.
+    pub fn parse_all(parsers: &[PolicyParser]) -> starlark::Result<Policy> {
+        let policy_builder = PolicyBuilder::new();
+        for parser in parsers {
+            parser.eval_into(&policy_builder)?;
+        }
+        let policy = policy_builder.build();
+        policy.map_err(|e| starlark::Error::new_kind(starlark::ErrorKind::Other(e.into())))
+    }
+
+    fn eval_into(&self, policy_builder: &PolicyBuilder) -> starlark::Result<()> {
         let mut dialect = Dialect::Extended.clone();
         dialect.enable_f_strings = true;
         let ast = AstModule::parse(&self.policy_source, self.unparsed_policy.clone(), &dialect)?;
@@ -60,14 +77,10 @@ impl PolicyParser {
             heap.alloc(ArgMatcher::UnverifiedVarargs),
         );
 
-        let policy_builder = PolicyBuilder::new();
-        {
-            let mut eval = Evaluator::new(&module);
-            eval.extra = Some(&policy_builder);
-            eval.eval_module(ast, &globals)?;
-        }
-        let policy = policy_builder.build();
-        policy.map_err(|e| starlark::Error::new_kind(starlark::ErrorKind::Other(e.into())))
+        let mut eval = Evaluator::new(&module);
+        eval.extra = Some(policy_builder);
+        eval.eval_module(ast, &globals)?;
+        Ok(())
     }
 }
 
@@ -220,7 +233,7 @@ fn policy_builtins(builder: &mut GlobalsBuilder) {
         ))
     }
 
-    fn flag(name: String) -> anyhow::Result<Opt> {
-        Ok(Opt::new(name, OptMeta::Flag, false))
+    fn flag(name: String, required: Option<bool>) -> anyhow::Result<Opt> {
+        Ok(Opt::new(name, OptMeta::Flag, required.unwrap_or(false)))
     }
 }
@@ -160,10 +160,11 @@ impl ProgramSpec {
         let matched_args =
             resolve_observed_args_with_patterns(&self.program, args, &self.arg_patterns)?;
 
-        // Verify all required options are present.
+        // Verify all required options (and required flags) are present.
         let matched_opt_names: HashSet<String> = matched_opts
             .iter()
             .map(|opt| opt.name().to_string())
+            .chain(matched_flags.iter().map(|flag| flag.name.clone()))
             .collect();
         if !matched_opt_names.is_superset(&self.required_options) {
             let mut options = self
@@ -0,0 +1,108 @@
+extern crate codex_execpolicy;
+
+use codex_execpolicy::Error;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::ValidExec;
+use codex_execpolicy::default_policy_parser;
+
+#[test]
+fn test_extension_adds_programs_to_default_policy() {
+    let extension = PolicyParser::new(
+        "#extension",
+        r#"
+define_program(
+    program="true",
+)
+"#,
+    );
+    let policy = PolicyParser::parse_all(&[default_policy_parser(), extension])
+        .expect("failed to parse policies");
+
+    // Programs from the default policy are still matched.
+    assert!(matches!(
+        policy.check(&ExecCall::new("pwd", &[])),
+        Ok(MatchedExec::Match { .. })
+    ));
+    // Programs from the extension are matched as well.
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec {
+                program: "true".into(),
+                ..Default::default()
+            }
+        }),
+        policy.check(&ExecCall::new("true", &[]))
+    );
+}
+
+#[test]
+fn test_extension_can_forbid_program() {
+    let extension = PolicyParser::new(
+        "#extension",
+        r#"
+forbid_program_regex(
+    regex="^pwd$",
+    reason="use `echo $PWD` instead",
+)
+"#,
+    );
+    let policy = PolicyParser::parse_all(&[default_policy_parser(), extension])
+        .expect("failed to parse policies");
+
+    let pwd = ExecCall::new("pwd", &[]);
+    match policy.check(&pwd) {
+        Ok(MatchedExec::Forbidden { reason, .. }) => {
+            assert_eq!("use `echo $PWD` instead", reason);
+        }
+        other => panic!("expected pwd to be forbidden, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_all_with_no_policies_matches_nothing() {
+    let policy = PolicyParser::parse_all(&[]).expect("empty policy should parse");
+    assert_eq!(
+        Err(Error::NoSpecForProgram {
+            program: "ls".into()
+        }),
+        policy.check(&ExecCall::new("ls", &[]))
+    );
+}
+
+#[test]
+fn test_required_flag_forbids_only_matching_invocations() {
+    let extension = PolicyParser::new(
+        "#extension",
+        r#"
+define_program(
+    program="git",
+    options=[flag("--force", required=True)],
+    args=["push", ARG_UNVERIFIED_VARARGS],
+    forbidden="force-pushing is not allowed",
+)
+
+define_program(
+    program="git",
+    args=["push", ARG_UNVERIFIED_VARARGS],
+)
+"#,
+    );
+    let policy = PolicyParser::parse_all(&[default_policy_parser(), extension])
+        .expect("failed to parse policies");
+
+    match policy.check(&ExecCall::new(
+        "git",
+        &["push", "--force", "origin", "main"],
+    )) {
+        Ok(MatchedExec::Forbidden { reason, .. }) => {
+            assert_eq!("force-pushing is not allowed", reason);
+        }
+        other => panic!("expected force push to be forbidden, got {other:?}"),
+    }
+    assert!(matches!(
+        policy.check(&ExecCall::new("git", &["push", "origin", "main"])),
+        Ok(MatchedExec::Match { .. })
+    ));
+}
@@ -6,12 +6,15 @@ use codex_core::error::CodexErr;
 use codex_core::error::Result;
 use codex_core::error::SandboxErr;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR;
 
 use landlock::ABI;
 use landlock::Access;
 use landlock::AccessFs;
+use landlock::AccessNet;
 use landlock::CompatLevel;
 use landlock::Compatible;
+use landlock::NetPort;
 use landlock::Ruleset;
 use landlock::RulesetAttr;
 use landlock::RulesetCreatedAttr;
@@ -32,7 +35,19 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
     cwd: &Path,
 ) -> Result<()> {
     if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
+        // Without a proxy port (e.g. the proxy failed to start), an allowlist
+        // policy falls back to blocking the network entirely.
+        let network_proxy_port = if sandbox_policy.network_allowlist().is_empty() {
+            None
+        } else {
+            std::env::var(CODEX_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR)
+                .ok()
+                .and_then(|port| port.parse::<u16>().ok())
+        };
+        install_network_seccomp_filter_on_current_thread(network_proxy_port.is_some())?;
+        if let Some(port) = network_proxy_port {
+            install_network_landlock_rules_on_current_thread(port)?;
+        }
     }
 
     if !sandbox_policy.has_full_disk_write_access() {
@@ -41,31 +56,38 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
             .into_iter()
             .map(|writable_root| writable_root.root)
             .collect();
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
+        let readable_roots = sandbox_policy.get_readable_roots_with_cwd(cwd);
+        install_filesystem_landlock_rules_on_current_thread(readable_roots, writable_roots)?;
     }
 
-    // TODO(ragona): Add appropriate restrictions if
-    // `sandbox_policy.has_full_disk_read_access()` is `false`.
-
     Ok(())
 }
 
-/// Installs Landlock file-system rules on the current thread allowing read
-/// access to the entire file-system while restricting write access to
-/// `/dev/null` and the provided list of `writable_roots`.
+/// Installs Landlock file-system rules on the current thread restricting
+/// write access to `/dev/null` and the provided list of `writable_roots`.
+/// Read access is granted to the entire file-system when `readable_roots` is
+/// `None`, and otherwise only beneath `readable_roots` and `writable_roots`.
 ///
 /// # Errors
 /// Returns [`CodexErr::Sandbox`] variants when the ruleset fails to apply.
-fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathBuf>) -> Result<()> {
+fn install_filesystem_landlock_rules_on_current_thread(
+    readable_roots: Option<Vec<PathBuf>>,
+    writable_roots: Vec<PathBuf>,
+) -> Result<()> {
     let abi = ABI::V5;
     let access_rw = AccessFs::from_all(abi);
     let access_ro = AccessFs::from_read(abi);
 
+    // Roots that do not exist cannot be opened to create a rule for them.
+    let readable_roots: Vec<PathBuf> = match readable_roots {
+        Some(roots) => roots.into_iter().filter(|root| root.exists()).collect(),
+        None => vec![PathBuf::from("/")],
+    };
     let mut ruleset = Ruleset::default()
         .set_compatibility(CompatLevel::BestEffort)
         .handle_access(access_rw)?
         .create()?
-        .add_rules(landlock::path_beneath_rules(&["/"], access_ro))?
+        .add_rules(landlock::path_beneath_rules(&readable_roots, access_ro))?
         .add_rules(landlock::path_beneath_rules(&["/dev/null"], access_rw))?
         .set_no_new_privs(true);
 
@@ -82,9 +104,34 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
+/// Installs Landlock network rules on the current thread so that TCP
+/// connections can only be made to `proxy_port` and no TCP port can be bound.
+///
+/// Landlock cannot filter by address, so this relies on the proxy listening on
+/// an ephemeral loopback port. Network rules need Landlock ABI v4 (Linux 6.7);
+/// older kernels fail here rather than silently allowing all of TCP.
+fn install_network_landlock_rules_on_current_thread(proxy_port: u16) -> Result<()> {
+    let status = Ruleset::default()
+        .set_compatibility(CompatLevel::HardRequirement)
+        .handle_access(AccessNet::from_all(ABI::V4))?
+        .create()?
+        .add_rule(NetPort::new(proxy_port, AccessNet::ConnectTcp))?
+        .set_no_new_privs(true)
+        .restrict_self()?;
+
+    if status.ruleset != landlock::RulesetStatus::FullyEnforced {
+        return Err(CodexErr::Sandbox(SandboxErr::LandlockRestrict));
+    }
+
+    Ok(())
+}
+
 /// Installs a seccomp filter that blocks outbound network access except for
-/// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+/// AF_UNIX domain sockets. When `allow_tcp` is set, TCP sockets may also be
+/// created and used; Landlock then limits which ports they may connect to.
+fn install_network_seccomp_filter_on_current_thread(
+    allow_tcp: bool,
+) -> std::result::Result<(), SandboxErr> {
     // Build rule map.
     let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
@@ -93,24 +140,26 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         rules.insert(nr, vec![]); // empty rule vec = unconditional match
     };
 
-    deny_syscall(libc::SYS_connect);
-    deny_syscall(libc::SYS_accept);
-    deny_syscall(libc::SYS_accept4);
-    deny_syscall(libc::SYS_bind);
-    deny_syscall(libc::SYS_listen);
-    deny_syscall(libc::SYS_getpeername);
-    deny_syscall(libc::SYS_getsockname);
-    deny_syscall(libc::SYS_shutdown);
-    deny_syscall(libc::SYS_sendto);
-    deny_syscall(libc::SYS_sendmsg);
-    deny_syscall(libc::SYS_sendmmsg);
-    // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
-    // with their socketpair + child processes for sub-proc management
-    // deny_syscall(libc::SYS_recvfrom);
-    deny_syscall(libc::SYS_recvmsg);
-    deny_syscall(libc::SYS_recvmmsg);
-    deny_syscall(libc::SYS_getsockopt);
-    deny_syscall(libc::SYS_setsockopt);
+    if !allow_tcp {
+        deny_syscall(libc::SYS_connect);
+        deny_syscall(libc::SYS_accept);
+        deny_syscall(libc::SYS_accept4);
+        deny_syscall(libc::SYS_bind);
+        deny_syscall(libc::SYS_listen);
+        deny_syscall(libc::SYS_getpeername);
+        deny_syscall(libc::SYS_getsockname);
+        deny_syscall(libc::SYS_shutdown);
+        deny_syscall(libc::SYS_sendto);
+        deny_syscall(libc::SYS_sendmsg);
+        deny_syscall(libc::SYS_sendmmsg);
+        // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
+        // with their socketpair + child processes for sub-proc management
+        // deny_syscall(libc::SYS_recvfrom);
+        deny_syscall(libc::SYS_recvmsg);
+        deny_syscall(libc::SYS_recvmmsg);
+        deny_syscall(libc::SYS_getsockopt);
+        deny_syscall(libc::SYS_setsockopt);
+    }
     deny_syscall(libc::SYS_ptrace);
 
     // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
@@ -121,7 +170,24 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         libc::AF_UNIX as u64,
     )?])?;
 
-    rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
+    if allow_tcp {
+        rules.insert(libc::SYS_socket, tcp_or_unix_only_rules()?);
+        // TCP Fast Open connects from within sendto/sendmsg; make clients
+        // fall back to connect(2), which Landlock checks.
+        let fastopen_rule = |flags_arg: u8| -> std::result::Result<SeccompRule, SandboxErr> {
+            Ok(SeccompRule::new(vec![SeccompCondition::new(
+                flags_arg,
+                SeccompCmpArgLen::Dword,
+                SeccompCmpOp::MaskedEq(libc::MSG_FASTOPEN as u64),
+                libc::MSG_FASTOPEN as u64,
+            )?])?)
+        };
+        rules.insert(libc::SYS_sendto, vec![fastopen_rule(3)?]);
+        rules.insert(libc::SYS_sendmsg, vec![fastopen_rule(2)?]);
+        rules.insert(libc::SYS_sendmmsg, vec![fastopen_rule(3)?]);
+    } else {
+        rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
+    }
     rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
 
     let filter = SeccompFilter::new(
@@ -143,3 +209,51 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
 
     Ok(())
 }
+
+/// Rules for `socket` that match (and therefore deny) everything except
+/// AF_UNIX sockets and plain TCP sockets. UDP, raw and MPTCP sockets are
+/// rejected because Landlock only restricts TCP connections.
+fn tcp_or_unix_only_rules() -> std::result::Result<Vec<SeccompRule>, SandboxErr> {
+    let domain_cond = |op: SeccompCmpOp, domain: i32| {
+        SeccompCondition::new(0, SeccompCmpArgLen::Dword, op, domain as u64)
+    };
+
+    let mut rules = vec![SeccompRule::new(vec![
+        domain_cond(SeccompCmpOp::Ne, libc::AF_UNIX)?,
+        domain_cond(SeccompCmpOp::Ne, libc::AF_INET)?,
+        domain_cond(SeccompCmpOp::Ne, libc::AF_INET6)?,
+    ])?];
+
+    for domain in [libc::AF_INET, libc::AF_INET6] {
+        // Deny any type other than SOCK_STREAM (with or without flags).
+        let mut not_stream = vec![domain_cond(SeccompCmpOp::Eq, domain)?];
+        for flags in [
+            0,
+            libc::SOCK_NONBLOCK,
+            libc::SOCK_CLOEXEC,
+            libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+        ] {
+            not_stream.push(SeccompCondition::new(
+                1, // second argument (type)
+                SeccompCmpArgLen::Dword,
+                SeccompCmpOp::Ne,
+                (libc::SOCK_STREAM | flags) as u64,
+            )?);
+        }
+        rules.push(SeccompRule::new(not_stream)?);
+
+        // Deny stream protocols other than TCP, such as MPTCP or SCTP.
+        rules.push(SeccompRule::new(vec![
+            domain_cond(SeccompCmpOp::Eq, domain)?,
+            SeccompCondition::new(2, SeccompCmpArgLen::Dword, SeccompCmpOp::Ne, 0)?,
+            SeccompCondition::new(
+                2, // third argument (protocol)
+                SeccompCmpArgLen::Dword,
+                SeccompCmpOp::Ne,
+                libc::IPPROTO_TCP as u64,
+            )?,
+        ])?);
+    }
+
+    Ok(rules)
+}
@@ -3,13 +3,23 @@ use codex_core::config_types::ShellEnvironmentPolicy;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
 use codex_core::exec::ExecParams;
+use codex_core::exec::ExecToolCallOutput;
 use codex_core::exec::SandboxType;
 use codex_core::exec::process_exec_tool_call;
 use codex_core::exec_env::create_env;
 use codex_core::protocol::SandboxPolicy;
+use landlock::ABI;
+use landlock::AccessNet;
+use landlock::CompatLevel;
+use landlock::Compatible;
+use landlock::Ruleset;
+use landlock::RulesetAttr;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use tempfile::NamedTempFile;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
 
 // At least on GitHub CI, the arm64 tests appear to need longer timeouts.
 
@@ -30,37 +40,44 @@ const NETWORK_TIMEOUT_MS: u64 = 10_000;
 
 fn create_env_from_core_vars() -> HashMap<String, String> {
     let policy = ShellEnvironmentPolicy::default();
-    create_env(&policy)
+    create_env(&policy, &SandboxPolicy::new_read_only_policy())
 }
 
-#[expect(clippy::print_stdout, clippy::expect_used, clippy::unwrap_used)]
 async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
-    let params = ExecParams {
-        command: cmd.iter().map(|elm| elm.to_string()).collect(),
-        cwd: std::env::current_dir().expect("cwd should exist"),
-        timeout_ms: Some(timeout_ms),
-        env: create_env_from_core_vars(),
-        with_escalated_permissions: None,
-        justification: None,
-    };
-
     let sandbox_policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: writable_roots.to_vec(),
         network_access: false,
+        network_allowlist: Vec::new(),
         // Exclude tmp-related folders from writable roots because we need a
         // folder that is writable by tests but that we intentionally disallow
         // writing to in the sandbox.
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
+        readable_roots: None,
     };
+    run_cmd_with_policy(cmd, &sandbox_policy, timeout_ms).await;
+}
+
+#[expect(clippy::print_stdout, clippy::expect_used, clippy::unwrap_used)]
+async fn run_cmd_with_policy(cmd: &[&str], sandbox_policy: &SandboxPolicy, timeout_ms: u64) {
+    let params = ExecParams {
+        command: cmd.iter().map(|elm| elm.to_string()).collect(),
+        cwd: std::env::current_dir().expect("cwd should exist"),
+        timeout_ms: Some(timeout_ms),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+
     let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
     let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
     let res = process_exec_tool_call(
         params,
         SandboxType::LinuxSeccomp,
-        &sandbox_policy,
+        sandbox_policy,
         &codex_linux_sandbox_exe,
         None,
+        None,
     )
     .await
     .unwrap();
@@ -120,6 +137,38 @@ async fn test_writable_root() {
     .await;
 }
 
+#[tokio::test]
+async fn test_readable_root_read() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let file_path = tmpdir.path().join("notes.txt");
+    std::fs::write(&file_path, "hello").unwrap();
+    let policy = SandboxPolicy::new_read_only_policy()
+        .with_readable_roots(Some(vec![tmpdir.path().to_path_buf()]));
+    run_cmd_with_policy(
+        &["cat", &file_path.to_string_lossy()],
+        &policy,
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+#[tokio::test]
+#[should_panic]
+async fn test_unlisted_root_read() {
+    let readable = tempfile::tempdir().unwrap();
+    let secret_dir = tempfile::tempdir().unwrap();
+    let secret_path = secret_dir.path().join("secret.txt");
+    std::fs::write(&secret_path, "hunter2").unwrap();
+    let policy = SandboxPolicy::new_read_only_policy()
+        .with_readable_roots(Some(vec![readable.path().to_path_buf()]));
+    run_cmd_with_policy(
+        &["cat", &secret_path.to_string_lossy()],
+        &policy,
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
 #[tokio::test]
 #[should_panic(expected = "Sandbox(Timeout)")]
 async fn test_timeout() {
@@ -153,6 +202,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         &sandbox_policy,
         &codex_linux_sandbox_exe,
         None,
+        None,
     )
     .await;
 
@@ -228,3 +278,101 @@ async fn sandbox_blocks_dev_tcp_redirection() {
     // all images ship bash, so we guard against 127 as well.
     assert_network_blocked(&["bash", "-c", "echo hi > /dev/tcp/127.0.0.1/80"]).await;
 }
+
+/// Whether the kernel supports the Landlock network rules that
+/// `network_allowlist` relies on (Linux 6.7 or newer).
+fn landlock_network_supported() -> bool {
+    Ruleset::default()
+        .set_compatibility(CompatLevel::HardRequirement)
+        .handle_access(AccessNet::from_all(ABI::V4))
+        .and_then(|ruleset| ruleset.create())
+        .is_ok()
+}
+
+#[expect(clippy::expect_used)]
+async fn run_curl_with_allowlist(
+    url: &str,
+    network_allowlist: Vec<String>,
+) -> Result<ExecToolCallOutput, CodexErr> {
+    let sandbox_policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: Vec::new(),
+        network_access: false,
+        network_allowlist,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        readable_roots: None,
+    };
+    let params = ExecParams {
+        command: ["curl", "-sf", "--max-time", "5", url]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        cwd: std::env::current_dir().expect("cwd should exist"),
+        timeout_ms: Some(NETWORK_TIMEOUT_MS * 5),
+        // Starts the proxy and points curl at it.
+        env: create_env(&ShellEnvironmentPolicy::default(), &sandbox_policy),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        &codex_linux_sandbox_exe,
+        None,
+        None,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[expect(clippy::print_stdout, clippy::unwrap_used)]
+async fn sandbox_network_allowlist_allows_only_listed_hosts() {
+    let curl_available = std::process::Command::new("curl")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !landlock_network_supported() || !curl_available {
+        println!("Skipping test: needs curl and Landlock network rules (Linux 6.7+).");
+        return;
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request).await;
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello")
+                    .await;
+            });
+        }
+    });
+
+    // `localhost` is on the allowlist, so the proxy forwards the request.
+    let output = run_curl_with_allowlist(
+        &format!("http://localhost:{port}/"),
+        vec!["localhost".to_string()],
+    )
+    .await
+    .unwrap();
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout.text, "hello");
+
+    // The same server under a name that is not listed is refused by the
+    // proxy (curl -f exits 22 on the 403).
+    let result = run_curl_with_allowlist(
+        &format!("http://127.0.0.1:{port}/"),
+        vec!["localhost".to_string()],
+    )
+    .await;
+    match result {
+        Err(CodexErr::Sandbox(SandboxErr::Denied(exit_code, _, _))) => assert_eq!(exit_code, 22),
+        other => panic!("expected the request to be refused, got: {other:?}"),
+    }
+}
@@ -0,0 +1,460 @@
+//! HTTP transports for [`crate::McpClient`].
+//!
+//! Two flavors are supported:
+//!
+//! * [Streamable HTTP]: every JSON-RPC message is `POST`ed to a single
+//!   endpoint and the server answers either with a JSON body or with an SSE
+//!   stream that carries the response (and possibly notifications).
+//! * The legacy [HTTP+SSE] transport: the client opens an SSE stream whose
+//!   first `endpoint` event tells it where to `POST` messages; all responses
+//!   arrive on the stream.
+//!
+//! In both cases the transport plugs into the same `outgoing_tx` / pending map
+//! machinery that the stdio transport uses.
+//!
+//! [Streamable HTTP]: https://modelcontextprotocol.io/specification/2025-06-18/basic/transports#streamable-http
+//! [HTTP+SSE]: https://modelcontextprotocol.io/specification/2024-11-05/basic/transports#http-with-sse
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::JSONRPCMessage;
+use mcp_types::MCP_SCHEMA_VERSION;
+use reqwest::Response;
+use reqwest::StatusCode;
+use reqwest::Url;
+use reqwest::header::ACCEPT;
+use reqwest::header::AUTHORIZATION;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+
+use crate::mcp_client::PendingRequests;
+use crate::mcp_client::ServerStatus;
+use crate::mcp_client::SharedServerStatus;
+use crate::mcp_client::dispatch_error;
+use crate::mcp_client::handle_incoming_message;
+use crate::mcp_client::mark_closed;
+
+/// Header used by Streamable HTTP servers to identify a session.
+const MCP_SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// Header that carries the negotiated protocol version on every request.
+const MCP_PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// JSON-RPC error code reported to callers when the HTTP request itself fails.
+const TRANSPORT_ERROR_CODE: i64 = -32000;
+
+/// Build the HTTP client shared by every request to one server. `headers` and
+/// the optional bearer token are attached to every request.
+pub(crate) fn build_http_client(
+    headers: &HashMap<String, String>,
+    bearer_token: Option<&str>,
+) -> Result<reqwest::Client> {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("invalid HTTP header name `{name}`"))?;
+        let value = HeaderValue::from_str(value)
+            .with_context(|| format!("invalid value for HTTP header `{name}`"))?;
+        header_map.insert(name, value);
+    }
+    if let Some(token) = bearer_token {
+        let mut value = HeaderValue::from_str(&format!("Bearer {token}"))
+            .context("invalid bearer token for MCP server")?;
+        value.set_sensitive(true);
+        header_map.insert(AUTHORIZATION, value);
+    }
+    header_map.insert(
+        HeaderName::from_static(MCP_PROTOCOL_VERSION_HEADER),
+        HeaderValue::from_static(MCP_SCHEMA_VERSION),
+    );
+
+    Ok(reqwest::Client::builder()
+        .default_headers(header_map)
+        .build()?)
+}
+
+/// Spawn the task that `POST`s every outgoing message to a Streamable HTTP
+/// endpoint and feeds the replies back into `pending`.
+pub(crate) fn spawn_streamable_http_writer(
+    http_client: reqwest::Client,
+    url: Url,
+    mut outgoing_rx: mpsc::Receiver<JSONRPCMessage>,
+    pending: PendingRequests,
+    status: SharedServerStatus,
+) {
+    tokio::spawn(async move {
+        // Assigned by the server in its reply to `initialize`, if at all.
+        let mut session_id: Option<HeaderValue> = None;
+
+        while let Some(msg) = outgoing_rx.recv().await {
+            debug!("MCP message to server: {msg:?}");
+            let mut request = http_client
+                .post(url.clone())
+                .header(ACCEPT, "application/json, text/event-stream")
+                .json(&msg);
+            if let Some(session_id) = &session_id {
+                request = request.header(MCP_SESSION_ID_HEADER, session_id.clone());
+            }
+
+            let response = match request.send().await.and_then(Response::error_for_status) {
+                Ok(response) => response,
+                Err(e) => {
+                    error!("failed to POST message to MCP server: {e}");
+                    fail_request(&msg, format!("HTTP request failed: {e}"), &pending).await;
+                    // The server is gone, or has ended the session (the spec
+                    // answers requests for an unknown session with 404):
+                    // either way the client has to be recreated.
+                    let session_ended =
+                        session_id.is_some() && e.status() == Some(StatusCode::NOT_FOUND);
+                    if e.is_connect() || session_ended {
+                        mark_closed(&pending, &status).await;
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            if let Some(id) = response.headers().get(MCP_SESSION_ID_HEADER) {
+                session_id = Some(id.clone());
+            }
+
+            // The body is read on its own task so that a long-running
+            // request (whose reply may be streamed) does not block the
+            // messages queued behind it.
+            let pending = pending.clone();
+            let status = status.clone();
+            tokio::spawn(async move {
+                read_streamable_http_response(response, &msg, &pending, &status).await;
+            });
+        }
+    });
+}
+
+async fn read_streamable_http_response(
+    response: Response,
+    msg: &JSONRPCMessage,
+    pending: &PendingRequests,
+    status: &ServerStatus,
+) {
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    if content_type.starts_with("text/event-stream") {
+        let mut events = response.bytes_stream().eventsource();
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(event) if event.event.is_empty() || event.event == "message" => {
+                    debug!("MCP message from server: {}", event.data);
+                    handle_incoming_message(&event.data, pending, status).await;
+                }
+                Ok(event) => info!("<- ignoring SSE event `{}`", event.event),
+                Err(e) => {
+                    error!("error reading MCP SSE stream: {e}");
+                    break;
+                }
+            }
+        }
+        return;
+    }
+
+    match response.text().await {
+        // Notifications and responses are acknowledged with an empty
+        // `202 Accepted`.
+        Ok(body) if body.trim().is_empty() => {}
+        Ok(body) => {
+            debug!("MCP message from server: {body}");
+            handle_incoming_message(&body, pending, status).await;
+        }
+        Err(e) => {
+            error!("failed to read MCP server response: {e}");
+            fail_request(msg, format!("failed to read response: {e}"), pending).await;
+        }
+    }
+}
+
+/// Open the legacy SSE stream at `url`, wait for the `endpoint` event and
+/// spawn the tasks that post outgoing messages and read incoming ones.
+pub(crate) async fn connect_sse(
+    http_client: reqwest::Client,
+    url: Url,
+    mut outgoing_rx: mpsc::Receiver<JSONRPCMessage>,
+    pending: PendingRequests,
+    status: SharedServerStatus,
+) -> Result<()> {
+    let response = http_client
+        .get(url.clone())
+        .header(ACCEPT, "text/event-stream")
+        .send()
+        .await
+        .and_then(Response::error_for_status)
+        .with_context(|| format!("failed to open SSE stream at {url}"))?;
+    let mut events = response.bytes_stream().eventsource();
+
+    let endpoint = loop {
+        match events.next().await {
+            Some(Ok(event)) if event.event == "endpoint" => {
+                break url
+                    .join(event.data.trim())
+                    .with_context(|| format!("invalid SSE endpoint `{}`", event.data))?;
+            }
+            Some(Ok(event)) => info!("<- ignoring SSE event `{}` before endpoint", event.event),
+            Some(Err(e)) => return Err(anyhow!("error reading SSE stream: {e}")),
+            None => return Err(anyhow!("SSE stream closed before the endpoint was sent")),
+        }
+    };
+
+    // Reader task: every `message` event carries one JSON-RPC message. The
+    // connection is lost once the stream ends.
+    {
+        let pending = pending.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(event) if event.event.is_empty() || event.event == "message" => {
+                        debug!("MCP message from server: {}", event.data);
+                        handle_incoming_message(&event.data, &pending, &status).await;
+                    }
+                    Ok(event) => info!("<- ignoring SSE event `{}`", event.event),
+                    Err(e) => {
+                        error!("error reading MCP SSE stream: {e}");
+                        break;
+                    }
+                }
+            }
+            mark_closed(&pending, &status).await;
+        });
+    }
+
+    // Writer task: replies arrive on the SSE stream, so the POST bodies are
+    // ignored.
+    tokio::spawn(async move {
+        while let Some(msg) = outgoing_rx.recv().await {
+            debug!("MCP message to server: {msg:?}");
+            let result = http_client
+                .post(endpoint.clone())
+                .json(&msg)
+                .send()
+                .await
+                .and_then(Response::error_for_status);
+            if let Err(e) = result {
+                error!("failed to POST message to MCP server: {e}");
+                fail_request(&msg, format!("HTTP request failed: {e}"), &pending).await;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// If `msg` is a request, answer it with a JSON-RPC error so the caller does
+/// not wait for a reply that will never come.
+async fn fail_request(msg: &JSONRPCMessage, message: String, pending: &PendingRequests) {
+    let JSONRPCMessage::Request(request) = msg else {
+        return;
+    };
+    let err = JSONRPCError {
+        error: JSONRPCErrorError {
+            code: TRANSPORT_ERROR_CODE,
+            data: None,
+            message,
+        },
+        id: request.id.clone(),
+        jsonrpc: JSONRPC_VERSION.to_string(),
+    };
+    dispatch_error(err, pending).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use mcp_types::ListToolsResult;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::body_partial_json;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    use crate::McpClient;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    fn list_tools_result(id: i64) -> serde_json::Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": { "tools": [] },
+        })
+    }
+
+    #[tokio::test]
+    async fn streamable_http_client_handles_json_and_sse_replies() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(header("authorization", "Bearer secret"))
+            .and(header("x-team", "codex"))
+            .and(body_partial_json(json!({ "id": 1 })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("mcp-session-id", "session-1")
+                    .set_body_json(list_tools_result(1)),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(header("mcp-session-id", "session-1"))
+            .and(body_partial_json(json!({ "id": 2 })))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                format!("event: message\ndata: {}\n\n", list_tools_result(2)),
+                "text/event-stream",
+            ))
+            .mount(&server)
+            .await;
+
+        let client = McpClient::new_streamable_http_client(
+            format!("{}/mcp", server.uri()),
+            HashMap::from([("X-Team".to_string(), "codex".to_string())]),
+            Some("secret".to_string()),
+        )
+        .await
+        .unwrap();
+
+        let expected = ListToolsResult {
+            next_cursor: None,
+            tools: Vec::new(),
+        };
+        assert_eq!(expected, client.list_tools(None, TIMEOUT).await.unwrap());
+        assert_eq!(expected, client.list_tools(None, TIMEOUT).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn streamable_http_client_reports_http_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let client = McpClient::new_streamable_http_client(server.uri(), HashMap::new(), None)
+            .await
+            .unwrap();
+
+        let err = client.list_tools(None, TIMEOUT).await.unwrap_err();
+        assert!(err.to_string().contains("HTTP request failed"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn streamable_http_client_is_closed_when_the_session_ends() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "id": 1 })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("mcp-session-id", "session-1")
+                    .set_body_json(list_tools_result(1)),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(header("mcp-session-id", "session-1"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = McpClient::new_streamable_http_client(server.uri(), HashMap::new(), None)
+            .await
+            .unwrap();
+
+        client.list_tools(None, TIMEOUT).await.unwrap();
+        assert!(!client.is_closed());
+        client.list_tools(None, TIMEOUT).await.unwrap_err();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !client.is_closed() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("client should be reported as closed");
+    }
+
+    #[tokio::test]
+    async fn sse_client_tracks_list_changed_and_closed_stream() {
+        let server = MockServer::start().await;
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/tools/list_changed",
+        });
+        Mock::given(method("GET"))
+            .and(path("/sse"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                format!(
+                    "event: endpoint\ndata: /messages\n\nevent: message\ndata: {notification}\n\n"
+                ),
+                "text/event-stream",
+            ))
+            .mount(&server)
+            .await;
+
+        let client =
+            McpClient::new_sse_client(format!("{}/sse", server.uri()), HashMap::new(), None)
+                .await
+                .unwrap();
+
+        // The whole body is sent at once, so the stream ends right after the
+        // notification.
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !client.is_closed() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("stream should be reported as closed");
+        assert!(client.take_tools_list_changed());
+        assert!(!client.take_tools_list_changed());
+    }
+
+    #[tokio::test]
+    async fn sse_client_requires_endpoint_event() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sse"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("event: ping\ndata: {}\n\n", "text/event-stream"),
+            )
+            .mount(&server)
+            .await;
+
+        let result =
+            McpClient::new_sse_client(format!("{}/sse", server.uri()), HashMap::new(), None).await;
+        let Err(err) = result else {
+            panic!("expected the SSE handshake to fail");
+        };
+        assert!(err.to_string().contains("endpoint"), "{err}");
+    }
+}
@@ -2,10 +2,14 @@
 //!
 //! The client is intentionally lightweight – it is only capable of:
 //!   1. Spawning a subprocess that launches a conforming MCP server that
-//!      communicates over stdio.
+//!      communicates over stdio, or connecting to a remote MCP server over
+//!      Streamable HTTP or the legacy HTTP+SSE transport.
 //!   2. Sending MCP requests and pairing them with their corresponding
 //!      responses.
-//!   3. Offering a convenience helper for the common `tools/list` request.
+//!   3. Offering convenience helpers for the common `tools/*`, `resources/*`
+//!      and `prompts/*` requests.
+//!   4. Reporting whether the connection to the server was lost and whether
+//!      the server announced a change to its tool list.
 //!
 //! The crate hides all JSON‐RPC framing details behind a typed API. Users
 //! interact with the [`ModelContextProtocolRequest`] trait from `mcp-types` to
@@ -14,6 +18,7 @@
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicI64;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
@@ -23,6 +28,9 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
@@ -31,12 +39,22 @@ use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
+use mcp_types::ToolListChangedNotification;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use tokio::io::AsyncBufReadExt;
@@ -52,6 +70,8 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::http_transport;
+
 /// Capacity of the bounded channels used for transporting messages between the
 /// client API and the IO tasks.
 const CHANNEL_CAPACITY: usize = 128;
@@ -59,23 +79,46 @@ const CHANNEL_CAPACITY: usize = 128;
 /// Internal representation of a pending request sender.
 type PendingSender = oneshot::Sender<JSONRPCMessage>;
 
+/// Map of `request.id -> oneshot::Sender` shared between the client API and
+/// the transport tasks.
+pub(crate) type PendingRequests = Arc<Mutex<HashMap<i64, PendingSender>>>;
+
+/// Connection state reported by the transport tasks.
+#[derive(Default)]
+pub(crate) struct ServerStatus {
+    /// Set when the connection to the server is lost, e.g. because the server
+    /// process exited.
+    closed: AtomicBool,
+
+    /// Set when the server sends `notifications/tools/list_changed`.
+    tools_list_changed: AtomicBool,
+}
+
+/// Shared handle to the [`ServerStatus`] of one client.
+pub(crate) type SharedServerStatus = Arc<ServerStatus>;
+
 /// A running MCP client instance.
 pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
     /// will make a "best effort" to reap the process after it exits, but it is
     /// not a guarantee. See the `kill_on_drop` documentation for details.
+    ///
+    /// `None` for clients connected to a remote server over HTTP.
     #[allow(dead_code)]
-    child: tokio::process::Child,
+    child: Option<tokio::process::Child>,
 
     /// Channel for sending JSON-RPC messages *to* the background writer task.
     outgoing_tx: mpsc::Sender<JSONRPCMessage>,
 
     /// Map of `request.id -> oneshot::Sender` used to dispatch responses back
     /// to the originating caller.
-    pending: Arc<Mutex<HashMap<i64, PendingSender>>>,
+    pending: PendingRequests,
 
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
+
+    /// Connection state updated by the transport tasks.
+    status: SharedServerStatus,
 }
 
 impl McpClient {
@@ -110,7 +153,8 @@ impl McpClient {
             .ok_or_else(|| std::io::Error::other("failed to capture child stdout"))?;
 
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
-        let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let status = SharedServerStatus::default();
 
         // Spawn writer task. It listens on the `outgoing_rx` channel and
         // writes messages to the child's STDIN.
@@ -138,35 +182,19 @@ impl McpClient {
         };
 
         // Spawn reader task. It reads line-delimited JSON from the child's
-        // STDOUT and dispatches responses to the pending map.
+        // STDOUT and dispatches responses to the pending map. The server has
+        // exited (or closed its STDOUT) once the stream ends.
         let reader_handle = {
             let pending = pending.clone();
+            let status = status.clone();
             let mut lines = BufReader::new(stdout).lines();
 
             tokio::spawn(async move {
                 while let Ok(Some(line)) = lines.next_line().await {
                     debug!("MCP message from server: {line}");
-                    match serde_json::from_str::<JSONRPCMessage>(&line) {
-                        Ok(JSONRPCMessage::Response(resp)) => {
-                            Self::dispatch_response(resp, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Error(err)) => {
-                            Self::dispatch_error(err, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
-                            // For now we only log server-initiated notifications.
-                            info!("<- notification: {}", line);
-                        }
-                        Ok(other) => {
-                            // Batch responses and requests are currently not
-                            // expected from the server – log and ignore.
-                            info!("<- unhandled message: {:?}", other);
-                        }
-                        Err(e) => {
-                            error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
-                        }
-                    }
+                    handle_incoming_message(&line, &pending, &status).await;
                 }
+                mark_closed(&pending, &status).await;
             })
         };
 
@@ -177,13 +205,93 @@ impl McpClient {
         let _ = (writer_handle, reader_handle);
 
         Ok(Self {
-            child,
+            child: Some(child),
+            outgoing_tx,
+            pending,
+            id_counter: AtomicI64::new(1),
+            status,
+        })
+    }
+
+    /// Connect to an MCP server that speaks the Streamable HTTP transport at
+    /// `url`. Every entry in `headers` is sent with each request, as is
+    /// `Authorization: Bearer <token>` when `bearer_token` is set. Caller is
+    /// responsible for sending the `initialize` request.
+    pub async fn new_streamable_http_client(
+        url: String,
+        headers: HashMap<String, String>,
+        bearer_token: Option<String>,
+    ) -> Result<Self> {
+        let http_client = http_transport::build_http_client(&headers, bearer_token.as_deref())?;
+        let url = reqwest::Url::parse(&url).with_context(|| format!("invalid MCP url `{url}`"))?;
+
+        let (outgoing_tx, outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let status = SharedServerStatus::default();
+        http_transport::spawn_streamable_http_writer(
+            http_client,
+            url,
+            outgoing_rx,
+            pending.clone(),
+            status.clone(),
+        );
+
+        Ok(Self {
+            child: None,
+            outgoing_tx,
+            pending,
+            id_counter: AtomicI64::new(1),
+            status,
+        })
+    }
+
+    /// Connect to an MCP server that speaks the legacy HTTP+SSE transport. The
+    /// event stream is opened at `url` and this waits for the server to
+    /// announce the endpoint that messages should be posted to. `headers` and
+    /// `bearer_token` are handled as in
+    /// [`new_streamable_http_client`](Self::new_streamable_http_client).
+    pub async fn new_sse_client(
+        url: String,
+        headers: HashMap<String, String>,
+        bearer_token: Option<String>,
+    ) -> Result<Self> {
+        let http_client = http_transport::build_http_client(&headers, bearer_token.as_deref())?;
+        let url = reqwest::Url::parse(&url).with_context(|| format!("invalid MCP url `{url}`"))?;
+
+        let (outgoing_tx, outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let status = SharedServerStatus::default();
+        http_transport::connect_sse(
+            http_client,
+            url,
+            outgoing_rx,
+            pending.clone(),
+            status.clone(),
+        )
+        .await?;
+
+        Ok(Self {
+            child: None,
             outgoing_tx,
             pending,
             id_counter: AtomicI64::new(1),
+            status,
         })
     }
 
+    /// Returns `true` once the connection to the server has been lost, e.g.
+    /// because the server process exited. A closed client never recovers: a
+    /// new one has to be created.
+    pub fn is_closed(&self) -> bool {
+        self.status.closed.load(Ordering::SeqCst)
+    }
+
+    /// Returns `true` if the server sent `notifications/tools/list_changed`
+    /// since the last call, i.e. its tools should be listed again.
+    pub fn take_tools_list_changed(&self) -> bool {
+        self.status.tools_list_changed.swap(false, Ordering::SeqCst)
+    }
+
     /// Send an arbitrary MCP request and await the typed result.
     ///
     /// If `timeout` is `None` the call waits indefinitely. If `Some(duration)`
@@ -335,6 +443,48 @@ impl McpClient {
         self.send_request::<ListToolsRequest>(params, timeout).await
     }
 
+    /// Convenience wrapper around `resources/list`.
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        self.send_request::<ListResourcesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/read`.
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let params = ReadResourceRequestParams { uri };
+        self.send_request::<ReadResourceRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/list`.
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.send_request::<ListPromptsRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/get`.
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let params = GetPromptRequestParams { arguments, name };
+        self.send_request::<GetPromptRequest>(params, timeout).await
+    }
+
     /// Convenience wrapper around `tools/call`.
     pub async fn call_tool(
         &self,
@@ -346,51 +496,83 @@ impl McpClient {
         debug!("MCP tool call: {params:?}");
         self.send_request::<CallToolRequest>(params, timeout).await
     }
+}
 
-    /// Internal helper: route a JSON-RPC *response* object to the pending map.
-    async fn dispatch_response(
-        resp: JSONRPCResponse,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
-    ) {
-        let id = match resp.id {
-            RequestId::Integer(i) => i,
-            RequestId::String(_) => {
-                // We only ever generate integer IDs. Receiving a string here
-                // means we will not find a matching entry in `pending`.
-                error!("response with string ID - no matching pending request");
-                return;
+/// Parse a single JSON-RPC message received from the server and route it to
+/// the pending request it answers, if any.
+pub(crate) async fn handle_incoming_message(
+    raw: &str,
+    pending: &PendingRequests,
+    status: &ServerStatus,
+) {
+    match serde_json::from_str::<JSONRPCMessage>(raw) {
+        Ok(JSONRPCMessage::Response(resp)) => {
+            dispatch_response(resp, pending).await;
+        }
+        Ok(JSONRPCMessage::Error(err)) => {
+            dispatch_error(err, pending).await;
+        }
+        Ok(JSONRPCMessage::Notification(JSONRPCNotification { method, .. })) => {
+            info!("<- notification: {}", raw);
+            if method == ToolListChangedNotification::METHOD {
+                status.tools_list_changed.store(true, Ordering::SeqCst);
             }
-        };
-
-        let tx_opt = {
-            let mut guard = pending.lock().await;
-            guard.remove(&id)
-        };
-        if let Some(tx) = tx_opt {
-            // Ignore send errors – the receiver might have been dropped.
-            let _ = tx.send(JSONRPCMessage::Response(resp));
-        } else {
-            warn!(id, "no pending request found for response");
+        }
+        Ok(other) => {
+            // Batch responses and requests are currently not
+            // expected from the server – log and ignore.
+            info!("<- unhandled message: {:?}", other);
+        }
+        Err(e) => {
+            error!("failed to deserialize JSONRPCMessage: {e}; line = {}", raw)
         }
     }
+}
 
-    /// Internal helper: route a JSON-RPC *error* object to the pending map.
-    async fn dispatch_error(
-        err: mcp_types::JSONRPCError,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
-    ) {
-        let id = match err.id {
-            RequestId::Integer(i) => i,
-            RequestId::String(_) => return, // see comment above
-        };
+/// Record that the connection to the server was lost. Requests that are still
+/// waiting for a reply fail immediately instead of waiting for their timeout.
+pub(crate) async fn mark_closed(pending: &PendingRequests, status: &ServerStatus) {
+    status.closed.store(true, Ordering::SeqCst);
+    pending.lock().await.clear();
+}
 
-        let tx_opt = {
-            let mut guard = pending.lock().await;
-            guard.remove(&id)
-        };
-        if let Some(tx) = tx_opt {
-            let _ = tx.send(JSONRPCMessage::Error(err));
+/// Route a JSON-RPC *response* object to the pending map.
+async fn dispatch_response(resp: JSONRPCResponse, pending: &PendingRequests) {
+    let id = match resp.id {
+        RequestId::Integer(i) => i,
+        RequestId::String(_) => {
+            // We only ever generate integer IDs. Receiving a string here
+            // means we will not find a matching entry in `pending`.
+            error!("response with string ID - no matching pending request");
+            return;
         }
+    };
+
+    let tx_opt = {
+        let mut guard = pending.lock().await;
+        guard.remove(&id)
+    };
+    if let Some(tx) = tx_opt {
+        // Ignore send errors – the receiver might have been dropped.
+        let _ = tx.send(JSONRPCMessage::Response(resp));
+    } else {
+        warn!(id, "no pending request found for response");
+    }
+}
+
+/// Route a JSON-RPC *error* object to the pending map.
+pub(crate) async fn dispatch_error(err: mcp_types::JSONRPCError, pending: &PendingRequests) {
+    let id = match err.id {
+        RequestId::Integer(i) => i,
+        RequestId::String(_) => return, // see comment above
+    };
+
+    let tx_opt = {
+        let mut guard = pending.lock().await;
+        guard.remove(&id)
+    };
+    if let Some(tx) = tx_opt {
+        let _ = tx.send(JSONRPCMessage::Error(err));
     }
 }
 
@@ -400,7 +582,9 @@ impl Drop for McpClient {
         // `kill_on_drop(true)` above, this extra check has the benefit of
         // forcing the process to be reaped immediately if it has already exited
         // instead of waiting for the Tokio runtime to reap it later.
-        let _ = self.child.try_wait();
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.try_wait();
+        }
     }
 }
 
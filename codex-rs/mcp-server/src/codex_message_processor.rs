@@ -31,6 +31,7 @@ use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::InputItem as CoreInputItem;
+use codex_core::protocol::McpToolCallApprovalRequestEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
 use codex_login::ServerOptions as LoginServerOptions;
@@ -64,6 +65,9 @@ use codex_protocol::mcp_protocol::LoginApiKeyParams;
 use codex_protocol::mcp_protocol::LoginApiKeyResponse;
 use codex_protocol::mcp_protocol::LoginChatGptCompleteNotification;
 use codex_protocol::mcp_protocol::LoginChatGptResponse;
+use codex_protocol::mcp_protocol::MCP_TOOL_CALL_APPROVAL_METHOD;
+use codex_protocol::mcp_protocol::McpToolCallApprovalParams;
+use codex_protocol::mcp_protocol::McpToolCallApprovalResponse;
 use codex_protocol::mcp_protocol::NewConversationParams;
 use codex_protocol::mcp_protocol::NewConversationResponse;
 use codex_protocol::mcp_protocol::RemoveConversationListenerParams;
@@ -555,7 +559,10 @@ impl CodexMessageProcessor {
         }
 
         let cwd = params.cwd.unwrap_or_else(|| self.config.cwd.clone());
-        let env = create_env(&self.config.shell_environment_policy);
+        let effective_policy = params
+            .sandbox_policy
+            .unwrap_or_else(|| self.config.sandbox_policy.clone());
+        let env = create_env(&self.config.shell_environment_policy, &effective_policy);
         let timeout_ms = params.timeout_ms;
         let exec_params = ExecParams {
             command: params.command,
@@ -566,18 +573,18 @@ impl CodexMessageProcessor {
             justification: None,
         };
 
-        let effective_policy = params
-            .sandbox_policy
-            .unwrap_or_else(|| self.config.sandbox_policy.clone());
-
         let sandbox_type = match &effective_policy {
             codex_core::protocol::SandboxPolicy::DangerFullAccess => {
                 codex_core::exec::SandboxType::None
             }
+            _ if self.config.container_sandbox.is_some() => {
+                codex_core::exec::SandboxType::Container
+            }
             _ => get_platform_sandbox().unwrap_or(codex_core::exec::SandboxType::None),
         };
         tracing::debug!("Sandbox type: {sandbox_type:?}");
         let codex_linux_sandbox_exe = self.config.codex_linux_sandbox_exe.clone();
+        let container_sandbox = self.config.container_sandbox.clone();
         let outgoing = self.outgoing.clone();
         let req_id = request_id;
 
@@ -587,6 +594,7 @@ impl CodexMessageProcessor {
                 sandbox_type,
                 &effective_policy,
                 &codex_linux_sandbox_exe,
+                container_sandbox.as_ref(),
                 None,
             )
             .await
@@ -664,13 +672,37 @@ impl CodexMessageProcessor {
         };
         let cursor_ref = cursor_obj.as_ref();
 
-        let page = match RolloutRecorder::list_conversations(
-            &self.config.codex_home,
-            page_size,
-            cursor_ref,
-        )
-        .await
-        {
+        let query = params.query.filter(|q| !q.trim().is_empty());
+        let page = match &query {
+            Some(query) => RolloutRecorder::search_conversations(
+                &self.config.codex_home,
+                query,
+                page_size,
+                cursor_ref,
+            )
+            .await
+            .map(|page| {
+                let items = page
+                    .items
+                    .into_iter()
+                    .map(|hit| (hit.path, hit.head))
+                    .collect::<Vec<_>>();
+                (items, page.next_cursor)
+            }),
+            None => {
+                RolloutRecorder::list_conversations(&self.config.codex_home, page_size, cursor_ref)
+                    .await
+                    .map(|page| {
+                        let items = page
+                            .items
+                            .into_iter()
+                            .map(|it| (it.path, it.head))
+                            .collect::<Vec<_>>();
+                        (items, page.next_cursor)
+                    })
+            }
+        };
+        let (items, next_cursor) = match page {
             Ok(p) => p,
             Err(err) => {
                 let error = JSONRPCErrorError {
@@ -683,14 +715,13 @@ impl CodexMessageProcessor {
             }
         };
 
-        let items = page
-            .items
+        let items = items
             .into_iter()
-            .filter_map(|it| extract_conversation_summary(it.path, &it.head))
+            .filter_map(|(path, head)| extract_conversation_summary(path, &head))
             .collect();
 
         // Encode next_cursor as a plain string
-        let next_cursor = match page.next_cursor {
+        let next_cursor = match next_cursor {
             Some(c) => match serde_json::to_value(&c) {
                 Ok(serde_json::Value::String(s)) => Some(s),
                 _ => None,
@@ -1211,6 +1242,27 @@ async fn apply_bespoke_event_handling(
                 on_exec_approval_response(event_id, rx, conversation).await;
             });
         }
+        EventMsg::McpToolCallApprovalRequest(McpToolCallApprovalRequestEvent {
+            call_id,
+            invocation,
+            reason,
+        }) => {
+            let params = McpToolCallApprovalParams {
+                conversation_id,
+                call_id,
+                invocation,
+                reason,
+            };
+            let value = serde_json::to_value(&params).unwrap_or_default();
+            let rx = outgoing
+                .send_request(MCP_TOOL_CALL_APPROVAL_METHOD, Some(value))
+                .await;
+
+            // TODO(mbolin): Enforce a timeout so this task does not live indefinitely?
+            tokio::spawn(async move {
+                on_mcp_tool_call_approval_response(event_id, rx, conversation).await;
+            });
+        }
         // If this is a TurnAborted, reply to any pending interrupt requests.
         EventMsg::TurnAborted(turn_aborted_event) => {
             let pending = {
@@ -1261,6 +1313,9 @@ fn derive_config_from_params(
         include_view_image_tool: None,
         show_raw_agent_reasoning: None,
         tools_web_search_request: None,
+        output_schema: None,
+        cassette: None,
+        worktree: None,
     };
 
     let cli_overrides = cli_overrides
@@ -1350,6 +1405,41 @@ async fn on_exec_approval_response(
     }
 }
 
+async fn on_mcp_tool_call_approval_response(
+    event_id: String,
+    receiver: oneshot::Receiver<mcp_types::Result>,
+    conversation: Arc<CodexConversation>,
+) {
+    let response = receiver.await;
+    let value = match response {
+        Ok(value) => value,
+        Err(err) => {
+            error!("request failed: {err:?}");
+            return;
+        }
+    };
+
+    let response =
+        serde_json::from_value::<McpToolCallApprovalResponse>(value).unwrap_or_else(|err| {
+            error!("failed to deserialize McpToolCallApprovalResponse: {err}");
+            // If we cannot deserialize the response, we deny the request to be
+            // conservative.
+            McpToolCallApprovalResponse {
+                decision: ReviewDecision::Denied,
+            }
+        });
+
+    if let Err(err) = conversation
+        .submit(Op::McpToolCallApproval {
+            id: event_id,
+            decision: response.decision,
+        })
+        .await
+    {
+        error!("failed to submit McpToolCallApproval: {err}");
+    }
+}
+
 fn extract_conversation_summary(
     path: PathBuf,
     head: &[serde_json::Value],
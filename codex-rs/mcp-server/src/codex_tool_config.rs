@@ -165,6 +165,8 @@ impl CodexToolCallParam {
             include_view_image_tool: None,
             show_raw_agent_reasoning: None,
             tools_web_search_request: None,
+            output_schema: None,
+            cassette: None,
         };
 
         let cli_overrides = cli_overrides
@@ -26,6 +26,7 @@ mod codex_tool_runner;
 mod error_code;
 mod exec_approval;
 mod json_to_toml;
+mod mcp_tool_call_approval;
 pub(crate) mod message_processor;
 mod outgoing_message;
 mod patch_approval;
@@ -38,6 +39,8 @@ pub use crate::codex_tool_config::CodexToolCallParam;
 pub use crate::codex_tool_config::CodexToolCallReplyParam;
 pub use crate::exec_approval::ExecApprovalElicitRequestParams;
 pub use crate::exec_approval::ExecApprovalResponse;
+pub use crate::mcp_tool_call_approval::McpToolCallApprovalElicitRequestParams;
+pub use crate::mcp_tool_call_approval::McpToolCallApprovalResponse;
 pub use crate::patch_approval::PatchApprovalElicitRequestParams;
 pub use crate::patch_approval::PatchApprovalResponse;
 
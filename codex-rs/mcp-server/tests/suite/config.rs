@@ -89,6 +89,7 @@ async fn get_config_toml_parses_all_fields() {
             sandbox_settings: Some(SandboxSettings {
                 writable_roots: vec!["/tmp".into()],
                 network_access: Some(true),
+                network_allowlist: Vec::new(),
                 exclude_tmpdir_env_var: Some(true),
                 exclude_slash_tmp: Some(true),
             }),
@@ -99,6 +100,7 @@ async fn get_config_toml_parses_all_fields() {
             tools: Some(Tools {
                 web_search: Some(false),
                 view_image: Some(true),
+                delegate: None,
             }),
             profile: Some("test".to_string()),
             profiles: HashMap::from([(
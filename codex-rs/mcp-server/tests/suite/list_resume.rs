@@ -55,6 +55,7 @@ async fn test_list_and_resume_conversations() {
         .send_list_conversations_request(ListConversationsParams {
             page_size: Some(2),
             cursor: None,
+            query: None,
         })
         .await
         .expect("send listConversations");
@@ -80,6 +81,7 @@ async fn test_list_and_resume_conversations() {
         .send_list_conversations_request(ListConversationsParams {
             page_size: Some(2),
             cursor: next_cursor,
+            query: None,
         })
         .await
         .expect("send listConversations page 2");
@@ -99,6 +101,30 @@ async fn test_list_and_resume_conversations() {
     assert_eq!(items2[0].preview, "Hello C");
     assert!(next2.is_some());
 
+    // Full-text search matches every query term, case-insensitively.
+    let search_req_id = mcp
+        .send_list_conversations_request(ListConversationsParams {
+            page_size: None,
+            cursor: None,
+            query: Some("hello b".to_string()),
+        })
+        .await
+        .expect("send listConversations with query");
+    let search_resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(search_req_id)),
+    )
+    .await
+    .expect("listConversations query timeout")
+    .expect("listConversations query resp");
+    let ListConversationsResponse {
+        items: search_items,
+        next_cursor: search_next,
+    } = to_response::<ListConversationsResponse>(search_resp).expect("deserialize response");
+    assert_eq!(search_items.len(), 1);
+    assert_eq!(search_items[0].preview, "Hello B");
+    assert_eq!(search_next, None);
+
     // Now resume one of the sessions and expect a SessionConfigured notification and response.
     let resume_req_id = mcp
         .send_resume_conversation_request(ResumeConversationParams {
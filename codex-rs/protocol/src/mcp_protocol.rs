@@ -9,6 +9,7 @@ use crate::config_types::Verbosity;
 use crate::protocol::AskForApproval;
 use crate::protocol::EventMsg;
 use crate::protocol::FileChange;
+use crate::protocol::McpInvocation;
 use crate::protocol::ReviewDecision;
 use crate::protocol::SandboxPolicy;
 use crate::protocol::TurnAbortReason;
@@ -246,6 +247,10 @@ pub struct ListConversationsParams {
     /// Opaque pagination cursor returned by a previous call.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cursor: Option<String>,
+    /// Optional full-text query. When set, only conversations whose messages,
+    /// commands or touched files contain every term are returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -495,6 +500,8 @@ pub struct Tools {
     pub web_search: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub view_image: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegate: Option<bool>,
 }
 
 /// MCP representation of a [`codex_core::config_types::SandboxWorkspaceWrite`].
@@ -505,6 +512,8 @@ pub struct SandboxSettings {
     pub writable_roots: Vec<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network_access: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub network_allowlist: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exclude_tmpdir_env_var: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -587,6 +596,7 @@ pub enum InputItem {
 
 pub const APPLY_PATCH_APPROVAL_METHOD: &str = "applyPatchApproval";
 pub const EXEC_COMMAND_APPROVAL_METHOD: &str = "execCommandApproval";
+pub const MCP_TOOL_CALL_APPROVAL_METHOD: &str = "mcpToolCallApproval";
 
 /// Request initiated from the server and sent to the client.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -604,6 +614,12 @@ pub enum ServerRequest {
         request_id: RequestId,
         params: ExecCommandApprovalParams,
     },
+    /// Request to call an MCP tool.
+    McpToolCallApproval {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: McpToolCallApprovalParams,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -634,6 +650,17 @@ pub struct ExecCommandApprovalParams {
     pub reason: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct McpToolCallApprovalParams {
+    pub conversation_id: ConversationId,
+    /// Use to correlate this with [codex_core::protocol::McpToolCallBeginEvent]
+    /// and [codex_core::protocol::McpToolCallEndEvent].
+    pub call_id: String,
+    pub invocation: McpInvocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 pub struct ExecCommandApprovalResponse {
     pub decision: ReviewDecision,
@@ -644,6 +671,11 @@ pub struct ApplyPatchApprovalResponse {
     pub decision: ReviewDecision,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct McpToolCallApprovalResponse {
+    pub decision: ReviewDecision,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct LoginChatGptCompleteNotification {
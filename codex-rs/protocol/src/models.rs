@@ -234,6 +234,16 @@ impl From<Vec<InputItem>> for ResponseInputItem {
                             None
                         }
                     },
+                    // MCP items must be resolved into text/images by
+                    // codex-core before they are sent to the model.
+                    InputItem::McpResource { server, uri } => {
+                        tracing::warn!("Skipping unresolved MCP resource {server}:{uri}");
+                        None
+                    }
+                    InputItem::McpPrompt { server, name, .. } => {
+                        tracing::warn!("Skipping unresolved MCP prompt {server}/{name}");
+                        None
+                    }
                 })
                 .collect::<Vec<ContentItem>>(),
         }
@@ -20,6 +20,8 @@ use crate::num_format::format_with_separators;
 use crate::parse_command::ParsedCommand;
 use crate::plan_tool::UpdatePlanArgs;
 use mcp_types::CallToolResult;
+use mcp_types::Prompt as McpPrompt;
+use mcp_types::Resource as McpResource;
 use mcp_types::Tool as McpTool;
 use serde::Deserialize;
 use serde::Serialize;
@@ -131,6 +133,14 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Approve an MCP tool call
+    McpToolCallApproval {
+        /// The id of the submission we are approving
+        id: String,
+        /// The user's decision in response to the request.
+        decision: ReviewDecision,
+    },
+
     /// Approve a code patch
     PatchApproval {
         /// The id of the submission we are approving
@@ -151,6 +161,10 @@ pub enum Op {
     /// Request a single history entry identified by `log_id` + `offset`.
     GetHistoryEntryRequest { offset: usize, log_id: u64 },
 
+    /// Request every entry of the persistent message history, oldest first.
+    /// Reply is delivered via `EventMsg::ListHistoryEntriesResponse`.
+    ListHistoryEntries,
+
     /// Request the full in-memory conversation transcript for the current session.
     /// Reply is delivered via `EventMsg::ConversationHistory`.
     GetPath,
@@ -159,9 +173,22 @@ pub enum Op {
     /// Reply is delivered via `EventMsg::McpListToolsResponse`.
     ListMcpTools,
 
+    /// Re-read the `mcp_servers` table from `config.toml` and restart the
+    /// servers whose configuration changed, without restarting the
+    /// conversation. Reply is delivered via `EventMsg::McpListToolsResponse`.
+    ReloadMcpServers,
+
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Request the list of resources exposed by the configured MCP servers.
+    /// Reply is delivered via `EventMsg::McpListResourcesResponse`.
+    ListMcpResources,
+
+    /// Request the list of prompts exposed by the configured MCP servers.
+    /// Reply is delivered via `EventMsg::McpListPromptsResponse`.
+    ListMcpPrompts,
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
@@ -170,6 +197,12 @@ pub enum Op {
     /// Request a code review from the agent.
     Review { review_request: ReviewRequest },
 
+    /// Restore the workspace to the checkpoint taken before the most recent
+    /// turn that has not been undone yet. Reply is delivered via
+    /// `EventMsg::TurnUndone`, or `EventMsg::Error` when there is nothing to
+    /// undo.
+    UndoTurn,
+
     /// Request to shut down codex instance.
     Shutdown,
 }
@@ -211,9 +244,15 @@ pub enum SandboxPolicy {
     #[serde(rename = "danger-full-access")]
     DangerFullAccess,
 
-    /// Read-only access to the entire file-system.
+    /// Read-only access to the file-system.
     #[serde(rename = "read-only")]
-    ReadOnly,
+    ReadOnly {
+        /// When set, reads are restricted to these folders together with the
+        /// cwd and [`DEFAULT_READABLE_ROOTS`]. `None` allows reading the entire
+        /// file-system.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        readable_roots: Option<Vec<PathBuf>>,
+    },
 
     /// Same as `ReadOnly` but additionally grants write access to the current
     /// working directory ("workspace").
@@ -229,6 +268,12 @@ pub enum SandboxPolicy {
         #[serde(default)]
         network_access: bool,
 
+        /// When non-empty and `network_access` is `false`, sandboxed commands
+        /// may reach these hosts (and their subdomains) through the local
+        /// HTTP/CONNECT proxy started by Codex, and no other network.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        network_allowlist: Vec<String>,
+
         /// When set to `true`, will NOT include the per-user `TMPDIR`
         /// environment variable among the default writable roots. Defaults to
         /// `false`.
@@ -239,9 +284,44 @@ pub enum SandboxPolicy {
         /// writable roots on UNIX. Defaults to `false`.
         #[serde(default)]
         exclude_slash_tmp: bool,
+
+        /// When set, reads are restricted to these folders together with the
+        /// writable roots and [`DEFAULT_READABLE_ROOTS`]. `None` allows reading
+        /// the entire file-system.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        readable_roots: Option<Vec<PathBuf>>,
     },
 }
 
+/// System folders that remain readable when a [`SandboxPolicy`] restricts
+/// reads, so that shells, toolchains and TLS keep working. Folders that do not
+/// exist are skipped.
+pub const DEFAULT_READABLE_ROOTS: &[&str] = &[
+    "/bin",
+    "/sbin",
+    "/usr",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/opt",
+    "/nix/store",
+    "/etc/ssl",
+    "/etc/pki",
+    "/etc/ca-certificates",
+    "/etc/alternatives",
+    "/etc/ld.so.cache",
+    "/etc/ld.so.conf",
+    "/etc/ld.so.conf.d",
+    "/etc/localtime",
+    "/etc/passwd",
+    "/etc/group",
+    "/etc/nsswitch.conf",
+    "/etc/hosts",
+    "/etc/resolv.conf",
+    "/dev",
+    "/proc",
+];
+
 /// A writable root path accompanied by a list of subpaths that should remain
 /// read‑only even when the root is writable. This is primarily used to ensure
 /// top‑level VCS metadata directories (e.g. `.git`) under a writable root are
@@ -284,7 +364,9 @@ impl FromStr for SandboxPolicy {
 impl SandboxPolicy {
     /// Returns a policy with read-only disk access and no network.
     pub fn new_read_only_policy() -> Self {
-        SandboxPolicy::ReadOnly
+        SandboxPolicy::ReadOnly {
+            readable_roots: None,
+        }
     }
 
     /// Returns a policy that can read the entire disk, but can only write to
@@ -294,20 +376,43 @@ impl SandboxPolicy {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            readable_roots: None,
+        }
+    }
+
+    /// Returns the same policy with reads restricted to `readable_roots` (or
+    /// unrestricted for `None`). Has no effect on `DangerFullAccess`.
+    pub fn with_readable_roots(mut self, new_readable_roots: Option<Vec<PathBuf>>) -> Self {
+        match &mut self {
+            SandboxPolicy::DangerFullAccess => {}
+            SandboxPolicy::ReadOnly { readable_roots }
+            | SandboxPolicy::WorkspaceWrite { readable_roots, .. } => {
+                *readable_roots = new_readable_roots;
+            }
+        }
+        self
+    }
+
+    /// The configured `readable_roots`, or `None` if reads are unrestricted.
+    pub fn readable_roots(&self) -> Option<&[PathBuf]> {
+        match self {
+            SandboxPolicy::DangerFullAccess => None,
+            SandboxPolicy::ReadOnly { readable_roots }
+            | SandboxPolicy::WorkspaceWrite { readable_roots, .. } => readable_roots.as_deref(),
         }
     }
 
-    /// Always returns `true`; restricting read access is not supported.
     pub fn has_full_disk_read_access(&self) -> bool {
-        true
+        self.readable_roots().is_none()
     }
 
     pub fn has_full_disk_write_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { .. } => false,
         }
     }
@@ -315,23 +420,38 @@ impl SandboxPolicy {
     pub fn has_full_network_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { network_access, .. } => *network_access,
         }
     }
 
+    /// Hosts reachable through the network proxy. Empty unless the policy
+    /// restricts the network to an allowlist.
+    pub fn network_allowlist(&self) -> &[String] {
+        match self {
+            SandboxPolicy::WorkspaceWrite {
+                network_access: false,
+                network_allowlist,
+                ..
+            } => network_allowlist,
+            _ => &[],
+        }
+    }
+
     /// Returns the list of writable roots (tailored to the current working
     /// directory) together with subpaths that should remain read‑only under
     /// each writable root.
     pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<WritableRoot> {
         match self {
             SandboxPolicy::DangerFullAccess => Vec::new(),
-            SandboxPolicy::ReadOnly => Vec::new(),
+            SandboxPolicy::ReadOnly { .. } => Vec::new(),
             SandboxPolicy::WorkspaceWrite {
                 writable_roots,
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
                 network_access: _,
+                network_allowlist: _,
+                readable_roots: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -381,6 +501,26 @@ impl SandboxPolicy {
             }
         }
     }
+
+    /// Returns the folders (tailored to the current working directory) that
+    /// may be read from, or `None` if the entire file-system is readable.
+    /// Writable roots are always readable.
+    pub fn get_readable_roots_with_cwd(&self, cwd: &Path) -> Option<Vec<PathBuf>> {
+        let configured = self.readable_roots()?;
+        let mut roots: Vec<PathBuf> = DEFAULT_READABLE_ROOTS
+            .iter()
+            .map(PathBuf::from)
+            .filter(|root| root.exists())
+            .collect();
+        roots.push(cwd.to_path_buf());
+        roots.extend(configured.iter().cloned());
+        roots.extend(
+            self.get_writable_roots_with_cwd(cwd)
+                .into_iter()
+                .map(|writable_root| writable_root.root),
+        );
+        Some(roots)
+    }
 }
 
 /// User input
@@ -401,6 +541,22 @@ pub enum InputItem {
     LocalImage {
         path: std::path::PathBuf,
     },
+
+    /// Resource exposed by an MCP server. Codex reads it with
+    /// `resources/read` and includes its contents in the turn.
+    McpResource {
+        server: String,
+        uri: String,
+    },
+
+    /// Prompt exposed by an MCP server. Codex fetches it with `prompts/get`
+    /// and submits the resulting messages as the user input.
+    McpPrompt {
+        server: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        arguments: Option<HashMap<String, String>>,
+    },
 }
 
 /// Event Queue Entry - events from agent
@@ -476,12 +632,31 @@ pub enum EventMsg {
 
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    McpToolCallApprovalRequest(McpToolCallApprovalRequestEvent),
+
     BackgroundEvent(BackgroundEventEvent),
 
     /// Notification that a model stream experienced an error or disconnect
     /// and the system is handling it (e.g., retrying with backoff).
     StreamError(StreamErrorEvent),
 
+    /// The session reached `max_session_tokens` or `max_session_cost`; the
+    /// task stops before the next model request.
+    SessionBudgetExceeded(SessionBudgetExceededEvent),
+
+    /// Secrets were scrubbed from tool output before it was recorded in the
+    /// conversation history.
+    SecretsRedacted(SecretsRedactedEvent),
+
+    /// A sub-agent started by the `delegate` tool began working.
+    SubAgentBegin(SubAgentBeginEvent),
+
+    /// Progress of a running sub-agent, wrapping one of its own events.
+    SubAgent(SubAgentEvent),
+
+    /// A sub-agent finished; its final message becomes the tool output.
+    SubAgentEnd(SubAgentEndEvent),
+
     /// Notification that the agent is about to apply a code patch. Mirrors
     /// `ExecCommandBegin` so front‑ends can show progress indicators.
     PatchApplyBegin(PatchApplyBeginEvent),
@@ -491,15 +666,30 @@ pub enum EventMsg {
 
     TurnDiff(TurnDiffEvent),
 
+    /// The git working tree was snapshotted before a turn started.
+    CheckpointCreated(CheckpointCreatedEvent),
+
+    /// The workspace was restored to the checkpoint taken before a turn.
+    TurnUndone(TurnUndoneEvent),
+
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
+    /// Response to ListHistoryEntries.
+    ListHistoryEntriesResponse(ListHistoryEntriesResponseEvent),
+
     /// List of MCP tools available to the agent.
     McpListToolsResponse(McpListToolsResponseEvent),
 
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// List of resources exposed by the configured MCP servers.
+    McpListResourcesResponse(McpListResourcesResponseEvent),
+
+    /// List of prompts exposed by the configured MCP servers.
+    McpListPromptsResponse(McpListPromptsResponseEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     TurnAborted(TurnAbortedEvent),
@@ -547,6 +737,10 @@ pub struct TokenUsageInfo {
     pub total_token_usage: TokenUsage,
     pub last_token_usage: TokenUsage,
     pub model_context_window: Option<u64>,
+    /// Estimated cost of the session so far in USD. Only turns on models
+    /// with known pricing are included; `None` if there were none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_cost_usd: Option<f64>,
 }
 
 impl TokenUsageInfo {
@@ -565,6 +759,7 @@ impl TokenUsageInfo {
                 total_token_usage: TokenUsage::default(),
                 last_token_usage: TokenUsage::default(),
                 model_context_window,
+                total_cost_usd: None,
             },
         };
         if let Some(last) = last {
@@ -577,6 +772,10 @@ impl TokenUsageInfo {
         self.total_token_usage.add_assign(last);
         self.last_token_usage = last.clone();
     }
+
+    pub fn add_cost(&mut self, cost_usd: f64) {
+        self.total_cost_usd = Some(self.total_cost_usd.unwrap_or(0.0) + cost_usd);
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -784,7 +983,7 @@ pub struct AgentReasoningDeltaEvent {
     pub delta: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
 pub struct McpInvocation {
     /// Name of the MCP server as defined in the config.
     pub server: String,
@@ -947,6 +1146,10 @@ pub struct TurnContextItem {
     pub approval_policy: AskForApproval,
     pub sandbox_policy: SandboxPolicy,
     pub model: String,
+    /// Key of the model provider the turn was sent to. Changes within a task
+    /// when the turn fails over to a fallback provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_provider: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub effort: Option<ReasoningEffortConfig>,
     pub summary: ReasoningSummaryConfig,
@@ -1087,6 +1290,17 @@ pub struct ExecApprovalRequestEvent {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpToolCallApprovalRequestEvent {
+    /// Responses API call id for the associated tool call.
+    pub call_id: String,
+    /// The tool call awaiting approval.
+    pub invocation: McpInvocation,
+    /// Optional human-readable reason for the approval.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct ApplyPatchApprovalRequestEvent {
     /// Responses API call id for the associated patch apply call, if available.
@@ -1110,6 +1324,51 @@ pub struct StreamErrorEvent {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SessionBudgetExceededEvent {
+    /// Describes which limit was reached and by how much.
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SecretsRedactedEvent {
+    /// Number of secrets replaced in this turn's tool output.
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SubAgentBeginEvent {
+    /// Identifier of the `delegate` tool call that started the sub-agent.
+    pub call_id: String,
+    /// Index of the sub-agent's task within that call.
+    pub task: usize,
+    /// Conversation the sub-agent runs in.
+    pub conversation_id: ConversationId,
+    pub prompt: String,
+    pub cwd: PathBuf,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SubAgentEvent {
+    /// Identifier of the `delegate` tool call that started the sub-agent.
+    pub call_id: String,
+    /// Index of the sub-agent's task within that call.
+    pub task: usize,
+    /// Event emitted by the sub-agent's conversation.
+    pub msg: Box<EventMsg>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SubAgentEndEvent {
+    /// Identifier of the `delegate` tool call that started the sub-agent.
+    pub call_id: String,
+    /// Index of the sub-agent's task within that call.
+    pub task: usize,
+    /// Final message of the sub-agent, or why it failed.
+    pub result: Result<String, String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct PatchApplyBeginEvent {
     /// Identifier so this can be paired with the PatchApplyEnd event.
@@ -1137,6 +1396,28 @@ pub struct TurnDiffEvent {
     pub unified_diff: String,
 }
 
+/// Snapshot of the git working tree taken before a turn.
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct Checkpoint {
+    /// Commit object holding the snapshot, kept alive by a ref under
+    /// `refs/codex/checkpoints/`.
+    pub commit: String,
+    /// First line of the user message that started the turn.
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct CheckpointCreatedEvent {
+    pub checkpoint: Checkpoint,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct TurnUndoneEvent {
+    /// The checkpoint the workspace was restored to; it is removed from the
+    /// list of checkpoints.
+    pub checkpoint: Checkpoint,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct GetHistoryEntryResponseEvent {
     pub offset: usize,
@@ -1146,6 +1427,13 @@ pub struct GetHistoryEntryResponseEvent {
     pub entry: Option<HistoryEntry>,
 }
 
+/// Response payload for `Op::ListHistoryEntries`.
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct ListHistoryEntriesResponseEvent {
+    /// All parseable entries of the history file, oldest first.
+    pub entries: Vec<HistoryEntry>,
+}
+
 /// Response payload for `Op::ListMcpTools`.
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct McpListToolsResponseEvent {
@@ -1153,6 +1441,20 @@ pub struct McpListToolsResponseEvent {
     pub tools: std::collections::HashMap<String, McpTool>,
 }
 
+/// Response payload for `Op::ListMcpResources`.
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpListResourcesResponseEvent {
+    /// Server name -> resources exposed by that server.
+    pub resources: HashMap<String, Vec<McpResource>>,
+}
+
+/// Response payload for `Op::ListMcpPrompts`.
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpListPromptsResponseEvent {
+    /// Server name -> prompts exposed by that server.
+    pub prompts: HashMap<String, Vec<McpPrompt>>,
+}
+
 /// Response payload for `Op::ListCustomPrompts`.
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct ListCustomPromptsResponseEvent {
@@ -1183,6 +1485,20 @@ pub struct SessionConfiguredEvent {
     pub initial_messages: Option<Vec<EventMsg>>,
 
     pub rollout_path: PathBuf,
+
+    /// Git worktree the session runs in when worktree isolation is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<WorktreeInfo>,
+}
+
+/// A git worktree created for a conversation, on its own branch.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, TS)]
+pub struct WorktreeInfo {
+    /// Name used by `codex worktree` commands.
+    pub name: String,
+    pub branch: String,
+    /// Root of the worktree checkout.
+    pub path: PathBuf,
 }
 
 /// User's decision in response to an ExecApprovalRequest.
@@ -1264,6 +1580,7 @@ mod tests {
                 history_entry_count: 0,
                 initial_messages: None,
                 rollout_path: rollout_file.path().to_path_buf(),
+                worktree: None,
             }),
         };
 
@@ -1298,4 +1615,54 @@ mod tests {
         let deserialized: ExecCommandOutputDeltaEvent = serde_json::from_str(&serialized).unwrap();
         assert_eq!(deserialized, event);
     }
+
+    #[test]
+    fn sandbox_policy_readable_roots() {
+        let policy: SandboxPolicy = serde_json::from_str(r#"{"mode":"read-only"}"#).unwrap();
+        assert_eq!(policy, SandboxPolicy::new_read_only_policy());
+        assert!(policy.has_full_disk_read_access());
+        assert_eq!(policy.get_readable_roots_with_cwd(Path::new("/work")), None);
+
+        let policy = SandboxPolicy::new_workspace_write_policy()
+            .with_readable_roots(Some(vec![PathBuf::from("/shared")]));
+        assert!(!policy.has_full_disk_read_access());
+        assert_eq!(
+            serde_json::to_value(&policy).unwrap(),
+            json!({
+                "mode": "workspace-write",
+                "network_access": false,
+                "exclude_tmpdir_env_var": false,
+                "exclude_slash_tmp": false,
+                "readable_roots": ["/shared"],
+            })
+        );
+        let roots = policy
+            .get_readable_roots_with_cwd(Path::new("/work"))
+            .unwrap();
+        assert!(roots.contains(&PathBuf::from("/work")));
+        assert!(roots.contains(&PathBuf::from("/shared")));
+        assert!(!roots.contains(&PathBuf::from("/")));
+
+        assert_eq!(
+            SandboxPolicy::DangerFullAccess.with_readable_roots(Some(Vec::new())),
+            SandboxPolicy::DangerFullAccess
+        );
+    }
+
+    #[test]
+    fn sandbox_policy_network_allowlist() {
+        let policy: SandboxPolicy =
+            serde_json::from_str(r#"{"mode":"workspace-write","network_allowlist":["crates.io"]}"#)
+                .unwrap();
+        assert!(!policy.has_full_network_access());
+        assert_eq!(policy.network_allowlist(), ["crates.io".to_string()]);
+
+        // Full network access makes the allowlist irrelevant.
+        let policy: SandboxPolicy = serde_json::from_str(
+            r#"{"mode":"workspace-write","network_access":true,"network_allowlist":["crates.io"]}"#,
+        )
+        .unwrap();
+        assert!(policy.has_full_network_access());
+        assert!(policy.network_allowlist().is_empty());
+    }
 }
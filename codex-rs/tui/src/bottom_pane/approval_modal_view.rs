@@ -101,6 +101,8 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            keymap: crate::keymap::Keymap::default(),
+            editing_mode: codex_core::config_types::EditingMode::default(),
         });
         assert_eq!(CancellationEvent::Handled, view.on_ctrl_c(&mut pane));
         assert!(view.queue.is_empty());
@@ -1,3 +1,5 @@
+use codex_core::config_types::EditingMode;
+use codex_core::protocol::Op;
 use codex_core::protocol::TokenUsageInfo;
 use codex_protocol::num_format::format_si_suffix;
 use crossterm::event::KeyCode;
@@ -24,23 +26,30 @@ use ratatui::widgets::WidgetRef;
 use super::chat_composer_history::ChatComposerHistory;
 use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
+use super::command_popup::McpPromptEntry;
+use super::command_popup::parse_mcp_prompt_arguments;
 use super::file_search_popup::FileSearchPopup;
+use super::history_search_popup::HistorySearchPopup;
 use super::paste_burst::CharDecision;
 use super::paste_burst::PasteBurst;
 use crate::bottom_pane::paste_burst::FlushResult;
 use crate::slash_command::SlashCommand;
 use codex_protocol::custom_prompts::CustomPrompt;
+use codex_protocol::message_history::HistoryEntry;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::textarea::TextArea;
 use crate::bottom_pane::textarea::TextAreaState;
+use crate::bottom_pane::textarea::VimMode;
 use crate::clipboard_paste::normalize_pasted_path;
 use crate::clipboard_paste::pasted_image_format;
 use crate::key_hint;
+use crate::keymap::Keymap;
 use codex_file_search::FileMatch;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -55,6 +64,12 @@ const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
 pub enum InputResult {
     Submitted(String),
     Command(SlashCommand),
+    /// Send the prompt `name` exposed by the MCP server `server`.
+    McpPrompt {
+        server: String,
+        name: String,
+        arguments: Option<HashMap<String, String>>,
+    },
     None,
 }
 
@@ -86,6 +101,11 @@ pub(crate) struct ChatComposer {
     // When true, disables paste-burst logic and inserts characters immediately.
     disable_paste_burst: bool,
     custom_prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<McpPromptEntry>,
+    /// Servers whose resources can be referenced as `@server:uri`; such
+    /// tokens do not open the file search popup.
+    mcp_resource_servers: HashSet<String>,
+    keymap: Keymap,
 }
 
 /// Popup state – at most one can be visible at any time.
@@ -93,6 +113,7 @@ enum ActivePopup {
     None,
     Command(CommandPopup),
     File(FileSearchPopup),
+    HistorySearch(HistorySearchPopup),
 }
 
 const FOOTER_HINT_HEIGHT: u16 = 1;
@@ -129,18 +150,42 @@ impl ChatComposer {
             paste_burst: PasteBurst::default(),
             disable_paste_burst: false,
             custom_prompts: Vec::new(),
+            mcp_prompts: Vec::new(),
+            mcp_resource_servers: HashSet::new(),
+            keymap: Keymap::default(),
         };
         // Apply configuration via the setter to keep side-effects centralized.
         this.set_disable_paste_burst(disable_paste_burst);
         this
     }
 
+    pub(crate) fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    pub(crate) fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub(crate) fn set_editing_mode(&mut self, editing_mode: EditingMode) {
+        self.textarea
+            .set_vim_enabled(editing_mode == EditingMode::Vim);
+    }
+
+    /// True while Esc is needed to leave Vim insert mode or close the history
+    /// search, so it must not interrupt a running task or start backtracking.
+    pub(crate) fn captures_esc(&self) -> bool {
+        matches!(self.active_popup, ActivePopup::HistorySearch(_))
+            || self.textarea.vim_mode() == Some(VimMode::Insert)
+    }
+
     pub fn desired_height(&self, width: u16) -> u16 {
         self.textarea.desired_height(width - 1)
             + match &self.active_popup {
                 ActivePopup::None => FOOTER_HEIGHT_WITH_HINT,
                 ActivePopup::Command(c) => c.calculate_required_height(),
                 ActivePopup::File(c) => c.calculate_required_height(),
+                ActivePopup::HistorySearch(c) => c.calculate_required_height(),
             }
     }
 
@@ -148,6 +193,7 @@ impl ChatComposer {
         let popup_constraint = match &self.active_popup {
             ActivePopup::Command(popup) => Constraint::Max(popup.calculate_required_height()),
             ActivePopup::File(popup) => Constraint::Max(popup.calculate_required_height()),
+            ActivePopup::HistorySearch(popup) => Constraint::Max(popup.calculate_required_height()),
             ActivePopup::None => Constraint::Max(FOOTER_HEIGHT_WITH_HINT),
         };
         let [textarea_rect, _] =
@@ -194,7 +240,25 @@ impl ChatComposer {
         true
     }
 
+    /// Integrate the persistent history requested when the history search
+    /// was opened. Ignored once the search has been closed.
+    pub(crate) fn on_history_search_entries(
+        &mut self,
+        entries: Vec<HistoryEntry>,
+        repo_root: &Path,
+    ) -> bool {
+        let ActivePopup::HistorySearch(popup) = &mut self.active_popup else {
+            return false;
+        };
+        popup.set_entries(entries, repo_root);
+        true
+    }
+
     pub fn handle_paste(&mut self, pasted: String) -> bool {
+        if let ActivePopup::HistorySearch(popup) = &mut self.active_popup {
+            popup.push_query_str(&pasted.replace(['\n', '\r'], " "));
+            return true;
+        }
         let char_count = pasted.chars().count();
         if char_count > LARGE_PASTE_CHAR_THRESHOLD {
             let placeholder = format!("[Pasted Content {char_count} chars]");
@@ -323,9 +387,15 @@ impl ChatComposer {
         let result = match &mut self.active_popup {
             ActivePopup::Command(_) => self.handle_key_event_with_slash_popup(key_event),
             ActivePopup::File(_) => self.handle_key_event_with_file_popup(key_event),
+            ActivePopup::HistorySearch(_) => self.handle_key_event_with_history_search(key_event),
             ActivePopup::None => self.handle_key_event_without_popup(key_event),
         };
 
+        // The history search owns the keyboard until it is closed.
+        if matches!(self.active_popup, ActivePopup::HistorySearch(_)) {
+            return result;
+        }
+
         // Update (or hide/show) popup after processing the key.
         self.sync_command_popup();
         if matches!(self.active_popup, ActivePopup::Command(_)) {
@@ -395,6 +465,14 @@ impl ChatComposer {
                                 }
                             }
                         }
+                        CommandItem::McpPrompt(idx) => {
+                            if let Some(entry) = popup.mcp_prompt(idx) {
+                                let command = format!("/{}:{}", entry.server, entry.prompt.name);
+                                if !first_line.trim_start().starts_with(&command) {
+                                    self.textarea.set_text(&format!("{command} "));
+                                }
+                            }
+                        }
                     }
                     // After completing the command, move cursor to the end.
                     if !self.textarea.text().is_empty() {
@@ -410,8 +488,6 @@ impl ChatComposer {
                 ..
             } => {
                 if let Some(sel) = popup.selected_item() {
-                    // Clear textarea so no residual text remains.
-                    self.textarea.set_text("");
                     // Capture any needed data from popup before clearing it.
                     let prompt_content = match sel {
                         CommandItem::UserPrompt(idx) => {
@@ -419,6 +495,26 @@ impl ChatComposer {
                         }
                         _ => None,
                     };
+                    let mcp_prompt = match sel {
+                        CommandItem::McpPrompt(idx) => popup.mcp_prompt(idx).map(|entry| {
+                            // Everything after the command token holds the
+                            // prompt arguments.
+                            let first_line = self.textarea.text().lines().next().unwrap_or("");
+                            let args = first_line
+                                .trim_start()
+                                .split_once(char::is_whitespace)
+                                .map(|(_, rest)| rest)
+                                .unwrap_or("");
+                            InputResult::McpPrompt {
+                                server: entry.server.clone(),
+                                name: entry.prompt.name.clone(),
+                                arguments: parse_mcp_prompt_arguments(&entry.prompt, args),
+                            }
+                        }),
+                        _ => None,
+                    };
+                    // Clear textarea so no residual text remains.
+                    self.textarea.set_text("");
                     // Hide popup since an action has been dispatched.
                     self.active_popup = ActivePopup::None;
 
@@ -432,6 +528,9 @@ impl ChatComposer {
                             }
                             return (InputResult::None, true);
                         }
+                        CommandItem::McpPrompt(_) => {
+                            return (mcp_prompt.unwrap_or(InputResult::None), true);
+                        }
                     }
                 }
                 // Fallback to default newline handling if no command selected.
@@ -569,6 +668,69 @@ impl ChatComposer {
         }
     }
 
+    /// Open the history search, seeded with the current composer text, and
+    /// request the persistent history to search through.
+    fn open_history_search(&mut self) -> (InputResult, bool) {
+        let popup = HistorySearchPopup::new(self.textarea.text(), self.history.local_entries());
+        self.active_popup = ActivePopup::HistorySearch(popup);
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::ListHistoryEntries));
+        (InputResult::None, true)
+    }
+
+    /// Handle key events while the history search is open. Typed characters
+    /// edit the search query; the composer text only changes on accept.
+    fn handle_key_event_with_history_search(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        let ActivePopup::HistorySearch(popup) = &mut self.active_popup else {
+            unreachable!();
+        };
+        if key_event.kind == KeyEventKind::Release {
+            return (InputResult::None, false);
+        }
+
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Esc, ..
+            } => {
+                self.active_popup = ActivePopup::None;
+            }
+            KeyEvent {
+                code: KeyCode::Enter,
+                ..
+            } => {
+                if let Some(text) = popup.selected_text().map(str::to_string) {
+                    self.textarea.set_text(&text);
+                    self.textarea.set_cursor(text.len());
+                }
+                self.active_popup = ActivePopup::None;
+            }
+            KeyEvent {
+                code: KeyCode::Up, ..
+            } => popup.move_up(),
+            // Pressing the search key again steps to the next older match.
+            key_event
+                if key_event.code == KeyCode::Down
+                    || self.keymap.history_search.matches(&key_event) =>
+            {
+                popup.move_down()
+            }
+            KeyEvent {
+                code: KeyCode::Tab, ..
+            } => popup.toggle_repo_only(),
+            KeyEvent {
+                code: KeyCode::Backspace,
+                ..
+            } => popup.pop_query_char(),
+            KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+                ..
+            } => popup.push_query_str(&c.to_string()),
+            _ => return (InputResult::None, false),
+        }
+        (InputResult::None, true)
+    }
+
     fn is_image_path(path: &str) -> bool {
         let lower = path.to_ascii_lowercase();
         lower.ends_with(".png") || lower.ends_with(".jpg") || lower.ends_with(".jpeg")
@@ -721,11 +883,13 @@ impl ChatComposer {
     fn handle_key_event_without_popup(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
         match key_event {
             KeyEvent {
-                code: KeyCode::Char('d'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 ..
-            } if self.is_empty() => {
+            } if self.keymap.history_search.matches(&key_event) => self.open_history_search(),
+            KeyEvent {
+                kind: KeyEventKind::Press,
+                ..
+            } if self.keymap.quit.matches(&key_event) && self.is_empty() => {
                 self.app_event_tx.send(AppEvent::ExitRequest);
                 (InputResult::None, true)
             }
@@ -755,11 +919,7 @@ impl ChatComposer {
                 }
                 self.handle_input_basic(key_event)
             }
-            KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => {
+            key_event if self.keymap.submit.matches(&key_event) => {
                 // If we're in a paste-like burst capture, treat Enter as part of the burst
                 // and accumulate it rather than submitting or inserting immediately.
                 // Do not treat Enter as paste inside a slash-command context.
@@ -827,6 +987,9 @@ impl ChatComposer {
                 // Do not clear attached_images here; ChatWidget drains them via take_recent_submission_images().
                 (InputResult::Submitted(text), true)
             }
+            key_event if self.keymap.newline.matches(&key_event) => {
+                self.handle_input_basic(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            }
             input => self.handle_input_basic(input),
         }
     }
@@ -879,7 +1042,9 @@ impl ChatComposer {
         {
             let has_ctrl_or_alt =
                 modifiers.contains(KeyModifiers::CONTROL) || modifiers.contains(KeyModifiers::ALT);
-            if !has_ctrl_or_alt {
+            // In Vim normal mode characters are commands, not text to buffer.
+            let is_vim_command = self.textarea.vim_mode() == Some(VimMode::Normal);
+            if !has_ctrl_or_alt && !is_vim_command {
                 // Non-ASCII characters (e.g., from IMEs) can arrive in quick bursts and be
                 // misclassified by paste heuristics. Flush any active burst buffer and insert
                 // non-ASCII characters directly.
@@ -1152,6 +1317,7 @@ impl ChatComposer {
             _ => {
                 if input_starts_with_slash {
                     let mut command_popup = CommandPopup::new(self.custom_prompts.clone());
+                    command_popup.set_mcp_prompts(self.mcp_prompts.clone());
                     command_popup.on_composer_text_change(first_line.to_string());
                     self.active_popup = ActivePopup::Command(command_popup);
                 }
@@ -1166,6 +1332,17 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPromptEntry>) {
+        self.mcp_prompts = prompts.clone();
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_mcp_prompts(prompts);
+        }
+    }
+
+    pub(crate) fn set_mcp_resource_servers(&mut self, servers: HashSet<String>) {
+        self.mcp_resource_servers = servers;
+    }
+
     /// Synchronize `self.file_search_popup` with the current text in the textarea.
     /// Note this is only called when self.active_popup is NOT Command.
     fn sync_file_search_popup(&mut self) {
@@ -1184,6 +1361,15 @@ impl ChatComposer {
             return;
         }
 
+        // `@server:uri` references an MCP resource rather than a file.
+        if query
+            .split_once(':')
+            .is_some_and(|(server, _)| self.mcp_resource_servers.contains(server))
+        {
+            self.active_popup = ActivePopup::None;
+            return;
+        }
+
         if !query.is_empty() {
             self.app_event_tx
                 .send(AppEvent::StartFileSearch(query.clone()));
@@ -1230,6 +1416,9 @@ impl WidgetRef for ChatComposer {
         let (popup_constraint, hint_spacing) = match &self.active_popup {
             ActivePopup::Command(popup) => (Constraint::Max(popup.calculate_required_height()), 0),
             ActivePopup::File(popup) => (Constraint::Max(popup.calculate_required_height()), 0),
+            ActivePopup::HistorySearch(popup) => {
+                (Constraint::Max(popup.calculate_required_height()), 0)
+            }
             ActivePopup::None => (
                 Constraint::Length(FOOTER_HEIGHT_WITH_HINT),
                 FOOTER_SPACING_HEIGHT,
@@ -1244,6 +1433,9 @@ impl WidgetRef for ChatComposer {
             ActivePopup::File(popup) => {
                 popup.render_ref(popup_rect, buf);
             }
+            ActivePopup::HistorySearch(popup) => {
+                popup.render_ref(popup_rect, buf);
+            }
             ActivePopup::None => {
                 let hint_rect = if hint_spacing > 0 {
                     let [_, hint_rect] = Layout::vertical([
@@ -1255,40 +1447,53 @@ impl WidgetRef for ChatComposer {
                 } else {
                     popup_rect
                 };
-                let mut hint: Vec<Span<'static>> = if self.ctrl_c_quit_hint {
+                let keymap = &self.keymap;
+                let mut hint: Vec<Span<'static>> = Vec::new();
+                if let Some(mode) = self.textarea.vim_mode() {
+                    hint.push(" ".into());
+                    hint.push(match mode {
+                        VimMode::Normal => "NORMAL".bold(),
+                        VimMode::Insert => "INSERT".dim(),
+                    });
+                    hint.push("  ".into());
+                }
+                if self.ctrl_c_quit_hint {
                     let ctrl_c_followup = if self.is_task_running {
                         " to interrupt"
                     } else {
                         " to quit"
                     };
-                    vec![
-                        " ".into(),
-                        key_hint::ctrl('C'),
-                        " again".into(),
-                        ctrl_c_followup.into(),
-                    ]
+                    hint.push(" ".into());
+                    hint.extend(keymap.interrupt.hint());
+                    hint.push(" again".into());
+                    hint.push(ctrl_c_followup.into());
                 } else {
-                    let newline_hint_key = if self.use_shift_enter_hint {
-                        key_hint::shift('⏎')
-                    } else {
-                        key_hint::ctrl('J')
-                    };
-                    vec![
-                        " ".into(),
-                        key_hint::plain('⏎'),
-                        " send   ".into(),
-                        newline_hint_key,
-                        " newline   ".into(),
-                        key_hint::ctrl('T'),
-                        " transcript   ".into(),
-                        key_hint::ctrl('C'),
-                        " quit".into(),
-                    ]
-                };
+                    let mut first = true;
+                    for (key, label) in [
+                        (keymap.submit.hint(), " send"),
+                        (
+                            keymap.newline.hint_for_terminal(self.use_shift_enter_hint),
+                            " newline",
+                        ),
+                        (keymap.open_transcript.hint(), " transcript"),
+                        (keymap.interrupt.hint(), " quit"),
+                    ] {
+                        let Some(key) = key else {
+                            continue;
+                        };
+                        hint.push(if first { " " } else { "   " }.into());
+                        hint.push(key);
+                        hint.push(label.into());
+                        first = false;
+                    }
+                }
 
-                if !self.ctrl_c_quit_hint && self.esc_backtrack_hint {
+                if !self.ctrl_c_quit_hint
+                    && self.esc_backtrack_hint
+                    && let Some(key) = keymap.backtrack.hint()
+                {
                     hint.push("   ".into());
-                    hint.push(key_hint::plain("Esc"));
+                    hint.push(key);
                     hint.push(" edit prev".into());
                 }
 
@@ -1632,6 +1837,116 @@ mod tests {
         }
     }
 
+    #[test]
+    fn keymap_rebinds_submit_and_newline() {
+        use codex_core::config_types::KeyBindings;
+        use codex_core::config_types::TuiKeymap;
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            true,
+        );
+        composer.set_keymap(
+            Keymap::from_config(&TuiKeymap {
+                submit: Some(KeyBindings::One("ctrl+enter".to_string())),
+                newline: Some(KeyBindings::One("enter".to_string())),
+                ..Default::default()
+            })
+            .expect("valid keymap"),
+        );
+
+        composer.handle_paste("hello".to_string());
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(result, InputResult::None);
+        assert_eq!(composer.textarea.text(), "hello\n");
+
+        composer.handle_paste("world".to_string());
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL));
+        assert_eq!(result, InputResult::Submitted("hello\nworld".to_string()));
+    }
+
+    #[test]
+    fn history_search_replaces_composer_text_on_accept() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        composer.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::CodexOp(Op::ListHistoryEntries))
+        ));
+        assert!(composer.captures_esc());
+
+        let entry = |text: &str| HistoryEntry {
+            conversation_id: "c".to_string(),
+            ts: 0,
+            text: text.to_string(),
+            cwd: None,
+        };
+        assert!(composer.on_history_search_entries(
+            vec![entry("refactor the parser"), entry("run the tests")],
+            Path::new("/repo"),
+        ));
+        for c in "parse".chars() {
+            composer.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        // Typing edits the search query, not the composer.
+        assert_eq!(composer.textarea.text(), "");
+
+        composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(composer.textarea.text(), "refactor the parser");
+        assert!(!composer.popup_active());
+    }
+
+    #[test]
+    fn vim_insert_mode_captures_esc() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+        assert!(!composer.captures_esc());
+
+        composer.set_editing_mode(EditingMode::Vim);
+        assert!(composer.captures_esc());
+        composer.handle_paste("ab".to_string());
+        composer.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(!composer.captures_esc());
+
+        // In normal mode characters are commands, even when typed quickly.
+        composer.handle_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        assert_eq!(composer.textarea.text(), "a");
+    }
+
     #[test]
     fn handle_paste_large_uses_placeholder_and_replaces_on_submit() {
         use crossterm::event::KeyCode;
@@ -1801,7 +2116,7 @@ mod tests {
                 Some(CommandItem::Builtin(cmd)) => {
                     assert_eq!(cmd.command(), "model")
                 }
-                Some(CommandItem::UserPrompt(_)) => {
+                Some(CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_)) => {
                     panic!("unexpected prompt selected for '/mo'")
                 }
                 None => panic!("no selected command for '/mo'"),
@@ -69,6 +69,11 @@ impl ChatComposerHistory {
         self.last_history_text = None;
     }
 
+    /// Messages submitted during this UI session, oldest first.
+    pub fn local_entries(&self) -> &[String] {
+        &self.local_history
+    }
+
     /// Should Up/Down key presses be interpreted as history navigation given
     /// the current content and cursor position of `textarea`?
     pub fn should_handle_navigation(&self, text: &str, cursor: usize) -> bool {
@@ -0,0 +1,291 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use codex_common::fuzzy_match::fuzzy_match;
+use codex_protocol::message_history::HistoryEntry;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Widget;
+use ratatui::widgets::WidgetRef;
+
+use super::popup_consts::MAX_POPUP_ROWS;
+use super::scroll_state::ScrollState;
+use super::selection_popup_common::GenericDisplayRow;
+use super::selection_popup_common::render_rows;
+use crate::key_hint;
+
+/// Long prompts are cut off when displayed; matching still sees the full text.
+const MAX_DISPLAY_CHARS: usize = 200;
+
+struct SearchEntry {
+    text: String,
+    /// `text` flattened onto a single line for matching and display.
+    display: String,
+    /// True when the entry was recorded in the current repository.
+    in_repo: bool,
+}
+
+struct HistoryMatch {
+    entry_idx: usize,
+    indices: Vec<usize>,
+}
+
+/// Reverse-incremental search over the persistent cross-session message
+/// history, opened with Ctrl-R in the composer.
+pub(crate) struct HistorySearchPopup {
+    query: String,
+    /// Entries newest first, without duplicates.
+    entries: Vec<SearchEntry>,
+    /// Prompts submitted during this session, newest last. Kept so they can be
+    /// merged with the persistent history once it arrives.
+    local_entries: Vec<String>,
+    /// When `true` we are still waiting for the persistent history.
+    waiting: bool,
+    /// Only show entries recorded in the current repository.
+    repo_only: bool,
+    matches: Vec<HistoryMatch>,
+    state: ScrollState,
+}
+
+impl HistorySearchPopup {
+    pub(crate) fn new(query: &str, local_entries: &[String]) -> Self {
+        let mut popup = Self {
+            query: query.to_string(),
+            entries: Vec::new(),
+            local_entries: local_entries.to_vec(),
+            waiting: true,
+            repo_only: false,
+            matches: Vec::new(),
+            state: ScrollState::new(),
+        };
+        popup.rebuild_entries(Vec::new(), Path::new(""));
+        popup
+    }
+
+    /// Merge the persistent history (oldest first) into the search list.
+    /// Entries recorded under `repo_root` count as belonging to this repo.
+    pub(crate) fn set_entries(&mut self, entries: Vec<HistoryEntry>, repo_root: &Path) {
+        self.waiting = false;
+        self.rebuild_entries(entries, repo_root);
+    }
+
+    fn rebuild_entries(&mut self, entries: Vec<HistoryEntry>, repo_root: &Path) {
+        let persistent = entries.into_iter().rev().map(|entry| {
+            let in_repo = entry
+                .cwd
+                .as_deref()
+                .is_some_and(|cwd| cwd.starts_with(repo_root));
+            (entry.text, in_repo)
+        });
+        // Prompts from this session are newer than anything in the file.
+        let local = self
+            .local_entries
+            .iter()
+            .rev()
+            .map(|text| (text.clone(), true));
+
+        let mut seen = HashSet::new();
+        self.entries = local
+            .chain(persistent)
+            .filter(|(text, _)| seen.insert(text.clone()))
+            .map(|(text, in_repo)| SearchEntry {
+                display: text.replace(['\n', '\r', '\t'], " "),
+                text,
+                in_repo,
+            })
+            .collect();
+        self.refilter();
+    }
+
+    pub(crate) fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub(crate) fn push_query_str(&mut self, text: &str) {
+        self.query.push_str(text);
+        self.refilter();
+    }
+
+    pub(crate) fn pop_query_char(&mut self) {
+        self.query.pop();
+        self.refilter();
+    }
+
+    pub(crate) fn toggle_repo_only(&mut self) {
+        self.repo_only = !self.repo_only;
+        self.refilter();
+    }
+
+    fn refilter(&mut self) {
+        let mut matches: Vec<(HistoryMatch, i32)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !self.repo_only || entry.in_repo)
+            .filter_map(|(entry_idx, entry)| {
+                fuzzy_match(&entry.display, &self.query)
+                    .map(|(indices, score)| (HistoryMatch { entry_idx, indices }, score))
+            })
+            .collect();
+        // Best score first; the stable sort keeps newer entries ahead on ties.
+        matches.sort_by_key(|(_, score)| *score);
+        self.matches = matches.into_iter().map(|(m, _)| m).collect();
+        self.state.reset();
+        let len = self.matches.len();
+        self.state.clamp_selection(len);
+        self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
+    }
+
+    /// Move selection towards newer (better) matches.
+    pub(crate) fn move_up(&mut self) {
+        let len = self.matches.len();
+        self.state.move_up_wrap(len);
+        self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
+    }
+
+    /// Move selection towards older (worse) matches.
+    pub(crate) fn move_down(&mut self) {
+        let len = self.matches.len();
+        self.state.move_down_wrap(len);
+        self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
+    }
+
+    pub(crate) fn selected_text(&self) -> Option<&str> {
+        self.state
+            .selected_idx
+            .and_then(|idx| self.matches.get(idx))
+            .map(|m| self.entries[m.entry_idx].text.as_str())
+    }
+
+    pub(crate) fn calculate_required_height(&self) -> u16 {
+        // One header row plus the matches (or a single "no matches" row).
+        1 + self.matches.len().clamp(1, MAX_POPUP_ROWS) as u16
+    }
+}
+
+impl WidgetRef for &HistorySearchPopup {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let [header_area, rows_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
+
+        let scope = if self.repo_only {
+            "this repo"
+        } else {
+            "all sessions"
+        };
+        Line::from(vec![
+            " search history".dim(),
+            format!(" ({scope}): ").dim(),
+            self.query.clone().into(),
+            "   ".into(),
+            key_hint::plain("Tab"),
+            " scope".dim(),
+        ])
+        .render(header_area, buf);
+
+        let rows_all: Vec<GenericDisplayRow> = self
+            .matches
+            .iter()
+            .map(|m| GenericDisplayRow {
+                name: self.entries[m.entry_idx]
+                    .display
+                    .chars()
+                    .take(MAX_DISPLAY_CHARS)
+                    .collect(),
+                match_indices: Some(m.indices.clone()),
+                is_current: false,
+                description: None,
+            })
+            .collect();
+
+        let empty_message = if self.waiting && self.entries.is_empty() {
+            "loading..."
+        } else {
+            "no matches"
+        };
+
+        render_rows(
+            rows_area,
+            buf,
+            &rows_all,
+            &self.state,
+            MAX_POPUP_ROWS,
+            false,
+            empty_message,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn entry(text: &str, cwd: Option<&str>) -> HistoryEntry {
+        HistoryEntry {
+            conversation_id: "c".to_string(),
+            ts: 0,
+            text: text.to_string(),
+            cwd: cwd.map(PathBuf::from),
+        }
+    }
+
+    fn match_texts(popup: &HistorySearchPopup) -> Vec<&str> {
+        popup
+            .matches
+            .iter()
+            .map(|m| popup.entries[m.entry_idx].text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn lists_newest_first_without_duplicates() {
+        let mut popup = HistorySearchPopup::new("", &["local prompt".to_string()]);
+        assert_eq!(match_texts(&popup), vec!["local prompt"]);
+
+        popup.set_entries(
+            vec![
+                entry("fix the tests", Some("/repo")),
+                entry("local prompt", Some("/repo")),
+                entry("write docs", None),
+            ],
+            Path::new("/repo"),
+        );
+        assert_eq!(
+            match_texts(&popup),
+            vec!["local prompt", "write docs", "fix the tests"]
+        );
+        assert_eq!(popup.selected_text(), Some("local prompt"));
+    }
+
+    #[test]
+    fn fuzzy_query_and_repo_filter() {
+        let mut popup = HistorySearchPopup::new("ft", &[]);
+        popup.set_entries(
+            vec![
+                entry("fix the tests", Some("/repo/sub")),
+                entry("fmt\nthen commit", Some("/elsewhere")),
+                entry("write docs", Some("/repo")),
+            ],
+            Path::new("/repo"),
+        );
+        assert_eq!(
+            match_texts(&popup),
+            vec!["fmt\nthen commit", "fix the tests"]
+        );
+
+        popup.toggle_repo_only();
+        assert_eq!(match_texts(&popup), vec!["fix the tests"]);
+
+        popup.pop_query_char();
+        popup.pop_query_char();
+        assert_eq!(match_texts(&popup), vec!["write docs", "fix the tests"]);
+        popup.push_query_str("zzz");
+        assert_eq!(popup.selected_text(), None);
+    }
+}
@@ -1,10 +1,14 @@
 //! Bottom pane: shows the ChatComposer or a BottomPaneView, if one is active.
+use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
 
 use crate::app_event_sender::AppEventSender;
+use crate::keymap::Keymap;
 use crate::tui::FrameRequester;
 use crate::user_approval_widget::ApprovalRequest;
 use bottom_pane_view::BottomPaneView;
+use codex_core::config_types::EditingMode;
 use codex_core::protocol::TokenUsageInfo;
 use codex_file_search::FileMatch;
 use crossterm::event::KeyEvent;
@@ -21,6 +25,7 @@ mod chat_composer;
 mod chat_composer_history;
 mod command_popup;
 mod file_search_popup;
+mod history_search_popup;
 mod list_selection_view;
 mod paste_burst;
 mod popup_consts;
@@ -37,6 +42,8 @@ pub(crate) enum CancellationEvent {
 pub(crate) use chat_composer::ChatComposer;
 pub(crate) use chat_composer::InputResult;
 use codex_protocol::custom_prompts::CustomPrompt;
+use codex_protocol::message_history::HistoryEntry;
+pub(crate) use command_popup::McpPromptEntry;
 
 use crate::status_indicator_widget::StatusIndicatorWidget;
 use approval_modal_view::ApprovalModalView;
@@ -73,20 +80,25 @@ pub(crate) struct BottomPaneParams {
     pub(crate) enhanced_keys_supported: bool,
     pub(crate) placeholder_text: String,
     pub(crate) disable_paste_burst: bool,
+    pub(crate) keymap: Keymap,
+    pub(crate) editing_mode: EditingMode,
 }
 
 impl BottomPane {
     const BOTTOM_PAD_LINES: u16 = 1;
     pub fn new(params: BottomPaneParams) -> Self {
         let enhanced_keys_supported = params.enhanced_keys_supported;
+        let mut composer = ChatComposer::new(
+            params.has_input_focus,
+            params.app_event_tx.clone(),
+            enhanced_keys_supported,
+            params.placeholder_text,
+            params.disable_paste_burst,
+        );
+        composer.set_keymap(params.keymap);
+        composer.set_editing_mode(params.editing_mode);
         Self {
-            composer: ChatComposer::new(
-                params.has_input_focus,
-                params.app_event_tx.clone(),
-                enhanced_keys_supported,
-                params.placeholder_text,
-                params.disable_paste_burst,
-            ),
+            composer,
             active_view: None,
             app_event_tx: params.app_event_tx,
             frame_requester: params.frame_requester,
@@ -171,6 +183,7 @@ impl BottomPane {
             // If a task is running and a status line is visible, allow Esc to
             // send an interrupt even while the composer has focus.
             if matches!(key_event.code, crossterm::event::KeyCode::Esc)
+                && !self.composer.captures_esc()
                 && self.is_task_running
                 && let Some(status) = &self.status
             {
@@ -351,10 +364,32 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// Update MCP server prompts available for the slash popup.
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPromptEntry>) {
+        self.composer.set_mcp_prompts(prompts);
+        self.request_redraw();
+    }
+
+    /// Update the MCP servers whose resources can be attached as
+    /// `@server:uri`.
+    pub(crate) fn set_mcp_resource_servers(&mut self, servers: HashSet<String>) {
+        self.composer.set_mcp_resource_servers(servers);
+    }
+
     pub(crate) fn composer_is_empty(&self) -> bool {
         self.composer.is_empty()
     }
 
+    pub(crate) fn keymap(&self) -> &Keymap {
+        self.composer.keymap()
+    }
+
+    /// True when Esc belongs to the composer (leaving Vim insert mode)
+    /// rather than to interrupts or backtracking.
+    pub(crate) fn composer_captures_esc(&self) -> bool {
+        self.active_view.is_none() && self.composer.captures_esc()
+    }
+
     pub(crate) fn is_task_running(&self) -> bool {
         self.is_task_running
     }
@@ -366,6 +401,17 @@ impl BottomPane {
         !self.is_task_running && self.active_view.is_none() && !self.composer.popup_active()
     }
 
+    /// Deliver the persistent history to an open history search.
+    pub(crate) fn on_history_search_entries(
+        &mut self,
+        entries: Vec<HistoryEntry>,
+        repo_root: &Path,
+    ) {
+        if self.composer.on_history_search_entries(entries, repo_root) {
+            self.request_redraw();
+        }
+    }
+
     /// Update the *context-window remaining* indicator in the composer. This
     /// is forwarded directly to the underlying `ChatComposer`.
     pub(crate) fn set_token_usage(&mut self, token_info: Option<TokenUsageInfo>) {
@@ -519,6 +565,8 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            keymap: Keymap::default(),
+            editing_mode: EditingMode::default(),
         });
         pane.push_approval_request(exec_request());
         assert_eq!(CancellationEvent::Handled, pane.on_ctrl_c());
@@ -539,6 +587,8 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            keymap: Keymap::default(),
+            editing_mode: EditingMode::default(),
         });
 
         // Create an approval modal (active view).
@@ -570,6 +620,8 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            keymap: Keymap::default(),
+            editing_mode: EditingMode::default(),
         });
 
         // Start a running task so the status indicator is active above the composer.
@@ -638,6 +690,8 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            keymap: Keymap::default(),
+            editing_mode: EditingMode::default(),
         });
 
         // Begin a task: show initial status.
@@ -669,6 +723,8 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            keymap: Keymap::default(),
+            editing_mode: EditingMode::default(),
         });
 
         // Activate spinner (status view replaces composer) with no live ring.
@@ -720,6 +776,8 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            keymap: Keymap::default(),
+            editing_mode: EditingMode::default(),
         });
 
         pane.set_task_running(true);
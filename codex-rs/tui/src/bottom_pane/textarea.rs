@@ -26,6 +26,22 @@ pub(crate) struct TextArea {
     wrap_cache: RefCell<Option<WrapCache>>,
     preferred_col: Option<usize>,
     elements: Vec<TextElement>,
+    /// Vim-style modal editing state; `None` when Emacs-style editing is used.
+    vim: Option<VimState>,
+}
+
+/// Mode of the optional Vim-style modal editing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VimMode {
+    Insert,
+    Normal,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct VimState {
+    mode: VimMode,
+    /// First key of a two-key command such as `dd` or `gg`.
+    pending: Option<char>,
 }
 
 #[derive(Debug, Clone)]
@@ -48,9 +64,23 @@ impl TextArea {
             wrap_cache: RefCell::new(None),
             preferred_col: None,
             elements: Vec::new(),
+            vim: None,
         }
     }
 
+    /// Enable or disable Vim-style modal editing. Editing starts in insert mode.
+    pub fn set_vim_enabled(&mut self, enabled: bool) {
+        self.vim = enabled.then_some(VimState {
+            mode: VimMode::Insert,
+            pending: None,
+        });
+    }
+
+    /// The current Vim mode, or `None` when Vim-style editing is disabled.
+    pub fn vim_mode(&self) -> Option<VimMode> {
+        self.vim.map(|vim| vim.mode)
+    }
+
     pub fn set_text(&mut self, text: &str) {
         self.text = text.to_string();
         self.cursor_pos = self.cursor_pos.clamp(0, self.text.len());
@@ -203,6 +233,9 @@ impl TextArea {
     }
 
     pub fn input(&mut self, event: KeyEvent) {
+        if self.handle_vim_input(event) {
+            return;
+        }
         match event {
             // Some terminals (or configurations) send Control key chords as
             // C0 control characters without reporting the CONTROL modifier.
@@ -406,6 +439,176 @@ impl TextArea {
         }
     }
 
+    // ####### Vim Mode #######
+
+    /// Handle `event` according to the current Vim mode. Returns false when
+    /// the key should get the regular (insert mode) handling instead.
+    fn handle_vim_input(&mut self, event: KeyEvent) -> bool {
+        let Some(vim) = self.vim else {
+            return false;
+        };
+        match vim.mode {
+            VimMode::Insert => {
+                if event.code != KeyCode::Esc || event.modifiers != KeyModifiers::NONE {
+                    return false;
+                }
+                // Like Vim, leaving insert mode steps back onto the last
+                // inserted character.
+                if self.cursor_pos > self.beginning_of_current_line() {
+                    self.move_cursor_left();
+                }
+                self.set_vim_state(VimMode::Normal, None);
+                true
+            }
+            VimMode::Normal => {
+                let KeyEvent {
+                    code: KeyCode::Char(c),
+                    modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+                    ..
+                } = event
+                else {
+                    // Arrow keys, Backspace etc. keep their usual meaning.
+                    self.set_vim_state(VimMode::Normal, None);
+                    return false;
+                };
+                self.set_vim_state(VimMode::Normal, None);
+                self.vim_normal_command(vim.pending, c);
+                true
+            }
+        }
+    }
+
+    fn set_vim_state(&mut self, mode: VimMode, pending: Option<char>) {
+        if self.vim.is_some() {
+            self.vim = Some(VimState { mode, pending });
+        }
+    }
+
+    fn vim_normal_command(&mut self, pending: Option<char>, c: char) {
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        match (pending, c) {
+            (Some('g'), 'g') => self.set_cursor(0),
+            (Some('d'), 'd') => self.delete_current_line(),
+            // Unknown two-key commands are dropped, as in Vim.
+            (Some(_), _) => {}
+            (None, 'g' | 'd') => self.set_vim_state(VimMode::Normal, Some(c)),
+            (None, 'h') => {
+                if self.cursor_pos > bol {
+                    self.move_cursor_left();
+                }
+            }
+            (None, 'l') => {
+                if self.next_atomic_boundary(self.cursor_pos) < eol {
+                    self.move_cursor_right();
+                }
+            }
+            (None, 'j') => self.move_cursor_down(),
+            (None, 'k') => self.move_cursor_up(),
+            (None, '0') => self.set_cursor(bol),
+            (None, '^') => self.set_cursor(self.first_non_blank_of_current_line()),
+            (None, '$') => self.set_cursor(self.last_char_of_line(bol, eol)),
+            (None, 'w') => self.set_cursor(self.start_of_next_word()),
+            (None, 'b') => self.set_cursor(self.beginning_of_previous_word()),
+            (None, 'e') => {
+                self.move_cursor_right();
+                let end = self.end_of_next_word();
+                self.set_cursor(self.prev_atomic_boundary(end));
+            }
+            (None, 'G') => self.set_cursor(self.beginning_of_line(self.text.len())),
+            (None, 'x') => {
+                if self.cursor_pos < eol {
+                    self.delete_forward(1);
+                }
+                let eol = self.end_of_current_line();
+                self.set_cursor(self.last_char_of_line(bol, eol).min(self.cursor_pos));
+            }
+            (None, 'X') => {
+                if self.cursor_pos > bol {
+                    self.delete_backward(1);
+                }
+            }
+            (None, 'D') => {
+                self.replace_range(self.cursor_pos..eol, "");
+                let eol = self.end_of_current_line();
+                self.set_cursor(self.last_char_of_line(bol, eol));
+            }
+            (None, 'C') => {
+                self.replace_range(self.cursor_pos..eol, "");
+                self.set_vim_state(VimMode::Insert, None);
+            }
+            (None, 'i') => self.set_vim_state(VimMode::Insert, None),
+            (None, 'a') => {
+                if self.cursor_pos < eol {
+                    self.move_cursor_right();
+                }
+                self.set_vim_state(VimMode::Insert, None);
+            }
+            (None, 'I') => {
+                self.set_cursor(self.first_non_blank_of_current_line());
+                self.set_vim_state(VimMode::Insert, None);
+            }
+            (None, 'A') => {
+                self.set_cursor(eol);
+                self.set_vim_state(VimMode::Insert, None);
+            }
+            (None, 'o') => {
+                self.set_cursor(eol);
+                self.insert_str("\n");
+                self.set_vim_state(VimMode::Insert, None);
+            }
+            (None, 'O') => {
+                self.set_cursor(bol);
+                self.insert_str("\n");
+                self.set_cursor(bol);
+                self.set_vim_state(VimMode::Insert, None);
+            }
+            (None, _) => {}
+        }
+    }
+
+    /// Position of the last character on the line `bol..eol`, where the
+    /// cursor rests in normal mode.
+    fn last_char_of_line(&self, bol: usize, eol: usize) -> usize {
+        if eol > bol {
+            self.prev_atomic_boundary(eol).max(bol)
+        } else {
+            bol
+        }
+    }
+
+    fn first_non_blank_of_current_line(&self) -> usize {
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        self.text[bol..eol]
+            .find(|c: char| !c.is_whitespace())
+            .map_or(eol, |i| bol + i)
+    }
+
+    fn start_of_next_word(&self) -> usize {
+        let rest = &self.text[self.cursor_pos..];
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let next = rest[word_end..]
+            .find(|c: char| !c.is_whitespace())
+            .map_or(rest.len(), |i| word_end + i);
+        self.adjust_pos_out_of_elements(self.cursor_pos + next, false)
+    }
+
+    /// Delete the current line (Vim `dd`) and move to the start of the line
+    /// that takes its place.
+    fn delete_current_line(&mut self) {
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        let range = if eol < self.text.len() {
+            bol..eol + 1
+        } else {
+            bol.saturating_sub(1)..eol
+        };
+        let start = range.start;
+        self.replace_range(range, "");
+        self.set_cursor(self.beginning_of_line(start.min(self.text.len())));
+    }
+
     // ####### Input Functions #######
     pub fn delete_backward(&mut self, n: usize) {
         if n == 0 || self.cursor_pos == 0 {
@@ -1817,4 +2020,78 @@ mod tests {
             }
         }
     }
+
+    fn vim_with(text: &str) -> TextArea {
+        let mut t = ta_with(text);
+        t.set_vim_enabled(true);
+        t
+    }
+
+    fn press(t: &mut TextArea, keys: &str) {
+        for c in keys.chars() {
+            t.input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn vim_esc_enters_normal_mode_and_keys_become_commands() {
+        let mut t = vim_with("hello world");
+        assert_eq!(t.vim_mode(), Some(VimMode::Insert));
+        t.input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(t.vim_mode(), Some(VimMode::Normal));
+        // Cursor steps back onto the last character.
+        assert_eq!(t.cursor(), 10);
+
+        press(&mut t, "0");
+        assert_eq!(t.cursor(), 0);
+        press(&mut t, "w");
+        assert_eq!(t.cursor(), 6);
+        press(&mut t, "e");
+        assert_eq!(t.cursor(), 10);
+        press(&mut t, "b");
+        assert_eq!(t.cursor(), 6);
+        press(&mut t, "x");
+        assert_eq!(t.text(), "hello orld");
+        press(&mut t, "$");
+        assert_eq!(t.cursor(), 9);
+        assert_eq!(t.vim_mode(), Some(VimMode::Normal));
+
+        press(&mut t, "A!");
+        assert_eq!(t.text(), "hello orld!");
+        assert_eq!(t.vim_mode(), Some(VimMode::Insert));
+    }
+
+    #[test]
+    fn vim_line_commands() {
+        let mut t = vim_with("one\ntwo\nthree");
+        t.input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        press(&mut t, "gg");
+        assert_eq!(t.cursor(), 0);
+        press(&mut t, "j");
+        press(&mut t, "dd");
+        assert_eq!(t.text(), "one\nthree");
+        assert_eq!(t.cursor(), 4);
+
+        press(&mut t, "Onew");
+        assert_eq!(t.text(), "one\nnew\nthree");
+        t.input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        press(&mut t, "G");
+        assert_eq!(t.cursor(), 8);
+        press(&mut t, "lD");
+        assert_eq!(t.text(), "one\nnew\nt");
+        assert_eq!(t.cursor(), 8);
+
+        // Unknown commands are ignored rather than inserted.
+        press(&mut t, "zq");
+        assert_eq!(t.text(), "one\nnew\nt");
+    }
+
+    #[test]
+    fn vim_disabled_treats_esc_and_chars_normally() {
+        let mut t = ta_with("hi");
+        t.input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        press(&mut t, "x");
+        assert_eq!(t.text(), "hix");
+        assert_eq!(t.vim_mode(), None);
+    }
 }
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use codex_core::config::Config;
+use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::AgentMessageDeltaEvent;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
@@ -12,6 +13,8 @@ use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::Checkpoint;
+use codex_core::protocol::CheckpointCreatedEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
@@ -21,17 +24,27 @@ use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::InputMessageKind;
 use codex_core::protocol::ListCustomPromptsResponseEvent;
+use codex_core::protocol::ListHistoryEntriesResponseEvent;
+use codex_core::protocol::McpListPromptsResponseEvent;
+use codex_core::protocol::McpListResourcesResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
+use codex_core::protocol::McpToolCallApprovalRequestEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::SecretsRedactedEvent;
+use codex_core::protocol::SessionBudgetExceededEvent;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
+use codex_core::protocol::SubAgentEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TokenUsageInfo;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnUndoneEvent;
 use codex_core::protocol::UserMessageEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
@@ -40,6 +53,7 @@ use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::event::KeyModifiers;
+use mcp_types::Resource as McpResource;
 use rand::Rng;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Constraint;
@@ -56,6 +70,7 @@ use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
 use crate::bottom_pane::CancellationEvent;
 use crate::bottom_pane::InputResult;
+use crate::bottom_pane::McpPromptEntry;
 use crate::bottom_pane::SelectionAction;
 use crate::bottom_pane::SelectionItem;
 use crate::clipboard_paste::paste_image_to_temp_png;
@@ -65,6 +80,7 @@ use crate::history_cell::CommandOutput;
 use crate::history_cell::ExecCell;
 use crate::history_cell::HistoryCell;
 use crate::history_cell::PatchEventType;
+use crate::keymap::Keymap;
 use crate::slash_command::SlashCommand;
 use crate::tui::FrameRequester;
 // streaming internals are provided by crate::streaming and crate::markdown_stream
@@ -130,6 +146,11 @@ pub(crate) struct ChatWidget {
     suppress_session_configured_redraw: bool,
     // User messages queued while a turn is in progress
     queued_user_messages: VecDeque<UserMessage>,
+    // Resources exposed by MCP servers, keyed by server name. Used to turn
+    // `@server:uri` mentions into resource attachments.
+    mcp_resources: HashMap<String, Vec<McpResource>>,
+    // Checkpoints of turns that can still be undone, oldest first.
+    checkpoints: Vec<Checkpoint>,
 }
 
 struct UserMessage {
@@ -164,6 +185,14 @@ impl ChatWidget {
         self.bottom_pane
             .set_history_metadata(event.history_log_id, event.history_entry_count);
         self.conversation_id = Some(event.session_id);
+        // The session runs in its worktree; follow it so diffs and `/status`
+        // show the files the agent is actually editing.
+        if let Some(worktree) = &event.worktree
+            && let Some(repo_root) = get_git_repo_root(&self.config.cwd)
+            && let Ok(relative) = self.config.cwd.strip_prefix(&repo_root)
+        {
+            self.config.cwd = worktree.path.join(relative);
+        }
         let initial_messages = event.initial_messages.clone();
         if let Some(messages) = initial_messages {
             self.replay_initial_messages(messages);
@@ -175,6 +204,10 @@ impl ChatWidget {
         ));
         // Ask codex-core to enumerate custom prompts for this session.
         self.submit_op(Op::ListCustomPrompts);
+        if !self.config.mcp_servers.is_empty() {
+            self.submit_op(Op::ListMcpPrompts);
+            self.submit_op(Op::ListMcpResources);
+        }
         if let Some(user_message) = self.initial_user_message.take() {
             self.submit_user_message(user_message);
         }
@@ -332,6 +365,19 @@ impl ChatWidget {
         );
     }
 
+    fn on_mcp_tool_call_approval_request(
+        &mut self,
+        id: String,
+        ev: McpToolCallApprovalRequestEvent,
+    ) {
+        let id2 = id.clone();
+        let ev2 = ev.clone();
+        self.defer_or_handle(
+            |q| q.push_mcp_tool_call_approval(id, ev),
+            |s| s.handle_mcp_tool_call_approval_now(id2, ev2),
+        );
+    }
+
     fn on_exec_command_begin(&mut self, ev: ExecCommandBeginEvent) {
         self.flush_answer_stream_with_separator();
         let ev2 = ev.clone();
@@ -403,6 +449,15 @@ impl ChatWidget {
             .on_history_entry_response(log_id, offset, entry.map(|e| e.text));
     }
 
+    fn on_list_history_entries(&mut self, ev: ListHistoryEntriesResponseEvent) {
+        // The history search treats prompts recorded anywhere in this
+        // repository as local, not just those from the exact cwd.
+        let repo_root =
+            get_git_repo_root(&self.config.cwd).unwrap_or_else(|| self.config.cwd.clone());
+        self.bottom_pane
+            .on_history_search_entries(ev.entries, &repo_root);
+    }
+
     fn on_shutdown_complete(&mut self) {
         self.app_event_tx.send(AppEvent::ExitRequest);
     }
@@ -420,6 +475,54 @@ impl ChatWidget {
         self.add_to_history(history_cell::new_stream_error_event(message));
         self.request_redraw();
     }
+    fn on_session_budget_exceeded(&mut self, message: String) {
+        self.add_to_history(history_cell::new_session_budget_exceeded_event(message));
+        self.request_redraw();
+    }
+
+    fn on_turn_undone(&mut self, checkpoint: Checkpoint) {
+        self.checkpoints
+            .retain(|existing| existing.commit != checkpoint.commit);
+        self.add_to_history(history_cell::new_turn_undone_event(&checkpoint));
+        self.request_redraw();
+    }
+
+    fn on_secrets_redacted(&mut self, count: usize) {
+        self.add_to_history(history_cell::new_secrets_redacted_event(count));
+        self.request_redraw();
+    }
+
+    fn on_sub_agent_begin(&mut self, ev: SubAgentBeginEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_sub_agent_begin(
+            ev.task, &ev.prompt, &ev.cwd, &ev.model,
+        ));
+        self.request_redraw();
+    }
+
+    /// Sub-agents run many turns of their own; only the commands they run and
+    /// their errors are surfaced so the parent transcript stays readable.
+    fn on_sub_agent_event(&mut self, ev: SubAgentEvent) {
+        match *ev.msg {
+            EventMsg::ExecCommandBegin(begin) => {
+                self.add_to_history(history_cell::new_sub_agent_exec(ev.task, &begin.command));
+            }
+            EventMsg::Error(ErrorEvent { message }) => {
+                self.add_to_history(history_cell::new_error_event(format!(
+                    "sub-agent {}: {message}",
+                    ev.task
+                )));
+            }
+            _ => return,
+        }
+        self.request_redraw();
+    }
+
+    fn on_sub_agent_end(&mut self, ev: SubAgentEndEvent) {
+        self.add_to_history(history_cell::new_sub_agent_end(ev.task, &ev.result, 80));
+        self.request_redraw();
+    }
+
     /// Periodic tick to commit at most one queued line to history with a small delay,
     /// animating the output.
     pub(crate) fn on_commit_tick(&mut self) {
@@ -554,6 +657,23 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    pub(crate) fn handle_mcp_tool_call_approval_now(
+        &mut self,
+        id: String,
+        ev: McpToolCallApprovalRequestEvent,
+    ) {
+        self.flush_answer_stream_with_separator();
+
+        let request = ApprovalRequest::McpToolCall {
+            id,
+            server: ev.invocation.server,
+            tool: ev.invocation.tool,
+            reason: ev.reason,
+        };
+        self.bottom_pane.push_approval_request(request);
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_exec_begin_now(&mut self, ev: ExecCommandBeginEvent) {
         // Ensure the status indicator is visible while the command runs.
         self.running_commands.insert(
@@ -648,6 +768,8 @@ impl ChatWidget {
                 enhanced_keys_supported,
                 placeholder_text: placeholder,
                 disable_paste_burst: config.disable_paste_burst,
+                keymap: Keymap::from_config(&config.tui.keymap).unwrap_or_default(),
+                editing_mode: config.tui.editing_mode,
             }),
             active_exec_cell: None,
             config: config.clone(),
@@ -664,6 +786,8 @@ impl ChatWidget {
             full_reasoning_buffer: String::new(),
             conversation_id: None,
             queued_user_messages: VecDeque::new(),
+            mcp_resources: HashMap::new(),
+            checkpoints: Vec::new(),
             show_welcome_banner: true,
             suppress_session_configured_redraw: false,
         }
@@ -700,6 +824,8 @@ impl ChatWidget {
                 enhanced_keys_supported,
                 placeholder_text: placeholder,
                 disable_paste_burst: config.disable_paste_burst,
+                keymap: Keymap::from_config(&config.tui.keymap).unwrap_or_default(),
+                editing_mode: config.tui.editing_mode,
             }),
             active_exec_cell: None,
             config: config.clone(),
@@ -716,6 +842,8 @@ impl ChatWidget {
             full_reasoning_buffer: String::new(),
             conversation_id: None,
             queued_user_messages: VecDeque::new(),
+            mcp_resources: HashMap::new(),
+            checkpoints: Vec::new(),
             show_welcome_banner: false,
             suppress_session_configured_redraw: true,
         }
@@ -732,20 +860,16 @@ impl ChatWidget {
     pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) {
         match key_event {
             KeyEvent {
-                code: KeyCode::Char('c'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 ..
-            } => {
+            } if self.keymap().interrupt.matches(&key_event) => {
                 self.on_ctrl_c();
                 return;
             }
             KeyEvent {
-                code: KeyCode::Char('v'),
-                modifiers: KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 ..
-            } => {
+            } if self.keymap().paste_image.matches(&key_event) => {
                 if let Ok((path, info)) = paste_image_to_temp_png() {
                     self.attach_image(path, info.width, info.height, info.encoded_format.label());
                 }
@@ -789,6 +913,13 @@ impl ChatWidget {
                     InputResult::Command(cmd) => {
                         self.dispatch_command(cmd);
                     }
+                    InputResult::McpPrompt {
+                        server,
+                        name,
+                        arguments,
+                    } => {
+                        self.submit_mcp_prompt(server, name, arguments);
+                    }
                     InputResult::None => {}
                 }
             }
@@ -864,6 +995,9 @@ impl ChatWidget {
                     tx.send(AppEvent::DiffResult(text));
                 });
             }
+            SlashCommand::Undo => {
+                self.app_event_tx.send(AppEvent::CodexOp(Op::UndoTurn));
+            }
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
@@ -873,6 +1007,9 @@ impl ChatWidget {
             SlashCommand::Mcp => {
                 self.add_mcp_output();
             }
+            SlashCommand::McpReload => {
+                self.reload_mcp_servers();
+            }
             #[cfg(debug_assertions)]
             SlashCommand::TestApproval => {
                 use codex_core::protocol::EventMsg;
@@ -967,6 +1104,10 @@ impl ChatWidget {
             items.push(InputItem::LocalImage { path });
         }
 
+        for (server, uri) in mcp_resource_mentions(&text, &self.mcp_resources) {
+            items.push(InputItem::McpResource { server, uri });
+        }
+
         if items.is_empty() {
             return;
         }
@@ -992,6 +1133,31 @@ impl ChatWidget {
         }
     }
 
+    fn submit_mcp_prompt(
+        &mut self,
+        server: String,
+        name: String,
+        arguments: Option<HashMap<String, String>>,
+    ) {
+        let mut display = format!("/{server}:{name}");
+        if let Some(arguments) = &arguments {
+            let mut arguments: Vec<_> = arguments.iter().collect();
+            arguments.sort();
+            for (key, value) in arguments {
+                display.push_str(&format!(" {key}={value}"));
+            }
+        }
+
+        self.submit_op(Op::UserInput {
+            items: vec![InputItem::McpPrompt {
+                server,
+                name,
+                arguments,
+            }],
+        });
+        self.add_to_history(history_cell::new_user_prompt(display));
+    }
+
     /// Replay a subset of initial events into the UI to seed the transcript when
     /// resuming an existing session. This approximates the live event flow and
     /// is intentionally conservative: only safe-to-replay items are rendered to
@@ -1062,6 +1228,9 @@ impl ChatWidget {
             EventMsg::ApplyPatchApprovalRequest(ev) => {
                 self.on_apply_patch_approval_request(id.unwrap_or_default(), ev)
             }
+            EventMsg::McpToolCallApprovalRequest(ev) => {
+                self.on_mcp_tool_call_approval_request(id.unwrap_or_default(), ev)
+            }
             EventMsg::ExecCommandBegin(ev) => self.on_exec_command_begin(ev),
             EventMsg::ExecCommandOutputDelta(delta) => self.on_exec_command_output_delta(delta),
             EventMsg::PatchApplyBegin(ev) => self.on_patch_apply_begin(ev),
@@ -1072,14 +1241,30 @@ impl ChatWidget {
             EventMsg::WebSearchBegin(ev) => self.on_web_search_begin(ev),
             EventMsg::WebSearchEnd(ev) => self.on_web_search_end(ev),
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
+            EventMsg::ListHistoryEntriesResponse(ev) => self.on_list_history_entries(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
+            EventMsg::McpListPromptsResponse(ev) => self.on_list_mcp_prompts(ev),
+            EventMsg::McpListResourcesResponse(ev) => self.on_list_mcp_resources(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
+            EventMsg::CheckpointCreated(CheckpointCreatedEvent { checkpoint }) => {
+                self.checkpoints.push(checkpoint)
+            }
+            EventMsg::TurnUndone(TurnUndoneEvent { checkpoint }) => self.on_turn_undone(checkpoint),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 self.on_background_event(message)
             }
             EventMsg::StreamError(StreamErrorEvent { message }) => self.on_stream_error(message),
+            EventMsg::SessionBudgetExceeded(SessionBudgetExceededEvent { message }) => {
+                self.on_session_budget_exceeded(message)
+            }
+            EventMsg::SecretsRedacted(SecretsRedactedEvent { count }) => {
+                self.on_secrets_redacted(count)
+            }
+            EventMsg::SubAgentBegin(ev) => self.on_sub_agent_begin(ev),
+            EventMsg::SubAgent(ev) => self.on_sub_agent_event(ev),
+            EventMsg::SubAgentEnd(ev) => self.on_sub_agent_end(ev),
             EventMsg::UserMessage(ev) => {
                 if from_replay {
                     self.on_user_message_event(ev);
@@ -1163,7 +1348,9 @@ impl ChatWidget {
         self.add_to_history(history_cell::new_status_output(
             &self.config,
             usage_ref,
+            self.token_info.as_ref().and_then(|ti| ti.total_cost_usd),
             &self.conversation_id,
+            &self.checkpoints,
         ));
     }
 
@@ -1224,13 +1411,14 @@ impl ChatWidget {
     pub(crate) fn open_approvals_popup(&mut self) {
         let current_approval = self.config.approval_policy;
         let current_sandbox = self.config.sandbox_policy.clone();
+        // Presets change the sandbox mode but keep any configured read restrictions.
+        let readable_roots = current_sandbox.readable_roots().map(<[_]>::to_vec);
         let mut items: Vec<SelectionItem> = Vec::new();
         let presets: Vec<ApprovalPreset> = builtin_approval_presets();
         for preset in presets.into_iter() {
-            let is_current =
-                current_approval == preset.approval && current_sandbox == preset.sandbox;
+            let sandbox = preset.sandbox.with_readable_roots(readable_roots.clone());
+            let is_current = current_approval == preset.approval && current_sandbox == sandbox;
             let approval = preset.approval;
-            let sandbox = preset.sandbox.clone();
             let name = preset.label.to_string();
             let description = Some(preset.description.to_string());
             let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
@@ -1299,6 +1487,26 @@ impl ChatWidget {
         }
     }
 
+    /// Ask codex-core to reload the MCP servers from `config.toml`. The local
+    /// copy of `mcp_servers` is refreshed too so that `/mcp` describes the
+    /// reloaded servers.
+    fn reload_mcp_servers(&mut self) {
+        match codex_core::config::load_config_as_toml_with_cli_overrides(
+            &self.config.codex_home,
+            Vec::new(),
+        ) {
+            Ok(config_toml) => self.config.mcp_servers = config_toml.mcp_servers,
+            Err(e) => {
+                self.add_error_message(format!("Failed to read config.toml: {e}"));
+                return;
+            }
+        }
+        self.add_info_message("Reloading MCP servers…".to_string(), None);
+        self.submit_op(Op::ReloadMcpServers);
+        self.submit_op(Op::ListMcpPrompts);
+        self.submit_op(Op::ListMcpResources);
+    }
+
     /// Forward file-search results to the bottom pane.
     pub(crate) fn apply_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
         self.bottom_pane.on_file_search_result(query, matches);
@@ -1323,6 +1531,14 @@ impl ChatWidget {
         self.bottom_pane.composer_is_empty()
     }
 
+    pub(crate) fn keymap(&self) -> &Keymap {
+        self.bottom_pane.keymap()
+    }
+
+    pub(crate) fn composer_captures_esc(&self) -> bool {
+        self.bottom_pane.composer_captures_esc()
+    }
+
     /// True when the UI is in the regular composer state with no running task,
     /// no modal overlay (e.g. approvals or status indicator), and no composer popups.
     /// In this state Esc-Esc backtracking is enabled.
@@ -1366,6 +1582,27 @@ impl ChatWidget {
         self.bottom_pane.set_custom_prompts(ev.custom_prompts);
     }
 
+    fn on_list_mcp_prompts(&mut self, ev: McpListPromptsResponseEvent) {
+        let prompts: Vec<McpPromptEntry> = ev
+            .prompts
+            .into_iter()
+            .flat_map(|(server, prompts)| {
+                prompts.into_iter().map(move |prompt| McpPromptEntry {
+                    server: server.clone(),
+                    prompt,
+                })
+            })
+            .collect();
+        debug!("received {} MCP prompts", prompts.len());
+        self.bottom_pane.set_mcp_prompts(prompts);
+    }
+
+    fn on_list_mcp_resources(&mut self, ev: McpListResourcesResponseEvent) {
+        self.bottom_pane
+            .set_mcp_resource_servers(ev.resources.keys().cloned().collect());
+        self.mcp_resources = ev.resources;
+    }
+
     /// Programmatically submit a user text message as if typed in the
     /// composer. The text will be added to conversation history and sent to
     /// the agent.
@@ -1458,5 +1695,38 @@ fn extract_first_bold(s: &str) -> Option<String> {
     None
 }
 
+/// Returns the `(server, uri)` pairs of the `@server:uri` mentions in `text`
+/// that name a resource listed by an MCP server. Trailing punctuation is
+/// ignored so that a mention can end a sentence.
+fn mcp_resource_mentions(
+    text: &str,
+    resources: &HashMap<String, Vec<McpResource>>,
+) -> Vec<(String, String)> {
+    let mut mentions: Vec<(String, String)> = Vec::new();
+    for token in text.split_whitespace() {
+        let Some((server, uri)) = token
+            .strip_prefix('@')
+            .and_then(|mention| mention.split_once(':'))
+        else {
+            continue;
+        };
+        let Some(server_resources) = resources.get(server) else {
+            continue;
+        };
+        let trimmed = uri.trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+        let Some(resource) = server_resources
+            .iter()
+            .find(|resource| resource.uri == uri || resource.uri == trimmed)
+        else {
+            continue;
+        };
+        let mention = (server.to_string(), resource.uri.clone());
+        if !mentions.contains(&mention) {
+            mentions.push(mention);
+        }
+    }
+    mentions
+}
+
 #[cfg(test)]
 pub(crate) mod tests;
@@ -152,6 +152,7 @@ fn resumed_initial_messages_render_history() {
             }),
         ]),
         rollout_path: rollout_file.path().to_path_buf(),
+        worktree: None,
     };
 
     chat.handle_codex_event(Event {
@@ -223,6 +224,8 @@ fn make_chatwidget_manual() -> (
         enhanced_keys_supported: false,
         placeholder_text: "Ask Codex to do anything".to_string(),
         disable_paste_burst: false,
+        keymap: Keymap::default(),
+        editing_mode: cfg.tui.editing_mode,
     });
     let widget = ChatWidget {
         app_event_tx,
@@ -243,6 +246,8 @@ fn make_chatwidget_manual() -> (
         show_welcome_banner: true,
         queued_user_messages: VecDeque::new(),
         suppress_session_configured_redraw: false,
+        mcp_resources: HashMap::new(),
+        checkpoints: Vec::new(),
     };
     (widget, rx, op_rx)
 }
@@ -1888,3 +1893,32 @@ printf 'fenced within fenced\n'
     let visual = vt_lines.join("\n");
     assert_snapshot!(visual);
 }
+
+#[test]
+fn mcp_resource_mentions_match_listed_resources() {
+    let resource = |uri: &str| McpResource {
+        annotations: None,
+        description: None,
+        mime_type: None,
+        name: uri.to_string(),
+        size: None,
+        title: None,
+        uri: uri.to_string(),
+    };
+    let resources = HashMap::from([(
+        "docs".to_string(),
+        vec![resource("docs://runbook"), resource("docs://faq")],
+    )]);
+
+    assert_eq!(
+        vec![
+            ("docs".to_string(), "docs://runbook".to_string()),
+            ("docs".to_string(), "docs://faq".to_string()),
+        ],
+        mcp_resource_mentions(
+            "see @docs:docs://runbook and @docs:docs://faq. Not @docs:docs://missing, \
+             @other:docs://runbook or me@example.com",
+            &resources,
+        )
+    );
+}
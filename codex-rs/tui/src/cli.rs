@@ -84,6 +84,11 @@ pub struct Cli {
     #[clap(long = "cd", short = 'C', value_name = "DIR")]
     pub cwd: Option<PathBuf>,
 
+    /// Run the session in a new git worktree on its own branch, leaving the
+    /// current checkout untouched. See `codex worktree`.
+    #[arg(long = "worktree", default_value_t = false)]
+    pub worktree: bool,
+
     /// Enable web search (off by default). When enabled, the native Responses `web_search` tool is available to the model (no per‑call approval).
     #[arg(long = "search", default_value_t = false)]
     pub web_search: bool,
@@ -10,6 +10,8 @@ use std::path::PathBuf;
 
 use crate::exec_command::relativize_to_home;
 use crate::history_cell::PatchEventType;
+use crate::render::syntax::Language;
+use crate::render::syntax::styled_ranges;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::FileChange;
 
@@ -189,6 +191,7 @@ fn render_changes_block(
             out.push(RtLine::from(header));
         }
 
+        let language = Language::from_path(r.move_path.as_ref().unwrap_or(&r.path));
         match r.change {
             FileChange::Add { content } => {
                 for (i, raw) in content.lines().enumerate() {
@@ -197,6 +200,7 @@ fn render_changes_block(
                         DiffLineType::Insert,
                         raw,
                         term_cols,
+                        language,
                     ));
                 }
             }
@@ -207,6 +211,7 @@ fn render_changes_block(
                         DiffLineType::Delete,
                         raw,
                         term_cols,
+                        language,
                     ));
                 }
             }
@@ -230,6 +235,7 @@ fn render_changes_block(
                                         DiffLineType::Insert,
                                         s,
                                         term_cols,
+                                        language,
                                     ));
                                     new_ln += 1;
                                 }
@@ -240,6 +246,7 @@ fn render_changes_block(
                                         DiffLineType::Delete,
                                         s,
                                         term_cols,
+                                        language,
                                     ));
                                     old_ln += 1;
                                 }
@@ -250,6 +257,7 @@ fn render_changes_block(
                                         DiffLineType::Context,
                                         s,
                                         term_cols,
+                                        language,
                                     ));
                                     old_ln += 1;
                                     new_ln += 1;
@@ -300,10 +308,10 @@ fn push_wrapped_diff_line(
     kind: DiffLineType,
     text: &str,
     term_cols: usize,
+    language: Option<Language>,
 ) -> Vec<RtLine<'static>> {
     let indent = "    ";
     let ln_str = line_number.to_string();
-    let mut remaining_text: &str = text;
 
     // Reserve a fixed number of spaces after the line number so that content starts
     // at a consistent column. Content includes a 1-character diff sign prefix
@@ -318,46 +326,72 @@ fn push_wrapped_diff_line(
         DiffLineType::Delete => ('-', style_del()),
         DiffLineType::Context => (' ', style_context()),
     };
+    // Each line is highlighted on its own since hunks rarely contain enough
+    // context to carry state such as block comments across lines.
+    let segments: Vec<(std::ops::Range<usize>, Style)> = match language {
+        Some(language) => styled_ranges(text, language)
+            .into_iter()
+            .map(|(range, token_style)| (range, style_syntax(line_style, token_style, &kind)))
+            .collect(),
+        None => vec![(0..text.len(), line_style)],
+    };
     let mut lines: Vec<RtLine<'static>> = Vec::new();
+    let mut chunk_start = 0;
 
     loop {
         // Fit the content for the current terminal row:
         // compute how many columns are available after the prefix, then split
         // at a UTF-8 character boundary so this row's chunk fits exactly.
         let available_content_cols = term_cols.saturating_sub(prefix_cols + 1).max(1);
-        let split_at_byte_index = remaining_text
+        let chunk_end = text[chunk_start..]
             .char_indices()
             .nth(available_content_cols)
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| remaining_text.len());
-        let (chunk, rest) = remaining_text.split_at(split_at_byte_index);
-        remaining_text = rest;
+            .map(|(i, _)| chunk_start + i)
+            .unwrap_or_else(|| text.len());
+        let chunk_spans = segments.iter().filter_map(|(range, style)| {
+            let start = range.start.max(chunk_start);
+            let end = range.end.min(chunk_end);
+            (start < end).then(|| RtSpan::styled(text[start..end].to_string(), *style))
+        });
 
         if first {
             // Build gutter (indent + line number + spacing) as a dimmed span
             let gutter = format!("{indent}{ln_str}{}", " ".repeat(gap_after_ln));
-            // Content with a sign ('+'/'-'/' ') styled per diff kind
-            let content = format!("{sign_char}{chunk}");
-            lines.push(RtLine::from(vec![
+            // Content starts with a sign ('+'/'-'/' ') styled per diff kind
+            let mut spans = vec![
                 RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(content, line_style),
-            ]));
+                RtSpan::styled(sign_char.to_string(), line_style),
+            ];
+            spans.extend(chunk_spans);
+            lines.push(RtLine::from(spans));
             first = false;
         } else {
             // Continuation lines keep a space for the sign column so content aligns
             let gutter = format!("{indent}{} ", " ".repeat(ln_str.len() + gap_after_ln));
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(chunk.to_string(), line_style),
-            ]));
+            let mut spans = vec![RtSpan::styled(gutter, style_gutter())];
+            spans.extend(chunk_spans);
+            lines.push(RtLine::from(spans));
         }
-        if remaining_text.is_empty() {
+        chunk_start = chunk_end;
+        if chunk_start >= text.len() {
             break;
         }
     }
     lines
 }
 
+/// Added and removed lines keep their green/red foreground and only pick up
+/// the token's modifiers (bold keywords, dim comments); context lines get the
+/// full syntax colors.
+fn style_syntax(line_style: Style, token_style: Style, kind: &DiffLineType) -> Style {
+    match kind {
+        DiffLineType::Insert | DiffLineType::Delete => {
+            line_style.add_modifier(token_style.add_modifier)
+        }
+        DiffLineType::Context => line_style.patch(token_style),
+    }
+}
+
 fn style_gutter() -> Style {
     Style::default().add_modifier(Modifier::DIM)
 }
@@ -377,6 +411,8 @@ fn style_del() -> Style {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::syntax::TokenKind;
+    use crate::render::syntax::token_style;
     use insta::assert_snapshot;
     use ratatui::Terminal;
     use ratatui::backend::TestBackend;
@@ -462,7 +498,7 @@ mod tests {
         let long_line = "this is a very long line that should wrap across multiple terminal columns and continue";
 
         // Call the wrapping function directly so we can precisely control the width
-        let lines = push_wrapped_diff_line(1, DiffLineType::Insert, long_line, 80);
+        let lines = push_wrapped_diff_line(1, DiffLineType::Insert, long_line, 80, None);
 
         // Render into a small terminal to capture the visual layout
         snapshot_lines("wrap_behavior_insert", lines, 90, 8);
@@ -755,4 +791,53 @@ mod tests {
 
         snapshot_lines("apply_update_block_relativizes_path", lines, 80, 10);
     }
+
+    #[test]
+    fn highlights_diff_lines_by_file_extension() {
+        let keyword_style = token_style(TokenKind::Keyword);
+        let find_fn = |lines: &[RtLine<'static>]| {
+            lines[0]
+                .spans
+                .iter()
+                .find(|span| span.content == "fn")
+                .map(|span| span.style)
+        };
+
+        let context = push_wrapped_diff_line(
+            1,
+            DiffLineType::Context,
+            "fn main() {}",
+            80,
+            Some(Language::Rust),
+        );
+        assert_eq!(find_fn(&context), Some(keyword_style));
+
+        // Added lines stay green and only pick up the keyword's modifiers.
+        let inserted = push_wrapped_diff_line(
+            1,
+            DiffLineType::Insert,
+            "fn main() {}",
+            80,
+            Some(Language::Rust),
+        );
+        assert_eq!(
+            find_fn(&inserted),
+            Some(style_add().add_modifier(keyword_style.add_modifier))
+        );
+
+        // Wrapping splits highlighted tokens without losing any text.
+        let wrapped = push_wrapped_diff_line(
+            1,
+            DiffLineType::Context,
+            "let s = \"a long string literal\";",
+            20,
+            Some(Language::Rust),
+        );
+        let content: String = wrapped
+            .iter()
+            .flat_map(|line| line.spans.iter().skip(1))
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(content, " let s = \"a long string literal\";");
+    }
 }
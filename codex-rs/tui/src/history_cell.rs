@@ -17,11 +17,13 @@ use codex_common::elapsed::format_duration;
 use codex_core::auth::get_auth_file;
 use codex_core::auth::try_read_auth_json;
 use codex_core::config::Config;
+use codex_core::config_types::McpServerTransportConfig;
 use codex_core::config_types::ReasoningSummaryFormat;
 use codex_core::plan_tool::PlanItemArg;
 use codex_core::plan_tool::StepStatus;
 use codex_core::plan_tool::UpdatePlanArgs;
 use codex_core::project_doc::discover_project_doc_paths;
+use codex_core::protocol::Checkpoint;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::SandboxPolicy;
@@ -607,6 +609,7 @@ pub(crate) fn new_session_info(
         history_entry_count: _,
         initial_messages: _,
         rollout_path: _,
+        worktree,
     } = event;
     if is_first_event {
         let cwd_str = match relativize_to_home(&config.cwd) {
@@ -628,6 +631,15 @@ pub(crate) fn new_session_info(
             "You are using OpenAI Codex in".bold(),
             format!(" {cwd_str}").dim(),
         ]));
+        if let Some(worktree) = worktree {
+            lines.push(Line::from(
+                format!(
+                    "   worktree {} on branch {}; run `codex worktree merge {}` to bring the changes back",
+                    worktree.name, worktree.branch, worktree.name
+                )
+                .dim(),
+            ));
+        }
         lines.push(Line::from("".dim()));
         lines.push(Line::from(
             " To get started, describe a task or try one of these commands:".dim(),
@@ -824,7 +836,9 @@ pub(crate) fn new_completed_mcp_tool_call(
 pub(crate) fn new_status_output(
     config: &Config,
     usage: &TokenUsage,
+    total_cost_usd: Option<f64>,
     session_id: &Option<ConversationId>,
+    checkpoints: &[Checkpoint],
 ) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
     lines.push("/status".magenta().into());
@@ -855,7 +869,7 @@ pub(crate) fn new_status_output(
     // Sandbox (simplified name only)
     let sandbox_name = match &config.sandbox_policy {
         SandboxPolicy::DangerFullAccess => "danger-full-access",
-        SandboxPolicy::ReadOnly => "read-only",
+        SandboxPolicy::ReadOnly { .. } => "read-only",
         SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
     };
     lines.push(vec!["  • Sandbox: ".into(), sandbox_name.into()].into());
@@ -907,6 +921,23 @@ pub(crate) fn new_status_output(
     }
     lines.push("".into());
 
+    // ⏪ Checkpoints that `/undo` can restore, most recent first
+    if !checkpoints.is_empty() {
+        lines.push(vec![padded_emoji("⏪").into(), "Checkpoints".bold()].into());
+        for checkpoint in checkpoints.iter().rev() {
+            lines.push(
+                vec![
+                    "  • ".into(),
+                    short_commit(&checkpoint.commit).dim(),
+                    " ".into(),
+                    checkpoint.summary.clone().into(),
+                ]
+                .into(),
+            );
+        }
+        lines.push("".into());
+    }
+
     // 👤 Account (only if ChatGPT tokens exist), shown under the first block
     let auth_file = get_auth_file(&config.codex_home);
     if let Ok(auth) = try_read_auth_json(&auth_file)
@@ -984,6 +1015,14 @@ pub(crate) fn new_status_output(
         "  • Total: ".into(),
         format_with_separators(usage.blended_total()).into(),
     ]));
+    // Cost: $<cost> (estimated from model pricing)
+    if let Some(cost) = total_cost_usd {
+        lines.push(Line::from(vec![
+            "  • Cost: ".into(),
+            format!("${cost:.2}").into(),
+            " (estimated)".dim(),
+        ]));
+    }
 
     PlainHistoryCell { lines }
 }
@@ -1036,10 +1075,17 @@ pub(crate) fn new_mcp_tools_output(
 
         lines.push(vec!["  • Server: ".into(), server.clone().into()].into());
 
-        if !cfg.command.is_empty() {
-            let cmd_display = format!("{} {}", cfg.command, cfg.args.join(" "));
+        match &cfg.transport {
+            McpServerTransportConfig::Stdio { command, args, .. } => {
+                if !command.is_empty() {
+                    let cmd_display = format!("{} {}", command, args.join(" "));
 
-            lines.push(vec!["    • Command: ".into(), cmd_display.into()].into());
+                    lines.push(vec!["    • Command: ".into(), cmd_display.into()].into());
+                }
+            }
+            McpServerTransportConfig::Http { url, .. } => {
+                lines.push(vec!["    • URL: ".into(), url.clone().into()].into());
+            }
         }
 
         if names.is_empty() {
@@ -1071,6 +1117,112 @@ pub(crate) fn new_error_event(message: String) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
+pub(crate) fn new_session_budget_exceeded_event(message: String) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        vec![format!("■ {message}").red()].into(),
+        "  Start a new session or raise the limit in config.toml to continue."
+            .dim()
+            .into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_secrets_redacted_event(count: usize) -> PlainHistoryCell {
+    let noun = if count == 1 { "secret" } else { "secrets" };
+    let message = format!("Redacted {count} {noun} from tool output sent to the model");
+    let lines: Vec<Line<'static>> = vec![vec![padded_emoji("🔒").into(), message.dim()].into()];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_sub_agent_begin(
+    task: usize,
+    prompt: &str,
+    cwd: &Path,
+    model: &str,
+) -> PlainHistoryCell {
+    let prompt = prompt.lines().next().unwrap_or_default().to_string();
+    let lines: Vec<Line<'static>> = vec![
+        vec![
+            padded_emoji("🤖").into(),
+            format!("sub-agent {task}").magenta().bold(),
+            format!(" started ({model})").dim(),
+        ]
+        .into(),
+        vec!["  └ ".dim(), prompt.into()].into(),
+        vec![
+            "    ".into(),
+            relativize_to_home(cwd)
+                .map(|path| format!("~/{}", path.display()))
+                .unwrap_or_else(|| cwd.display().to_string())
+                .dim(),
+        ]
+        .into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_sub_agent_exec(task: usize, command: &[String]) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        vec![
+            format!("  sub-agent {task} ").magenta(),
+            strip_bash_lc_and_escape(command).dim(),
+        ]
+        .into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_sub_agent_end(
+    task: usize,
+    result: &Result<String, String>,
+    num_cols: usize,
+) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    match result {
+        Ok(message) => {
+            lines.push(
+                vec![
+                    padded_emoji("🤖").into(),
+                    format!("sub-agent {task}").magenta().bold(),
+                    " finished".green(),
+                ]
+                .into(),
+            );
+            let message = format_and_truncate_tool_result(message, TOOL_CALL_MAX_LINES, num_cols);
+            for line in message.lines() {
+                lines.push(vec!["    ".into(), line.to_string().dim()].into());
+            }
+        }
+        Err(e) => lines.push(
+            vec![
+                padded_emoji("🤖").into(),
+                format!("sub-agent {task}").magenta().bold(),
+                format!(" failed: {e}").red(),
+            ]
+            .into(),
+        ),
+    }
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_turn_undone_event(checkpoint: &Checkpoint) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        vec![
+            padded_emoji("⏪").into(),
+            "Restored the workspace to before: ".into(),
+            checkpoint.summary.clone().bold(),
+            " ".into(),
+            short_commit(&checkpoint.commit).dim(),
+        ]
+        .into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+fn short_commit(commit: &str) -> String {
+    commit.chars().take(7).collect()
+}
+
 pub(crate) fn new_stream_error_event(message: String) -> PlainHistoryCell {
     let lines: Vec<Line<'static>> = vec![vec![padded_emoji("⚠️").into(), message.dim()].into()];
     PlainHistoryCell { lines }
@@ -234,6 +234,7 @@ fn write_spans<'a, I>(mut writer: &mut impl Write, content: I) -> io::Result<()>
 where
     I: IntoIterator<Item = &'a Span<'a>>,
 {
+    let theme = crate::theme::current();
     let mut fg = Color::Reset;
     let mut bg = Color::Reset;
     let mut last_modifier = Modifier::empty();
@@ -249,8 +250,8 @@ where
             diff.queue(&mut writer)?;
             last_modifier = modifier;
         }
-        let next_fg = span.style.fg.unwrap_or(Color::Reset);
-        let next_bg = span.style.bg.unwrap_or(Color::Reset);
+        let next_fg = theme.map_color(span.style.fg.unwrap_or(Color::Reset));
+        let next_bg = theme.map_color(span.style.bg.unwrap_or(Color::Reset));
         if next_fg != fg || next_bg != bg {
             queue!(
                 writer,
@@ -1,3 +1,4 @@
+use crossterm::event::KeyModifiers;
 use ratatui::style::Color;
 use ratatui::style::Style;
 use ratatui::text::Span;
@@ -47,3 +48,18 @@ pub(crate) fn shift(key: impl Display) -> Span<'static> {
 pub(crate) fn plain(key: impl Display) -> Span<'static> {
     Span::styled(format!("{key}"), key_hint_style())
 }
+
+/// Hint for `key` pressed with any combination of Ctrl, Alt and Shift.
+pub(crate) fn with_modifiers(modifiers: KeyModifiers, key: impl Display) -> Span<'static> {
+    let mut prefix = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str(CTRL_PREFIX);
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str(ALT_PREFIX);
+    }
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        prefix.push_str(SHIFT_PREFIX);
+    }
+    modifier_span(&prefix, key)
+}
@@ -0,0 +1,297 @@
+//! Key bindings for TUI actions, configurable through `[tui.keymap]`.
+
+use codex_core::config_types::KeyBindings;
+use codex_core::config_types::TuiKeymap;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use ratatui::text::Span;
+
+use crate::key_hint;
+
+/// A single key chord such as `ctrl+t`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub(crate) const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Parse a chord written as `+`-separated modifiers followed by a key,
+    /// e.g. `enter`, `ctrl+j`, `alt+shift+up` or `f5`.
+    pub(crate) fn parse(spec: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+        let Some((key, modifier_names)) = parts.split_last() else {
+            return Err(format!("invalid key `{spec}`"));
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_names {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "option" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier `{name}` in `{spec}`")),
+            };
+        }
+        let lower = key.to_ascii_lowercase();
+        let code = match lower.as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            _ => {
+                let mut chars = lower.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                        Some(n @ 1..=12) => KeyCode::F(n),
+                        _ => return Err(format!("unknown key `{key}` in `{spec}`")),
+                    },
+                }
+            }
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    pub(crate) fn matches(&self, event: &KeyEvent) -> bool {
+        let code_matches = match (self.code, event.code) {
+            // Terminals report Shift+letter as an uppercase char.
+            (KeyCode::Char(bound), KeyCode::Char(pressed)) => bound.eq_ignore_ascii_case(&pressed),
+            (bound, pressed) => bound == pressed,
+        };
+        code_matches && event.modifiers == self.modifiers
+    }
+
+    /// Shift+Enter can only be told apart from Enter when the terminal
+    /// supports enhanced keyboard reporting.
+    fn needs_enhanced_keys(&self) -> bool {
+        self.code == KeyCode::Enter && self.modifiers.contains(KeyModifiers::SHIFT)
+    }
+
+    pub(crate) fn hint(&self) -> Span<'static> {
+        let key = match self.code {
+            KeyCode::Enter => "⏎".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_ascii_uppercase().to_string(),
+            KeyCode::F(n) => format!("F{n}"),
+            other => format!("{other:?}"),
+        };
+        key_hint::with_modifiers(self.modifiers, key)
+    }
+}
+
+/// The keys bound to one action. An empty set leaves the action unbound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Bindings(Vec<KeyBinding>);
+
+impl Bindings {
+    fn from_config(
+        action: &str,
+        config: Option<&KeyBindings>,
+        default: Bindings,
+    ) -> Result<Self, String> {
+        let Some(config) = config else {
+            return Ok(default);
+        };
+        config
+            .keys()
+            .iter()
+            .map(|key| {
+                KeyBinding::parse(key)
+                    .map_err(|err| format!("invalid binding for `tui.keymap.{action}`: {err}"))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+
+    pub(crate) fn matches(&self, event: &KeyEvent) -> bool {
+        self.0.iter().any(|binding| binding.matches(event))
+    }
+
+    /// Hint for the first binding, or `None` when the action is unbound.
+    pub(crate) fn hint(&self) -> Option<Span<'static>> {
+        self.0.first().map(KeyBinding::hint)
+    }
+
+    /// Like [`Bindings::hint`], but skips bindings the terminal cannot report.
+    pub(crate) fn hint_for_terminal(&self, enhanced_keys_supported: bool) -> Option<Span<'static>> {
+        self.0
+            .iter()
+            .find(|binding| enhanced_keys_supported || !binding.needs_enhanced_keys())
+            .map(KeyBinding::hint)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Keymap {
+    pub(crate) submit: Bindings,
+    pub(crate) newline: Bindings,
+    pub(crate) interrupt: Bindings,
+    pub(crate) quit: Bindings,
+    pub(crate) backtrack: Bindings,
+    pub(crate) open_transcript: Bindings,
+    pub(crate) paste_image: Bindings,
+    pub(crate) history_search: Bindings,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let key = |code, modifiers| KeyBinding::new(code, modifiers);
+        Self {
+            submit: Bindings(vec![key(KeyCode::Enter, KeyModifiers::NONE)]),
+            newline: Bindings(vec![
+                key(KeyCode::Enter, KeyModifiers::SHIFT),
+                key(KeyCode::Char('j'), KeyModifiers::CONTROL),
+            ]),
+            interrupt: Bindings(vec![key(KeyCode::Char('c'), KeyModifiers::CONTROL)]),
+            quit: Bindings(vec![key(KeyCode::Char('d'), KeyModifiers::CONTROL)]),
+            backtrack: Bindings(vec![key(KeyCode::Esc, KeyModifiers::NONE)]),
+            open_transcript: Bindings(vec![key(KeyCode::Char('t'), KeyModifiers::CONTROL)]),
+            paste_image: Bindings(vec![key(KeyCode::Char('v'), KeyModifiers::CONTROL)]),
+            history_search: Bindings(vec![key(KeyCode::Char('r'), KeyModifiers::CONTROL)]),
+        }
+    }
+}
+
+impl Keymap {
+    /// Resolve `[tui.keymap]`, keeping the default keys for unset actions.
+    pub(crate) fn from_config(config: &TuiKeymap) -> Result<Self, String> {
+        let default = Self::default();
+        Ok(Self {
+            submit: Bindings::from_config("submit", config.submit.as_ref(), default.submit)?,
+            newline: Bindings::from_config("newline", config.newline.as_ref(), default.newline)?,
+            interrupt: Bindings::from_config(
+                "interrupt",
+                config.interrupt.as_ref(),
+                default.interrupt,
+            )?,
+            quit: Bindings::from_config("quit", config.quit.as_ref(), default.quit)?,
+            backtrack: Bindings::from_config(
+                "backtrack",
+                config.backtrack.as_ref(),
+                default.backtrack,
+            )?,
+            open_transcript: Bindings::from_config(
+                "open_transcript",
+                config.open_transcript.as_ref(),
+                default.open_transcript,
+            )?,
+            paste_image: Bindings::from_config(
+                "paste_image",
+                config.paste_image.as_ref(),
+                default.paste_image,
+            )?,
+            history_search: Bindings::from_config(
+                "history_search",
+                config.history_search.as_ref(),
+                default.history_search,
+            )?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_key_chords() {
+        assert_eq!(
+            KeyBinding::parse("ctrl+t"),
+            Ok(KeyBinding::new(KeyCode::Char('t'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            KeyBinding::parse("Alt+Shift+Enter"),
+            Ok(KeyBinding::new(
+                KeyCode::Enter,
+                KeyModifiers::ALT | KeyModifiers::SHIFT
+            ))
+        );
+        assert_eq!(
+            KeyBinding::parse("f5"),
+            Ok(KeyBinding::new(KeyCode::F(5), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("f"),
+            Ok(KeyBinding::new(KeyCode::Char('f'), KeyModifiers::NONE))
+        );
+        assert!(KeyBinding::parse("hyper+t").is_err());
+        assert!(KeyBinding::parse("ctrl+banana").is_err());
+    }
+
+    #[test]
+    fn matches_require_exact_modifiers() {
+        let ctrl_t = KeyBinding::parse("ctrl+t").expect("valid");
+        assert!(ctrl_t.matches(&KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL)));
+        assert!(!ctrl_t.matches(&KeyEvent::new(
+            KeyCode::Char('t'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT
+        )));
+        let shift_a = KeyBinding::parse("shift+a").expect("valid");
+        assert!(shift_a.matches(&KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn config_overrides_only_listed_actions() {
+        let keymap = Keymap::from_config(&TuiKeymap {
+            submit: Some(KeyBindings::One("ctrl+enter".to_string())),
+            newline: Some(KeyBindings::Many(vec!["enter".to_string()])),
+            ..Default::default()
+        })
+        .expect("valid keymap");
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert!(keymap.newline.matches(&enter));
+        assert!(!keymap.submit.matches(&enter));
+        assert_eq!(keymap.open_transcript, Keymap::default().open_transcript);
+
+        let err = Keymap::from_config(&TuiKeymap {
+            quit: Some(KeyBindings::One("ctrl+nope".to_string())),
+            ..Default::default()
+        })
+        .expect_err("invalid key");
+        assert_eq!(
+            err,
+            "invalid binding for `tui.keymap.quit`: unknown key `nope` in `ctrl+nope`"
+        );
+    }
+
+    #[test]
+    fn newline_hint_skips_shift_enter_without_enhanced_keys() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.newline.hint_for_terminal(false),
+            Some(key_hint::ctrl('J'))
+        );
+        assert_eq!(
+            keymap.newline.hint_for_terminal(true),
+            Some(key_hint::shift('⏎'))
+        );
+    }
+}
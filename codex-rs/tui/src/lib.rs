@@ -45,6 +45,7 @@ mod get_git_diff;
 mod history_cell;
 pub mod insert_history;
 mod key_hint;
+mod keymap;
 pub mod live_wrap;
 mod markdown;
 mod markdown_render;
@@ -60,6 +61,7 @@ mod slash_command;
 mod status_indicator_widget;
 mod streaming;
 mod text_formatting;
+mod theme;
 mod tui;
 mod user_approval_widget;
 mod version;
@@ -135,6 +137,9 @@ pub async fn run_main(
         include_view_image_tool: None,
         show_raw_agent_reasoning: cli.oss.then_some(true),
         tools_web_search_request: cli.web_search.then_some(true),
+        output_schema: None,
+        cassette: None,
+        worktree: cli.worktree.then_some(true),
     };
     let raw_overrides = cli.config_overrides.raw_overrides.clone();
     let overrides_cli = codex_common::CliConfigOverrides { raw_overrides };
@@ -160,6 +165,13 @@ pub async fn run_main(
         }
     };
 
+    #[allow(clippy::print_stderr)]
+    if let Err(err) = keymap::Keymap::from_config(&config.tui.keymap) {
+        eprintln!("Error loading configuration: {err}");
+        std::process::exit(1);
+    }
+    theme::init(&config.tui.theme);
+
     // we load config.toml here to determine project state.
     #[allow(clippy::print_stderr)]
     let config_toml = {
@@ -339,7 +351,9 @@ async fn run_ratatui_app(
         .await?;
         if let Some(TrustDirectorySelection::Trust) = directory_trust_decision {
             config.approval_policy = AskForApproval::OnRequest;
-            config.sandbox_policy = SandboxPolicy::new_workspace_write_policy();
+            let readable_roots = config.sandbox_policy.readable_roots().map(<[_]>::to_vec);
+            config.sandbox_policy =
+                SandboxPolicy::new_workspace_write_policy().with_readable_roots(readable_roots);
         }
     }
 
@@ -479,7 +493,9 @@ fn determine_repo_trust_state(
         // if the current cwd project is trusted and no config has been set
         // skip the trust flow and set the approval policy and sandbox mode
         config.approval_policy = AskForApproval::OnRequest;
-        config.sandbox_policy = SandboxPolicy::new_workspace_write_policy();
+        let readable_roots = config.sandbox_policy.readable_roots().map(<[_]>::to_vec);
+        config.sandbox_policy =
+            SandboxPolicy::new_workspace_write_policy().with_readable_roots(readable_roots);
         Ok(false)
     } else {
         // if none of the above conditions are met, show the trust screen
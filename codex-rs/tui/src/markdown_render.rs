@@ -1,4 +1,6 @@
 use crate::citation_regex::CITATION_REGEX;
+use crate::render::highlight::highlight_code_to_lines;
+use crate::render::syntax::Language;
 use pulldown_cmark::CodeBlockKind;
 use pulldown_cmark::CowStr;
 use pulldown_cmark::Event;
@@ -75,6 +77,10 @@ where
     scheme: Option<String>,
     cwd: Option<std::path::PathBuf>,
     in_code_block: bool,
+    /// Source of the current fenced code block when its info string names a
+    /// language we can highlight. Buffered so tokens that span lines (block
+    /// comments, multi-line strings) are highlighted as a whole.
+    highlighted_code_block: Option<(Language, String)>,
 }
 
 impl<'a, I> Writer<'a, I>
@@ -95,6 +101,7 @@ where
             scheme,
             cwd,
             in_code_block: false,
+            highlighted_code_block: None,
         }
     }
 
@@ -237,6 +244,12 @@ where
     }
 
     fn text(&mut self, text: CowStr<'a>) {
+        if self.in_code_block
+            && let Some((_, code)) = self.highlighted_code_block.as_mut()
+        {
+            code.push_str(&text);
+            return;
+        }
         if self.pending_marker_line {
             self.push_line(Line::default());
         }
@@ -354,11 +367,15 @@ where
         self.needs_newline = false;
     }
 
-    fn start_codeblock(&mut self, _lang: Option<String>, indent: Option<Span<'static>>) {
+    fn start_codeblock(&mut self, lang: Option<String>, indent: Option<Span<'static>>) {
         if !self.text.lines.is_empty() {
             self.push_blank_line();
         }
         self.in_code_block = true;
+        self.highlighted_code_block = lang
+            .as_deref()
+            .and_then(Language::from_info_string)
+            .map(|language| (language, String::new()));
         self.indent_stack.push(IndentContext::new(
             vec![indent.unwrap_or_default()],
             None,
@@ -373,12 +390,40 @@ where
     }
 
     fn end_codeblock(&mut self) {
+        if let Some((language, code)) = self.highlighted_code_block.take() {
+            self.push_highlighted_code(&code, language);
+        }
         // self.push_line("```".into());
         self.needs_newline = true;
         self.in_code_block = false;
         self.indent_stack.pop();
     }
 
+    fn push_highlighted_code(&mut self, code: &str, language: Language) {
+        if self.pending_marker_line {
+            self.push_line(Line::default());
+        }
+        self.pending_marker_line = false;
+        let highlighted = highlight_code_to_lines(code, language);
+        for (i, line) in highlighted
+            .into_iter()
+            .take(code.lines().count())
+            .enumerate()
+        {
+            if self.needs_newline {
+                self.push_line(Line::default());
+                self.needs_newline = false;
+            }
+            if i > 0 {
+                self.push_line(Line::default());
+            }
+            for span in line.spans {
+                self.push_span(span);
+            }
+        }
+        self.needs_newline = false;
+    }
+
     fn push_inline_style(&mut self, style: Style) {
         let current = self.inline_styles.last().copied().unwrap_or_default();
         let merged = current.patch(style);
@@ -5,6 +5,8 @@ use ratatui::text::Span;
 use ratatui::text::Text;
 
 use crate::markdown_render::render_markdown_text;
+use crate::render::syntax::TokenKind;
+use crate::render::syntax::token_style;
 use insta::assert_snapshot;
 
 #[test]
@@ -653,7 +655,30 @@ fn link() {
 
 #[test]
 fn code_block_unhighlighted() {
-    let text = render_markdown_text("```rust\nfn main() {}\n```\n");
+    let text = render_markdown_text("```\nfn main() {}\n```\n");
+    let expected = Text::from_iter([Line::from_iter(["", "fn main() {}"])]);
+    assert_eq!(text, expected);
+}
+
+#[test]
+fn code_block_highlighted_by_info_string() {
+    let text = render_markdown_text("```rust\n/* a\nb */\nfn main() {}\n```\n");
+    let comment = token_style(TokenKind::Comment);
+    let expected = Text::from_iter([
+        Line::from_iter([Span::from(""), Span::styled("/* a", comment)]),
+        Line::from_iter([Span::from(""), Span::styled("b */", comment)]),
+        Line::from_iter([
+            Span::from(""),
+            Span::styled("fn", token_style(TokenKind::Keyword)),
+            Span::from(" main() {}"),
+        ]),
+    ]);
+    assert_eq!(text, expected);
+}
+
+#[test]
+fn code_block_with_unknown_language_is_unhighlighted() {
+    let text = render_markdown_text("```text\nfn main() {}\n```\n");
     let expected = Text::from_iter([Line::from_iter(["", "fn main() {}"])]);
     assert_eq!(text, expected);
 }
@@ -3,6 +3,9 @@ use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
 
+use crate::render::syntax::Language;
+use crate::render::syntax::styled_ranges;
+
 /// Convert the full bash script into per-line styled content by first
 /// computing operator-dimmed spans across the entire script, then splitting
 /// by newlines and dimming heredoc body lines. Performs a single parse and
@@ -78,9 +81,32 @@ pub(crate) fn highlight_bash_to_lines(script: &str) -> Vec<Line<'static>> {
     lines
 }
 
+/// Highlight `code` as `language`, returning one line per source line.
+/// Tokens that span lines, such as block comments and multi-line strings,
+/// keep their style on every line they cover.
+pub(crate) fn highlight_code_to_lines(code: &str, language: Language) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = vec![Line::from("")];
+    for (range, style) in styled_ranges(code, language) {
+        for (i, part) in code[range].split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Line::from(""));
+            }
+            if part.is_empty() {
+                continue;
+            }
+            if let Some(last) = lines.last_mut() {
+                last.spans.push(Span::styled(part.to_string(), style));
+            }
+        }
+    }
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::syntax::TokenKind;
+    use crate::render::syntax::token_style;
     use pretty_assertions::assert_eq;
     use ratatui::style::Modifier;
 
@@ -142,4 +168,22 @@ mod tests {
         assert!(!dimmed.contains(&"\"".to_string()));
         assert!(!dimmed.contains(&"'".to_string()));
     }
+
+    #[test]
+    fn highlights_code_across_lines() {
+        let code = "/* a\nb */\nfn x() {}";
+        let lines = highlight_code_to_lines(code, Language::Rust);
+        let comment = token_style(TokenKind::Comment);
+        assert_eq!(
+            lines,
+            vec![
+                Line::from(vec![Span::styled("/* a", comment)]),
+                Line::from(vec![Span::styled("b */", comment)]),
+                Line::from(vec![
+                    Span::styled("fn", token_style(TokenKind::Keyword)),
+                    Span::from(" x() {}"),
+                ]),
+            ]
+        );
+    }
 }
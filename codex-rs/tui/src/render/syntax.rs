@@ -0,0 +1,770 @@
+//! Lightweight, language-aware syntax highlighting for code shown in the TUI.
+//!
+//! This is a lexer rather than a parser: it recognizes comments, strings,
+//! numbers, keywords and keys well enough to color fenced code blocks and diff
+//! lines, and leaves everything else in the default style. Colors are limited
+//! to the ANSI palette described in `tui/styles.md`, and the palette adapts to
+//! the light or dark background reported by the [`theme`](crate::theme).
+
+use std::ops::Range;
+use std::path::Path;
+
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+
+use crate::theme;
+use crate::theme::TerminalBackground;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Language {
+    Rust,
+    Python,
+    TypeScript,
+    Go,
+    Json,
+    Toml,
+    Yaml,
+    Bash,
+}
+
+impl Language {
+    /// Resolve the language of a fenced code block from its info string, e.g.
+    /// `rust`, `rust,ignore` or `python title="example.py"`.
+    pub(crate) fn from_info_string(info: &str) -> Option<Self> {
+        let name = info
+            .trim()
+            .split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+            .next()?;
+        Self::from_name(&name.to_ascii_lowercase())
+    }
+
+    /// Resolve the language of a file from its extension.
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Self::from_name(&extension.to_ascii_lowercase())
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rust" | "rs" => Some(Self::Rust),
+            "python" | "py" | "pyi" => Some(Self::Python),
+            "typescript" | "ts" | "tsx" | "mts" | "cts" | "javascript" | "js" | "jsx" | "mjs"
+            | "cjs" => Some(Self::TypeScript),
+            "go" | "golang" => Some(Self::Go),
+            "json" | "jsonc" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "bash" | "sh" | "shell" | "zsh" => Some(Self::Bash),
+            _ => None,
+        }
+    }
+
+    fn spec(self) -> &'static LanguageSpec {
+        match self {
+            Self::Rust => &RUST,
+            Self::Python => &PYTHON,
+            Self::TypeScript => &TYPESCRIPT,
+            Self::Go => &GO,
+            Self::Json => &JSON,
+            Self::Toml => &TOML,
+            Self::Yaml => &YAML,
+            Self::Bash => &BASH,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Keyword,
+    Type,
+    String,
+    Number,
+    Literal,
+    Comment,
+    /// Object keys in JSON, keys and table headers in TOML, mapping keys in
+    /// YAML.
+    Key,
+}
+
+/// Style for a token kind on the current terminal background. Keywords and
+/// comments always carry a modifier so they stay distinguishable where the
+/// foreground color is already taken, e.g. on added or removed diff lines.
+pub(crate) fn token_style(kind: TokenKind) -> Style {
+    token_style_for(kind, theme::current().background())
+}
+
+fn token_style_for(kind: TokenKind, background: TerminalBackground) -> Style {
+    match (background, kind) {
+        (_, TokenKind::Keyword) => Style::new().magenta().bold(),
+        (_, TokenKind::String) => Style::new().green(),
+        (_, TokenKind::Comment) => Style::new().dim().italic(),
+        (
+            TerminalBackground::Dark,
+            TokenKind::Type | TokenKind::Key | TokenKind::Number | TokenKind::Literal,
+        ) => Style::new().cyan(),
+        // Cyan is hard to read on light backgrounds.
+        (TerminalBackground::Light, TokenKind::Type | TokenKind::Key) => Style::new().bold(),
+        (TerminalBackground::Light, TokenKind::Number | TokenKind::Literal) => Style::new().red(),
+    }
+}
+
+/// Split `code` into contiguous byte ranges that together cover the whole
+/// input, each paired with the style it should be rendered in.
+pub(crate) fn styled_ranges(code: &str, language: Language) -> Vec<(Range<usize>, Style)> {
+    let mut out: Vec<(Range<usize>, Style)> = Vec::new();
+    let mut pos = 0;
+    for (range, kind) in tokenize(code, language) {
+        if range.start > pos {
+            out.push((pos..range.start, Style::default()));
+        }
+        pos = range.end;
+        out.push((range, token_style(kind)));
+    }
+    if pos < code.len() {
+        out.push((pos..code.len(), Style::default()));
+    }
+    out
+}
+
+struct LanguageSpec {
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Quote characters that delimit single-line strings.
+    quotes: &'static [u8],
+    /// Quote characters whose strings may span lines (e.g. JS template
+    /// literals and Go raw strings).
+    multiline_quotes: &'static [u8],
+    /// Whether tripled quotes open a multi-line string (Python, TOML).
+    triple_quotes: bool,
+    keywords: &'static [&'static str],
+    types: &'static [&'static str],
+    literals: &'static [&'static str],
+    /// Treat identifiers starting with an uppercase letter as type names.
+    capitalized_types: bool,
+}
+
+static RUST: LanguageSpec = LanguageSpec {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: b"\"'",
+    multiline_quotes: b"",
+    triple_quotes: false,
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+        "unsafe", "use", "where", "while", "yield",
+    ],
+    types: &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8",
+        "u16", "u32", "u64", "u128", "usize",
+    ],
+    literals: &["true", "false"],
+    capitalized_types: true,
+};
+
+static PYTHON: LanguageSpec = LanguageSpec {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: b"\"'",
+    multiline_quotes: b"",
+    triple_quotes: true,
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "case", "class", "continue", "def",
+        "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+        "is", "lambda", "match", "nonlocal", "not", "or", "pass", "raise", "return", "try",
+        "while", "with", "yield",
+    ],
+    types: &[],
+    literals: &["True", "False", "None"],
+    capitalized_types: true,
+};
+
+static TYPESCRIPT: LanguageSpec = LanguageSpec {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: b"\"'",
+    multiline_quotes: b"`",
+    triple_quotes: false,
+    keywords: &[
+        "abstract",
+        "as",
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "declare",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "export",
+        "extends",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "keyof",
+        "let",
+        "new",
+        "of",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "try",
+        "type",
+        "typeof",
+        "var",
+        "void",
+        "while",
+        "yield",
+    ],
+    types: &[
+        "any", "bigint", "boolean", "never", "number", "object", "string", "symbol", "unknown",
+    ],
+    literals: &["true", "false", "null", "undefined", "NaN", "Infinity"],
+    capitalized_types: true,
+};
+
+static GO: LanguageSpec = LanguageSpec {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: b"\"'",
+    multiline_quotes: b"`",
+    triple_quotes: false,
+    keywords: &[
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "fallthrough",
+        "for",
+        "func",
+        "go",
+        "goto",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "type",
+        "var",
+    ],
+    types: &[
+        "any",
+        "bool",
+        "byte",
+        "complex64",
+        "complex128",
+        "error",
+        "float32",
+        "float64",
+        "int",
+        "int8",
+        "int16",
+        "int32",
+        "int64",
+        "rune",
+        "string",
+        "uint",
+        "uint8",
+        "uint16",
+        "uint32",
+        "uint64",
+        "uintptr",
+    ],
+    literals: &["true", "false", "nil", "iota"],
+    // Exported identifiers are capitalized in Go, so case says nothing about
+    // whether a name is a type.
+    capitalized_types: false,
+};
+
+static JSON: LanguageSpec = LanguageSpec {
+    line_comments: &[],
+    block_comment: None,
+    quotes: b"\"",
+    multiline_quotes: b"",
+    triple_quotes: false,
+    keywords: &[],
+    types: &[],
+    literals: &["true", "false", "null"],
+    capitalized_types: false,
+};
+
+static TOML: LanguageSpec = LanguageSpec {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: b"\"'",
+    multiline_quotes: b"",
+    triple_quotes: true,
+    keywords: &[],
+    types: &[],
+    literals: &["true", "false", "inf", "nan"],
+    capitalized_types: false,
+};
+
+static YAML: LanguageSpec = LanguageSpec {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: b"\"'",
+    multiline_quotes: b"",
+    triple_quotes: false,
+    keywords: &[],
+    types: &[],
+    literals: &[
+        "true", "false", "True", "False", "TRUE", "FALSE", "null", "Null", "NULL", "yes", "no",
+    ],
+    capitalized_types: false,
+};
+
+static BASH: LanguageSpec = LanguageSpec {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: b"\"'",
+    multiline_quotes: b"",
+    triple_quotes: false,
+    keywords: &[
+        "break", "case", "continue", "declare", "do", "done", "elif", "else", "esac", "export",
+        "fi", "for", "function", "if", "in", "local", "readonly", "return", "select", "then",
+        "until", "while",
+    ],
+    types: &[],
+    literals: &[],
+    capitalized_types: false,
+};
+
+fn is_ident_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || b == b'$' || !b.is_ascii()
+}
+
+fn is_ident_continue(b: u8) -> bool {
+    is_ident_start(b) || b.is_ascii_digit()
+}
+
+/// Tokenize `code`, returning non-overlapping, sorted ranges for the tokens
+/// that get a style. Token boundaries always fall on ASCII bytes, so every
+/// range is a valid `str` slice.
+pub(crate) fn tokenize(code: &str, language: Language) -> Vec<(Range<usize>, TokenKind)> {
+    let spec = language.spec();
+    let bytes = code.as_bytes();
+    let mut tokens: Vec<(Range<usize>, TokenKind)> = Vec::new();
+    let mut i = 0;
+    // True until the first non-whitespace byte of the current line.
+    let mut at_line_start = true;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b == b'\n' {
+            at_line_start = true;
+            i += 1;
+            continue;
+        }
+        if b.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let line_start = std::mem::replace(&mut at_line_start, false);
+        let rest = &code[i..];
+
+        // `#` only starts a comment at the start of a word, so `$#` in bash
+        // and `a#b` in YAML values are left alone.
+        if let Some(marker) = spec
+            .line_comments
+            .iter()
+            .find(|marker| rest.starts_with(**marker))
+            && (*marker != "#" || i == 0 || bytes[i - 1].is_ascii_whitespace())
+        {
+            let end = rest.find('\n').map_or(bytes.len(), |n| i + n);
+            tokens.push((i..end, TokenKind::Comment));
+            i = end;
+            continue;
+        }
+        if let Some((open, close)) = spec.block_comment
+            && rest.starts_with(open)
+        {
+            let end = rest[open.len()..]
+                .find(close)
+                .map_or(bytes.len(), |n| i + open.len() + n + close.len());
+            tokens.push((i..end, TokenKind::Comment));
+            i = end;
+            continue;
+        }
+
+        if line_start && let Some(key) = key_at_line_start(language, code, i) {
+            let end = key.end;
+            tokens.push((key, TokenKind::Key));
+            i = end;
+            continue;
+        }
+
+        if spec.quotes.contains(&b) || spec.multiline_quotes.contains(&b) {
+            if language == Language::Rust && b == b'\'' {
+                // Either a char literal or a lifetime.
+                if let Some(end) = rust_char_literal_end(code, i) {
+                    tokens.push((i..end, TokenKind::String));
+                    i = end;
+                } else {
+                    i += 1;
+                }
+                continue;
+            }
+            let end = string_end(code, i, spec);
+            let kind = if language == Language::Json && followed_by_colon(bytes, end) {
+                TokenKind::Key
+            } else {
+                TokenKind::String
+            };
+            tokens.push((i..end, kind));
+            i = end;
+            continue;
+        }
+
+        if b.is_ascii_digit() {
+            let end = number_end(bytes, i);
+            tokens.push((i..end, TokenKind::Number));
+            i = end;
+            continue;
+        }
+
+        if is_ident_start(b) {
+            if language == Language::Rust
+                && let Some(end) = rust_raw_string_end(code, i)
+            {
+                tokens.push((i..end, TokenKind::String));
+                i = end;
+                continue;
+            }
+            let mut end = i + 1;
+            while end < bytes.len() && is_ident_continue(bytes[end]) {
+                end += 1;
+            }
+            let word = &code[i..end];
+            let kind = if spec.keywords.contains(&word) {
+                Some(TokenKind::Keyword)
+            } else if spec.literals.contains(&word) {
+                Some(TokenKind::Literal)
+            } else if spec.types.contains(&word)
+                || (spec.capitalized_types && bytes[i].is_ascii_uppercase())
+            {
+                Some(TokenKind::Type)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                tokens.push((i..end, kind));
+            }
+            i = end;
+            continue;
+        }
+
+        i += 1;
+    }
+    tokens
+}
+
+/// End of the string starting at `start`, which must be a quote character.
+/// Unterminated single-line strings end at the newline.
+fn string_end(code: &str, start: usize, spec: &LanguageSpec) -> usize {
+    let bytes = code.as_bytes();
+    let quote = bytes[start];
+    if spec.triple_quotes {
+        let triple = [quote; 3];
+        if bytes[start..].starts_with(&triple) {
+            return find_bytes(bytes, start + 3, &triple).map_or(bytes.len(), |n| n + 3);
+        }
+    }
+    let multiline = spec.multiline_quotes.contains(&quote);
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' if !multiline => return i,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn find_bytes(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|n| from + n)
+}
+
+fn number_end(bytes: &[u8], start: usize) -> usize {
+    let mut i = start;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b.is_ascii_alphanumeric() || b == b'_' {
+            i += 1;
+        } else if b == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+            // Keep `1.5` together but stop before ranges like `0..10`.
+            i += 1;
+        } else if matches!(b, b'+' | b'-')
+            && matches!(bytes[i - 1], b'e' | b'E')
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+        {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    i
+}
+
+fn followed_by_colon(bytes: &[u8], from: usize) -> bool {
+    bytes[from..]
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| *b == b':')
+}
+
+/// `'a'`, `'\n'` and `'\u{1F600}'` are char literals; `'a` is a lifetime.
+fn rust_char_literal_end(code: &str, start: usize) -> Option<usize> {
+    let rest = &code[start + 1..];
+    if let Some(escaped) = rest.strip_prefix('\\') {
+        // Skip the escaped character so `'\''` closes at the final quote.
+        let escaped_char = escaped.chars().next()?;
+        let tail = &escaped[escaped_char.len_utf8()..];
+        let close = tail.find('\'')?;
+        return (!tail[..close].contains('\n'))
+            .then_some(start + 2 + escaped_char.len_utf8() + close + 1);
+    }
+    let ch = rest.chars().next()?;
+    rest[ch.len_utf8()..]
+        .starts_with('\'')
+        .then_some(start + 1 + ch.len_utf8() + 1)
+}
+
+/// Raw strings such as `r"..."`, `r#"..."#` and `br"..."`.
+fn rust_raw_string_end(code: &str, start: usize) -> Option<usize> {
+    let rest = &code[start..];
+    let after_prefix = rest.strip_prefix("br").or_else(|| rest.strip_prefix('r'))?;
+    let hashes = after_prefix.len() - after_prefix.trim_start_matches('#').len();
+    let body = after_prefix[hashes..].strip_prefix('"')?;
+    let closing = format!("\"{}", "#".repeat(hashes));
+    let body_start = code.len() - body.len();
+    Some(
+        body.find(&closing)
+            .map_or(code.len(), |n| body_start + n + closing.len()),
+    )
+}
+
+/// Keys that start a line: table headers and `key =` in TOML, `key:` in YAML
+/// (including list items like `- key: value`, where only the key is styled).
+fn key_at_line_start(language: Language, code: &str, start: usize) -> Option<Range<usize>> {
+    if !matches!(language, Language::Toml | Language::Yaml) {
+        return None;
+    }
+    let line_end = code[start..].find('\n').map_or(code.len(), |n| start + n);
+    let line = &code[start..line_end];
+    if language == Language::Toml {
+        if line.starts_with('[') {
+            let close = if line.starts_with("[[") { "]]" } else { "]" };
+            return line.find(close).map(|n| start..start + n + close.len());
+        }
+        let key = line[..line.find('=')?].trim_end();
+        let is_key = !key.is_empty()
+            && key.bytes().all(|b| {
+                b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.' | b'"' | b' ')
+            });
+        return is_key.then_some(start..start + key.len());
+    }
+
+    let offset = match line.strip_prefix("- ") {
+        Some(item) => line.len() - item.trim_start().len(),
+        None => 0,
+    };
+    let candidate = &line[offset..];
+    let colon = candidate
+        .find(": ")
+        .or_else(|| candidate.strip_suffix(':').map(str::len))?;
+    let key = &candidate[..colon];
+    let is_key =
+        !key.is_empty() && !key.starts_with('-') && !key.contains(['#', '{', '[', '"', '\'']);
+    is_key.then_some(start + offset..start + offset + colon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn tokens(code: &str, language: Language) -> Vec<(&str, TokenKind)> {
+        tokenize(code, language)
+            .into_iter()
+            .map(|(range, kind)| (&code[range], kind))
+            .collect()
+    }
+
+    #[test]
+    fn resolves_languages_from_info_strings_and_paths() {
+        assert_eq!(Language::from_info_string("rust"), Some(Language::Rust));
+        assert_eq!(
+            Language::from_info_string("rust,ignore"),
+            Some(Language::Rust)
+        );
+        assert_eq!(
+            Language::from_info_string("Python title=\"x.py\""),
+            Some(Language::Python)
+        );
+        assert_eq!(Language::from_info_string("text"), None);
+        assert_eq!(Language::from_info_string(""), None);
+        assert_eq!(
+            Language::from_path(Path::new("web/app.tsx")),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(
+            Language::from_path(Path::new(".github/ci.yml")),
+            Some(Language::Yaml)
+        );
+        assert_eq!(Language::from_path(Path::new("Makefile")), None);
+    }
+
+    #[test]
+    fn tokenizes_rust() {
+        let code =
+            "// hi\nfn main<'a>(x: &'a str) -> Option<u8> { let c = '\\''; r#\"raw\"#; 0..10 }";
+        assert_eq!(
+            tokens(code, Language::Rust),
+            vec![
+                ("// hi", TokenKind::Comment),
+                ("fn", TokenKind::Keyword),
+                ("str", TokenKind::Type),
+                ("Option", TokenKind::Type),
+                ("u8", TokenKind::Type),
+                ("let", TokenKind::Keyword),
+                ("'\\''", TokenKind::String),
+                ("r#\"raw\"#", TokenKind::String),
+                ("0", TokenKind::Number),
+                ("10", TokenKind::Number),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_python_triple_quoted_strings_across_lines() {
+        let code = "def f():\n    \"\"\"doc\n    more\"\"\"\n    return None  # done";
+        assert_eq!(
+            tokens(code, Language::Python),
+            vec![
+                ("def", TokenKind::Keyword),
+                ("\"\"\"doc\n    more\"\"\"", TokenKind::String),
+                ("return", TokenKind::Keyword),
+                ("None", TokenKind::Literal),
+                ("# done", TokenKind::Comment),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_typescript_and_go() {
+        assert_eq!(
+            tokens("const s: string = `a\nb`; /* c */", Language::TypeScript),
+            vec![
+                ("const", TokenKind::Keyword),
+                ("string", TokenKind::Type),
+                ("`a\nb`", TokenKind::String),
+                ("/* c */", TokenKind::Comment),
+            ]
+        );
+        assert_eq!(
+            tokens("func Run() error { return nil }", Language::Go),
+            vec![
+                ("func", TokenKind::Keyword),
+                ("error", TokenKind::Type),
+                ("return", TokenKind::Keyword),
+                ("nil", TokenKind::Literal),
+            ]
+        );
+    }
+
+    #[test]
+    fn distinguishes_keys_in_data_formats() {
+        assert_eq!(
+            tokens(
+                "{\"a\": \"b\", \"n\": -1.5e3, \"ok\": true}",
+                Language::Json
+            ),
+            vec![
+                ("\"a\"", TokenKind::Key),
+                ("\"b\"", TokenKind::String),
+                ("\"n\"", TokenKind::Key),
+                ("1.5e3", TokenKind::Number),
+                ("\"ok\"", TokenKind::Key),
+                ("true", TokenKind::Literal),
+            ]
+        );
+        assert_eq!(
+            tokens(
+                "[profile.dev]\nopt-level = 3 # fast\nname = \"x\"",
+                Language::Toml
+            ),
+            vec![
+                ("[profile.dev]", TokenKind::Key),
+                ("opt-level", TokenKind::Key),
+                ("3", TokenKind::Number),
+                ("# fast", TokenKind::Comment),
+                ("name", TokenKind::Key),
+                ("\"x\"", TokenKind::String),
+            ]
+        );
+        assert_eq!(
+            tokens("jobs:\n  test:\n    - if: true # always", Language::Yaml),
+            vec![
+                ("jobs", TokenKind::Key),
+                ("test", TokenKind::Key),
+                ("if", TokenKind::Key),
+                ("true", TokenKind::Literal),
+                ("# always", TokenKind::Comment),
+            ]
+        );
+    }
+
+    #[test]
+    fn styled_ranges_cover_the_input() {
+        let code = "let s = \"héllo\"; // ünïcode";
+        let ranges = styled_ranges(code, Language::Rust);
+        let rebuilt: String = ranges.iter().map(|(r, _)| &code[r.clone()]).collect();
+        assert_eq!(rebuilt, code);
+    }
+
+    #[test]
+    fn palette_depends_on_background() {
+        assert_ne!(
+            token_style_for(TokenKind::Number, TerminalBackground::Dark),
+            token_style_for(TokenKind::Number, TerminalBackground::Light)
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Color theme for the TUI.
+//!
+//! Widgets style themselves with the ANSI roles from `tui/styles.md`: cyan for
+//! accents, green for success, red for errors and magenta for Codex. The theme
+//! remaps those roles to the configured colors right before cells are written
+//! to the terminal, so individual widgets do not need to know about it.
+
+use std::sync::OnceLock;
+
+use codex_core::config_types::ThemeColor;
+use codex_core::config_types::ThemePreset;
+use codex_core::config_types::TuiTheme;
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TerminalBackground {
+    Dark,
+    Light,
+}
+
+impl TerminalBackground {
+    /// Best-effort detection of the terminal background. Many terminals
+    /// export `COLORFGBG` as `<fg>;<bg>` using ANSI color indices; when it is
+    /// missing we assume a dark background.
+    fn detect() -> Self {
+        std::env::var("COLORFGBG")
+            .ok()
+            .and_then(|value| Self::from_colorfgbg(&value))
+            .unwrap_or(Self::Dark)
+    }
+
+    fn from_colorfgbg(value: &str) -> Option<Self> {
+        let bg: u8 = value.rsplit(';').next()?.trim().parse().ok()?;
+        // 7 is light gray and 9-15 are the bright colors; everything else is
+        // dark enough for the dark palette.
+        match bg {
+            7 | 9..=15 => Some(Self::Light),
+            _ => Some(Self::Dark),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Theme {
+    background: TerminalBackground,
+    monochrome: bool,
+    accent: Color,
+    success: Color,
+    error: Color,
+    codex: Color,
+    warning: Color,
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Install the theme from the `[tui.theme]` config. Only the first call has
+/// an effect; call it before the first frame is drawn.
+pub(crate) fn init(config: &TuiTheme) {
+    let _ = THEME.set(Theme::from_config(config));
+}
+
+/// The active theme, or the default theme when [`init`] has not been called.
+pub(crate) fn current() -> Theme {
+    *THEME.get_or_init(|| Theme::from_config(&TuiTheme::default()))
+}
+
+impl Theme {
+    fn from_config(config: &TuiTheme) -> Self {
+        let background = match config.preset {
+            ThemePreset::Dark => TerminalBackground::Dark,
+            ThemePreset::Light => TerminalBackground::Light,
+            ThemePreset::Auto | ThemePreset::Monochrome => TerminalBackground::detect(),
+        };
+        let role = |color: Option<ThemeColor>, default: Color| color.map_or(default, ansi_color);
+        Self {
+            background,
+            monochrome: config.preset == ThemePreset::Monochrome,
+            accent: role(config.accent, Color::Cyan),
+            success: role(config.success, Color::Green),
+            error: role(config.error, Color::Red),
+            codex: role(config.codex, Color::Magenta),
+            warning: role(config.warning, Color::Yellow),
+        }
+    }
+
+    pub(crate) fn background(&self) -> TerminalBackground {
+        self.background
+    }
+
+    /// Map a color chosen by a widget to the color the terminal should show.
+    pub(crate) fn map_color(&self, color: Color) -> Color {
+        if self.monochrome {
+            return Color::Reset;
+        }
+        match color {
+            Color::Cyan => self.accent,
+            Color::Green => self.success,
+            Color::Red => self.error,
+            Color::Magenta => self.codex,
+            Color::Yellow => self.warning,
+            other => other,
+        }
+    }
+
+    fn is_identity(&self) -> bool {
+        !self.monochrome
+            && self.accent == Color::Cyan
+            && self.success == Color::Green
+            && self.error == Color::Red
+            && self.codex == Color::Magenta
+            && self.warning == Color::Yellow
+    }
+
+    /// Remap the foreground and background of every cell in `buf`.
+    pub(crate) fn apply_to_buffer(&self, buf: &mut Buffer) {
+        if self.is_identity() {
+            return;
+        }
+        for cell in &mut buf.content {
+            cell.fg = self.map_color(cell.fg);
+            cell.bg = self.map_color(cell.bg);
+        }
+    }
+}
+
+fn ansi_color(color: ThemeColor) -> Color {
+    match color {
+        ThemeColor::Default => Color::Reset,
+        ThemeColor::Black => Color::Black,
+        ThemeColor::Red => Color::Red,
+        ThemeColor::Green => Color::Green,
+        ThemeColor::Yellow => Color::Yellow,
+        ThemeColor::Blue => Color::Blue,
+        ThemeColor::Magenta => Color::Magenta,
+        ThemeColor::Cyan => Color::Cyan,
+        ThemeColor::Gray => Color::Gray,
+        ThemeColor::DarkGray => Color::DarkGray,
+        ThemeColor::LightRed => Color::LightRed,
+        ThemeColor::LightGreen => Color::LightGreen,
+        ThemeColor::LightYellow => Color::LightYellow,
+        ThemeColor::LightBlue => Color::LightBlue,
+        ThemeColor::LightMagenta => Color::LightMagenta,
+        ThemeColor::LightCyan => Color::LightCyan,
+        ThemeColor::White => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn detects_background_from_colorfgbg() {
+        assert_eq!(
+            TerminalBackground::from_colorfgbg("15;0"),
+            Some(TerminalBackground::Dark)
+        );
+        assert_eq!(
+            TerminalBackground::from_colorfgbg("0;default;15"),
+            Some(TerminalBackground::Light)
+        );
+        assert_eq!(TerminalBackground::from_colorfgbg("garbage"), None);
+    }
+
+    #[test]
+    fn overrides_remap_style_guide_roles() {
+        let theme = Theme::from_config(&TuiTheme {
+            preset: ThemePreset::Light,
+            accent: Some(ThemeColor::Blue),
+            error: Some(ThemeColor::LightRed),
+            ..Default::default()
+        });
+        assert_eq!(theme.background(), TerminalBackground::Light);
+        assert_eq!(theme.map_color(Color::Cyan), Color::Blue);
+        assert_eq!(theme.map_color(Color::Red), Color::LightRed);
+        assert_eq!(theme.map_color(Color::Green), Color::Green);
+        assert_eq!(theme.map_color(Color::Reset), Color::Reset);
+
+        let mut buf = Buffer::empty(ratatui::layout::Rect::new(0, 0, 2, 1));
+        buf.content[0].fg = Color::Cyan;
+        theme.apply_to_buffer(&mut buf);
+        assert_eq!(buf.content[0].fg, Color::Blue);
+    }
+
+    #[test]
+    fn monochrome_drops_all_colors() {
+        let theme = Theme::from_config(&TuiTheme {
+            preset: ThemePreset::Monochrome,
+            ..Default::default()
+        });
+        assert_eq!(theme.map_color(Color::Magenta), Color::Reset);
+        assert_eq!(theme.map_color(Color::LightBlue), Color::Reset);
+        assert!(Theme::from_config(&TuiTheme::default()).is_identity());
+    }
+}
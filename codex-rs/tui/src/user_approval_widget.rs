@@ -44,6 +44,12 @@ pub(crate) enum ApprovalRequest {
         reason: Option<String>,
         grant_root: Option<PathBuf>,
     },
+    McpToolCall {
+        id: String,
+        server: String,
+        tool: String,
+        reason: Option<String>,
+    },
 }
 
 /// Options displayed in the *select* mode.
@@ -96,6 +102,29 @@ static PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
     ]
 });
 
+static MCP_TOOL_CALL_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
+    vec![
+        SelectOption {
+            label: Line::from(vec!["Y".underlined(), "es".into()]),
+            description: "Approve and call the tool",
+            key: KeyCode::Char('y'),
+            decision: ReviewDecision::Approved,
+        },
+        SelectOption {
+            label: Line::from(vec!["A".underlined(), "lways".into()]),
+            description: "Approve calls to this tool for the remainder of this session",
+            key: KeyCode::Char('a'),
+            decision: ReviewDecision::ApprovedForSession,
+        },
+        SelectOption {
+            label: Line::from(vec!["N".underlined(), "o, provide feedback".into()]),
+            description: "Do not call the tool; provide feedback",
+            key: KeyCode::Char('n'),
+            decision: ReviewDecision::Abort,
+        },
+    ]
+});
+
 /// A modal prompting the user to approve or deny the pending request.
 pub(crate) struct UserApprovalWidget {
     approval_request: ApprovalRequest,
@@ -142,12 +171,33 @@ impl UserApprovalWidget {
 
                 Paragraph::new(contents).wrap(Wrap { trim: false })
             }
+            ApprovalRequest::McpToolCall {
+                server,
+                tool,
+                reason,
+                ..
+            } => {
+                let mut contents: Vec<Line> = vec![];
+                if let Some(reason) = reason {
+                    contents.push(Line::from(reason.clone().italic()));
+                    contents.push(Line::from(""));
+                }
+                contents.push(Line::from(vec![
+                    "Tool ".into(),
+                    tool.clone().bold(),
+                    " from MCP server ".into(),
+                    server.clone().bold(),
+                ]));
+                contents.push(Line::from(""));
+                Paragraph::new(contents).wrap(Wrap { trim: false })
+            }
         };
 
         Self {
             select_options: match &approval_request {
                 ApprovalRequest::Exec { .. } => &COMMAND_SELECT_OPTIONS,
                 ApprovalRequest::ApplyPatch { .. } => &PATCH_SELECT_OPTIONS,
+                ApprovalRequest::McpToolCall { .. } => &MCP_TOOL_CALL_SELECT_OPTIONS,
             },
             approval_request,
             app_event_tx,
@@ -223,77 +273,83 @@ impl UserApprovalWidget {
     }
 
     fn send_decision_with_feedback(&mut self, decision: ReviewDecision, feedback: String) {
-        match &self.approval_request {
+        let summary = match &self.approval_request {
             ApprovalRequest::Exec { command, .. } => {
                 let full_cmd = strip_bash_lc_and_escape(command);
                 // Construct a concise, single-line summary of the command:
                 // - If multi-line, take the first line and append " ...".
                 // - Truncate to 80 graphemes.
-                let mut snippet = match full_cmd.split_once('\n') {
+                let snippet = match full_cmd.split_once('\n') {
                     Some((first, _)) => format!("{first} ..."),
                     None => full_cmd.clone(),
                 };
                 // Enforce the 80 character length limit.
-                snippet = truncate_text(&snippet, 80);
-
-                let mut result_spans: Vec<Span<'static>> = Vec::new();
-                match decision {
-                    ReviewDecision::Approved => {
-                        result_spans.extend(vec![
-                            "✔ ".fg(Color::Green),
-                            "You ".into(),
-                            "approved".bold(),
-                            " codex to run ".into(),
-                            snippet.dim(),
-                            " this time".bold(),
-                        ]);
-                    }
-                    ReviewDecision::ApprovedForSession => {
-                        result_spans.extend(vec![
-                            "✔ ".fg(Color::Green),
-                            "You ".into(),
-                            "approved".bold(),
-                            " codex to run ".into(),
-                            snippet.dim(),
-                            " every time this session".bold(),
-                        ]);
-                    }
-                    ReviewDecision::Denied => {
-                        result_spans.extend(vec![
-                            "✗ ".fg(Color::Red),
-                            "You ".into(),
-                            "did not approve".bold(),
-                            " codex to run ".into(),
-                            snippet.dim(),
-                        ]);
-                    }
-                    ReviewDecision::Abort => {
-                        result_spans.extend(vec![
-                            "✗ ".fg(Color::Red),
-                            "You ".into(),
-                            "canceled".bold(),
-                            " the request to run ".into(),
-                            snippet.dim(),
-                        ]);
-                    }
+                Some(("run", truncate_text(&snippet, 80)))
+            }
+            ApprovalRequest::ApplyPatch { .. } => {
+                // No history line for patch approval decisions.
+                None
+            }
+            ApprovalRequest::McpToolCall { server, tool, .. } => {
+                Some(("call", truncate_text(&format!("{server}.{tool}"), 80)))
+            }
+        };
+
+        if let Some((verb, snippet)) = summary {
+            let mut result_spans: Vec<Span<'static>> = Vec::new();
+            match decision {
+                ReviewDecision::Approved => {
+                    result_spans.extend(vec![
+                        "✔ ".fg(Color::Green),
+                        "You ".into(),
+                        "approved".bold(),
+                        format!(" codex to {verb} ").into(),
+                        snippet.dim(),
+                        " this time".bold(),
+                    ]);
+                }
+                ReviewDecision::ApprovedForSession => {
+                    result_spans.extend(vec![
+                        "✔ ".fg(Color::Green),
+                        "You ".into(),
+                        "approved".bold(),
+                        format!(" codex to {verb} ").into(),
+                        snippet.dim(),
+                        " every time this session".bold(),
+                    ]);
                 }
+                ReviewDecision::Denied => {
+                    result_spans.extend(vec![
+                        "✗ ".fg(Color::Red),
+                        "You ".into(),
+                        "did not approve".bold(),
+                        format!(" codex to {verb} ").into(),
+                        snippet.dim(),
+                    ]);
+                }
+                ReviewDecision::Abort => {
+                    result_spans.extend(vec![
+                        "✗ ".fg(Color::Red),
+                        "You ".into(),
+                        "canceled".bold(),
+                        format!(" the request to {verb} ").into(),
+                        snippet.dim(),
+                    ]);
+                }
+            }
 
-                let mut lines: Vec<Line<'static>> = vec![Line::from(result_spans)];
+            let mut lines: Vec<Line<'static>> = vec![Line::from(result_spans)];
 
-                if !feedback.trim().is_empty() {
-                    lines.push(Line::from("feedback:"));
-                    for l in feedback.lines() {
-                        lines.push(Line::from(l.to_string()));
-                    }
+            if !feedback.trim().is_empty() {
+                lines.push(Line::from("feedback:"));
+                for l in feedback.lines() {
+                    lines.push(Line::from(l.to_string()));
                 }
-
-                self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
-                    history_cell::new_user_approval_decision(lines),
-                )));
-            }
-            ApprovalRequest::ApplyPatch { .. } => {
-                // No history line for patch approval decisions.
             }
+
+            self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+                history_cell::new_user_approval_decision(lines),
+            )));
         }
 
         let op = match &self.approval_request {
@@ -305,6 +361,10 @@ impl UserApprovalWidget {
                 id: id.clone(),
                 decision,
             },
+            ApprovalRequest::McpToolCall { id, .. } => Op::McpToolCallApproval {
+                id: id.clone(),
+                decision,
+            },
         };
 
         self.app_event_tx.send(AppEvent::CodexOp(op));
@@ -357,6 +417,7 @@ impl WidgetRef for &UserApprovalWidget {
         let title = match &self.approval_request {
             ApprovalRequest::Exec { .. } => "Allow command?",
             ApprovalRequest::ApplyPatch { .. } => "Apply changes?",
+            ApprovalRequest::McpToolCall { .. } => "Allow tool call?",
         };
         Line::from(title).render(title_area, buf);
 
@@ -421,6 +482,32 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn mcp_tool_call_can_be_approved_for_session() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let req = ApprovalRequest::McpToolCall {
+            id: "3".to_string(),
+            server: "github".to_string(),
+            tool: "create_issue".to_string(),
+            reason: None,
+        };
+        let mut widget = UserApprovalWidget::new(req, tx);
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+        assert!(widget.is_complete());
+        let mut events: Vec<AppEvent> = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            events.push(ev);
+        }
+        assert!(events.iter().any(|e| matches!(
+            e,
+            AppEvent::CodexOp(Op::McpToolCallApproval {
+                decision: ReviewDecision::ApprovedForSession,
+                ..
+            })
+        )));
+    }
+
     #[test]
     fn uppercase_shortcut_is_accepted() {
         let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();